@@ -1,7 +1,8 @@
+use crate::merge::MergeStrategy;
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -12,6 +13,22 @@ pub struct AppConfig {
     pub default: Option<DefaultConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub codex: Option<CodexConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync: Option<SyncConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backups: Option<BackupsConfig>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Names available to `${NAME}` interpolation in `mcpServers.json`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Projects synced together by `config sync --all-projects`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<ProjectConfig>,
+    /// Project roots whose `.claudius/` layer project-local commands apply;
+    /// paths are resolved like `[[projects]]` paths.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trusted_projects: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,6 +46,63 @@ pub struct CodexConfig {
     pub skill_target: Option<CodexSkillTargetMode>,
 }
 
+/// Defaults for `claudius config sync`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct SyncConfig {
+    /// Merge strategy used when `--strategy` is not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<MergeStrategy>,
+}
+
+/// Backup storage and retention for files Claudius replaces.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct BackupsConfig {
+    /// Newest backups kept per file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep: Option<usize>,
+    /// Backups older than this many days are removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+    /// Store backups under `$XDG_STATE_HOME/claudius/backups` instead of next to each file.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub central: bool,
+}
+
+/// A named `[profiles.<name>]` table selected with `config sync --profile`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProfileConfig {
+    /// Names from `mcpServers.json` to sync; every server when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub servers: Option<Vec<String>>,
+    /// Deep-merged over `claude.settings.json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claude_settings: Option<toml::Table>,
+    /// Deep-merged over `codex.settings.toml`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub codex_settings: Option<toml::Table>,
+    /// Deep-merged over `gemini.settings.json`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gemini_settings: Option<toml::Table>,
+}
+
+/// A `[[projects]]` entry synced by `config sync --all-projects`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ProjectConfig {
+    /// Project root; a leading `~/` is expanded to the home directory, and a
+    /// relative path is resolved against the directory holding `config.toml`.
+    pub path: String,
+    /// Agents to sync; the default agent when omitted.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<Agent>,
+    /// Claude Code scope for this project (`project` or `local`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<ClaudeCodeScope>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
 #[serde(rename_all = "kebab-case")]
 pub enum Agent {
@@ -38,6 +112,19 @@ pub enum Agent {
     Gemini,
 }
 
+impl Agent {
+    /// Human-readable agent name for reports.
+    #[must_use]
+    pub const fn display_name(self) -> &'static str {
+        match self {
+            Self::Claude => "Claude",
+            Self::ClaudeCode => "Claude Code",
+            Self::Codex => "Codex",
+            Self::Gemini => "Gemini",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, clap::ValueEnum)]
 #[serde(rename_all = "kebab-case")]
 pub enum ClaudeCodeScope {
@@ -72,6 +159,10 @@ pub struct SecretManagerConfig {
     pub manager_type: SecretManagerType,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub onepassword: Option<OnePasswordConfig>,
+    /// Program agents start to run `claudius secrets run`; `claudius` from `PATH`
+    /// when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launcher: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -298,12 +389,19 @@ mod tests {
                         "~/.config/op/service-accounts/headless-linux-cli.token".to_string(),
                     ),
                 }),
+                launcher: None,
             }),
             default: Some(DefaultConfig {
                 agent: Agent::Claude,
                 context_file: Some("CUSTOM.md".to_string()),
             }),
             codex: Some(CodexConfig { skill_target: Some(CodexSkillTargetMode::Agents) }),
+            sync: None,
+            backups: None,
+            profiles: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            projects: Vec::new(),
+            trusted_projects: Vec::new(),
         };
 
         let toml_str = toml::to_string(&config).expect("Failed to serialize AppConfig");
@@ -453,6 +551,20 @@ agent = "claude"
         assert_eq!(config.default.expect("Default config should be present").agent, Agent::Claude);
     }
 
+    #[test]
+    fn test_sync_strategy_config() {
+        let toml_str = r#"
+[sync]
+strategy = "preserve-existing"
+"#;
+
+        let config: AppConfig = toml::from_str(toml_str).expect("Failed to deserialize AppConfig");
+        assert_eq!(
+            config.sync.and_then(|sync| sync.strategy),
+            Some(MergeStrategy::MergePreserveExisting)
+        );
+    }
+
     #[test]
     fn test_onepassword_mode_serialization() {
         assert_eq!(
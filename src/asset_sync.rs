@@ -1,5 +1,6 @@
 #![allow(missing_docs)]
 
+use crate::undo::UndoRecorder;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
@@ -102,6 +103,7 @@ pub fn sync_managed_tree(
     target_dir: &Path,
     mappings: &[SourceFileMapping],
     behavior: SyncBehavior,
+    undo: &UndoRecorder,
 ) -> Result<ManagedTreeSyncReport> {
     let manifest_path = manifest_path(target_dir);
     let previous_manifest = read_manifest(&manifest_path)?;
@@ -121,6 +123,12 @@ pub fn sync_managed_tree(
     };
 
     if !behavior.dry_run {
+        // Record before the target directory is created so undo can remove it again.
+        for relative_path in synced_files.iter().chain(&pruned_files) {
+            undo.record(&target_dir.join(relative_path));
+        }
+        undo.record(&manifest_path);
+
         let should_prepare_target =
             !mappings.is_empty() || !previous_manifest.managed_files.is_empty();
         if should_prepare_target {
@@ -150,6 +158,43 @@ pub fn sync_managed_tree(
     Ok(ManagedTreeSyncReport { target_dir: target_dir.to_path_buf(), synced_files, pruned_files })
 }
 
+/// Delete the listed files from a Claudius-managed target tree without syncing it.
+///
+/// Only files the manifest still records are removed, so an outdated list never
+/// touches files Claudius does not own. Returns the files actually pruned.
+///
+/// # Errors
+///
+/// Returns an error if files cannot be deleted or the manifest cannot be read
+/// or written.
+pub fn prune_managed_files(
+    target_dir: &Path,
+    files: &[String],
+    undo: &UndoRecorder,
+) -> Result<Vec<String>> {
+    let manifest_path = manifest_path(target_dir);
+    let manifest = read_manifest(&manifest_path)?;
+    let pruned = files
+        .iter()
+        .filter(|relative_path| manifest.managed_files.contains(*relative_path))
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    if pruned.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    for relative_path in &pruned {
+        undo.record(&target_dir.join(relative_path));
+    }
+    undo.record(&manifest_path);
+    for relative_path in &pruned {
+        delete_managed_file(target_dir, relative_path)?;
+    }
+    write_manifest(&manifest_path, &manifest.managed_files.difference(&pruned).cloned().collect())?;
+
+    Ok(pruned.into_iter().collect())
+}
+
 fn collect_directory_tree_mappings_recursive(
     root_dir: &Path,
     current_dir: &Path,
@@ -347,6 +392,7 @@ mod tests {
             &target_dir,
             &mappings,
             SyncBehavior { dry_run: false, prune: false },
+            &UndoRecorder::disabled(),
         )
         .expect("sync should succeed");
 
@@ -366,8 +412,13 @@ mod tests {
         fs::write(source_dir.join("keep.txt"), "keep").expect("write source file");
 
         let mappings = collect_directory_tree_mappings(&source_dir).expect("collect mappings");
-        sync_managed_tree(&target_dir, &mappings, SyncBehavior { dry_run: false, prune: false })
-            .expect("initial sync should succeed");
+        sync_managed_tree(
+            &target_dir,
+            &mappings,
+            SyncBehavior { dry_run: false, prune: false },
+            &UndoRecorder::disabled(),
+        )
+        .expect("initial sync should succeed");
 
         fs::write(target_dir.join("old.txt"), "old").expect("write old file");
         fs::write(target_dir.join("manual").join("note.txt"), "manual").expect("write note");
@@ -377,9 +428,13 @@ mod tests {
         )
         .expect("write manifest");
 
-        let report =
-            sync_managed_tree(&target_dir, &mappings, SyncBehavior { dry_run: false, prune: true })
-                .expect("prune sync should succeed");
+        let report = sync_managed_tree(
+            &target_dir,
+            &mappings,
+            SyncBehavior { dry_run: false, prune: true },
+            &UndoRecorder::disabled(),
+        )
+        .expect("prune sync should succeed");
 
         assert_eq!(report.pruned_files, vec!["old.txt".to_string()]);
         assert!(target_dir.join("keep.txt").exists());
@@ -400,9 +455,13 @@ mod tests {
             .expect("write manifest");
 
         let mappings = collect_directory_tree_mappings(&source_dir).expect("collect mappings");
-        let report =
-            sync_managed_tree(&target_dir, &mappings, SyncBehavior { dry_run: true, prune: true })
-                .expect("dry-run sync should succeed");
+        let report = sync_managed_tree(
+            &target_dir,
+            &mappings,
+            SyncBehavior { dry_run: true, prune: true },
+            &UndoRecorder::disabled(),
+        )
+        .expect("dry-run sync should succeed");
 
         assert_eq!(report.synced_files, vec!["new.txt".to_string()]);
         assert_eq!(report.pruned_files, vec!["old.txt".to_string()]);
@@ -430,6 +489,36 @@ mod tests {
         assert_eq!(inspection.stale_files, vec!["old.txt".to_string()]);
     }
 
+    #[test]
+    fn prune_managed_files_removes_only_manifested_files() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let target_dir = temp_dir.path().join("target");
+        fs::create_dir_all(&target_dir).expect("create target");
+        fs::write(target_dir.join("keep.txt"), "keep").expect("write kept file");
+        fs::write(target_dir.join("old.txt"), "old").expect("write old file");
+        fs::write(target_dir.join("manual.txt"), "manual").expect("write manual file");
+        write_manifest(
+            &manifest_path(&target_dir),
+            &BTreeSet::from(["keep.txt".to_string(), "old.txt".to_string()]),
+        )
+        .expect("write manifest");
+
+        let pruned = prune_managed_files(
+            &target_dir,
+            &["old.txt".to_string(), "manual.txt".to_string()],
+            &UndoRecorder::disabled(),
+        )
+        .expect("prune should succeed");
+
+        assert_eq!(pruned, vec!["old.txt".to_string()]);
+        assert!(!target_dir.join("old.txt").exists());
+        assert!(target_dir.join("manual.txt").exists());
+        assert_eq!(
+            read_manifest(&manifest_path(&target_dir)).expect("read manifest").managed_files,
+            BTreeSet::from(["keep.txt".to_string()])
+        );
+    }
+
     #[test]
     fn sync_managed_tree_can_overwrite_read_only_targets() {
         let temp_dir = TempDir::new().expect("temp dir");
@@ -447,6 +536,7 @@ mod tests {
             &target_dir,
             &initial_mappings,
             SyncBehavior { dry_run: false, prune: false },
+            &UndoRecorder::disabled(),
         )
         .expect("initial sync should succeed");
 
@@ -465,6 +555,7 @@ mod tests {
             &target_dir,
             &updated_mappings,
             SyncBehavior { dry_run: false, prune: false },
+            &UndoRecorder::disabled(),
         )
         .expect("second sync should succeed");
 
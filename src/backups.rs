@@ -0,0 +1,444 @@
+#![allow(missing_docs)]
+
+//! Inventory, restore, and retention for the timestamped backups Claudius creates.
+//!
+//! `config sync --backup`, `config migrate`, and `config backups restore` copy a
+//! file to `<name>.backup.<YYYYmmdd_HHMMSS>[.<n>]` before replacing it. Backups
+//! sit next to the file unless `central = true`, in which case they are stored
+//! under `$XDG_STATE_HOME/claudius/backups/` in a tree that mirrors the
+//! original absolute path:
+//!
+//! ```toml
+//! [backups]
+//! keep = 5            # newest backups kept per file
+//! max-age-days = 30   # older backups are removed
+//! central = true
+//! ```
+//!
+//! The settings are read from `config.toml` by each command and passed to the
+//! functions that write backups. The retention policy is applied to a file's
+//! backups whenever a new one is created, and to every discovered backup by
+//! `config backups prune`.
+
+use crate::agent_paths;
+use crate::app_config::BackupsConfig;
+use crate::config::writer::{atomic_write, copy_to_backup, FileMode};
+use crate::file_walk::collect_files;
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, TimeDelta};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+const BACKUP_MARKER: &str = ".backup.";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// How many backups to keep per file, and for how long.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    /// Newest backups kept per file.
+    pub keep: Option<usize>,
+    /// Backups older than this are removed.
+    pub max_age: Option<TimeDelta>,
+}
+
+impl RetentionPolicy {
+    #[must_use]
+    pub fn from_config(config: Option<&BackupsConfig>) -> Self {
+        Self {
+            keep: config.and_then(|backups| backups.keep).filter(|keep| *keep > 0),
+            max_age: config
+                .and_then(|backups| backups.max_age_days)
+                .map(|days| TimeDelta::days(i64::from(days))),
+        }
+    }
+
+    #[must_use]
+    pub const fn is_unlimited(&self) -> bool {
+        self.keep.is_none() && self.max_age.is_none()
+    }
+}
+
+/// Where backups are written and how long they are kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackupSettings {
+    /// Central backup directory; backups sit next to their file when `None`.
+    pub central_dir: Option<PathBuf>,
+    pub retention: RetentionPolicy,
+}
+
+impl BackupSettings {
+    #[must_use]
+    pub fn from_config(config: Option<&BackupsConfig>) -> Self {
+        Self {
+            central_dir: config
+                .filter(|backups| backups.central)
+                .and_then(|_| central_backup_dir()),
+            retention: RetentionPolicy::from_config(config),
+        }
+    }
+}
+
+/// `$XDG_STATE_HOME/claudius/backups`, falling back to `~/.local/state/claudius/backups`.
+#[must_use]
+pub fn central_backup_dir() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            directories::BaseDirs::new().map(|dirs| dirs.home_dir().join(".local").join("state"))
+        })?;
+    Some(state_home.join("claudius").join("backups"))
+}
+
+/// Directory that holds the backups of `path`.
+#[must_use]
+pub fn backup_directory(path: &Path, settings: &BackupSettings) -> PathBuf {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let parent = absolute.parent().unwrap_or_else(|| Path::new("/"));
+    settings.central_dir.as_ref().map_or_else(
+        || parent.to_path_buf(),
+        |central| {
+            central.join(
+                parent
+                    .components()
+                    .filter(|component| matches!(component, Component::Normal(_)))
+                    .collect::<PathBuf>(),
+            )
+        },
+    )
+}
+
+/// File name of a backup of `file_name` taken at `timestamp`.
+#[must_use]
+pub fn backup_file_name(file_name: &str, timestamp: &str, sequence: u64) -> String {
+    if sequence > 0 {
+        format!("{file_name}{BACKUP_MARKER}{timestamp}.{sequence}")
+    } else {
+        format!("{file_name}{BACKUP_MARKER}{timestamp}")
+    }
+}
+
+/// A backup discovered on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// File the backup was taken from.
+    pub original: PathBuf,
+    pub created: NaiveDateTime,
+    /// Counter appended when two backups of a file share a timestamp.
+    pub sequence: u64,
+}
+
+impl Backup {
+    /// Parse a backup path; backups under `central_dir` map back to their mirrored original.
+    #[must_use]
+    pub fn from_path(path: &Path, central_dir: Option<&Path>) -> Option<Self> {
+        let file_name = path.file_name()?.to_str()?;
+        let (original_name, stamp) = file_name.rsplit_once(BACKUP_MARKER)?;
+        if original_name.is_empty() {
+            return None;
+        }
+        let (timestamp, sequence) = match stamp.split_once('.') {
+            Some((timestamp, counter)) => (timestamp, counter.parse().ok()?),
+            None => (stamp, 0),
+        };
+        let created = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+
+        let directory = path.parent()?;
+        let original_dir = central_dir
+            .and_then(|central| directory.strip_prefix(central).ok())
+            .map_or_else(|| directory.to_path_buf(), |relative| Path::new("/").join(relative));
+        Some(Self {
+            path: path.to_path_buf(),
+            original: original_dir.join(original_name),
+            created,
+            sequence,
+        })
+    }
+
+    /// Size of the backup in bytes, or 0 when it cannot be read.
+    #[must_use]
+    pub fn size(&self) -> u64 {
+        fs::metadata(&self.path).map_or(0, |metadata| metadata.len())
+    }
+}
+
+/// Orders backups by original file, newest first within each file.
+fn newest_first(left: &Backup, right: &Backup) -> Ordering {
+    left.original
+        .cmp(&right.original)
+        .then_with(|| right.created.cmp(&left.created))
+        .then_with(|| right.sequence.cmp(&left.sequence))
+}
+
+/// Directories where Claudius writes side-by-side backups: the config
+/// directory, global agent targets, and the agent directories of each project.
+#[must_use]
+pub fn default_backup_directories(
+    config_dir: &Path,
+    home_dir: &Path,
+    system_config_dir: &Path,
+    project_dirs: &[PathBuf],
+) -> Vec<PathBuf> {
+    let mut directories = vec![
+        config_dir.to_path_buf(),
+        home_dir.to_path_buf(),
+        home_dir.join(".claude"),
+        home_dir.join(".codex"),
+        home_dir.join(".gemini"),
+        system_config_dir.join("Claude"),
+        agent_paths::claude_code_managed_dir(),
+    ];
+    directories.extend(
+        [
+            agent_paths::codex_requirements_path(),
+            agent_paths::codex_managed_config_path(),
+            agent_paths::gemini_cli_system_settings_path(),
+            agent_paths::gemini_cli_system_defaults_path(),
+        ]
+        .iter()
+        .filter_map(|path| path.parent().map(Path::to_path_buf)),
+    );
+    for project in project_dirs {
+        directories.extend([
+            project.clone(),
+            project.join(".claude"),
+            project.join(".codex"),
+            project.join(".gemini"),
+        ]);
+    }
+    directories.sort();
+    directories.dedup();
+    directories
+}
+
+/// Backups in `directories` (non-recursive) and anywhere under `central_dir`,
+/// grouped by original file, newest first.
+#[must_use]
+pub fn discover_backups(directories: &[PathBuf], central_dir: Option<&Path>) -> Vec<Backup> {
+    let side_by_side = directories
+        .iter()
+        .filter_map(|directory| fs::read_dir(directory).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter_map(|path| Backup::from_path(&path, None));
+
+    let central_paths = central_dir.map(collect_files).unwrap_or_default();
+    let central = central_paths.iter().filter_map(|path| Backup::from_path(path, central_dir));
+
+    let mut backups = side_by_side
+        .chain(central)
+        .map(|backup| (backup.path.clone(), backup))
+        .collect::<BTreeMap<_, _>>()
+        .into_values()
+        .collect::<Vec<_>>();
+    backups.sort_by(newest_first);
+    backups
+}
+
+/// Backups of `path` in its backup directory, newest first.
+#[must_use]
+pub fn backups_of(path: &Path, settings: &BackupSettings) -> Vec<Backup> {
+    let directory = backup_directory(path, settings);
+    discover_backups(std::slice::from_ref(&directory), None)
+        .into_iter()
+        .filter(|backup| backup.original.file_name() == path.file_name())
+        .collect()
+}
+
+/// Backups outside `policy` as of `now`: beyond the newest `keep` of each
+/// file, or older than `max_age`.
+#[must_use]
+pub fn expired_backups(
+    backups: &[Backup],
+    policy: RetentionPolicy,
+    now: NaiveDateTime,
+) -> Vec<Backup> {
+    let mut sorted = backups.to_vec();
+    sorted.sort_by(newest_first);
+    sorted
+        .chunk_by(|left, right| left.original == right.original)
+        .flat_map(|group| group.iter().enumerate())
+        .filter(|(rank, backup)| {
+            policy.keep.is_some_and(|keep| *rank >= keep)
+                || policy
+                    .max_age
+                    .is_some_and(|age| now.signed_duration_since(backup.created) > age)
+        })
+        .map(|(_, backup)| backup.clone())
+        .collect()
+}
+
+/// Delete `backups`.
+///
+/// # Errors
+///
+/// Returns an error if a backup cannot be removed.
+pub fn remove_backups(backups: &[Backup]) -> Result<()> {
+    for backup in backups {
+        fs::remove_file(&backup.path)
+            .with_context(|| format!("Failed to remove backup {}", backup.path.display()))?;
+    }
+    Ok(())
+}
+
+/// Remove the backups of `path` that fall outside the retention policy of `settings`.
+///
+/// `exempt` names a backup that is kept regardless, such as one just restored.
+///
+/// # Errors
+///
+/// Returns an error if an expired backup cannot be removed.
+pub fn enforce_retention(
+    path: &Path,
+    settings: &BackupSettings,
+    exempt: Option<&Path>,
+) -> Result<Vec<Backup>> {
+    if settings.retention.is_unlimited() {
+        return Ok(Vec::new());
+    }
+
+    let expired = expired_backups(
+        &backups_of(path, settings),
+        settings.retention,
+        chrono::Local::now().naive_local(),
+    )
+    .into_iter()
+    .filter(|backup| Some(backup.path.as_path()) != exempt)
+    .collect::<Vec<_>>();
+    remove_backups(&expired)?;
+    Ok(expired)
+}
+
+/// Atomically replace the backup's original file with the backup, backing up
+/// the current file first. Returns the path of that new backup, if any.
+///
+/// Retention runs once the original is restored and never removes `backup`
+/// itself, so restoring works even when `keep` is 1.
+///
+/// # Errors
+///
+/// Returns an error if the backup cannot be read, the current file cannot be
+/// backed up, or the original cannot be written.
+pub fn restore_backup(backup: &Backup, settings: &BackupSettings) -> Result<Option<String>> {
+    let content = fs::read(&backup.path)
+        .with_context(|| format!("Failed to read backup {}", backup.path.display()))?;
+    let previous = copy_to_backup(&backup.original, settings)
+        .with_context(|| format!("Failed to back up {}", backup.original.display()))?;
+    atomic_write(&backup.original, &content, FileMode::Preserve)
+        .with_context(|| format!("Failed to restore {}", backup.original.display()))?;
+    if let Err(error) = enforce_retention(&backup.original, settings, Some(&backup.path)) {
+        tracing::warn!("Failed to apply backup retention: {error:#}");
+    }
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn at(timestamp: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).unwrap()
+    }
+
+    #[test]
+    fn backup_from_path_parses_side_by_side_and_central_names() {
+        let side_by_side =
+            Backup::from_path(Path::new("/home/user/.claude.json.backup.20261017_120000.2"), None)
+                .unwrap();
+        assert_eq!(side_by_side.original, Path::new("/home/user/.claude.json"));
+        assert_eq!(side_by_side.created, at("20261017_120000"));
+        assert_eq!(side_by_side.sequence, 2);
+
+        let central = Backup::from_path(
+            Path::new("/state/backups/home/user/.codex/config.toml.backup.20261017_120000"),
+            Some(Path::new("/state/backups")),
+        )
+        .unwrap();
+        assert_eq!(central.original, Path::new("/home/user/.codex/config.toml"));
+
+        assert!(Backup::from_path(Path::new("/work/notes.backup.txt"), None).is_none());
+        assert!(Backup::from_path(Path::new("/work/.backup.20261017_120000"), None).is_none());
+    }
+
+    #[test]
+    fn expired_backups_applies_count_and_age_per_file() {
+        let backup = |original: &str, timestamp: &str| Backup {
+            path: PathBuf::from(format!("{original}.backup.{timestamp}")),
+            original: PathBuf::from(original),
+            created: at(timestamp),
+            sequence: 0,
+        };
+        let backups = [
+            backup("/a.json", "20261017_120000"),
+            backup("/a.json", "20261016_120000"),
+            backup("/a.json", "20261015_120000"),
+            backup("/b.json", "20260901_120000"),
+            backup("/b.json", "20261017_110000"),
+        ];
+
+        let by_count = expired_backups(
+            &backups,
+            RetentionPolicy { keep: Some(1), max_age: None },
+            at("20261017_130000"),
+        );
+        let names = by_count
+            .iter()
+            .map(|expired| expired.path.display().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "/a.json.backup.20261016_120000",
+                "/a.json.backup.20261015_120000",
+                "/b.json.backup.20260901_120000"
+            ]
+        );
+
+        let by_age = expired_backups(
+            &backups,
+            RetentionPolicy { keep: None, max_age: Some(TimeDelta::days(30)) },
+            at("20261017_130000"),
+        );
+        assert_eq!(by_age.len(), 1);
+        assert!(by_age.iter().all(|expired| expired.original == Path::new("/b.json")));
+    }
+
+    #[test]
+    fn restore_keeps_the_restored_backup_under_retention() {
+        let dir = TempDir::new().unwrap();
+        let original = dir.path().join("settings.json");
+        let restored = dir.path().join(backup_file_name("settings.json", "20200101_000000", 0));
+        fs::write(&original, "current").unwrap();
+        fs::write(&restored, "restored").unwrap();
+        let settings = BackupSettings {
+            central_dir: None,
+            retention: RetentionPolicy { keep: Some(1), max_age: None },
+        };
+
+        let backup = Backup::from_path(&restored, None).unwrap();
+        let previous = restore_backup(&backup, &settings).unwrap().unwrap();
+
+        assert_eq!(fs::read_to_string(&original).unwrap(), "restored");
+        assert!(restored.exists());
+        assert_eq!(fs::read_to_string(previous).unwrap(), "current");
+    }
+
+    #[test]
+    fn central_backup_directory_mirrors_the_original_path() {
+        let central = TempDir::new().unwrap();
+        let settings = BackupSettings {
+            central_dir: Some(central.path().to_path_buf()),
+            retention: RetentionPolicy::default(),
+        };
+
+        let directory = backup_directory(Path::new("/home/user/.claude.json"), &settings);
+
+        assert_eq!(directory, central.path().join("home/user"));
+        let name = backup_file_name(".claude.json", "20261017_120000", 0);
+        let parsed = Backup::from_path(&directory.join(name), Some(central.path())).unwrap();
+        assert_eq!(parsed.original, Path::new("/home/user/.claude.json"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::interaction::confirm;
 use anyhow::{Context, Result};
 use std::fs;
 use std::path::Path;
@@ -275,18 +276,6 @@ fn create_directory(path: &Path, force: bool) -> Result<()> {
     Ok(())
 }
 
-fn prompt_yes_no(prompt: &str) -> Result<bool> {
-    use std::io::{self, Write};
-
-    eprint!("{prompt}");
-    io::stderr().flush()?;
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-
-    Ok(input.trim().eq_ignore_ascii_case("y"))
-}
-
 fn maybe_backup_context_file(path: &Path, label: &str) -> Result<()> {
     let metadata = fs::metadata(path)?;
     let file_size = metadata.len();
@@ -296,8 +285,8 @@ fn maybe_backup_context_file(path: &Path, label: &str) -> Result<()> {
         return Ok(());
     }
 
-    let should_backup = prompt_yes_no(&format!(
-        "\nThe {label} file contains {file_size} bytes of data. Create a backup? [y/N]: ",
+    let should_backup = confirm(&format!(
+        "\nThe {label} file contains {file_size} bytes of data. Create a backup?",
     ))?;
 
     if !should_backup {
@@ -451,7 +440,7 @@ fn init_context_files(target_dir: &Path, default_context: Option<&str>, force: b
                 eprintln!("  - {secondary_file} (secondary)");
             }
 
-            if !prompt_yes_no("\nDo you want to overwrite them? [y/N]: ")? {
+            if !confirm("\nDo you want to overwrite them?")? {
                 info!("Skipping context file initialization");
                 return Ok(());
             }
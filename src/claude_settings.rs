@@ -1,7 +1,7 @@
 use serde_json::Value;
 
 use crate::app_config::ClaudeCodeScope;
-use crate::validation::Diagnostic;
+use crate::validation::{Diagnostic, DiagnosticRule};
 
 /// Validate Claude Code settings for known compatibility concerns.
 ///
@@ -38,10 +38,13 @@ pub fn validate_claude_settings_scope(settings: &Value, scope: ClaudeCodeScope)
     if matches!(scope, ClaudeCodeScope::Project | ClaudeCodeScope::Local) {
         for field in ["askUserQuestionTimeout", "autoMode"] {
             if fields.contains_key(field) {
-                diagnostics.push(Diagnostic::warning(format!(
-                    "{field} is ignored by Claude Code in {} scope; move it to user or managed settings",
-                    claude_scope_name(scope),
-                )));
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "{field} is ignored by Claude Code in {} scope; move it to user or managed settings",
+                        claude_scope_name(scope),
+                    ))
+                    .with_rule(DiagnosticRule::ScopeRestriction),
+                );
             }
         }
     }
@@ -56,10 +59,13 @@ pub fn validate_claude_settings_scope(settings: &Value, scope: ClaudeCodeScope)
             "claudeMd",
         ] {
             if fields.contains_key(field) {
-                diagnostics.push(Diagnostic::warning(format!(
-                    "{field} is a managed-only Claude Code setting and is ignored in {} scope",
-                    claude_scope_name(scope),
-                )));
+                diagnostics.push(
+                    Diagnostic::warning(format!(
+                        "{field} is a managed-only Claude Code setting and is ignored in {} scope",
+                        claude_scope_name(scope),
+                    ))
+                    .with_rule(DiagnosticRule::ManagedOnlySetting),
+                );
             }
         }
     }
@@ -128,7 +134,8 @@ mod tests {
         );
 
         assert_eq!(diagnostics.len(), 2);
-        assert!(diagnostics.iter().all(|diagnostic| !diagnostic.contains("futureSetting")));
+        assert!(diagnostics.iter().all(|diagnostic| !diagnostic.contains("futureSetting")
+            && diagnostic.rule == DiagnosticRule::ScopeRestriction));
     }
 
     #[test]
@@ -139,9 +146,8 @@ mod tests {
         );
 
         assert_eq!(diagnostics.len(), 1);
-        assert!(diagnostics
-            .first()
-            .is_some_and(|diagnostic| diagnostic.contains("managed-only")));
+        assert!(diagnostics.first().is_some_and(|diagnostic| diagnostic.contains("managed-only")
+            && diagnostic.rule == DiagnosticRule::ManagedOnlySetting));
     }
 
     #[test]
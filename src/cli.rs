@@ -68,6 +68,14 @@ pub struct Cli {
     /// Enable trace output (shows all log messages including TRACE)
     #[arg(short = 't', long, global = true)]
     pub trace: bool,
+
+    /// Answer "yes" to every confirmation prompt
+    #[arg(long, global = true, conflicts_with = "no_input")]
+    pub yes: bool,
+
+    /// Never prompt; fail wherever a confirmation would be required
+    #[arg(long, global = true)]
+    pub no_input: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -80,6 +88,10 @@ pub enum Commands {
     #[command(subcommand, name = "skills")]
     Skills(SkillsCommands),
 
+    /// Edit and check the MCP servers defined in mcpServers.json
+    #[command(subcommand)]
+    Mcp(McpCommands),
+
     /// Manage project context rules and templates
     #[command(subcommand)]
     Context(ContextCommands),
@@ -87,6 +99,41 @@ pub enum Commands {
     /// Execute processes with automatic secret resolution
     #[command(subcommand)]
     Secrets(SecretsCommands),
+
+    /// Restore the files changed by the last `config sync` or `skills sync`
+    #[command(long_about = "Restore the files changed by the last `config sync` or `skills sync`.
+
+Every sync that changes files records their previous content under
+.claudius-undo/ in the Claudius config directory: configuration targets,
+synced skills, commands, and agents, their manifests, and Claudius' own
+bookkeeping. Undo restores the newest record exactly, deleting files the
+sync created, and then drops it, so running undo again steps further back.
+The last 20 syncs are kept.
+
+Undo refuses to run when a file was modified after the sync, so later edits
+are never lost.
+
+Examples:
+  # Undo the last sync
+  claudius undo
+
+  # Show what would be restored
+  claudius undo --dry-run
+
+  # List the recorded syncs, newest first
+  claudius undo --list")]
+    Undo(UndoArgs),
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct UndoArgs {
+    /// List the recorded syncs instead of undoing the last one
+    #[arg(long, conflicts_with = "dry_run")]
+    pub list: bool,
+
+    /// Show the files that would be restored or removed without changing them
+    #[arg(short, long)]
+    pub dry_run: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -170,13 +217,69 @@ Examples:
   # Sync legacy Claude Desktop global MCP configuration
   claudius config sync --global --agent claude
 
-  # Preview changes without writing
+  # Preview changes as per-file unified diffs without writing
   claudius config sync --dry-run
 
   # Create backup before syncing
-  claudius config sync --backup")]
+  claudius config sync --backup
+
+  # Overwrite conflicting MCP servers without prompting (e.g. in CI)
+  claudius config sync --strategy merge --no-input")]
     Sync(ConfigSyncArgs),
 
+    /// Report targets that are out of date with the sources, without writing
+    #[command(
+        long_about = "Report what `claudius config sync` would change, without writing anything.
+
+This command runs the same read and merge pipeline as `config sync` and
+compares the result with the deployed files:
+  • MCP server entries and settings keys in each target configuration file
+  • skills, Gemini commands and agents, and Claude Code subagents
+
+The selected agent and scope follow the same rules as `config sync`; in
+global mode without --agent, every agent with source settings is checked.
+Interactive merges are evaluated as if every conflict were accepted.
+
+Exit codes:
+  0  every target is up to date
+  1  an error occurred
+  2  invalid command-line usage
+  3  at least one target is out of date
+
+Examples:
+  # Check project-local targets for the default agent
+  claudius config status
+
+  # Fail a pre-commit hook or cron job when global targets drifted
+  claudius config status --global --agent claude-code"
+    )]
+    Status(ConfigStatusArgs),
+
+    /// Import MCP servers and settings from existing agent files into Claudius sources
+    #[command(long_about = "Import MCP servers and settings from existing agent files into the
+Claudius configuration sources.
+
+This command reads the files `config sync` writes and converts them back:
+  • Project-local mode (default): ./.mcp.json, ./.claude/settings.json,
+    ./.codex/config.toml, ./.gemini/settings.json
+  • Global mode (--global): ~/.claude.json, ~/.claude/settings.json,
+    claude_desktop_config.json, ~/.codex/config.toml, ~/.gemini/settings.json
+
+MCP servers are normalized to the mcpServers.json format. Servers that launch
+the same command or reach the same URL are imported once; servers whose name
+is already taken by a different definition are reported as conflicts and the
+existing definition is kept. Agent settings are written to claude.settings.json,
+codex.settings.toml, and gemini.settings.json only when those files do not
+exist yet.
+
+Examples:
+  # Preview what would be imported from the current project
+  claudius config import --dry-run
+
+  # Import global Codex configuration only
+  claudius config import --global --agent codex")]
+    Import(ConfigImportArgs),
+
     /// Validate configuration source files without writing anything
     #[command(
         long_about = "Validate Claudius configuration source files without writing anything.
@@ -214,7 +317,10 @@ to report situations such as:
   • stale deployed assets that no longer exist in the source tree
 
 Use --global to inspect global deployment targets under $HOME.
-Use --agent to focus on a single agent surface."
+Use --agent to focus on a single agent surface.
+Use --fix to rename legacy source files and prune stale deployed assets, then
+re-run the doctor; migrating deprecated skill overrides asks for confirmation.
+Add --dry-run to list the remediations without applying them."
     )]
     Doctor(ConfigDoctorArgs),
 
@@ -254,6 +360,295 @@ Examples:
   # Migrate Claude Code settings
   claudius config migrate --agent claude-code")]
     Migrate(ConfigMigrateArgs),
+
+    /// List, restore, and prune the timestamped backups Claudius creates
+    #[command(subcommand)]
+    Backups(BackupsCommands),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupsCommands {
+    /// List backups of agent targets and Claudius sources, newest first
+    #[command(long_about = "List the timestamped backups created by `config sync --backup`,
+`config migrate`, and `config backups restore`.
+
+Backups are looked up next to the Claudius sources, the global agent targets,
+the current project and every [[projects]] entry of config.toml, and in the
+central backup directory ($XDG_STATE_HOME/claudius/backups).
+
+Examples:
+  # List every backup
+  claudius config backups list
+
+  # List the backups of one file
+  claudius config backups list --file ~/.claude.json")]
+    List(BackupsListArgs),
+
+    /// Restore a backup, backing up the current file first
+    #[command(long_about = "Restore a backup over the file it was taken from.
+
+The current file is backed up first and then atomically replaced. PATH may be
+a backup file as printed by `config backups list`, or a backed-up file, in
+which case its newest backup is restored.
+
+Examples:
+  # Restore a specific backup
+  claudius config backups restore ~/.claude.json.backup.20261017_120000
+
+  # Restore the newest backup of ~/.claude.json
+  claudius config backups restore ~/.claude.json")]
+    Restore(BackupsRestoreArgs),
+
+    /// Remove backups outside the retention policy
+    #[command(long_about = "Remove backups outside the retention policy.
+
+The policy comes from [backups] in config.toml (keep, max-age-days) unless
+--keep or --max-age-days is given. Backups are counted per original file.
+
+Examples:
+  # Keep the three newest backups of every file
+  claudius config backups prune --keep 3
+
+  # Preview what the configured policy would remove
+  claudius config backups prune --dry-run")]
+    Prune(BackupsPruneArgs),
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BackupsListArgs {
+    /// Only list backups of this file
+    #[arg(long, value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BackupsRestoreArgs {
+    /// Backup to restore, or a backed-up file to restore its newest backup
+    #[arg(value_name = "PATH", value_hint = clap::ValueHint::FilePath)]
+    pub path: PathBuf,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct BackupsPruneArgs {
+    /// Keep the newest N backups of each file (overrides [backups].keep)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub keep: Option<u64>,
+
+    /// Remove backups older than DAYS (overrides [backups].max-age-days)
+    #[arg(long, value_name = "DAYS")]
+    pub max_age_days: Option<u32>,
+
+    /// List the backups that would be removed without deleting them
+    #[arg(short, long)]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum McpCommands {
+    /// Add a server to mcpServers.json
+    #[command(long_about = "Add a server to mcpServers.json.
+
+A stdio server takes its program and arguments after `--`; a remote server
+takes --url and is written with `type: http` unless --type sse is given.
+--preset renders a definition from the preset catalog instead, filling its
+parameters from --set and recording the preset version so `config doctor` can
+report newer ones; --env and --header add to it. The definition is checked for
+its transport before anything is written, and the file keeps its key order and
+formatting. --agent and --exclude write the `x-claudius` targeting block that
+limits which agents receive the server.
+
+Examples:
+  # A stdio server with a secret resolved at launch
+  claudius mcp add github --env GITHUB_TOKEN=op://dev/github/token -- npx -y @modelcontextprotocol/server-github
+
+  # A remote server for Codex only, in the project layer, then sync
+  claudius mcp add linear --url https://mcp.linear.app/mcp --agent codex --project --sync
+
+  # A server from the preset catalog (see `claudius mcp presets`)
+  claudius mcp add db --preset postgres --set url=postgresql://localhost/app")]
+    Add(McpAddArgs),
+
+    /// Remove a server from mcpServers.json
+    Remove(McpRemoveArgs),
+
+    /// Rename a server in mcpServers.json, keeping its position
+    Rename(McpRenameArgs),
+
+    /// List the servers agents receive, with the layer each comes from
+    List(McpListArgs),
+
+    /// Print a server's definition as written in its source
+    Show(McpShowArgs),
+
+    /// List the presets `claudius mcp add --preset` can render
+    Presets(McpPresetsArgs),
+
+    /// Connect to MCP servers and run the MCP handshake against them
+    #[command(long_about = "Connect to MCP servers and run the MCP handshake against them.
+
+Each stdio server in mcpServers.json (including the project layer) is started
+the way an agent starts it: with its env and cwd, after ${NAME} interpolation,
+and through `claudius secrets run` when its env references op:// secrets. A url
+server is reached over streamable HTTP, or over the legacy HTTP+SSE transport
+when its type is `sse`, with its configured headers. The check sends
+`initialize` and `tools/list`, then stops the server or ends the session, and
+reports the server info, protocol version, advertised capabilities, tool count,
+and `initialize` latency. A failing server is reported with its error and, for
+stdio servers, the tail of its stderr. A remote server answering HTTP 401 is
+reported as requiring authorization.
+
+Exits with status 1 when any server fails; servers that only require
+authorization do not fail the check.
+
+Examples:
+  # Check every server
+  claudius mcp check
+
+  # Check one server with a longer timeout
+  claudius mcp check github --timeout 30")]
+    Check(McpCheckArgs),
+}
+
+/// Which mcpServers.json an edit applies to, and what happens afterwards
+#[derive(Args, Debug, Clone, Copy)]
+pub struct McpEditArgs {
+    /// Edit .claudius/mcpServers.json in the current project instead of the global file
+    #[arg(long)]
+    pub project: bool,
+
+    /// Run `claudius config sync` after the edit (--global unless --project; --prune for remove and rename)
+    #[arg(long)]
+    pub sync: bool,
+}
+
+#[derive(Args, Debug, Clone)]
+#[command(group(
+    clap::ArgGroup::new("transport").required(true).args(["command", "url", "preset"])
+))]
+pub struct McpAddArgs {
+    /// Name of the server
+    pub name: String,
+
+    /// Program and arguments of a stdio server
+    #[arg(last = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+
+    /// URL of a remote (http or sse) server
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Preset from the catalog to render the server from
+    #[arg(long, value_name = "PRESET")]
+    pub preset: Option<String>,
+
+    /// Value of a preset parameter (repeatable)
+    #[arg(
+        long = "set",
+        value_name = "KEY=VALUE",
+        value_parser = parse_key_value,
+        conflicts_with_all = ["command", "url"]
+    )]
+    pub set: Vec<(String, String)>,
+
+    /// Server type (default: stdio for a command, http for a url)
+    #[arg(
+        long = "type",
+        value_name = "TYPE",
+        value_parser = ["stdio", "http", "sse"],
+        conflicts_with = "preset"
+    )]
+    pub server_type: Option<String>,
+
+    /// Environment variable for a stdio server (repeatable)
+    #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub env: Vec<(String, String)>,
+
+    /// HTTP header for a remote server (repeatable)
+    #[arg(long = "header", value_name = "NAME=VALUE", value_parser = parse_key_value)]
+    pub headers: Vec<(String, String)>,
+
+    /// Only sync the server to this agent, as `agent` or `agent:scope` (repeatable)
+    #[arg(long = "agent", value_name = "SELECTOR")]
+    pub agents: Vec<String>,
+
+    /// Never sync the server to this agent, as `agent` or `agent:scope` (repeatable)
+    #[arg(long, value_name = "SELECTOR")]
+    pub exclude: Vec<String>,
+
+    /// Overwrite a server that already has this name
+    #[arg(long)]
+    pub replace: bool,
+
+    #[command(flatten)]
+    pub edit: McpEditArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct McpRemoveArgs {
+    /// Name of the server
+    pub name: String,
+
+    #[command(flatten)]
+    pub edit: McpEditArgs,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct McpRenameArgs {
+    /// Current name of the server
+    pub from: String,
+
+    /// New name of the server
+    pub to: String,
+
+    #[command(flatten)]
+    pub edit: McpEditArgs,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct McpListArgs {
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::OutputFormat,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct McpPresetsArgs {
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::OutputFormat,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct McpShowArgs {
+    /// Name of the server
+    pub name: String,
+
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::OutputFormat,
+}
+
+/// Parse a `KEY=VALUE` argument.
+fn parse_key_value(argument: &str) -> Result<(String, String), String> {
+    argument
+        .split_once('=')
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{argument}'"))
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct McpCheckArgs {
+    /// Server to check (default: every server)
+    pub name: Option<String>,
+
+    /// Seconds each server gets to complete the handshake
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    pub timeout: u64,
+
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::OutputFormat,
 }
 
 #[derive(Subcommand, Debug)]
@@ -446,8 +841,8 @@ pub struct ConfigSyncArgs {
     #[arg(short, long, env = "CLAUDIUS_CONFIG", value_hint = clap::ValueHint::FilePath)]
     pub config: Option<PathBuf>,
 
-    /// Preview changes without writing them
-    #[arg(short, long, help = "Preview changes without writing them")]
+    /// Preview changes as unified diffs without writing them
+    #[arg(short, long, help = "Preview changes as unified diffs without writing them")]
     pub dry_run: bool,
 
     /// Create timestamped backup before making changes
@@ -458,13 +853,61 @@ pub struct ConfigSyncArgs {
     )]
     pub backup: bool,
 
-    /// Remove stale auxiliary files that Claudius previously deployed
+    /// Remove stale MCP servers and auxiliary files that Claudius previously deployed
     #[arg(
         long,
-        help = "Remove stale skills, commands, and subagents previously deployed by Claudius"
+        help = "Remove stale MCP servers, skills, commands, and subagents previously deployed by Claudius"
     )]
     pub prune: bool,
 
+    /// How MCP servers are merged with those already in the target
+    #[arg(
+        long,
+        value_enum,
+        help = "How to merge MCP servers already in the target (default: [sync].strategy in config.toml, else interactive)"
+    )]
+    pub strategy: Option<crate::merge::MergeStrategy>,
+
+    /// Apply a named profile from config.toml and remember it for later syncs
+    #[arg(
+        long,
+        value_name = "NAME",
+        conflicts_with = "no_profile",
+        help = "Apply [profiles.<NAME>] from config.toml and remember it for later syncs"
+    )]
+    pub profile: Option<String>,
+
+    /// Sync without a profile and forget the remembered one
+    #[arg(
+        long,
+        help = "Sync every MCP server without profile overlays and forget the active profile"
+    )]
+    pub no_profile: bool,
+
+    /// Keep running and re-sync whenever Claudius sources change
+    #[arg(
+        long,
+        conflicts_with = "dry_run",
+        help = "Watch mcpServers.json, *.settings.*, skills/, commands/, and agents/ and re-sync on change"
+    )]
+    pub watch: bool,
+
+    /// Run the project-local sync in every project registered in config.toml
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "watch",
+            "target_config",
+            "global",
+            "codex_requirements",
+            "codex_managed_config",
+            "gemini_system",
+            "gemini_system_defaults",
+        ],
+        help = "Run the project-local sync in every [[projects]] entry of config.toml and report the results"
+    )]
+    pub all_projects: bool,
+
     /// Override target configuration file path
     #[arg(short = 'T', long, env = "TARGET_CONFIG_PATH", value_hint = clap::ValueHint::FilePath)]
     pub target_config: Option<PathBuf>,
@@ -517,6 +960,37 @@ pub struct ConfigSyncArgs {
         help = "Target Gemini CLI system-defaults.json (e.g. /etc/gemini-cli/system-defaults.json; global Gemini only)"
     )]
     pub gemini_system_defaults: bool,
+
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t, conflicts_with_all = ["watch", "all_projects"])]
+    pub format: crate::output::OutputFormat,
+}
+
+impl ConfigSyncArgs {
+    /// The sync run by `mcp add|remove|rename --sync`: global targets for an edit of the
+    /// global mcpServers.json, the project-local sync for a `--project` edit.
+    pub const fn for_mcp_edit(edit: McpEditArgs, prune: bool) -> Self {
+        Self {
+            config: None,
+            dry_run: false,
+            backup: false,
+            prune,
+            strategy: None,
+            profile: None,
+            no_profile: false,
+            watch: false,
+            all_projects: false,
+            target_config: None,
+            global: !edit.project,
+            agent: None,
+            scope: None,
+            codex_requirements: false,
+            codex_managed_config: false,
+            gemini_system: false,
+            gemini_system_defaults: false,
+            format: crate::output::OutputFormat::Text,
+        }
+    }
 }
 
 #[derive(Args, Debug, Clone, Copy)]
@@ -532,6 +1006,10 @@ pub struct ConfigValidateArgs {
     /// Treat warnings as errors (exit non-zero)
     #[arg(long)]
     pub strict: bool,
+
+    /// Print the result as text, one versioned JSON document, or a SARIF 2.1.0 log
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::ValidationFormat,
 }
 
 #[derive(Args, Debug, Clone, Copy)]
@@ -543,6 +1021,44 @@ pub struct ConfigMigrateArgs {
     /// Show pending migrations as diffs without writing anything
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::OutputFormat,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct ConfigStatusArgs {
+    /// Check agent global configuration instead of project-local files
+    #[arg(short, long)]
+    pub global: bool,
+
+    /// Specify the agent to check (overrides config file)
+    #[arg(short, long, value_enum)]
+    pub agent: Option<crate::app_config::Agent>,
+
+    /// Claude Code configuration scope (only valid with --agent claude-code)
+    #[arg(long, value_enum, help = "Claude Code scope: managed, user, project, or local")]
+    pub scope: Option<crate::app_config::ClaudeCodeScope>,
+
+    /// Merge strategy to evaluate (default: [sync].strategy in config.toml)
+    #[arg(long, value_enum)]
+    pub strategy: Option<crate::merge::MergeStrategy>,
+}
+
+#[derive(Args, Debug, Clone, Copy)]
+pub struct ConfigImportArgs {
+    /// Import from agent global configuration instead of project-local files
+    #[arg(short, long)]
+    pub global: bool,
+
+    /// Import only from a specific agent
+    #[arg(short, long, value_enum)]
+    pub agent: Option<crate::app_config::Agent>,
+
+    /// Preview the source changes as unified diffs without writing them
+    #[arg(short, long)]
+    pub dry_run: bool,
 }
 
 #[derive(Args, Debug, Clone, Copy)]
@@ -554,6 +1070,18 @@ pub struct ConfigDoctorArgs {
     /// Focus diagnostics on a specific agent
     #[arg(short, long, value_enum)]
     pub agent: Option<crate::app_config::Agent>,
+
+    /// Apply the mechanical remediations behind the findings, then re-run the doctor
+    #[arg(long)]
+    pub fix: bool,
+
+    /// With --fix, list the remediations without applying them
+    #[arg(short, long, requires = "fix")]
+    pub dry_run: bool,
+
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::OutputFormat,
 }
 
 #[derive(Args, Debug, Clone, Copy)]
@@ -578,9 +1106,21 @@ pub struct SkillsSyncArgs {
     #[arg(short, long, value_enum, help = "Agent to use: claude, claude-code, codex, or gemini")]
     pub agent: Option<crate::app_config::Agent>,
 
+    /// Keep running and re-sync whenever skill sources change
+    #[arg(
+        long,
+        conflicts_with = "dry_run",
+        help = "Watch skills/ and commands/ and re-sync on change"
+    )]
+    pub watch: bool,
+
     /// Deprecated no-op kept for backward compatibility
     #[arg(long, help = "Deprecated: Codex skills sync is enabled by default")]
     pub enable_codex_skills: bool,
+
+    /// Print the result as text or as one versioned JSON document
+    #[arg(long, value_enum, default_value_t, conflicts_with = "watch")]
+    pub format: crate::output::OutputFormat,
 }
 
 #[derive(Args, Debug, Clone, Copy)]
@@ -592,6 +1132,10 @@ pub struct SkillsValidateArgs {
     /// Treat warnings as errors
     #[arg(long)]
     pub strict: bool,
+
+    /// Print the result as text, one versioned JSON document, or a SARIF 2.1.0 log
+    #[arg(long, value_enum, default_value_t)]
+    pub format: crate::output::ValidationFormat,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -371,3 +371,63 @@ pub fn convert_mcp_to_toml<S: std::hash::BuildHasher>(
 
     toml_servers
 }
+
+/// Convert Codex `[mcp_servers]` tables back into the shared JSON format.
+///
+/// This is the inverse of [`convert_mcp_to_toml`]: `http_headers` become
+/// `headers`, URL servers are marked as streamable HTTP, and any other keys are
+/// carried over as extra fields. Entries that are not tables are skipped.
+#[must_use]
+pub fn convert_toml_to_mcp(
+    mcp_servers: &toml::map::Map<String, TomlValue>,
+) -> HashMap<String, crate::config::McpServerConfig> {
+    mcp_servers
+        .iter()
+        .filter_map(|(name, value)| {
+            value.as_table().map(|table| (name.clone(), convert_toml_server_to_mcp(table)))
+        })
+        .collect()
+}
+
+fn convert_toml_server_to_mcp(
+    table: &toml::map::Map<String, TomlValue>,
+) -> crate::config::McpServerConfig {
+    let string_of = |key: &str| table.get(key).and_then(TomlValue::as_str).map(str::to_string);
+    let string_table = |key: &str| {
+        table
+            .get(key)
+            .and_then(TomlValue::as_table)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|(k, v)| v.as_str().map(|text| (k.clone(), text.to_string())))
+                    .collect::<HashMap<_, _>>()
+            })
+            .unwrap_or_default()
+    };
+
+    let url = string_of("url");
+    let args = table
+        .get("args")
+        .and_then(TomlValue::as_array)
+        .map(|items| items.iter().filter_map(|item| item.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    let extra = table
+        .iter()
+        .filter(|(key, _)| {
+            !matches!(key.as_str(), "command" | "args" | "env" | "url" | "http_headers")
+        })
+        .filter_map(|(key, value)| serde_json::to_value(value).ok().map(|json| (key.clone(), json)))
+        .collect();
+
+    crate::config::McpServerConfig {
+        command: string_of("command"),
+        args,
+        env: string_table("env"),
+        server_type: url.as_ref().map(|_| "http".to_string()),
+        url,
+        headers: string_table("http_headers"),
+        targeting: None,
+        extra,
+    }
+}
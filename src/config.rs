@@ -1,13 +1,15 @@
 #![allow(clippy::self_named_module_files)]
 
-use crate::app_config::{AppConfig, CodexSkillTargetMode};
+use crate::app_config::{Agent, AppConfig, CodexSkillTargetMode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+pub mod json_document;
 pub mod reader;
+pub mod toml_document;
 pub mod writer;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -24,10 +26,72 @@ pub struct McpServerConfig {
     pub url: Option<String>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub headers: HashMap<String, String>,
+    /// Claudius-only `x-claudius` metadata; never written to agent targets.
+    #[serde(rename = "x-claudius", default, skip_serializing)]
+    pub targeting: Option<ServerTargeting>,
     #[serde(default, flatten)]
     pub extra: HashMap<String, Value>,
 }
 
+/// Which agents and scopes receive an MCP server.
+///
+/// Selectors are agent names (`claude`, `claude-code`, `codex`, `gemini`),
+/// optionally narrowed to a scope with `agent:scope`. Scopes are `global` and
+/// `project` for every agent, plus `managed`, `user`, and `local` for Claude
+/// Code. An empty `agents` list means every agent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServerTargeting {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+impl ServerTargeting {
+    /// Whether the server is synced to `agent` in a target addressed by `scopes`.
+    #[must_use]
+    pub fn includes(&self, agent: Agent, scopes: &[&str]) -> bool {
+        let matches = |selector: &String| {
+            parse_target_selector(selector).is_ok_and(|(selected, scope)| {
+                selected == agent && scope.is_none_or(|name| scopes.contains(&name))
+            })
+        };
+
+        (self.agents.is_empty() || self.agents.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+/// Parse an `agent` or `agent:scope` selector from an `x-claudius` block.
+///
+/// # Errors
+///
+/// Returns a description of the problem when the agent or scope is unknown.
+pub fn parse_target_selector(selector: &str) -> Result<(Agent, Option<&str>), String> {
+    let (agent_name, scope) = selector
+        .split_once(':')
+        .map_or((selector, None), |(name, scope)| (name, Some(scope)));
+    let agent = <Agent as clap::ValueEnum>::from_str(agent_name, false).map_err(|_| {
+        format!("unknown agent '{agent_name}' (expected: claude, claude-code, codex, gemini)")
+    })?;
+
+    if let Some(scope_name) = scope {
+        let allowed: &[&str] = if agent == Agent::ClaudeCode {
+            &["global", "project", "managed", "user", "local"]
+        } else {
+            &["global", "project"]
+        };
+        if !allowed.contains(&scope_name) {
+            return Err(format!(
+                "unknown scope '{scope_name}' for {agent_name} (expected: {})",
+                allowed.join(", ")
+            ));
+        }
+    }
+
+    Ok((agent, scope))
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct McpServersConfig {
     #[serde(rename = "mcpServers")]
@@ -99,6 +163,8 @@ pub struct Config {
     pub skills_target_dir: PathBuf,
     pub is_global: bool,
     pub agent: Option<crate::app_config::Agent>,
+    /// Project `.claudius/` directory layered over the global sources (project-local only).
+    pub project_layer_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -137,6 +203,9 @@ impl Config {
         let current_dir = if use_global { None } else { Some(std::env::current_dir()?) };
         let skills_target_dir =
             Self::determine_skills_dir(use_global, agent, &home_dir, current_dir.as_deref())?;
+        let project_layer_dir = current_dir.as_deref().and_then(|project_dir| {
+            crate::source_layers::discover_project_layer(project_dir, &config_dir)
+        });
 
         Ok(Self {
             mcp_servers_path: config_dir.join("mcpServers.json"),
@@ -148,6 +217,7 @@ impl Config {
             skills_target_dir,
             is_global: use_global,
             agent,
+            project_layer_dir,
         })
     }
 
@@ -265,6 +335,30 @@ impl Config {
         fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_some())
     }
 
+    /// The project layer counterpart of `settings_path`, when that file exists.
+    #[must_use]
+    pub fn project_layer_settings_path(&self) -> Option<PathBuf> {
+        let layer_dir = self.project_layer_dir.as_deref()?;
+        let file_name = self.settings_path.file_name()?;
+        let candidate = if file_name == "settings.json" || file_name == "claude.settings.json" {
+            Self::resolve_claude_settings_source_path(layer_dir)
+        } else {
+            layer_dir.join(file_name)
+        };
+        candidate.exists().then_some(candidate)
+    }
+
+    /// Source directories for an auxiliary tree, global first and project layer last.
+    #[must_use]
+    pub fn layered_source_dirs(&self, global_dir: Option<PathBuf>, relative: &str) -> Vec<PathBuf> {
+        let project_dir = self
+            .project_layer_dir
+            .as_deref()
+            .map(|layer_dir| layer_dir.join(relative))
+            .filter(|candidate| Self::skills_dir_has_entries(candidate));
+        global_dir.into_iter().chain(project_dir).collect()
+    }
+
     #[must_use]
     pub fn resolve_gemini_commands_source_dir(&self) -> Option<PathBuf> {
         if self.agent != Some(crate::app_config::Agent::Gemini) {
@@ -399,6 +493,7 @@ impl Config {
             skills_target_dir,
             is_global: true,
             agent: None,
+            project_layer_dir: None,
         }
     }
 
@@ -419,6 +514,7 @@ impl Config {
             skills_target_dir,
             is_global: true,
             agent: None,
+            project_layer_dir: None,
         }
     }
 
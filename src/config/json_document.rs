@@ -0,0 +1,538 @@
+//! Format-preserving updates of JSON target documents.
+//!
+//! Files such as `~/.claude.json` are also written by the agents themselves,
+//! so re-serializing them would normalize indentation and churn unrelated
+//! keys. [`update_json_document`] patches the existing text instead: values
+//! that already match keep their original bytes, and only the members whose
+//! data changed are rewritten using the document's own indentation.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::ops::Range;
+use tracing::debug;
+
+const DEFAULT_INDENT: &str = "  ";
+
+/// Rewrite `existing` so its data equals `desired`, touching as few bytes as possible.
+///
+/// # Errors
+///
+/// Returns an error if `existing` is not valid JSON.
+pub fn update_json_document(existing: &str, desired: &Value) -> Result<String> {
+    serde_json::from_str::<Value>(existing).context("Existing JSON document is not valid")?;
+
+    let mut parser = Parser { text: existing, pos: 0 };
+    parser.skip_whitespace();
+    let root = parser.parse_node()?;
+    let editor = Editor { text: existing, indent_unit: detect_indent_unit(existing) };
+
+    let mut output = String::with_capacity(existing.len());
+    output.push_str(existing.get(..root.span.start).unwrap_or_default());
+    output.push_str(&editor.render(&root, desired)?);
+    output.push_str(existing.get(root.span.end..).unwrap_or_default());
+    Ok(output)
+}
+
+/// Render `value` as JSON, patching `existing` in place when it is a valid document.
+///
+/// Falls back to a fresh pretty-printed document when there is no existing
+/// content or it cannot be parsed.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be serialized.
+pub fn render_json<T: Serialize>(existing: Option<&str>, value: &T) -> Result<String> {
+    let desired = serde_json::to_value(value).context("Failed to serialize JSON")?;
+    let updated = existing.and_then(|current| {
+        update_json_document(current, &desired)
+            .inspect_err(|error| debug!("Rewriting JSON document from scratch: {error:#}"))
+            .ok()
+    });
+    updated.map_or_else(
+        || serde_json::to_string_pretty(&desired).context("Failed to serialize JSON"),
+        Ok,
+    )
+}
+
+/// Rename member `from` of the object at `path` to `to`, keeping its position and bytes.
+///
+/// # Errors
+///
+/// Returns an error if `existing` is not valid JSON or has no such member.
+pub fn rename_json_member(existing: &str, path: &[&str], from: &str, to: &str) -> Result<String> {
+    serde_json::from_str::<Value>(existing).context("Existing JSON document is not valid")?;
+
+    let mut parser = Parser { text: existing, pos: 0 };
+    parser.skip_whitespace();
+    let mut node = parser.parse_node()?;
+    for key in path {
+        node = find_member(node, key)
+            .with_context(|| format!("JSON document has no `{key}` object"))?
+            .value;
+    }
+    let member =
+        find_member(node, from).with_context(|| format!("JSON document has no `{from}` member"))?;
+
+    let mut key_parser = Parser { text: existing, pos: member.start };
+    key_parser.skip_string()?;
+    let encoded_key = serde_json::to_string(to).context("Failed to encode key")?;
+    Ok(format!(
+        "{}{encoded_key}{}",
+        existing.get(..member.start).unwrap_or_default(),
+        existing.get(key_parser.pos..).unwrap_or_default()
+    ))
+}
+
+fn find_member(node: Node, key: &str) -> Option<Member> {
+    node.members?.into_iter().find(|member| member.key == key)
+}
+
+/// A parsed JSON value together with its byte range in the source text.
+struct Node {
+    span: Range<usize>,
+    members: Option<Vec<Member>>,
+}
+
+struct Member {
+    key: String,
+    start: usize,
+    value: Node,
+}
+
+struct ObjectLayout {
+    leading: String,
+    separator: String,
+    trailing: String,
+    colon: String,
+    member_indent: String,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn advance(&mut self) {
+        self.pos = self.pos.saturating_add(1);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.advance();
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.peek() != Some(byte) {
+            bail!("Expected '{}' at byte {}", char::from(byte), self.pos);
+        }
+        self.advance();
+        Ok(())
+    }
+
+    fn parse_node(&mut self) -> Result<Node> {
+        let start = self.pos;
+        let members = match self.peek() {
+            Some(b'{') => Some(self.parse_object()?),
+            Some(b'[') => {
+                self.parse_array()?;
+                None
+            },
+            Some(b'"') => {
+                self.skip_string()?;
+                None
+            },
+            Some(_) => {
+                self.skip_scalar();
+                None
+            },
+            None => bail!("Unexpected end of JSON document"),
+        };
+        Ok(Node { span: start..self.pos, members })
+    }
+
+    fn parse_object(&mut self) -> Result<Vec<Member>> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.advance();
+            return Ok(members);
+        }
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            self.skip_string()?;
+            let key: String =
+                serde_json::from_str(self.text.get(start..self.pos).unwrap_or_default())
+                    .context("Invalid JSON object key")?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            self.skip_whitespace();
+            let value = self.parse_node()?;
+            members.push(Member { key, start, value });
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                self.advance();
+            } else {
+                self.expect(b'}')?;
+                return Ok(members);
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<()> {
+        self.expect(b'[')?;
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.advance();
+            return Ok(());
+        }
+        loop {
+            self.skip_whitespace();
+            self.parse_node()?;
+            self.skip_whitespace();
+            if self.peek() == Some(b',') {
+                self.advance();
+            } else {
+                return self.expect(b']');
+            }
+        }
+    }
+
+    fn skip_string(&mut self) -> Result<()> {
+        self.expect(b'"')?;
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    self.advance();
+                    return Ok(());
+                },
+                Some(b'\\') => {
+                    self.advance();
+                    self.advance();
+                },
+                Some(_) => self.advance(),
+                None => bail!("Unterminated JSON string"),
+            }
+        }
+    }
+
+    fn skip_scalar(&mut self) {
+        while let Some(byte) = self.peek() {
+            if matches!(byte, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r') {
+                break;
+            }
+            self.advance();
+        }
+    }
+}
+
+struct Editor<'a> {
+    text: &'a str,
+    indent_unit: String,
+}
+
+impl Editor<'_> {
+    fn slice(&self, range: Range<usize>) -> &str {
+        self.text.get(range).unwrap_or_default()
+    }
+
+    fn render(&self, node: &Node, desired: &Value) -> Result<String> {
+        let original = self.slice(node.span.clone());
+        let current: Value = serde_json::from_str(original).context("Invalid JSON value")?;
+        if &current == desired {
+            return Ok(original.to_string());
+        }
+
+        match (&node.members, desired) {
+            (Some(members), Value::Object(entries)) => {
+                let same_keys = members.len() == entries.len()
+                    && members.iter().all(|member| entries.contains_key(&member.key));
+                if same_keys {
+                    self.patch_members(node, members, entries)
+                } else {
+                    self.rebuild_object(node, members, entries)
+                }
+            },
+            _ => self.render_new(desired, &self.line_indent(node.span.start)),
+        }
+    }
+
+    /// Replace changed member values in place, keeping every separator intact.
+    fn patch_members(
+        &self,
+        node: &Node,
+        members: &[Member],
+        entries: &serde_json::Map<String, Value>,
+    ) -> Result<String> {
+        let mut output = String::new();
+        let mut cursor = node.span.start;
+        for member in members {
+            let Some(value) = entries.get(&member.key) else {
+                continue;
+            };
+            output.push_str(self.slice(cursor..member.value.span.start));
+            output.push_str(&self.render(&member.value, value)?);
+            cursor = member.value.span.end;
+        }
+        output.push_str(self.slice(cursor..node.span.end));
+        Ok(output)
+    }
+
+    /// Reassemble an object whose key set changed, reusing its original spacing.
+    fn rebuild_object(
+        &self,
+        node: &Node,
+        members: &[Member],
+        entries: &serde_json::Map<String, Value>,
+    ) -> Result<String> {
+        let layout = self.object_layout(node, members);
+        // Objects written on a single line stay on a single line.
+        let single_line = !layout.separator.contains('\n');
+
+        let mut pieces = Vec::new();
+        for member in members {
+            if let Some(value) = entries.get(&member.key) {
+                let key_and_colon = self.slice(member.start..member.value.span.start);
+                pieces.push(format!("{key_and_colon}{}", self.render(&member.value, value)?));
+            }
+        }
+        // New keys are appended in sorted order so the output does not depend on how the
+        // desired value was assembled.
+        let mut added: Vec<(&String, &Value)> = entries
+            .iter()
+            .filter(|(key, _)| members.iter().all(|member| &member.key != *key))
+            .collect();
+        added.sort_by(|left, right| left.0.cmp(right.0));
+        for (key, value) in added {
+            let encoded_key = serde_json::to_string(key).context("Failed to encode key")?;
+            let rendered = if single_line {
+                render_inline(value, &layout.colon, &layout.separator)?
+            } else {
+                self.render_new(value, &layout.member_indent)?
+            };
+            pieces.push(format!("{encoded_key}{}{rendered}", layout.colon));
+        }
+
+        if pieces.is_empty() {
+            return Ok("{}".to_string());
+        }
+        Ok(format!("{{{}{}{}}}", layout.leading, pieces.join(&layout.separator), layout.trailing))
+    }
+
+    /// Spacing used between the braces and members of an existing object.
+    fn object_layout(&self, node: &Node, members: &[Member]) -> ObjectLayout {
+        let object_indent = self.line_indent(node.span.start);
+        let open = node.span.start.saturating_add(1);
+        let close = node.span.end.saturating_sub(1);
+        if let (Some(first), Some(last)) = (members.first(), members.last()) {
+            let leading = self.slice(open..first.start).to_string();
+            let colon = self.colon_spacing(first);
+            let separator = members.get(1).map_or_else(
+                || {
+                    if leading.contains('\n') {
+                        format!(",{leading}")
+                    } else if colon == ":" {
+                        // A compact `"key":value` document gets compact separators too.
+                        ",".to_string()
+                    } else {
+                        ", ".to_string()
+                    }
+                },
+                |second| self.slice(first.value.span.end..second.start).to_string(),
+            );
+            ObjectLayout {
+                member_indent: self.line_indent(first.start),
+                trailing: self.slice(last.value.span.end..close).to_string(),
+                colon,
+                leading,
+                separator,
+            }
+        } else {
+            let member_indent = format!("{object_indent}{}", self.indent_unit);
+            ObjectLayout {
+                leading: format!("\n{member_indent}"),
+                separator: format!(",\n{member_indent}"),
+                trailing: format!("\n{object_indent}"),
+                colon: ": ".to_string(),
+                member_indent,
+            }
+        }
+    }
+
+    fn colon_spacing(&self, member: &Member) -> String {
+        let between = self.slice(member.start..member.value.span.start);
+        between
+            .rfind(':')
+            .and_then(|index| between.get(index..))
+            .unwrap_or(": ")
+            .to_string()
+    }
+
+    /// Whitespace that precedes the first non-blank character on the line containing `offset`.
+    fn line_indent(&self, offset: usize) -> String {
+        let line_start =
+            self.slice(0..offset).rfind('\n').map_or(0, |index| index.saturating_add(1));
+        self.slice(line_start..offset)
+            .chars()
+            .take_while(|c| matches!(c, ' ' | '\t'))
+            .collect()
+    }
+
+    fn render_new(&self, value: &Value, indent: &str) -> Result<String> {
+        let mut buffer = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent_unit.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        value.serialize(&mut serializer).context("Failed to serialize JSON value")?;
+        let rendered = String::from_utf8(buffer).context("Serialized JSON is not UTF-8")?;
+        Ok(rendered.replace('\n', &format!("\n{indent}")))
+    }
+}
+
+/// Render a value on one line, using the colon and member separator of the enclosing object.
+fn render_inline(value: &Value, colon: &str, separator: &str) -> Result<String> {
+    match value {
+        Value::Object(entries) if !entries.is_empty() => {
+            let members = entries
+                .iter()
+                .map(|(key, member)| {
+                    let encoded_key = serde_json::to_string(key).context("Failed to encode key")?;
+                    Ok(format!("{encoded_key}{colon}{}", render_inline(member, colon, separator)?))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!("{{{}}}", members.join(separator)))
+        },
+        Value::Array(items) if !items.is_empty() => {
+            let rendered = items
+                .iter()
+                .map(|item| render_inline(item, colon, separator))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!("[{}]", rendered.join(separator)))
+        },
+        _ => serde_json::to_string(value).context("Failed to serialize JSON value"),
+    }
+}
+
+fn detect_indent_unit(text: &str) -> String {
+    text.lines()
+        .map(|line| line.chars().take_while(|c| matches!(c, ' ' | '\t')).collect::<String>())
+        .find(|indent| !indent.is_empty())
+        .unwrap_or_else(|| DEFAULT_INDENT.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn unchanged_document_is_returned_verbatim() {
+        let existing = "{\n    \"theme\": \"dark\",\n    \"numStartups\": 3\n}\n";
+        let desired = json!({"numStartups": 3, "theme": "dark"});
+
+        let updated = update_json_document(existing, &desired).expect("update");
+        assert_eq!(updated, existing);
+    }
+
+    #[test]
+    fn changed_value_only_rewrites_that_member() {
+        let existing = "{\n    \"theme\":\"dark\",\n    \"mcpServers\": {\"a\": {\"command\": \"x\"}},\n    \"tips\": [1,2]\n}";
+        let desired = json!({
+            "theme": "dark",
+            "mcpServers": {"a": {"command": "y"}},
+            "tips": [1, 2]
+        });
+
+        let updated = update_json_document(existing, &desired).expect("update");
+        assert_eq!(
+            updated,
+            "{\n    \"theme\":\"dark\",\n    \"mcpServers\": {\"a\": {\"command\": \"y\"}},\n    \"tips\": [1,2]\n}"
+        );
+    }
+
+    #[test]
+    fn added_and_removed_members_use_document_indentation() {
+        let existing = "{\n    \"theme\": \"dark\",\n    \"mcpServers\": {\n        \"old\": {\"command\": \"o\"}\n    }\n}\n";
+        let desired = json!({
+            "theme": "dark",
+            "mcpServers": {"new": {"command": "n"}}
+        });
+
+        let updated = update_json_document(existing, &desired).expect("update");
+        assert_eq!(
+            updated,
+            "{\n    \"theme\": \"dark\",\n    \"mcpServers\": {\n        \"new\": {\n            \"command\": \"n\"\n        }\n    }\n}\n"
+        );
+        let reparsed: Value = serde_json::from_str(&updated).expect("valid JSON");
+        assert_eq!(reparsed, desired);
+    }
+
+    #[test]
+    fn members_can_be_added_to_an_empty_object_and_removed_entirely() {
+        let added = update_json_document("{}", &json!({"a": 1})).expect("add");
+        assert_eq!(added, "{\n  \"a\": 1\n}");
+
+        let removed = update_json_document("{\n  \"a\": 1\n}", &json!({})).expect("remove");
+        assert_eq!(removed, "{}");
+    }
+
+    #[test]
+    fn single_line_objects_stay_on_one_line() {
+        let existing = r#"{"mcpServers": {"alpha": {"command": "a"}}}"#;
+        let desired = json!({"mcpServers": {"alpha": {"command": "a"}, "beta": {"command": "b"}}});
+
+        let updated = update_json_document(existing, &desired).expect("update");
+        assert_eq!(
+            updated,
+            r#"{"mcpServers": {"alpha": {"command": "a"}, "beta": {"command": "b"}}}"#
+        );
+    }
+
+    #[test]
+    fn single_line_objects_keep_their_compact_separators() {
+        let existing = r#"{"mcpServers":{"alpha":{"command":"a"}}}"#;
+        let desired =
+            json!({"mcpServers": {"alpha": {"command": "a"}, "beta": {"args": ["x", "y"]}}});
+
+        let updated = update_json_document(existing, &desired).expect("update");
+        assert_eq!(
+            updated,
+            r#"{"mcpServers":{"alpha":{"command":"a"},"beta":{"args":["x","y"]}}}"#
+        );
+    }
+
+    #[test]
+    fn new_keys_are_added_in_sorted_order() {
+        let existing = "{\n  \"a\": 1\n}";
+        let desired = json!({"a": 1, "zeta": 2, "beta": 3});
+
+        let updated = update_json_document(existing, &desired).expect("update");
+        assert_eq!(updated, "{\n  \"a\": 1,\n  \"beta\": 3,\n  \"zeta\": 2\n}");
+    }
+
+    #[test]
+    fn render_json_falls_back_to_pretty_output_for_invalid_documents() {
+        let rendered = render_json(Some("{ not json"), &json!({"a": 1})).expect("render");
+        assert_eq!(rendered, "{\n  \"a\": 1\n}");
+    }
+
+    #[test]
+    fn rename_keeps_the_member_in_place() {
+        let existing = "{\n  \"mcpServers\": {\n    \"a\": {\"command\": \"x\"},\n    \"b\": {\"command\": \"y\"}\n  }\n}\n";
+
+        let renamed = rename_json_member(existing, &["mcpServers"], "a", "z").expect("rename");
+        assert_eq!(
+            renamed,
+            "{\n  \"mcpServers\": {\n    \"z\": {\"command\": \"x\"},\n    \"b\": {\"command\": \"y\"}\n  }\n}\n"
+        );
+        assert!(rename_json_member(existing, &["mcpServers"], "missing", "z").is_err());
+        assert!(rename_json_member(existing, &["servers"], "a", "z").is_err());
+    }
+}
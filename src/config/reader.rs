@@ -112,6 +112,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
         McpServersConfig { mcp_servers: servers }
@@ -195,6 +196,7 @@ mod tests {
                     url: None,
                     headers: HashMap::new(),
                     extra: HashMap::new(),
+                    targeting: None,
                 },
             )])),
             other: HashMap::from([("key".to_string(), serde_json::json!("value"))]),
@@ -0,0 +1,188 @@
+//! Format-preserving updates of TOML target documents.
+//!
+//! Agent targets such as `~/.codex/config.toml` are hand-edited files. Instead
+//! of re-serializing the whole document, [`update_toml_document`] edits the
+//! existing document in place with `toml_edit` so that comments, key order,
+//! whitespace and inline tables survive for every entry whose value does not
+//! change.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use toml::Value as TomlValue;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, InlineTable, Item, Table, TableLike, Value};
+use tracing::debug;
+
+/// Rewrite `existing` so its data equals `desired`.
+///
+/// Keys whose values already match are left byte-for-byte intact, changed
+/// scalars keep their surrounding comments, keys missing from `desired` are
+/// removed, and new keys are appended to the table that owns them.
+///
+/// # Errors
+///
+/// Returns an error if `existing` is not valid TOML.
+pub fn update_toml_document(existing: &str, desired: &toml::Table) -> Result<String> {
+    let mut document: DocumentMut =
+        existing.parse().context("Existing TOML document is not valid")?;
+    sync_table_like(document.as_table_mut(), desired)?;
+    Ok(document.to_string())
+}
+
+/// Render `value` as TOML, editing `existing` in place when it is a valid document.
+///
+/// Falls back to a fresh pretty-printed document when there is no existing
+/// content or it cannot be parsed.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be represented as a TOML table.
+pub fn render_toml<T: Serialize>(existing: Option<&str>, value: &T) -> Result<String> {
+    let desired = toml::Table::try_from(value).context("Failed to serialize TOML")?;
+    let updated = existing.and_then(|current| {
+        update_toml_document(current, &desired)
+            .inspect_err(|error| debug!("Rewriting TOML document from scratch: {error:#}"))
+            .ok()
+    });
+    updated.map_or_else(|| toml::to_string_pretty(&desired).context("Failed to serialize TOML"), Ok)
+}
+
+fn sync_table_like(table: &mut dyn TableLike, desired: &toml::Table) -> Result<()> {
+    let removed_keys = table
+        .iter()
+        .map(|(key, _)| key.to_string())
+        .filter(|key| !desired.contains_key(key))
+        .collect::<Vec<_>>();
+    for key in removed_keys {
+        table.remove(&key);
+    }
+
+    for (key, value) in desired {
+        match table.get_mut(key) {
+            Some(item) => sync_item(item, value)?,
+            None => {
+                table.insert(key, new_item(value));
+            },
+        }
+    }
+    Ok(())
+}
+
+fn sync_item(item: &mut Item, desired: &TomlValue) -> Result<()> {
+    if let (TomlValue::Table(desired_table), Some(table_like)) = (desired, item.as_table_like_mut())
+    {
+        return sync_table_like(table_like, desired_table);
+    }
+
+    if item_equals(item, desired)? {
+        return Ok(());
+    }
+
+    if let Some(existing) = item.as_value() {
+        let mut replacement = new_value(desired);
+        *replacement.decor_mut() = existing.decor().clone();
+        *item = Item::Value(replacement);
+    } else {
+        *item = new_item(desired);
+    }
+    Ok(())
+}
+
+fn item_equals(item: &Item, desired: &TomlValue) -> Result<bool> {
+    let mut document = DocumentMut::new();
+    document.insert("value", item.clone());
+    let parsed: toml::Table =
+        toml::from_str(&document.to_string()).context("Failed to read existing TOML value")?;
+    Ok(parsed.get("value") == Some(desired))
+}
+
+fn new_item(value: &TomlValue) -> Item {
+    match value {
+        TomlValue::Table(entries) => {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            for (key, entry) in entries {
+                table.insert(key, new_item(entry));
+            }
+            Item::Table(table)
+        },
+        TomlValue::Array(entries)
+            if !entries.is_empty() && entries.iter().all(TomlValue::is_table) =>
+        {
+            let mut array = ArrayOfTables::new();
+            for entry in entries {
+                if let Item::Table(table) = new_item(entry) {
+                    array.push(table);
+                }
+            }
+            Item::ArrayOfTables(array)
+        },
+        _ => Item::Value(new_value(value)),
+    }
+}
+
+fn new_value(value: &TomlValue) -> Value {
+    match value {
+        TomlValue::String(text) => Value::from(text.as_str()),
+        TomlValue::Integer(number) => Value::from(*number),
+        TomlValue::Float(number) => Value::from(*number),
+        TomlValue::Boolean(flag) => Value::from(*flag),
+        TomlValue::Datetime(datetime) => {
+            let text = datetime.to_string();
+            text.parse::<toml_edit::Datetime>()
+                .map_or_else(|_| Value::from(text), Value::from)
+        },
+        TomlValue::Array(entries) => Value::Array(entries.iter().map(new_value).collect::<Array>()),
+        TomlValue::Table(entries) => Value::InlineTable(
+            entries
+                .iter()
+                .map(|(key, entry)| (key.clone(), new_value(entry)))
+                .collect::<InlineTable>(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn desired_from(content: &str) -> toml::Table {
+        toml::from_str(content).expect("desired TOML should parse")
+    }
+
+    #[test]
+    fn unchanged_document_is_preserved_byte_for_byte() {
+        let existing = "# Codex\nmodel = \"o3\" # pinned\n\n[mcp_servers.alpha]\ncommand = \"a\"\nenv = { KEY = \"1\" }\n";
+        let desired = desired_from(existing);
+
+        let updated = update_toml_document(existing, &desired).expect("update");
+        assert_eq!(updated, existing);
+    }
+
+    #[test]
+    fn changed_value_keeps_comments_and_inline_tables() {
+        let existing = "# Codex\nmodel = \"o3\" # pinned\n\n[mcp_servers.alpha]\ncommand = \"a\"\nenv = { KEY = \"1\" }\n";
+        let desired = desired_from(
+            "model = \"gpt-5\"\n[mcp_servers.alpha]\ncommand = \"a\"\nenv = { KEY = \"2\" }\n",
+        );
+
+        let updated = update_toml_document(existing, &desired).expect("update");
+        assert_eq!(
+            updated,
+            "# Codex\nmodel = \"gpt-5\" # pinned\n\n[mcp_servers.alpha]\ncommand = \"a\"\nenv = { KEY = \"2\" }\n"
+        );
+    }
+
+    #[test]
+    fn removed_and_added_servers_leave_other_entries_untouched() {
+        let existing = "model = \"o3\"\n\n# keep me\n[mcp_servers.alpha]\ncommand = \"a\"\n\n[mcp_servers.beta]\ncommand = \"b\"\n";
+        let desired = desired_from(
+            "model = \"o3\"\n[mcp_servers.alpha]\ncommand = \"a\"\n[mcp_servers.gamma]\ncommand = \"c\"\nargs = [\"-y\"]\n",
+        );
+
+        let updated = update_toml_document(existing, &desired).expect("update");
+        assert!(updated.contains("# keep me\n[mcp_servers.alpha]\ncommand = \"a\"\n"));
+        assert!(!updated.contains("beta"));
+        assert!(updated.contains("[mcp_servers.gamma]\nargs = [\"-y\"]\ncommand = \"c\"\n"));
+        assert_eq!(desired_from(&updated), desired);
+    }
+}
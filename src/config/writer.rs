@@ -1,4 +1,7 @@
+use super::json_document::render_json;
+use super::toml_document::render_toml;
 use super::{ClaudeConfig, McpServersConfig, Settings};
+use crate::backups::{self, BackupSettings};
 use crate::codex_settings::CodexSettings;
 use anyhow::Context;
 use chrono::Local;
@@ -8,6 +11,10 @@ use std::path::{Path, PathBuf};
 
 /// Write Claude configuration to a JSON file
 ///
+/// An existing file such as `~/.claude.json` is patched in place: only the
+/// members whose data changed are rewritten, and the file is replaced
+/// atomically with its permissions preserved.
+///
 /// # Errors
 ///
 /// Returns an error if:
@@ -15,38 +22,61 @@ use std::path::{Path, PathBuf};
 /// - Unable to serialize the configuration
 /// - Unable to write to the file
 pub fn write_claude_config<P: AsRef<Path>>(path: P, config: &ClaudeConfig) -> anyhow::Result<()> {
-    let path_ref = path.as_ref();
-
-    // Ensure parent directory exists
-    if let Some(parent) = path_ref.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    let json = serde_json::to_string_pretty(config)?;
-    fs::write(path_ref, json)?;
+    write_json_document(path.as_ref(), config)
+}
 
-    Ok(())
+fn write_json_document<T: serde::Serialize>(path: &Path, value: &T) -> anyhow::Result<()> {
+    let existing = fs::read_to_string(path).ok();
+    let json = render_json(existing.as_deref(), value)?;
+    atomic_write(path, json.as_bytes(), FileMode::Preserve)
 }
 
 /// Create a backup of a file with timestamp
 ///
+/// The backup is written next to the file, or under the central backup
+/// directory when `settings` selects one, and the retention policy is applied
+/// afterwards.
+///
 /// # Errors
 ///
 /// Returns an error if unable to copy the file
-pub fn backup_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Option<String>> {
+pub fn backup_file<P: AsRef<Path>>(
+    path: P,
+    settings: &BackupSettings,
+) -> anyhow::Result<Option<String>> {
     let path_ref = path.as_ref();
+    let backup = copy_to_backup(path_ref, settings)?;
+    if backup.is_some() {
+        if let Err(error) = backups::enforce_retention(path_ref, settings, None) {
+            tracing::warn!("Failed to apply backup retention: {error:#}");
+        }
+    }
+    Ok(backup)
+}
 
+/// Create a backup of a file with timestamp, without applying retention
+///
+/// # Errors
+///
+/// Returns an error if unable to copy the file
+pub fn copy_to_backup(
+    path_ref: &Path,
+    settings: &BackupSettings,
+) -> anyhow::Result<Option<String>> {
     if !path_ref.exists() {
         return Ok(None);
     }
 
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
+    let timestamp = Local::now().format("%Y%m%d_%H%M%S").to_string();
     let file_name = path_ref.file_name().and_then(|name| name.to_str()).unwrap_or("claude.json");
     let source_metadata = fs::metadata(path_ref)?;
+    let directory = backups::backup_directory(path_ref, settings);
+    fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create backup directory: {}", directory.display()))?;
 
     let mut suffix = 0_u64;
     loop {
-        let backup_path = backup_path(path_ref, file_name, &timestamp.to_string(), suffix);
+        let backup_path = directory.join(backups::backup_file_name(file_name, &timestamp, suffix));
         match OpenOptions::new().write(true).create_new(true).open(&backup_path) {
             Ok(mut backup) => {
                 let backup_result = (|| -> anyhow::Result<()> {
@@ -74,16 +104,21 @@ pub fn backup_file<P: AsRef<Path>>(path: P) -> anyhow::Result<Option<String>> {
     }
 }
 
-fn backup_path(path: &Path, file_name: &str, timestamp: &str, suffix: u64) -> PathBuf {
-    let suffix_text = if suffix > 0 { format!(".{suffix}") } else { String::new() };
-    path.with_file_name(format!("{file_name}.backup.{timestamp}{suffix_text}"))
+/// Permissions given to files written by [`atomic_write`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileMode {
+    /// Keep the permissions of an existing file; new files get the default
+    /// permissions the process umask allows.
+    Preserve,
+    /// Owner read/write only (`0600` on Unix), for copies of targets that may
+    /// hold credentials. Missing parent directories are created through
+    /// [`create_private_dir_all`].
+    Private,
 }
 
 /// Atomically replace a file while preserving its permissions.
 ///
-/// The temporary file is created beside the destination to keep the final
-/// rename on the same filesystem. If `path` is a symlink, its resolved target
-/// is replaced so the symlink itself remains intact.
+/// Shorthand for [`atomic_write`] with [`FileMode::Preserve`].
 ///
 /// # Errors
 ///
@@ -91,21 +126,66 @@ fn backup_path(path: &Path, file_name: &str, timestamp: &str, suffix: u64) -> Pa
 /// written or synchronized, permissions cannot be copied, or the atomic
 /// replacement fails.
 pub fn atomic_write_preserving_permissions(path: &Path, content: &[u8]) -> anyhow::Result<()> {
-    let destination = resolve_write_destination(path)?;
-    let metadata = fs::metadata(&destination)
-        .with_context(|| format!("Failed to read metadata for {}", destination.display()))?;
-    let parent = destination.parent().ok_or_else(|| {
-        anyhow::anyhow!(
-            "Cannot atomically replace path without a parent: {}",
-            destination.display()
-        )
-    })?;
-
-    let mut temporary = tempfile::NamedTempFile::new_in(parent)
+    atomic_write(path, content, FileMode::Preserve)
+}
+
+/// Atomically write a file, creating it and its parent directory when missing.
+///
+/// The temporary file is created beside the destination to keep the final
+/// rename on the same filesystem. If `path` is a symlink, its resolved target
+/// is replaced so the symlink itself remains intact. `mode` decides the
+/// permissions of the result.
+///
+/// # Errors
+///
+/// Returns an error if the parent directory cannot be created, metadata
+/// cannot be read, or the file cannot be staged, synchronized, or renamed
+/// into place.
+pub fn atomic_write(path: &Path, content: &[u8], mode: FileMode) -> anyhow::Result<()> {
+    let destination = if fs::symlink_metadata(path).is_ok() {
+        resolve_write_destination(path)?
+    } else {
+        path.to_path_buf()
+    };
+    let parent = match destination.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let preserved = match (mode, destination.exists()) {
+        (FileMode::Preserve, true) => Some(
+            fs::metadata(&destination)
+                .with_context(|| format!("Failed to read metadata for {}", destination.display()))?
+                .permissions(),
+        ),
+        (FileMode::Preserve, false) => {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+            None
+        },
+        (FileMode::Private, _) => {
+            if !parent.is_dir() {
+                create_private_dir_all(parent)?;
+            }
+            None
+        },
+    };
+
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".claudius-write-");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let create_mode = if mode == FileMode::Private { 0o600 } else { 0o666 };
+        builder.permissions(fs::Permissions::from_mode(create_mode));
+    }
+    let mut temporary = builder
+        .tempfile_in(parent)
         .with_context(|| format!("Failed to create temporary file in {}", parent.display()))?;
     temporary.write_all(content)?;
     temporary.flush()?;
-    temporary.as_file().set_permissions(metadata.permissions())?;
+    if let Some(permissions) = preserved {
+        temporary.as_file().set_permissions(permissions)?;
+    }
     temporary.as_file().sync_all()?;
     temporary
         .persist(&destination)
@@ -115,6 +195,31 @@ pub fn atomic_write_preserving_permissions(path: &Path, content: &[u8]) -> anyho
     Ok(())
 }
 
+/// Create `path` and its missing parents with mode `0700` on Unix, and
+/// restrict `path` itself to `0700` if it already existed.
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be created or its permissions set.
+pub fn create_private_dir_all(path: &Path) -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(path)
+            .with_context(|| format!("Failed to create directory: {}", path.display()))?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o700))
+            .with_context(|| format!("Failed to restrict permissions of {}", path.display()))
+    }
+    #[cfg(not(unix))]
+    {
+        fs::create_dir_all(path)
+            .with_context(|| format!("Failed to create directory: {}", path.display()))
+    }
+}
+
 fn resolve_write_destination(path: &Path) -> anyhow::Result<PathBuf> {
     let metadata = fs::symlink_metadata(path)
         .with_context(|| format!("Failed to inspect {}", path.display()))?;
@@ -139,6 +244,8 @@ fn sync_parent_directory(_parent: &Path) -> anyhow::Result<()> {
 
 /// Write MCP servers configuration to a JSON file
 ///
+/// The file is replaced atomically with its permissions preserved.
+///
 /// # Errors
 ///
 /// Returns an error if:
@@ -149,21 +256,15 @@ pub fn write_mcp_servers_config<P: AsRef<Path>>(
     path: P,
     config: &McpServersConfig,
 ) -> anyhow::Result<()> {
-    let path_ref = path.as_ref();
-
-    // Ensure parent directory exists
-    if let Some(parent) = path_ref.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
     let json = serde_json::to_string_pretty(config)?;
-    fs::write(path_ref, json)?;
-
-    Ok(())
+    atomic_write(path.as_ref(), json.as_bytes(), FileMode::Preserve)
 }
 
 /// Write settings to a JSON file
 ///
+/// Like [`write_claude_config`], an existing file is patched in place and
+/// replaced atomically.
+///
 /// # Errors
 ///
 /// Returns an error if:
@@ -171,21 +272,15 @@ pub fn write_mcp_servers_config<P: AsRef<Path>>(
 /// - Unable to serialize the settings
 /// - Unable to write to the file
 pub fn write_settings<P: AsRef<Path>>(path: P, settings: &Settings) -> anyhow::Result<()> {
-    let path_ref = path.as_ref();
-
-    // Ensure parent directory exists
-    if let Some(parent) = path_ref.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    let json = serde_json::to_string_pretty(settings)?;
-    fs::write(path_ref, json)?;
-
-    Ok(())
+    write_json_document(path.as_ref(), settings)
 }
 
 /// Write Codex settings to a TOML file
 ///
+/// An existing file is edited in place, so comments, key order and inline
+/// tables outside the changed entries are preserved, and replaced atomically
+/// with its permissions preserved.
+///
 /// # Errors
 ///
 /// Returns an error if:
@@ -197,16 +292,9 @@ pub fn write_codex_settings<P: AsRef<Path>>(
     settings: &CodexSettings,
 ) -> anyhow::Result<()> {
     let path_ref = path.as_ref();
-
-    // Ensure parent directory exists
-    if let Some(parent) = path_ref.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    let toml = toml::to_string_pretty(settings)?;
-    fs::write(path_ref, toml)?;
-
-    Ok(())
+    let existing = fs::read_to_string(path_ref).ok();
+    let toml = render_toml(existing.as_deref(), settings)?;
+    atomic_write(path_ref, toml.as_bytes(), FileMode::Preserve)
 }
 
 #[cfg(test)]
@@ -228,6 +316,7 @@ mod tests {
                     url: None,
                     headers: HashMap::new(),
                     extra: HashMap::new(),
+                    targeting: None,
                 },
             )])),
             other: HashMap::from([("apiKeyHelper".to_string(), serde_json::json!("/bin/helper"))]),
@@ -310,7 +399,8 @@ mod tests {
 
         fs::write(&file_path, "original content").expect("Failed to write original content");
 
-        let backup_result = backup_file(&file_path).expect("backup_file should succeed");
+        let backup_result = backup_file(&file_path, &BackupSettings::default())
+            .expect("backup_file should succeed");
         assert!(backup_result.is_some());
 
         let backup_path = backup_result.expect("Backup path should be present");
@@ -323,7 +413,7 @@ mod tests {
 
     #[test]
     fn test_backup_file_nonexistent() {
-        let result = backup_file("/nonexistent/file.json")
+        let result = backup_file("/nonexistent/file.json", &BackupSettings::default())
             .expect("backup_file should succeed for non-existent file");
         assert!(result.is_none());
     }
@@ -335,7 +425,7 @@ mod tests {
 
         fs::write(&file_path, "content").expect("Failed to write content");
 
-        let backup_path = backup_file(&file_path)
+        let backup_path = backup_file(&file_path, &BackupSettings::default())
             .expect("backup_file should succeed")
             .expect("Backup path should be present");
 
@@ -354,12 +444,12 @@ mod tests {
         let temp_dir = TempDir::new().expect("Failed to create temp directory");
         let file_path = temp_dir.path().join("test.json");
         fs::write(&file_path, "first version").expect("Failed to write first version");
-        let first = backup_file(&file_path)
+        let first = backup_file(&file_path, &BackupSettings::default())
             .expect("First backup should succeed")
             .expect("First backup should exist");
 
         fs::write(&file_path, "second version").expect("Failed to write second version");
-        let second = backup_file(&file_path)
+        let second = backup_file(&file_path, &BackupSettings::default())
             .expect("Second backup should succeed")
             .expect("Second backup should exist");
 
@@ -431,6 +521,7 @@ mod tests {
                     url: None,
                     headers: HashMap::new(),
                     extra: HashMap::new(),
+                    targeting: None,
                 },
             )]),
         };
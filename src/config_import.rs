@@ -0,0 +1,460 @@
+//! Import deployed agent configuration back into Claudius sources.
+//!
+//! This is the inverse of `config sync`: MCP servers are read from agent
+//! targets (`~/.claude.json`, `.mcp.json`, `claude_desktop_config.json`,
+//! Codex `config.toml`, Gemini `settings.json`), converted back to the shared
+//! `mcpServers.json` format, de-duplicated, and merged into the sources.
+//! Definitions already in `mcpServers.json` always win, and existing settings
+//! sources are never overwritten.
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+use crate::app_config::Agent;
+use crate::codex_settings::convert_toml_to_mcp;
+use crate::config::json_document::render_json;
+use crate::config::{reader, McpServerConfig, McpServersConfig};
+use crate::gemini_settings::restore_gemini_mcp_server;
+use crate::sync_operations::PlannedWrite;
+
+const MCP_SERVERS_FILE: &str = "mcpServers.json";
+
+/// An agent target to import from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSource {
+    pub agent: Agent,
+    /// File holding the agent's MCP server definitions.
+    pub path: PathBuf,
+    /// File holding the agent's settings, if they are importable.
+    pub settings_path: Option<PathBuf>,
+}
+
+impl ImportSource {
+    fn origin(&self) -> String {
+        format!("{} ({})", self.agent.display_name(), self.path.display())
+    }
+}
+
+/// What happened to one imported MCP server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerOutcome {
+    /// New server added to `mcpServers.json`.
+    Added,
+    /// The same definition is already in `mcpServers.json`.
+    AlreadyPresent,
+    /// An equivalent server was already imported under another name.
+    Duplicate { of: String },
+    /// A different definition with the same name was kept instead.
+    Conflict { kept: String },
+}
+
+/// One MCP server found in an agent target.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerImport {
+    pub name: String,
+    pub origin: String,
+    pub outcome: ServerOutcome,
+}
+
+/// Everything an import would change, computed without writing.
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    /// Agent targets that existed and were read.
+    pub sources_read: Vec<PathBuf>,
+    pub servers: Vec<ServerImport>,
+    /// Source files to create or update.
+    pub writes: Vec<PlannedWrite>,
+    /// Findings that were not imported automatically.
+    pub notes: Vec<String>,
+}
+
+/// Agent targets that `config sync` writes in the given mode, in import order.
+///
+/// Claude Code comes first because its JSON format is the one `mcpServers.json`
+/// uses, so its definitions lose the least information when duplicates are
+/// resolved.
+#[must_use]
+pub fn default_import_sources(
+    global: bool,
+    home_dir: &Path,
+    project_dir: &Path,
+    system_config_dir: &Path,
+) -> Vec<ImportSource> {
+    let source = |agent, path: PathBuf, settings_path| ImportSource { agent, path, settings_path };
+
+    if global {
+        let codex = home_dir.join(".codex").join("config.toml");
+        let gemini = home_dir.join(".gemini").join("settings.json");
+        vec![
+            source(
+                Agent::ClaudeCode,
+                home_dir.join(".claude.json"),
+                Some(home_dir.join(".claude").join("settings.json")),
+            ),
+            source(
+                Agent::Claude,
+                system_config_dir.join("Claude").join("claude_desktop_config.json"),
+                None,
+            ),
+            source(Agent::Codex, codex.clone(), Some(codex)),
+            source(Agent::Gemini, gemini.clone(), Some(gemini)),
+        ]
+    } else {
+        let codex = project_dir.join(".codex").join("config.toml");
+        let gemini = project_dir.join(".gemini").join("settings.json");
+        vec![
+            source(
+                Agent::ClaudeCode,
+                project_dir.join(".mcp.json"),
+                Some(project_dir.join(".claude").join("settings.json")),
+            ),
+            source(Agent::Codex, codex.clone(), Some(codex)),
+            source(Agent::Gemini, gemini.clone(), Some(gemini)),
+        ]
+    }
+}
+
+/// Plan importing the given agent targets into the sources in `config_dir`.
+///
+/// # Errors
+///
+/// Returns an error if an existing source or agent target cannot be read or
+/// parsed.
+pub fn plan_import(config_dir: &Path, sources: &[ImportSource]) -> Result<ImportPlan> {
+    let mut plan = ImportPlan::default();
+    let mcp_path = config_dir.join(MCP_SERVERS_FILE);
+    let existing_text = read_optional(&mcp_path)?;
+    let mut accepted = existing_servers(existing_text.as_deref(), &mcp_path)?;
+    let mut added = Vec::new();
+
+    for source in sources.iter().filter(|source| source.path.exists()) {
+        plan.sources_read.push(source.path.clone());
+
+        let mut servers = read_agent_servers(source)?.into_iter().collect::<Vec<_>>();
+        servers.sort_by(|left, right| left.0.cmp(&right.0));
+        for (name, server) in servers {
+            let outcome = classify_server(&accepted, &name, &server);
+            if outcome == ServerOutcome::Added {
+                accepted.push(AcceptedServer {
+                    name: name.clone(),
+                    server: server.clone(),
+                    origin: source.origin(),
+                });
+                added.push((name.clone(), server));
+            }
+            plan.servers.push(ServerImport { name, origin: source.origin(), outcome });
+        }
+
+        plan_settings_import(config_dir, source, &mut plan)?;
+    }
+
+    if !added.is_empty() {
+        plan.writes
+            .insert(0, plan_mcp_servers_write(&mcp_path, existing_text.as_deref(), &added)?);
+    }
+
+    Ok(plan)
+}
+
+struct AcceptedServer {
+    name: String,
+    server: McpServerConfig,
+    origin: String,
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn existing_servers(existing: Option<&str>, path: &Path) -> Result<Vec<AcceptedServer>> {
+    let Some(content) = existing else {
+        return Ok(Vec::new());
+    };
+    let config: McpServersConfig = serde_json::from_str(content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut servers = config
+        .mcp_servers
+        .into_iter()
+        .map(|(name, server)| AcceptedServer { name, server, origin: MCP_SERVERS_FILE.to_string() })
+        .collect::<Vec<_>>();
+    servers.sort_by(|left, right| left.name.cmp(&right.name));
+    Ok(servers)
+}
+
+fn read_agent_servers(source: &ImportSource) -> Result<Vec<(String, McpServerConfig)>> {
+    let servers = match source.agent {
+        Agent::Codex => {
+            let content = fs::read_to_string(&source.path)
+                .with_context(|| format!("Failed to read {}", source.path.display()))?;
+            let table = toml::from_str::<toml::Table>(&content)
+                .with_context(|| format!("Failed to parse {}", source.path.display()))?;
+            table
+                .get("mcp_servers")
+                .and_then(toml::Value::as_table)
+                .map(convert_toml_to_mcp)
+                .unwrap_or_default()
+        },
+        Agent::Gemini => reader::read_claude_config(&source.path)?
+            .mcp_servers
+            .unwrap_or_default()
+            .iter()
+            .map(|(name, server)| (name.clone(), restore_gemini_mcp_server(server)))
+            .collect(),
+        Agent::Claude | Agent::ClaudeCode => {
+            reader::read_claude_config(&source.path)?.mcp_servers.unwrap_or_default()
+        },
+    };
+
+    Ok(servers
+        .into_iter()
+        .map(|(name, server)| (name, normalize_server(server)))
+        .collect())
+}
+
+/// Drop fields that `config sync` adds implicitly so equal servers compare equal.
+fn normalize_server(mut server: McpServerConfig) -> McpServerConfig {
+    if server.command.is_some() && server.server_type.as_deref() == Some("stdio") {
+        server.server_type = None;
+    }
+    server
+}
+
+/// Servers are equivalent when they launch the same process or reach the same
+/// endpoint; agent-specific extras such as `trust` are not compared.
+fn same_transport(left: &McpServerConfig, right: &McpServerConfig) -> bool {
+    left.command == right.command
+        && left.args == right.args
+        && left.env == right.env
+        && left.url == right.url
+        && left.headers == right.headers
+}
+
+fn classify_server(
+    accepted: &[AcceptedServer],
+    name: &str,
+    server: &McpServerConfig,
+) -> ServerOutcome {
+    if let Some(existing) = accepted.iter().find(|existing| existing.name == name) {
+        return if !same_transport(&existing.server, server) {
+            ServerOutcome::Conflict { kept: existing.origin.clone() }
+        } else if existing.origin == MCP_SERVERS_FILE {
+            ServerOutcome::AlreadyPresent
+        } else {
+            ServerOutcome::Duplicate { of: existing.name.clone() }
+        };
+    }
+
+    accepted
+        .iter()
+        .find(|existing| same_transport(&existing.server, server))
+        .map_or(ServerOutcome::Added, |existing| ServerOutcome::Duplicate {
+            of: existing.name.clone(),
+        })
+}
+
+fn plan_mcp_servers_write(
+    path: &Path,
+    existing: Option<&str>,
+    added: &[(String, McpServerConfig)],
+) -> Result<PlannedWrite> {
+    let mut document = existing
+        .map(serde_json::from_str::<Value>)
+        .transpose()
+        .with_context(|| format!("Failed to parse {}", path.display()))?
+        .unwrap_or_else(|| Value::Object(Map::new()));
+    let root = document
+        .as_object_mut()
+        .with_context(|| format!("{} must contain a JSON object", path.display()))?;
+    let servers = root
+        .entry("mcpServers")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .with_context(|| format!("mcpServers in {} must be an object", path.display()))?;
+
+    for (name, server) in added {
+        servers.insert(name.clone(), server_to_value(server));
+    }
+
+    let content = render_json(existing, &document)
+        .with_context(|| format!("Failed to serialize {}", path.display()))?;
+    Ok(PlannedWrite { label: "MCP servers", path: path.to_path_buf(), content })
+}
+
+/// Render a server the way a person would write it in `mcpServers.json`:
+/// transport fields first, empty collections omitted, extras sorted.
+fn server_to_value(server: &McpServerConfig) -> Value {
+    let mut fields = Map::new();
+    let sorted = |entries: &std::collections::HashMap<String, String>| {
+        Value::Object(
+            entries
+                .iter()
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                .collect(),
+        )
+    };
+
+    if let Some(kind) = &server.server_type {
+        fields.insert("type".to_string(), Value::String(kind.clone()));
+    }
+    if let Some(command) = &server.command {
+        fields.insert("command".to_string(), Value::String(command.clone()));
+    }
+    if !server.args.is_empty() {
+        fields.insert(
+            "args".to_string(),
+            Value::Array(server.args.iter().cloned().map(Value::String).collect()),
+        );
+    }
+    if !server.env.is_empty() {
+        fields.insert("env".to_string(), sorted(&server.env));
+    }
+    if let Some(url) = &server.url {
+        fields.insert("url".to_string(), Value::String(url.clone()));
+    }
+    if !server.headers.is_empty() {
+        fields.insert("headers".to_string(), sorted(&server.headers));
+    }
+    let mut extras = server.extra.iter().collect::<Vec<_>>();
+    extras.sort_by(|left, right| left.0.cmp(right.0));
+    for (key, value) in extras {
+        fields.insert(key.clone(), value.clone());
+    }
+
+    Value::Object(fields)
+}
+
+fn plan_settings_import(
+    config_dir: &Path,
+    source: &ImportSource,
+    plan: &mut ImportPlan,
+) -> Result<()> {
+    let Some(settings_path) = source.settings_path.as_deref().filter(|path| path.exists()) else {
+        return Ok(());
+    };
+    let content = fs::read_to_string(settings_path)
+        .with_context(|| format!("Failed to read {}", settings_path.display()))?;
+
+    let (file_name, parsed) = match source.agent {
+        Agent::Codex => ("codex.settings.toml", codex_settings_without_servers(&content)),
+        Agent::Gemini => ("gemini.settings.json", json_settings_without_servers(&content)),
+        Agent::Claude | Agent::ClaudeCode => {
+            ("claude.settings.json", json_settings_without_servers(&content))
+        },
+    };
+    let settings =
+        parsed.with_context(|| format!("Failed to parse {}", settings_path.display()))?;
+
+    let Some(rendered) = settings else {
+        return Ok(());
+    };
+
+    let target = config_dir.join(file_name);
+    let legacy_claude =
+        file_name == "claude.settings.json" && config_dir.join("settings.json").exists();
+    if target.exists() || legacy_claude {
+        plan.notes.push(format!(
+            "Kept existing {file_name}; settings in {} were not imported",
+            settings_path.display()
+        ));
+        return Ok(());
+    }
+
+    plan.writes
+        .push(PlannedWrite { label: "Settings", path: target, content: rendered });
+    Ok(())
+}
+
+/// Codex keeps MCP servers and settings in one file; the servers move to
+/// `mcpServers.json`, everything else is kept with its comments.
+fn codex_settings_without_servers(content: &str) -> Result<Option<String>> {
+    let mut document = content.parse::<DocumentMut>()?;
+    document.remove("mcp_servers");
+    Ok((!document.is_empty()).then(|| document.to_string()))
+}
+
+fn json_settings_without_servers(content: &str) -> Result<Option<String>> {
+    let mut settings = serde_json::from_str::<Value>(content)?;
+    let Some(root) = settings.as_object_mut() else {
+        anyhow::bail!("settings must be a JSON object");
+    };
+    root.remove("mcpServers");
+    if root.is_empty() {
+        return Ok(None);
+    }
+    render_json(None, &settings).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn stdio(command: &str) -> McpServerConfig {
+        McpServerConfig {
+            command: Some(command.to_string()),
+            args: vec!["-y".to_string()],
+            env: HashMap::new(),
+            server_type: None,
+            url: None,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+            targeting: None,
+        }
+    }
+
+    fn accepted(name: &str, server: McpServerConfig, origin: &str) -> AcceptedServer {
+        AcceptedServer { name: name.to_string(), server, origin: origin.to_string() }
+    }
+
+    #[test]
+    fn classify_server_detects_duplicates_and_conflicts() {
+        let existing = vec![
+            accepted("kept", stdio("kept"), MCP_SERVERS_FILE),
+            accepted("imported", stdio("imported"), "Codex (config.toml)"),
+        ];
+
+        assert_eq!(
+            classify_server(&existing, "kept", &stdio("kept")),
+            ServerOutcome::AlreadyPresent
+        );
+        assert_eq!(
+            classify_server(&existing, "kept", &stdio("other")),
+            ServerOutcome::Conflict { kept: MCP_SERVERS_FILE.to_string() }
+        );
+        assert_eq!(
+            classify_server(&existing, "alias", &stdio("imported")),
+            ServerOutcome::Duplicate { of: "imported".to_string() }
+        );
+        assert_eq!(classify_server(&existing, "new", &stdio("new")), ServerOutcome::Added);
+    }
+
+    #[test]
+    fn normalize_server_drops_implicit_stdio_type() {
+        let mut server = stdio("npx");
+        server.server_type = Some("stdio".to_string());
+
+        assert_eq!(normalize_server(server), stdio("npx"));
+    }
+
+    #[test]
+    fn codex_settings_keep_comments_without_servers() {
+        let content = "# model choice\nmodel = \"o3\"\n\n[mcp_servers.x]\ncommand = \"y\"\n";
+
+        let settings = codex_settings_without_servers(content)
+            .expect("codex settings should parse")
+            .expect("settings should remain");
+
+        assert_eq!(settings, "# model choice\nmodel = \"o3\"\n");
+    }
+}
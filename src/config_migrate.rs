@@ -7,12 +7,14 @@
 //! re-running a migration on already-migrated files produces no changes.
 
 use anyhow::{Context, Result};
+use serde::Serialize;
 use serde_json::{Map, Value};
 use std::fs;
 use std::path::{Path, PathBuf};
 use toml_edit::{DocumentMut, Item, Key};
 
 use crate::app_config::Agent;
+use crate::backups::BackupSettings;
 use crate::config::writer::{atomic_write_preserving_permissions, backup_file};
 
 /// Official JSON schema for Claude Code `settings.json`.
@@ -29,13 +31,15 @@ const CODEX_RENAMED_KEYS: &[(&str, &str)] = &[
 ];
 
 /// A planned rewrite of a single configuration file.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileMigration {
     /// File the migration applies to.
     pub path: PathBuf,
     /// Content currently on disk.
+    #[serde(skip)]
     pub original: String,
     /// Content after applying all migration rules.
+    #[serde(skip)]
     pub migrated: String,
     /// Human-readable descriptions of the applied rules.
     pub changes: Vec<String>,
@@ -84,11 +88,13 @@ pub fn plan_migration(config_dir: &Path, agent: Option<Agent>) -> Result<Migrati
 /// Apply a previously computed plan, creating a collision-safe timestamped
 /// backup before atomically replacing each file. Returns the backup paths.
 ///
+/// Backups are placed and pruned according to `backups`.
+///
 /// # Errors
 ///
 /// Returns an error if a backup cannot be created or a file cannot be
 /// written.
-pub fn apply_migration(plan: &MigrationPlan) -> Result<Vec<String>> {
+pub fn apply_migration(plan: &MigrationPlan, backups: &BackupSettings) -> Result<Vec<String>> {
     let changed_files = plan.files.iter().filter(|file| file.is_changed()).collect::<Vec<_>>();
 
     for file in &changed_files {
@@ -105,7 +111,7 @@ pub fn apply_migration(plan: &MigrationPlan) -> Result<Vec<String>> {
     changed_files
         .iter()
         .map(|file| {
-            let backup = backup_file(&file.path)
+            let backup = backup_file(&file.path, backups)
                 .with_context(|| format!("Failed to back up {}", file.path.display()))?
                 .ok_or_else(|| {
                     anyhow::anyhow!("Refusing to migrate missing file: {}", file.path.display())
@@ -640,7 +646,8 @@ mod tests {
         };
         fs::write(&path, "changed externally").expect("file should be changed");
 
-        let error = apply_migration(&plan).expect_err("stale plan should be rejected");
+        let error = apply_migration(&plan, &BackupSettings::default())
+            .expect_err("stale plan should be rejected");
         assert!(error.to_string().contains("changed after the migration was planned"));
         assert_eq!(
             fs::read_to_string(path).expect("file should remain readable"),
@@ -668,7 +675,7 @@ mod tests {
             notes: Vec::new(),
         };
 
-        apply_migration(&plan).expect("migration should succeed");
+        apply_migration(&plan, &BackupSettings::default()).expect("migration should succeed");
 
         assert_eq!(fs::read_to_string(&path).expect("file should be readable"), "migrated");
         assert_eq!(
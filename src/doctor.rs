@@ -3,8 +3,12 @@
 use crate::app_config::Agent;
 use crate::asset_sync::{inspect_managed_tree, ManagedTreeInspection, SourceFileMapping};
 use crate::config::Config;
+use crate::mcp_presets::{outdated_servers, PresetCatalog, CATALOG_DIR_NAME};
 use crate::skills;
+use crate::source_layers::{describe_layers, LayeredAsset};
+use crate::undo::UndoRecorder;
 use anyhow::{Context, Result};
+use serde::Serialize;
 use serde_yaml::{Mapping as YamlMapping, Value as YamlValue};
 use std::collections::BTreeMap;
 use std::fs;
@@ -26,7 +30,8 @@ const CLAUDE_ONLY_SKILL_KEYS: &[&str] = &[
     "context",
 ];
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum DoctorStatus {
     Supported,
     BestEffort,
@@ -62,21 +67,178 @@ impl DoctorStatus {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DoctorFinding {
     pub status: DoctorStatus,
     pub summary: String,
     pub path: Option<PathBuf>,
     pub detail: Option<String>,
     pub recommendation: String,
+    /// Remediation `config doctor --fix` can apply; `None` when it needs judgment.
+    pub fix: Option<DoctorFix>,
 }
 
-#[derive(Debug, Clone)]
+/// A mechanical remediation behind a finding's recommendation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum DoctorFix {
+    /// Rename a legacy source file to its preferred name.
+    RenameSource { from: PathBuf, to: PathBuf },
+    /// Delete Claudius-managed files whose source no longer exists.
+    PruneStale { target_dir: PathBuf, files: Vec<String> },
+    /// Turn full agent override skill directories into canonical overlays.
+    MigrateSkillOverrides { agent: Agent },
+}
+
+impl DoctorFix {
+    /// Whether the fix rewrites sources in ways that deserve a confirmation.
+    ///
+    /// Skill migration rewrites `skill.yaml` files and deletes the override
+    /// directories; renames and stale-file pruning only move or drop files
+    /// that Claudius already treats as redundant.
+    #[must_use]
+    pub const fn is_risky(&self) -> bool {
+        matches!(self, Self::MigrateSkillOverrides { .. })
+    }
+
+    #[must_use]
+    pub fn describe(&self) -> String {
+        match self {
+            Self::RenameSource { from, to } => {
+                format!("rename {} to {}", from.display(), to.display())
+            },
+            Self::PruneStale { target_dir, files } => {
+                format!("remove {} stale file(s) from {}", files.len(), target_dir.display())
+            },
+            Self::MigrateSkillOverrides { agent } => {
+                format!("run `claudius skills migrate --agent {}`", agent_cli_name(*agent))
+            },
+        }
+    }
+
+    /// Apply the fix and return a line describing what changed.
+    ///
+    /// Renamed, pruned, and migrated files are captured by `undo`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rename target appeared in the meantime or if
+    /// files cannot be moved, deleted, or migrated.
+    pub fn apply(&self, config_dir: &Path, undo: &UndoRecorder) -> Result<String> {
+        match self {
+            Self::RenameSource { from, to } => {
+                if to.exists() {
+                    anyhow::bail!("{} already exists", to.display());
+                }
+                undo.record(from);
+                undo.record(to);
+                fs::rename(from, to).with_context(|| {
+                    format!("Failed to rename {} to {}", from.display(), to.display())
+                })?;
+                Ok(format!("Renamed {} to {}", from.display(), to.display()))
+            },
+            Self::PruneStale { target_dir, files } => {
+                let pruned = crate::asset_sync::prune_managed_files(target_dir, files, undo)?;
+                Ok(format!("Removed {} stale file(s) from {}", pruned.len(), target_dir.display()))
+            },
+            Self::MigrateSkillOverrides { agent } => {
+                let report = skills::migrate_deprecated_agent_overrides(
+                    config_dir,
+                    Some(*agent),
+                    false,
+                    undo,
+                )?;
+                Ok(format!(
+                    "Migrated {} {} skill override(s) into canonical overlays",
+                    report.migrated_overrides.len(),
+                    doctor_agent_label(*agent)
+                ))
+            },
+        }
+    }
+}
+
+/// What `config doctor --fix` did about one fixable finding.
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorFixOutcome {
+    pub summary: String,
+    pub fix: DoctorFix,
+    pub status: DoctorFixStatus,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DoctorFixStatus {
+    Applied,
+    WouldApply,
+    Skipped,
+    Failed,
+}
+
+/// Apply the fixes behind `report`'s findings, confirming risky ones first.
+///
+/// Risky fixes are skipped when no one can answer the confirmation (`--no-input`
+/// or a non-terminal stdin without `--yes`). A failing fix does not stop the
+/// others; it is reported with status [`DoctorFixStatus::Failed`]. Changed
+/// files are captured by `undo`.
+///
+/// # Errors
+///
+/// Returns an error if reading a confirmation answer fails.
+pub fn apply_fixes(
+    report: &DoctorReport,
+    dry_run: bool,
+    undo: &UndoRecorder,
+) -> Result<Vec<DoctorFixOutcome>> {
+    let mut seen = Vec::<&DoctorFix>::new();
+    let mut outcomes = Vec::new();
+    for finding in &report.findings {
+        let Some(fix) = finding.fix.as_ref().filter(|fix| !seen.contains(fix)) else {
+            continue;
+        };
+        seen.push(fix);
+
+        let (status, detail) = if dry_run {
+            let confirmation = if fix.is_risky() { " after confirmation" } else { "" };
+            (DoctorFixStatus::WouldApply, format!("Would {}{confirmation}", fix.describe()))
+        } else if fix.is_risky() && !confirm_risky_fix(fix)? {
+            (DoctorFixStatus::Skipped, format!("Skipped: {}", fix.describe()))
+        } else {
+            match fix.apply(&report.config_dir, undo) {
+                Ok(detail) => (DoctorFixStatus::Applied, detail),
+                Err(error) => (DoctorFixStatus::Failed, format!("{error:#}")),
+            }
+        };
+        outcomes.push(DoctorFixOutcome {
+            summary: finding.summary.clone(),
+            fix: fix.clone(),
+            status,
+            detail,
+        });
+    }
+    Ok(outcomes)
+}
+
+fn confirm_risky_fix(fix: &DoctorFix) -> Result<bool> {
+    use crate::interaction::{can_prompt, confirm, mode, InteractionMode};
+
+    if mode() != InteractionMode::AssumeYes && !can_prompt() {
+        return Ok(false);
+    }
+    confirm(&format!("Apply fix: {}?", fix.describe()))
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct DoctorReport {
     pub global: bool,
     pub agent_filter: Option<Agent>,
     pub config_dir: PathBuf,
     pub deployment_base_dir: PathBuf,
+    /// Project `.claudius/` layer applied by project-local syncs, if any.
+    pub project_layer_dir: Option<PathBuf>,
+    /// Every source asset and the layer it is taken from; empty without a project layer.
+    pub source_layers: Vec<LayeredAsset>,
     pub findings: Vec<DoctorFinding>,
 }
 
@@ -113,13 +275,20 @@ pub fn run_doctor(options: DoctorOptions) -> Result<DoctorReport> {
     let deployment_base_dir = config
         .deployment_base_dir()
         .context("Failed to determine deployment base dir")?;
-    let findings = collect_findings(options, &config_dir, &deployment_base_dir, &source_state)?;
+    let mut findings = collect_findings(options, &config_dir, &deployment_base_dir, &source_state)?;
+    inspect_mcp_presets(&config_dir, config.project_layer_dir.as_deref(), &mut findings)?;
+    let source_layers = match &config.project_layer_dir {
+        Some(layer_dir) => describe_layers(&config_dir, layer_dir)?,
+        None => Vec::new(),
+    };
 
     Ok(DoctorReport {
         global: options.global,
         agent_filter: options.agent_filter,
         config_dir,
         deployment_base_dir,
+        project_layer_dir: config.project_layer_dir,
+        source_layers,
         findings,
     })
 }
@@ -132,6 +301,10 @@ pub fn render_report(report: &DoctorReport) -> String {
         format!("Config directory: {}", report.config_dir.display()),
         format!("Deployment base: {}", report.deployment_base_dir.display()),
     ];
+    if let Some(layer_dir) = &report.project_layer_dir {
+        lines.push(format!("Project layer: {}", layer_dir.display()));
+        push_source_layers(&mut lines, &report.source_layers);
+    }
 
     let mut groups = BTreeMap::<DoctorStatus, Vec<&DoctorFinding>>::new();
     for finding in &report.findings {
@@ -166,6 +339,19 @@ pub fn render_report(report: &DoctorReport) -> String {
     lines.join("\n")
 }
 
+fn push_source_layers(lines: &mut Vec<String>, assets: &[LayeredAsset]) {
+    lines.push(String::new());
+    lines.push(format!("SOURCE LAYERS ({})", assets.len()));
+    for asset in assets {
+        let suffix = match (asset.overrides_global, asset.kind) {
+            (true, "settings") => " (merged over global)",
+            (true, _) => " (overrides global)",
+            (false, _) => "",
+        };
+        lines.push(format!("- {} {}: {}{suffix}", asset.kind, asset.name, asset.layer.label()));
+    }
+}
+
 fn load_source_surface_state(config_dir: &Path) -> Result<SourceSurfaceState> {
     Ok(SourceSurfaceState {
         shared_skills: skills::collect_shared_skill_mappings(&config_dir.join("skills"))?,
@@ -250,15 +436,17 @@ fn inspect_claude_sources(
             detail: None,
             recommendation: "Keep using claude.settings.json for Claude and Claude Code settings."
                 .to_string(),
+            fix: None,
         });
     } else if legacy.exists() {
         findings.push(DoctorFinding {
             status: DoctorStatus::Legacy,
             summary: "Legacy settings.json is still active for Claude / Claude Code settings."
                 .to_string(),
-            path: Some(legacy),
+            path: Some(legacy.clone()),
             detail: None,
             recommendation: "Rename or migrate settings.json to claude.settings.json.".to_string(),
+            fix: Some(DoctorFix::RenameSource { from: legacy, to: preferred }),
         });
     }
 }
@@ -281,6 +469,7 @@ fn inspect_codex_sources(
             detail: None,
             recommendation: "Keep this in sync with `claudius config sync --agent codex`."
                 .to_string(),
+            fix: None,
         });
     }
 
@@ -294,9 +483,14 @@ fn inspect_codex_sources(
             recommendation:
                 "Sync it with `claudius config sync --global --agent codex --codex-requirements` when admin requirements change."
                     .to_string(),
+            fix: None,
         });
     }
 
+    inspect_codex_managed_defaults(config_dir, findings);
+}
+
+fn inspect_codex_managed_defaults(config_dir: &Path, findings: &mut Vec<DoctorFinding>) {
     let managed = config_dir.join("codex.managed_config.toml");
     let legacy_managed = config_dir.join("managed_config.toml");
     if managed.exists() {
@@ -308,16 +502,18 @@ fn inspect_codex_sources(
             recommendation:
                 "Sync it with `claudius config sync --global --agent codex --codex-managed-config` when managed defaults change."
                     .to_string(),
+            fix: None,
         });
     } else if legacy_managed.exists() {
         findings.push(DoctorFinding {
             status: DoctorStatus::Legacy,
             summary: "Legacy managed_config.toml is still active for Codex managed defaults."
                 .to_string(),
-            path: Some(legacy_managed),
+            path: Some(legacy_managed.clone()),
             detail: None,
             recommendation: "Rename or migrate managed_config.toml to codex.managed_config.toml."
                 .to_string(),
+            fix: Some(DoctorFix::RenameSource { from: legacy_managed, to: managed }),
         });
     }
 }
@@ -340,6 +536,7 @@ fn inspect_gemini_sources(
             detail: None,
             recommendation: "Keep it in sync with `claudius config sync --agent gemini`."
                 .to_string(),
+            fix: None,
         });
     }
 
@@ -353,6 +550,7 @@ fn inspect_gemini_sources(
             recommendation:
                 "Sync it with `claudius config sync --global --agent gemini --gemini-system-defaults` when admin defaults change."
                     .to_string(),
+            fix: None,
         });
     }
 }
@@ -380,6 +578,7 @@ fn inspect_skill_sources(
                 shared_skill_mappings.len()
             )),
             recommendation: "Sync them with `claudius skills sync` or the relevant `claudius config sync` command.".to_string(),
+            fix: None,
         });
     }
 
@@ -438,6 +637,7 @@ fn inspect_skill_sources(
             recommendation:
                 "Move each commands/*.md file into a skill directory, preferably skills/<name>/skill.yaml + instructions.md (or skills/<name>/SKILL.md for passthrough compatibility)."
                     .to_string(),
+            fix: None,
         });
     }
 }
@@ -508,6 +708,7 @@ fn push_deprecated_override_finding(
         recommendation:
             "Run `claudius skills migrate` for these overrides, then keep shared resources and per-agent metadata in one canonical skill tree."
                 .to_string(),
+        fix: Some(DoctorFix::MigrateSkillOverrides { agent }),
     });
 }
 
@@ -556,6 +757,7 @@ fn inspect_shared_legacy_skill_metadata_leakage(
                     recommendation:
                         "Migrate this skill to canonical skill.yaml or move Claude-only metadata into a Claude-specific target overlay."
                             .to_string(),
+                    fix: None,
                 });
             }
             continue;
@@ -578,6 +780,7 @@ fn inspect_shared_legacy_skill_metadata_leakage(
                 recommendation:
                     "Migrate this skill to canonical skill.yaml or move Claude-only metadata into a Claude-specific target overlay."
                         .to_string(),
+                fix: None,
             });
         }
     }
@@ -638,6 +841,7 @@ fn inspect_auxiliary_sources(
                 gemini_command_mappings.len()
             )),
             recommendation: "Deploy them with `claudius config sync --agent gemini`.".to_string(),
+            fix: None,
         });
     }
 
@@ -651,6 +855,7 @@ fn inspect_auxiliary_sources(
                 gemini_agent_mappings.len()
             )),
             recommendation: "Deploy them with `claudius config sync --agent gemini`.".to_string(),
+            fix: None,
         });
     }
 
@@ -665,10 +870,56 @@ fn inspect_auxiliary_sources(
             )),
             recommendation: "Deploy them with `claudius config sync --agent claude-code`."
                 .to_string(),
+            fix: None,
         });
     }
 }
 
+/// MCP servers rendered from an older version of their preset.
+fn inspect_mcp_presets(
+    config_dir: &Path,
+    project_layer_dir: Option<&Path>,
+    findings: &mut Vec<DoctorFinding>,
+) -> Result<()> {
+    let (catalog, problems) = PresetCatalog::load_skipping_invalid(config_dir)?;
+    for problem in problems {
+        findings.push(DoctorFinding {
+            status: DoctorStatus::Unmanaged,
+            summary: "An MCP preset catalog file could not be loaded and was skipped.".to_string(),
+            path: Some(config_dir.join(CATALOG_DIR_NAME)),
+            detail: Some(format!("{problem:#}")),
+            recommendation: "Fix or remove the file; `claudius mcp presets` and \
+                             `claudius mcp add --preset` fail until it loads."
+                .to_string(),
+            fix: None,
+        });
+    }
+
+    let mut sources = vec![config_dir.join("mcpServers.json")];
+    sources.extend(project_layer_dir.map(|layer_dir| layer_dir.join("mcpServers.json")));
+
+    for outdated in outdated_servers(&catalog, &sources) {
+        let project_flag =
+            if sources.first() == Some(&outdated.source) { "" } else { " --project" };
+        findings.push(DoctorFinding {
+            status: DoctorStatus::Legacy,
+            summary: format!(
+                "MCP server '{}' uses version {} of preset '{}'; version {} is available.",
+                outdated.server, outdated.recorded, outdated.preset, outdated.current
+            ),
+            path: Some(outdated.source),
+            detail: None,
+            recommendation: format!(
+                "Re-create it with `claudius mcp add {} --preset {} --replace{project_flag}`, \
+                 repeating its --set values.",
+                outdated.server, outdated.preset
+            ),
+            fix: None,
+        });
+    }
+    Ok(())
+}
+
 fn inspect_target_surfaces(
     options: DoctorOptions,
     config_dir: &Path,
@@ -708,6 +959,7 @@ fn inspect_best_effort_targets(
             recommendation:
                 "Prefer Claude Code, Codex, or Gemini when you need actively managed surfaces."
                     .to_string(),
+            fix: None,
         });
     }
 
@@ -734,6 +986,7 @@ fn inspect_unmanaged_targets(
                 recommendation:
                     "Keep managing slash commands directly in .claude/commands or migrate shared workflows into Claudius skills."
                         .to_string(),
+                fix: None,
             });
         }
     }
@@ -750,6 +1003,7 @@ fn inspect_unmanaged_targets(
                 recommendation:
                     "Install and update Gemini extensions through the Gemini CLI workflow; Claudius does not sync them."
                         .to_string(),
+                fix: None,
             });
         }
     }
@@ -894,6 +1148,7 @@ fn inspect_codex_targets(
                 recommendation:
                     "Prefer the default .agents/skills target unless you still need legacy Codex compatibility copies."
                         .to_string(),
+                fix: None,
             });
         }
     }
@@ -917,6 +1172,7 @@ fn push_agent_skill_finding(
             detail: Some(format!("{} skill file(s) are ready to sync.", mappings.len())),
             recommendation: "Publish them with `claudius skills sync` for the matching agent."
                 .to_string(),
+            fix: None,
         });
     }
 }
@@ -941,13 +1197,17 @@ fn push_stale_finding(
     findings.push(DoctorFinding {
         status: DoctorStatus::Stale,
         summary: summary.to_string(),
-        path: Some(inspection.target_dir),
+        path: Some(inspection.target_dir.clone()),
         detail: Some(format!(
             "{} stale file(s): {}",
             inspection.stale_files.len(),
             summarize_paths(&inspection.stale_files),
         )),
         recommendation,
+        fix: Some(DoctorFix::PruneStale {
+            target_dir: inspection.target_dir,
+            files: inspection.stale_files,
+        }),
     });
 }
 
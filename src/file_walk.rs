@@ -0,0 +1,52 @@
+#![allow(missing_docs)]
+
+//! Recursive file listing shared by backup discovery and source watching.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Every file under `dir`, recursively; unreadable directories are skipped.
+///
+/// Symbolic links are listed as entries but never followed, so a link that
+/// points back up the tree cannot make the walk recurse forever.
+#[must_use]
+pub fn collect_files(dir: &Path) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    collect_into(dir, &mut paths);
+    paths
+}
+
+fn collect_into(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            collect_into(&path, paths);
+        } else {
+            paths.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_directories_are_not_followed() {
+        let root = TempDir::new().unwrap();
+        let nested = root.path().join("skills/review");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("SKILL.md"), "# Review").unwrap();
+        std::os::unix::fs::symlink(root.path(), nested.join("loop")).unwrap();
+
+        let mut files = collect_files(root.path());
+        files.sort();
+
+        assert_eq!(files, [nested.join("SKILL.md"), nested.join("loop")]);
+    }
+}
@@ -223,6 +223,30 @@ pub fn sanitize_gemini_mcp_servers(
     )
 }
 
+/// Convert a Gemini MCP server definition back into the shared format.
+///
+/// Gemini spells streamable HTTP endpoints as `httpUrl` and treats a bare `url`
+/// as SSE; both become `url` with an explicit `type`. Every other field,
+/// including `trust`, is kept as is.
+#[must_use]
+pub fn restore_gemini_mcp_server(server: &McpServerConfig) -> McpServerConfig {
+    let mut restored = server.clone();
+    let http_url = restored
+        .url
+        .is_none()
+        .then(|| restored.extra.get("httpUrl").and_then(Value::as_str).map(str::to_string))
+        .flatten();
+    if let Some(endpoint) = http_url {
+        restored.extra.remove("httpUrl");
+        restored.url = Some(endpoint);
+        restored.server_type.get_or_insert_with(|| "http".to_string());
+    }
+    if restored.url.is_some() && restored.command.is_none() {
+        restored.server_type.get_or_insert_with(|| "sse".to_string());
+    }
+    restored
+}
+
 fn sanitize_gemini_mcp_server(
     server_name: &str,
     server: &McpServerConfig,
@@ -364,6 +388,7 @@ mod tests {
             url: None,
             headers: HashMap::new(),
             extra,
+            targeting: None,
         }
     }
 
@@ -410,4 +435,28 @@ mod tests {
         assert!(!server.extra.contains_key(LEGACY_AUTO_APPROVE_FIELD));
         assert!(warnings.iter().any(|warning| warning.contains("`trust` is already set")));
     }
+
+    #[test]
+    fn test_restore_gemini_mcp_server_maps_http_url() {
+        let server = McpServerConfig {
+            command: None,
+            args: Vec::new(),
+            env: HashMap::new(),
+            server_type: None,
+            url: None,
+            headers: HashMap::new(),
+            extra: HashMap::from([
+                ("httpUrl".to_string(), Value::String("https://example.com/mcp".to_string())),
+                (GEMINI_MCP_TRUST_FIELD.to_string(), Value::Bool(true)),
+            ]),
+            targeting: None,
+        };
+
+        let restored = restore_gemini_mcp_server(&server);
+
+        assert_eq!(restored.url.as_deref(), Some("https://example.com/mcp"));
+        assert_eq!(restored.server_type.as_deref(), Some("http"));
+        assert!(!restored.extra.contains_key("httpUrl"));
+        assert_eq!(restored.extra.get(GEMINI_MCP_TRUST_FIELD), Some(&Value::Bool(true)));
+    }
 }
@@ -0,0 +1,127 @@
+#![allow(missing_docs)]
+
+//! Process-wide policy for yes/no confirmation prompts.
+//!
+//! Prompts used to block on stdin unconditionally, which hangs CI jobs and
+//! provisioning scripts. Every prompt now goes through [`confirm`], which
+//! answers automatically under `--yes`, and fails with an actionable error
+//! under `--no-input` or whenever stdin is not a terminal.
+
+use anyhow::{bail, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// How confirmation prompts are answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InteractionMode {
+    /// Ask on the terminal; fail when stdin is not a TTY.
+    #[default]
+    Prompt,
+    /// Answer every prompt with "yes" (`--yes`).
+    AssumeYes,
+    /// Never prompt; fail wherever an answer would be needed (`--no-input`).
+    NoInput,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+impl InteractionMode {
+    const fn as_u8(self) -> u8 {
+        match self {
+            Self::Prompt => 0,
+            Self::AssumeYes => 1,
+            Self::NoInput => 2,
+        }
+    }
+
+    const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::AssumeYes,
+            2 => Self::NoInput,
+            _ => Self::Prompt,
+        }
+    }
+
+    /// Mode selected by the global `--yes` / `--no-input` flags.
+    #[must_use]
+    pub const fn from_flags(yes: bool, no_input: bool) -> Self {
+        if yes {
+            Self::AssumeYes
+        } else if no_input {
+            Self::NoInput
+        } else {
+            Self::Prompt
+        }
+    }
+}
+
+/// Select how prompts are answered for the rest of the process.
+pub fn set_mode(mode: InteractionMode) {
+    MODE.store(mode.as_u8(), Ordering::Relaxed);
+}
+
+/// The currently selected prompt mode.
+#[must_use]
+pub fn mode() -> InteractionMode {
+    InteractionMode::from_u8(MODE.load(Ordering::Relaxed))
+}
+
+/// Whether a call to [`confirm`] would actually ask the user.
+#[must_use]
+pub fn can_prompt() -> bool {
+    mode() == InteractionMode::Prompt && io::stdin().is_terminal()
+}
+
+/// Ask a yes/no question on stderr; anything but `y` counts as "no".
+///
+/// # Errors
+///
+/// Returns an error if prompting is disabled with `--no-input`, stdin is not a
+/// terminal, or reading the answer fails.
+pub fn confirm(question: &str) -> Result<bool> {
+    match mode() {
+        InteractionMode::AssumeYes => {
+            eprintln!("{question} [y/N] y (--yes)");
+            Ok(true)
+        },
+        InteractionMode::NoInput => refuse(question, "--no-input is set"),
+        InteractionMode::Prompt if !io::stdin().is_terminal() => {
+            refuse(question, "stdin is not a terminal")
+        },
+        InteractionMode::Prompt => {
+            eprint!("{question} [y/N] ");
+            io::stderr().flush()?;
+
+            let mut input = String::new();
+            io::stdin().lock().read_line(&mut input)?;
+            Ok(input.trim().eq_ignore_ascii_case("y"))
+        },
+    }
+}
+
+fn refuse(question: &str, reason: &str) -> Result<bool> {
+    bail!(
+        "Cannot ask \"{question}\" because {reason}; re-run with --yes to accept, or choose a \
+         non-interactive option such as --strategy"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_map_to_modes() {
+        assert_eq!(InteractionMode::from_flags(false, false), InteractionMode::Prompt);
+        assert_eq!(InteractionMode::from_flags(true, false), InteractionMode::AssumeYes);
+        assert_eq!(InteractionMode::from_flags(false, true), InteractionMode::NoInput);
+    }
+
+    #[test]
+    fn modes_round_trip_through_storage() {
+        for mode in [InteractionMode::Prompt, InteractionMode::AssumeYes, InteractionMode::NoInput]
+        {
+            assert_eq!(InteractionMode::from_u8(mode.as_u8()), mode);
+        }
+    }
+}
@@ -0,0 +1,409 @@
+#![allow(missing_docs)]
+
+//! `${NAME}` interpolation for `mcpServers.json`.
+//!
+//! Server `command`, `args`, `env`, `cwd`, `url`, and `headers` values are
+//! interpolated when a sync renders them:
+//!
+//! - `${NAME}` is replaced with the value of `NAME`
+//! - `${NAME:-default}` falls back to `default` when `NAME` is unset or empty
+//! - `$${NAME}` is written out as a literal `${NAME}`, for agents that expand
+//!   references themselves when they launch the server
+//!
+//! Names are looked up in the built-ins (`CLAUDIUS_PROJECT_ROOT`,
+//! `CLAUDIUS_CONFIG_DIR`), then the `[variables]` table of `config.toml`, then
+//! the process environment. `HOME` falls back to the user's home directory.
+//! `[variables]` values may reference other names; cycles are reported.
+
+use crate::config::{McpServerConfig, McpServersConfig};
+use crate::sync_operations::AgentContext;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Project root of a project-local sync; undefined for global syncs.
+pub const PROJECT_ROOT_VARIABLE: &str = "CLAUDIUS_PROJECT_ROOT";
+
+/// Directory holding `mcpServers.json`.
+pub const CONFIG_DIR_VARIABLE: &str = "CLAUDIUS_CONFIG_DIR";
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InterpolationError {
+    #[error("undefined variable '{0}' (set it in the environment or [variables] of config.toml, or give a default with ${{{0}:-...}})")]
+    Undefined(String),
+
+    #[error("circular variable reference: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+
+    #[error("invalid variable name '{0}'")]
+    InvalidName(String),
+
+    #[error("unterminated '${{' in '{0}'")]
+    Unterminated(String),
+}
+
+/// Resolves `${NAME}` references against built-ins, `[variables]`, and the environment.
+#[derive(Debug, Clone, Default)]
+pub struct Interpolator {
+    builtins: HashMap<String, String>,
+    variables: BTreeMap<String, String>,
+    environment: HashMap<String, String>,
+    pass_environment_through: bool,
+}
+
+impl Interpolator {
+    /// Interpolator for a sync reading sources from `config_dir`.
+    #[must_use]
+    pub fn new(
+        config_dir: &Path,
+        project_root: Option<&Path>,
+        variables: BTreeMap<String, String>,
+    ) -> Self {
+        let mut environment: HashMap<String, String> = std::env::vars().collect();
+        if !environment.contains_key("HOME") {
+            if let Some(home) = directories::BaseDirs::new() {
+                environment.insert("HOME".to_string(), home.home_dir().display().to_string());
+            }
+        }
+
+        let mut builtins =
+            HashMap::from([(CONFIG_DIR_VARIABLE.to_string(), config_dir.display().to_string())]);
+        if let Some(root) = project_root {
+            builtins.insert(PROJECT_ROOT_VARIABLE.to_string(), root.display().to_string());
+        }
+
+        Self { builtins, variables, environment, pass_environment_through: false }
+    }
+
+    /// Replace the process environment used for lookups.
+    #[must_use]
+    pub fn with_environment(mut self, environment: HashMap<String, String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Leave references to anything but built-ins and `[variables]` in place,
+    /// for an agent that expands them from its own environment.
+    #[must_use]
+    pub const fn passing_environment_through(mut self) -> Self {
+        self.pass_environment_through = true;
+        self
+    }
+
+    /// Interpolate every `${...}` reference in `input`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for undefined names, `[variables]` cycles, invalid
+    /// names, and unterminated references.
+    pub fn interpolate(&self, input: &str) -> Result<String, InterpolationError> {
+        self.expand(input, &mut Vec::new())
+    }
+
+    /// Check that a `[variables]` entry resolves.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first problem found while resolving `name`.
+    pub fn check_variable(&self, name: &str) -> Result<(), InterpolationError> {
+        self.lookup(name, &mut Vec::new()).map(|_| ())
+    }
+
+    fn expand(&self, input: &str, stack: &mut Vec<String>) -> Result<String, InterpolationError> {
+        let mut output = String::with_capacity(input.len());
+        let mut rest = input;
+
+        while let Some((before, after_dollar)) = rest.split_once('$') {
+            output.push_str(before);
+
+            if let Some(escaped) = after_dollar.strip_prefix("${") {
+                let (body, remainder) = split_reference(escaped, input)?;
+                output.push_str("${");
+                output.push_str(body);
+                output.push('}');
+                rest = remainder;
+            } else if let Some(reference) = after_dollar.strip_prefix('{') {
+                let (body, remainder) = split_reference(reference, input)?;
+                output.push_str(&self.resolve_reference(body, stack)?);
+                rest = remainder;
+            } else {
+                output.push('$');
+                rest = after_dollar;
+            }
+        }
+
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn resolve_reference(
+        &self,
+        body: &str,
+        stack: &mut Vec<String>,
+    ) -> Result<String, InterpolationError> {
+        let (name, default) = match body.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (body, None),
+        };
+        if !is_valid_name(name) {
+            return Err(InterpolationError::InvalidName(name.to_string()));
+        }
+        if self.pass_environment_through
+            && !self.builtins.contains_key(name)
+            && !self.variables.contains_key(name)
+        {
+            return match default {
+                Some(fallback) => Ok(format!("${{{name}:-{}}}", self.expand(fallback, stack)?)),
+                None => Ok(format!("${{{name}}}")),
+            };
+        }
+
+        match (self.lookup(name, stack), default) {
+            (Ok(value), Some(fallback)) if value.is_empty() => self.expand(fallback, stack),
+            (Err(InterpolationError::Undefined(_)), Some(fallback)) => self.expand(fallback, stack),
+            (result, _) => result,
+        }
+    }
+
+    fn lookup(&self, name: &str, stack: &mut Vec<String>) -> Result<String, InterpolationError> {
+        if let Some(value) = self.builtins.get(name) {
+            return Ok(value.clone());
+        }
+
+        if let Some(raw) = self.variables.get(name) {
+            if let Some(start) = stack.iter().position(|entry| entry == name) {
+                let mut cycle = stack.get(start..).unwrap_or_default().to_vec();
+                cycle.push(name.to_string());
+                return Err(InterpolationError::Cycle(cycle));
+            }
+            stack.push(name.to_string());
+            let resolved = self.expand(raw, stack);
+            stack.pop();
+            return resolved;
+        }
+
+        self.environment
+            .get(name)
+            .cloned()
+            .ok_or_else(|| InterpolationError::Undefined(name.to_string()))
+    }
+}
+
+/// Split `text` (just after `${`) into the reference body and the text after its `}`.
+fn split_reference<'a>(
+    text: &'a str,
+    input: &str,
+) -> Result<(&'a str, &'a str), InterpolationError> {
+    let mut depth = 0_usize;
+    let mut previous = None;
+    for (index, character) in text.char_indices() {
+        match character {
+            '{' if previous == Some('$') => depth = depth.saturating_add(1),
+            '}' if depth == 0 => {
+                let body = text.get(..index).unwrap_or_default();
+                let remainder = text.get(index.saturating_add(1)..).unwrap_or_default();
+                return Ok((body, remainder));
+            },
+            '}' => depth = depth.saturating_sub(1),
+            _ => {},
+        }
+        previous = Some(character);
+    }
+    Err(InterpolationError::Unterminated(input.to_string()))
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut characters = name.chars();
+    characters
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && characters.all(|rest| rest.is_ascii_alphanumeric() || rest == '_')
+}
+
+/// Whether the agent expands `${NAME}` in MCP server definitions at launch.
+#[must_use]
+pub const fn agent_expands_references(agent_context: AgentContext) -> bool {
+    agent_context.is_claude_code || agent_context.is_gemini
+}
+
+/// Interpolate every server in place.
+///
+/// For an agent that expands `${NAME}` itself, only built-ins and
+/// `[variables]` are resolved; environment references are written through
+/// unchanged, so secrets such as tokens stay out of the agent's config file.
+///
+/// Returns warnings for `$${NAME}` escapes written for an agent that does not
+/// expand them.
+///
+/// # Errors
+///
+/// Returns an error listing every value that cannot be interpolated.
+pub fn interpolate_mcp_servers(
+    servers: &mut McpServersConfig,
+    interpolator: &Interpolator,
+    agent_context: AgentContext,
+) -> anyhow::Result<Vec<String>> {
+    let passing_through;
+    let resolver = if agent_expands_references(agent_context) {
+        passing_through = interpolator.clone().passing_environment_through();
+        &passing_through
+    } else {
+        interpolator
+    };
+    let mut warnings = Vec::new();
+    let mut problems = Vec::new();
+
+    for (name, server) in &mut servers.mcp_servers {
+        for (location, value) in interpolated_fields(name, server) {
+            if value.contains("$${") && !agent_expands_references(agent_context) {
+                warnings.push(format!(
+                    "{location} passes a literal ${{...}} through, but this agent does not expand it"
+                ));
+            }
+            match resolver.interpolate(value) {
+                Ok(interpolated) => *value = interpolated,
+                Err(problem) => problems.push(format!("{location}: {problem}")),
+            }
+        }
+    }
+
+    if !problems.is_empty() {
+        problems.sort();
+        anyhow::bail!("Failed to interpolate mcpServers.json:\n  {}", problems.join("\n  "));
+    }
+
+    warnings.sort();
+    Ok(warnings)
+}
+
+/// Every interpolation problem in `servers`, as `(location, problem)` pairs.
+#[must_use]
+pub fn check_mcp_servers(
+    servers: &McpServersConfig,
+    interpolator: &Interpolator,
+) -> Vec<(String, InterpolationError)> {
+    let mut checked = servers.clone();
+    let mut problems = Vec::new();
+    for (name, server) in &mut checked.mcp_servers {
+        for (location, value) in interpolated_fields(name, server) {
+            if let Err(problem) = interpolator.interpolate(value) {
+                problems.push((location, problem));
+            }
+        }
+    }
+    problems.sort_by(|left, right| left.0.cmp(&right.0));
+    problems
+}
+
+fn interpolated_fields<'a>(
+    name: &str,
+    server: &'a mut McpServerConfig,
+) -> Vec<(String, &'a mut String)> {
+    let prefix = format!("mcpServers.{name}");
+    let mut fields = Vec::new();
+
+    if let Some(command) = server.command.as_mut() {
+        fields.push((format!("{prefix}.command"), command));
+    }
+    for (index, arg) in server.args.iter_mut().enumerate() {
+        fields.push((format!("{prefix}.args[{index}]"), arg));
+    }
+    for (key, value) in &mut server.env {
+        fields.push((format!("{prefix}.env.{key}"), value));
+    }
+    if let Some(Value::String(cwd)) = server.extra.get_mut("cwd") {
+        fields.push((format!("{prefix}.cwd"), cwd));
+    }
+    if let Some(url) = server.url.as_mut() {
+        fields.push((format!("{prefix}.url"), url));
+    }
+    for (key, value) in &mut server.headers {
+        fields.push((format!("{prefix}.headers.{key}"), value));
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn interpolator(variables: &[(&str, &str)], environment: &[(&str, &str)]) -> Interpolator {
+        Interpolator::new(
+            &PathBuf::from("/config/claudius"),
+            Some(&PathBuf::from("/work/project")),
+            variables
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+                .collect(),
+        )
+        .with_environment(
+            environment
+                .iter()
+                .map(|(name, value)| ((*name).to_string(), (*value).to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn interpolate_resolves_builtins_variables_environment_and_defaults() {
+        let subject = interpolator(
+            &[("DATA_DIR", "${CLAUDIUS_PROJECT_ROOT}/data")],
+            &[("HOME", "/home/dev")],
+        );
+
+        assert_eq!(
+            subject
+                .interpolate(
+                    "${HOME}:${DATA_DIR}:${CLAUDIUS_CONFIG_DIR}:${DB_URL:-postgres://localhost}"
+                )
+                .unwrap(),
+            "/home/dev:/work/project/data:/config/claudius:postgres://localhost"
+        );
+    }
+
+    #[test]
+    fn interpolate_keeps_escaped_and_bare_references() {
+        let subject = interpolator(&[], &[]);
+
+        assert_eq!(
+            subject.interpolate("$${API_KEY:-none} $PATH cost$").unwrap(),
+            "${API_KEY:-none} $PATH cost$"
+        );
+    }
+
+    #[test]
+    fn interpolate_reports_undefined_names_and_cycles() {
+        let subject = interpolator(&[("A", "${B}"), ("B", "${A}")], &[]);
+
+        assert_eq!(
+            subject.interpolate("${MISSING}").unwrap_err(),
+            InterpolationError::Undefined("MISSING".to_string())
+        );
+        assert_eq!(
+            subject.interpolate("${A}").unwrap_err(),
+            InterpolationError::Cycle(vec!["A".into(), "B".into(), "A".into()])
+        );
+        assert!(matches!(
+            subject.interpolate("${UNCLOSED").unwrap_err(),
+            InterpolationError::Unterminated(_)
+        ));
+    }
+
+    #[test]
+    fn passing_environment_through_resolves_only_builtins_and_variables() {
+        let subject = interpolator(
+            &[("DATA_DIR", "${CLAUDIUS_PROJECT_ROOT}/data")],
+            &[("GITHUB_TOKEN", "ghp_secret")],
+        )
+        .passing_environment_through();
+
+        assert_eq!(
+            subject
+                .interpolate("Bearer ${GITHUB_TOKEN} ${DATA_DIR} ${PORT:-${CLAUDIUS_CONFIG_DIR}}")
+                .unwrap(),
+            "Bearer ${GITHUB_TOKEN} /work/project/data ${PORT:-/config/claudius}"
+        );
+    }
+}
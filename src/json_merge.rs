@@ -29,3 +29,25 @@ fn deep_merge_json_value(target: &mut Value, overlay: &Value) {
         },
     }
 }
+
+/// Deep-merge the serialized form of `overlay` over `base` and deserialize the result.
+///
+/// # Errors
+///
+/// Returns an error if either value is not a map or the merged map does not
+/// deserialize back into `T`.
+pub fn merge_serialized<T, O>(base: Option<&T>, overlay: &O) -> anyhow::Result<T>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+    O: serde::Serialize,
+{
+    let mut fields: HashMap<String, Value> = match base {
+        Some(value) => serde_json::from_value(serde_json::to_value(value)?)?,
+        None => HashMap::new(),
+    };
+    let overlay_fields: HashMap<String, Value> =
+        serde_json::from_value(serde_json::to_value(overlay)?)?;
+    deep_merge_json_maps(&mut fields, &overlay_fields);
+
+    Ok(serde_json::from_value(serde_json::to_value(fields)?)?)
+}
@@ -3,23 +3,43 @@
 pub mod agent_paths;
 pub mod app_config;
 pub mod asset_sync;
+pub mod backups;
 pub mod bootstrap;
 pub mod claude_settings;
 pub mod cli;
 pub mod codex_settings;
 pub mod config;
+pub mod config_import;
 pub mod config_migrate;
 pub mod doctor;
+pub mod file_walk;
 pub mod gemini_settings;
+pub mod interaction;
+pub mod interpolation;
 pub(crate) mod json_merge;
+pub mod mcp_edit;
+pub mod mcp_lint;
+pub mod mcp_ownership;
+pub mod mcp_presets;
+pub mod mcp_probe;
 pub mod merge;
+pub mod output;
+pub mod profiles;
 pub mod profiling;
+pub mod projects;
+pub mod sarif;
+pub mod secret_launch;
 pub mod secrets;
 pub mod skills;
+pub mod source_layers;
 pub mod sync_operations;
+pub mod sync_status;
+pub mod sync_transaction;
 pub mod template;
+pub mod undo;
 pub mod validation;
 pub mod variable_expansion;
+pub mod watch;
 
 pub use config::{Config, McpServersConfig};
 pub use merge::{merge_configs, MergeStrategy};
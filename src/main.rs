@@ -1,30 +1,41 @@
 #![allow(missing_docs)]
 
 use anyhow::{Context, Result};
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, Parser, ValueEnum};
+use claudius::backups::{self, Backup, RetentionPolicy};
 #[cfg(feature = "profiling")]
 use claudius::profiling::profile_report;
 use claudius::{
     agent_paths,
-    app_config::AppConfig,
+    app_config::{AppConfig, ProfileConfig},
     asset_sync::SyncBehavior,
     bootstrap,
     cli::{self, Cli},
     config::{reader, Config},
+    config_import,
     doctor::{render_report, run_doctor, DoctorOptions},
+    interaction,
+    merge::MergeStrategy,
+    output::{self, OutputFormat},
+    profiles,
     secrets::SecretResolver,
     skills,
     sync_operations::{
-        determine_agent, handle_backup, handle_dry_run, merge_all_configs,
-        print_supporting_assets_dry_run, read_configurations, sync_supporting_assets,
-        write_configurations, AgentContext, CodexGlobalSyncOptions, ReadConfigResult,
-        SupportingAssetSyncReport,
+        adjust_sources, collect_stale_mcp_servers, commit_planned_writes, determine_agent,
+        handle_backup, handle_dry_run, load_target_claude_config, merge_all_configs,
+        plan_configuration_writes, print_planned_write_diff, print_supporting_assets_dry_run,
+        read_configurations, stage_mcp_ownership, sync_supporting_assets, write_configurations,
+        AdjustedSources, AgentContext, AgentSyncReport, CodexGlobalSyncOptions, ReadConfigResult,
+        SourceAdjustments, SupportingAssetSyncReport,
     },
+    sync_status,
     template::{
         append_rules_to_context_file, append_template_to_context_file, ensure_rules_directory,
     },
+    undo::{self, UndoRecorder},
+    watch::{SourceArea, SourceWatcher, SyncPhases},
 };
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet};
 use tracing::{debug, error, info, warn, Level};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
@@ -32,6 +43,13 @@ fn main() -> Result<()> {
     let cli = Cli::parse();
 
     initialize_tracing(cli.debug, cli.trace);
+    let output_format = requested_output_format(cli.command.as_ref());
+    output::set_format(output_format);
+    // Prompts cannot be answered when stdout is consumed as JSON
+    interaction::set_mode(interaction::InteractionMode::from_flags(
+        cli.yes,
+        cli.no_input || output_format == OutputFormat::Json,
+    ));
 
     let log_app_config_warnings = !matches!(
         cli.command.as_ref(),
@@ -53,6 +71,23 @@ fn main() -> Result<()> {
     dispatch_command(command, app_config.as_ref())
 }
 
+/// `--format` of the reporting commands; text for every other command
+fn requested_output_format(command: Option<&cli::Commands>) -> OutputFormat {
+    match command {
+        Some(cli::Commands::Config(cli::ConfigCommands::Sync(args))) => args.format,
+        Some(cli::Commands::Config(cli::ConfigCommands::Validate(args))) => args.format.into(),
+        Some(cli::Commands::Config(cli::ConfigCommands::Doctor(args))) => args.format,
+        Some(cli::Commands::Config(cli::ConfigCommands::Migrate(args))) => args.format,
+        Some(cli::Commands::Skills(cli::SkillsCommands::Sync(args))) => args.format,
+        Some(cli::Commands::Skills(cli::SkillsCommands::Validate(args))) => args.format.into(),
+        Some(cli::Commands::Mcp(cli::McpCommands::Check(args))) => args.format,
+        Some(cli::Commands::Mcp(cli::McpCommands::List(args))) => args.format,
+        Some(cli::Commands::Mcp(cli::McpCommands::Show(args))) => args.format,
+        Some(cli::Commands::Mcp(cli::McpCommands::Presets(args))) => args.format,
+        _ => OutputFormat::Text,
+    }
+}
+
 /// Initialize tracing with the specified debug/trace flags
 fn initialize_tracing(debug: bool, trace: bool) {
     let log_level = if trace {
@@ -120,9 +155,14 @@ fn dispatch_command(command: cli::Commands, app_config: Option<&AppConfig>) -> R
         cli::Commands::Config(subcommand) => match subcommand {
             cli::ConfigCommands::Init(args) => run_init(args.force, app_config),
             cli::ConfigCommands::Sync(args) => run_config_sync(args, app_config),
+            cli::ConfigCommands::Status(args) => run_config_status(args, app_config),
+            cli::ConfigCommands::Import(args) => run_config_import(args),
             cli::ConfigCommands::Validate(args) => run_config_validate(args, app_config),
             cli::ConfigCommands::Doctor(args) => run_config_doctor(args),
             cli::ConfigCommands::Migrate(args) => run_config_migrate(args, app_config),
+            cli::ConfigCommands::Backups(backups_command) => {
+                run_config_backups(backups_command, app_config)
+            },
         },
         cli::Commands::Skills(subcommand) => match subcommand {
             cli::SkillsCommands::Sync(args) => run_sync_skills(args, app_config),
@@ -130,6 +170,7 @@ fn dispatch_command(command: cli::Commands, app_config: Option<&AppConfig>) -> R
             cli::SkillsCommands::Migrate(args) => run_migrate_skills(args),
             cli::SkillsCommands::Render(args) => run_render_skills(&args, app_config),
         },
+        cli::Commands::Mcp(subcommand) => dispatch_mcp_command(subcommand, app_config),
         cli::Commands::Context(subcommand) => match subcommand {
             cli::ContextCommands::Append(args) => run_append_context(
                 args.rule,
@@ -152,6 +193,19 @@ fn dispatch_command(command: cli::Commands, app_config: Option<&AppConfig>) -> R
         cli::Commands::Secrets(subcommand) => match subcommand {
             cli::SecretsCommands::Run(args) => run_command(&args.command, app_config),
         },
+        cli::Commands::Undo(args) => run_undo(args),
+    }
+}
+
+fn dispatch_mcp_command(command: cli::McpCommands, app_config: Option<&AppConfig>) -> Result<()> {
+    match command {
+        cli::McpCommands::Add(args) => run_mcp_add(args, app_config),
+        cli::McpCommands::Remove(args) => run_mcp_remove(&args, app_config),
+        cli::McpCommands::Rename(args) => run_mcp_rename(&args, app_config),
+        cli::McpCommands::List(_) => run_mcp_list(),
+        cli::McpCommands::Show(args) => run_mcp_show(&args),
+        cli::McpCommands::Presets(_) => run_mcp_presets(),
+        cli::McpCommands::Check(args) => run_mcp_check(&args, app_config),
     }
 }
 
@@ -217,61 +271,126 @@ fn run_init(force: bool, app_config: Option<&AppConfig>) -> Result<()> {
 }
 
 fn run_sync_skills(args: cli::SkillsSyncArgs, app_config: Option<&AppConfig>) -> Result<()> {
-    let cli::SkillsSyncArgs { global, agent, enable_codex_skills, dry_run, prune } = args;
+    let cli::SkillsSyncArgs {
+        global,
+        agent,
+        enable_codex_skills,
+        dry_run,
+        prune,
+        watch,
+        format: _,
+    } = args;
 
     let effective_agent = determine_agent(agent, app_config);
 
     if effective_agent == Some(claudius::app_config::Agent::Codex) && enable_codex_skills {
-        println!("Warning: --enable-codex-skills is deprecated and no longer required.");
+        output::progress("Warning: --enable-codex-skills is deprecated and no longer required.");
     }
 
     let config = Config::new_with_agent(global, effective_agent)?;
-    let source_set =
-        skills::collect_claudius_skill_source_set(config.config_root_dir()?, config.agent)?;
+    if !global {
+        warn_untrusted_project_layer()?;
+    }
+    recording_undo(dry_run, |undo| {
+        sync_skills_once(&config, SyncBehavior { dry_run, prune }, undo)
+    })?;
+
+    if watch {
+        watch_skills_sync(&config, SyncBehavior { dry_run, prune })?;
+    }
+    Ok(())
+}
+
+fn sync_skills_once(config: &Config, behavior: SyncBehavior, undo: &UndoRecorder) -> Result<()> {
+    let source_set = skills::collect_layered_skill_source_set(
+        config.config_root_dir()?,
+        config.project_layer_dir.as_deref(),
+        config.agent,
+    )?;
 
     if source_set.includes_legacy_commands {
-        println!(
+        output::progress(format!(
             "Legacy commands directory detected; syncing skills from {}",
             config.config_root_dir()?.join("commands").display()
-        );
+        ));
     }
     for warning in &source_set.warnings {
-        println!("Warning: {warning}");
+        output::progress(format!("Warning: {warning}"));
     }
 
-    let skill_targets = determine_skill_sync_targets(&config)?;
+    let skill_targets = determine_skill_sync_targets(config)?;
     let reports = skill_targets
         .iter()
         .map(|target_dir| {
             skills::sync_skill_mappings_with_options(
                 &source_set.mappings,
                 target_dir,
-                SyncBehavior { dry_run, prune },
+                behavior,
+                undo,
             )
         })
         .collect::<Result<Vec<_>>>()?;
 
-    print_skill_sync_result(&reports, dry_run);
+    if output::is_json() {
+        return output::print_json(
+            "skills sync",
+            &serde_json::json!({
+                "dry_run": behavior.dry_run,
+                "warnings": source_set
+                    .warnings
+                    .iter()
+                    .map(|warning| &warning.message)
+                    .collect::<Vec<_>>(),
+                "targets": reports,
+            }),
+        );
+    }
+    print_skill_sync_result(&reports, behavior.dry_run);
     Ok(())
 }
 
-fn run_validate_skills(args: cli::SkillsValidateArgs) -> Result<()> {
-    let config_dir = Config::get_config_dir().context("Failed to determine Claudius config dir")?;
-    let report = skills::validate_claudius_skill_sources(&config_dir, args.agent)?;
+/// Re-validate and re-sync skills after every batch of skill source changes
+fn watch_skills_sync(config: &Config, behavior: SyncBehavior) -> Result<()> {
+    let config_dir = config.config_root_dir()?.to_path_buf();
+    let mut watcher = source_watcher(&config_dir, config.is_global, |area| {
+        matches!(area, SourceArea::Skills | SourceArea::Commands)
+    })?;
 
-    println!("Skills validation succeeded for {}", config_dir.display());
+    loop {
+        let changes = watcher.wait_for_changes();
+        print_watch_cycle(&changes);
 
-    if report.warnings.is_empty() {
-        println!("No skill warnings detected.");
-        return Ok(());
+        if let Err(error) = skills::validate_claudius_skill_sources(&config_dir, config.agent) {
+            report_watch_validation(Err(error));
+            continue;
+        }
+        match recording_undo(behavior.dry_run, |undo| sync_skills_once(config, behavior, undo)) {
+            Ok(()) => println!("Re-synced skills"),
+            Err(error) => println!("Sync failed: {error:#}"),
+        }
     }
+}
 
-    println!("Warnings:");
-    for warning in &report.warnings {
-        println!("  - {warning}");
+fn run_validate_skills(args: cli::SkillsValidateArgs) -> Result<()> {
+    let config_dir = Config::get_config_dir().context("Failed to determine Claudius config dir")?;
+    let report = skills::validate_claudius_skill_sources(&config_dir, args.agent)?;
+
+    let diagnostics = report
+        .warnings
+        .iter()
+        .cloned()
+        .map(claudius::validation::Diagnostic::from)
+        .collect::<Vec<_>>();
+    match args.format {
+        output::ValidationFormat::Text => print_skill_validation(&config_dir, &report.warnings),
+        output::ValidationFormat::Json => output::print_json(
+            "skills validate",
+            &serde_json::json!({ "config_dir": config_dir, "diagnostics": diagnostics }),
+        )?,
+        output::ValidationFormat::Sarif => claudius::sarif::print(&diagnostics)?,
     }
 
-    if args.strict {
+    if args.strict && !report.warnings.is_empty() {
         anyhow::bail!(
             "Skills validation produced {} warning(s) under --strict",
             report.warnings.len()
@@ -281,9 +400,28 @@ fn run_validate_skills(args: cli::SkillsValidateArgs) -> Result<()> {
     Ok(())
 }
 
+fn print_skill_validation(config_dir: &std::path::Path, warnings: &[skills::SkillWarning]) {
+    println!("Skills validation succeeded for {}", config_dir.display());
+
+    if warnings.is_empty() {
+        println!("No skill warnings detected.");
+        return;
+    }
+
+    println!("Warnings:");
+    for warning in warnings {
+        println!("  - {warning}");
+    }
+}
+
 fn run_migrate_skills(args: cli::SkillsMigrateArgs) -> Result<()> {
     let config_dir = Config::get_config_dir().context("Failed to determine Claudius config dir")?;
-    let report = skills::migrate_deprecated_agent_overrides(&config_dir, args.agent, args.dry_run)?;
+    let report = skills::migrate_deprecated_agent_overrides(
+        &config_dir,
+        args.agent,
+        args.dry_run,
+        &UndoRecorder::disabled(),
+    )?;
 
     if report.migrated_overrides.is_empty() {
         println!(
@@ -336,6 +474,7 @@ fn run_render_skills(args: &cli::SkillsRenderArgs, app_config: Option<&AppConfig
         &source_set.mappings,
         &args.output,
         SyncBehavior { dry_run: false, prune: args.prune },
+        &UndoRecorder::disabled(),
     )?;
 
     println!(
@@ -370,8 +509,406 @@ fn determine_skill_sync_targets(config: &Config) -> Result<Vec<std::path::PathBu
 }
 
 fn run_config_sync(args: cli::ConfigSyncArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    if args.all_projects {
+        return run_all_projects_sync(&args, app_config);
+    }
+
     let options = build_sync_options(args, app_config)?;
-    run_sync(&options, app_config)
+    let reports = recording_undo(options.dry_run, |undo| {
+        let reports = run_sync(&options, app_config, undo)?;
+
+        if options.remember_profile && !options.dry_run {
+            let config_dir = Config::get_config_dir()
+                .context("Failed to determine Claudius config directory")?;
+            profiles::save_active_profile(&config_dir, options.profile.as_deref(), undo)?;
+        }
+        Ok(reports)
+    })?;
+
+    if output::is_json() {
+        output::print_json(
+            "config sync",
+            &serde_json::json!({
+                "dry_run": options.dry_run,
+                "profile": options.profile,
+                "agents": reports,
+            }),
+        )?;
+    }
+
+    if options.watch {
+        watch_config_sync(options, app_config)?;
+    }
+    Ok(())
+}
+
+/// Warn that the `.claudius/` layer of the current project is ignored until it is trusted
+fn warn_untrusted_project_layer() -> Result<()> {
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    let project_dir = std::env::current_dir().context("Failed to determine current directory")?;
+    if let Some(layer_dir) =
+        claudius::source_layers::untrusted_project_layer(&project_dir, &config_dir)
+    {
+        output::progress(format!(
+            "Warning: ignoring {}; add {} to `trusted-projects` in config.toml to apply it",
+            layer_dir.display(),
+            project_dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Run a sync while recording the files it changes for `claudius undo`
+///
+/// Dry runs change nothing and get a disabled recorder. Failing to save the
+/// record only warns; the sync result is returned unchanged.
+fn recording_undo<T>(dry_run: bool, sync: impl FnOnce(&UndoRecorder) -> Result<T>) -> Result<T> {
+    if dry_run {
+        return sync(&UndoRecorder::disabled());
+    }
+
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    let command_line = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    let recorder = UndoRecorder::new(&config_dir, &command_line);
+    let result = sync(&recorder);
+    match recorder.finish() {
+        Ok(Some(saved)) => debug!("Recorded undo history in {}", saved.dir.display()),
+        Ok(None) => debug!("Sync changed no files; nothing recorded for undo"),
+        Err(error) => warn!("Failed to record undo history: {error:#}"),
+    }
+    result
+}
+
+/// Restore the newest undo record, or list the history
+fn run_undo(args: cli::UndoArgs) -> Result<()> {
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    let history = undo::history(&config_dir)?;
+
+    if args.list {
+        if history.is_empty() {
+            println!("Nothing to undo");
+        }
+        for saved in &history {
+            println!(
+                "{}  claudius {}  ({} file(s))",
+                saved.record.created,
+                saved.record.command,
+                saved.record.entries.len()
+            );
+        }
+        return Ok(());
+    }
+
+    let Some(newest) = history.first() else {
+        println!("Nothing to undo");
+        return Ok(());
+    };
+
+    let modified = undo::modified_files(&newest.record)?;
+    if !modified.is_empty() {
+        let listed = modified
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join("\n  ");
+        anyhow::bail!(
+            "Refusing to undo `claudius {}`: these files changed after it ran:\n  {listed}",
+            newest.record.command
+        );
+    }
+
+    let verb = if args.dry_run { "Would undo" } else { "Undoing" };
+    println!("{verb} `claudius {}` from {}:", newest.record.command, newest.record.created);
+    for entry in &newest.record.entries {
+        let action = if entry.removes_file() { "remove " } else { "restore" };
+        println!("  {action} {}", entry.path.display());
+    }
+
+    if !args.dry_run {
+        undo::apply(newest)?;
+        println!("Restored {} file(s)", newest.record.entries.len());
+    }
+    Ok(())
+}
+
+/// Run the project-local sync in each `[[projects]]` entry and report every result
+fn run_all_projects_sync(args: &cli::ConfigSyncArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    let projects = app_config.map(|cfg| cfg.projects.as_slice()).unwrap_or_default();
+    if projects.is_empty() {
+        anyhow::bail!(
+            "No projects registered; add [[projects]] entries with a path to config.toml"
+        );
+    }
+
+    let base_dir = claudius::projects::registry_base_dir()?;
+    let syncs = claudius::projects::plan_project_syncs(projects, &base_dir, args.agent, args.scope);
+    if let Some(global_sync) = syncs.iter().find(|sync| compute_effective_global(false, sync.scope))
+    {
+        anyhow::bail!(
+            "{} uses a global Claude Code scope; --all-projects only runs project-local syncs",
+            global_sync.label()
+        );
+    }
+
+    let program = std::env::current_exe().context("Failed to locate the claudius executable")?;
+    let forwarded = project_sync_args(args)?;
+    println!(
+        "Syncing {} project target(s) {}",
+        syncs.len(),
+        if args.dry_run { "in parallel" } else { "one at a time" }
+    );
+    // Writing syncs share the journal, ownership ledger, and `~/.claude.json`; see
+    // `claudius::projects` for why only dry runs are parallel.
+    let outcomes = claudius::projects::run_project_syncs(&program, &forwarded, syncs, args.dry_run);
+
+    for outcome in &outcomes {
+        println!("\n== {} ==", outcome.sync.label());
+        print!("{}", outcome.output);
+    }
+    println!("\n{}", claudius::projects::summarize_outcomes(&outcomes));
+
+    let failed = outcomes.iter().filter(|outcome| !outcome.success).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} project sync(s) failed", outcomes.len());
+    }
+    Ok(())
+}
+
+/// Arguments every per-project `config sync` child receives
+///
+/// Children cannot prompt, so they run with `--no-input` unless `--yes` was given.
+fn project_sync_args(args: &cli::ConfigSyncArgs) -> Result<Vec<String>> {
+    let interaction_flag = if interaction::mode() == interaction::InteractionMode::AssumeYes {
+        "--yes"
+    } else {
+        "--no-input"
+    };
+    let mut forwarded = vec![interaction_flag.to_string(), "config".into(), "sync".into()];
+    for (enabled, flag) in [
+        (args.dry_run, "--dry-run"),
+        (args.backup, "--backup"),
+        (args.prune, "--prune"),
+        (args.no_profile, "--no-profile"),
+    ] {
+        if enabled {
+            forwarded.push(flag.to_string());
+        }
+    }
+    if let Some(strategy) = args.strategy.and_then(|value| value.to_possible_value()) {
+        forwarded.extend(["--strategy".to_string(), strategy.get_name().to_string()]);
+    }
+    if let Some(profile) = &args.profile {
+        forwarded.extend(["--profile".to_string(), profile.clone()]);
+    }
+    if let Some(config) = &args.config {
+        let absolute = std::path::absolute(config)
+            .with_context(|| format!("Failed to resolve {}", config.display()))?;
+        forwarded.extend(["--config".to_string(), absolute.display().to_string()]);
+    }
+    Ok(forwarded)
+}
+
+/// Re-run validation and the affected sync phases after every batch of source changes
+fn watch_config_sync(mut options: SyncOptions, app_config: Option<&AppConfig>) -> Result<()> {
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    let mut watcher = source_watcher(&config_dir, options.global, |_| true)?;
+    let effective_agent = determine_agent(options.agent_override, app_config);
+
+    loop {
+        let changes = watcher.wait_for_changes();
+        print_watch_cycle(&changes);
+
+        let diagnostics = collect_config_diagnostics(
+            &config_dir,
+            effective_agent,
+            options.claude_code_scope,
+            app_config,
+        );
+        if !report_watch_validation(diagnostics) {
+            continue;
+        }
+
+        options.phases = SyncPhases::for_changes(&changes);
+        match recording_undo(options.dry_run, |undo| run_sync(&options, app_config, undo)) {
+            Ok(_) => println!("Re-synced {}", describe_phases(options.phases)),
+            Err(error) => println!("Sync failed: {error:#}"),
+        }
+    }
+}
+
+/// Watcher over the config directory and, for project-local syncs, the `.claudius/` layer
+fn source_watcher(
+    config_dir: &std::path::Path,
+    global: bool,
+    include: fn(SourceArea) -> bool,
+) -> Result<SourceWatcher> {
+    let mut roots = vec![config_dir.to_path_buf()];
+    if !global {
+        let project_dir = std::env::current_dir()?;
+        roots.extend(claudius::source_layers::discover_project_layer(&project_dir, config_dir));
+    }
+
+    let watched_paths = display_paths(&roots);
+    let watcher = SourceWatcher::new(roots, include);
+    println!("\nWatching {watched_paths} for changes (Ctrl+C to stop)");
+    Ok(watcher)
+}
+
+fn print_watch_cycle(changes: &[claudius::watch::SourceChange]) {
+    println!(
+        "\n[{}] {} change(s): {}",
+        chrono::Local::now().format("%H:%M:%S"),
+        changes.len(),
+        claudius::watch::summarize_changes(changes)
+    );
+}
+
+/// Print validation errors; `false` when the sync should wait for the next change
+fn report_watch_validation(diagnostics: Result<Vec<claudius::validation::Diagnostic>>) -> bool {
+    let errors = match diagnostics {
+        Ok(found) => found
+            .into_iter()
+            .filter(|diagnostic| {
+                diagnostic.severity == claudius::validation::DiagnosticSeverity::Error
+            })
+            .map(|diagnostic| diagnostic.to_string())
+            .collect::<Vec<_>>(),
+        Err(error) => vec![format!("{error:#}")],
+    };
+    if errors.is_empty() {
+        return true;
+    }
+
+    println!("Validation failed; waiting for the next change:");
+    for error in &errors {
+        println!("  - {error}");
+    }
+    false
+}
+
+const fn describe_phases(phases: SyncPhases) -> &'static str {
+    match (phases.configuration, phases.assets) {
+        (true, true) => "configuration and supporting assets",
+        (true, false) => "configuration",
+        _ => "supporting assets",
+    }
+}
+
+/// `--profile` wins over the remembered profile; `--no-profile` ignores both
+fn resolve_profile_name(profile: Option<String>, no_profile: bool) -> Result<Option<String>> {
+    if profile.is_some() || no_profile {
+        return Ok(profile);
+    }
+
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    profiles::load_active_profile(&config_dir)
+}
+
+fn select_profile<'a>(
+    name: Option<&str>,
+    app_config: Option<&'a AppConfig>,
+) -> Result<Option<&'a ProfileConfig>> {
+    let Some(profile_name) = name else {
+        return Ok(None);
+    };
+
+    let profile = profiles::find_profile(app_config, profile_name)?;
+    output::progress(format!("Using profile '{profile_name}'"));
+    Ok(Some(profile))
+}
+
+/// Report drift between sources and deployed targets; exit with
+/// `DRIFT_EXIT_CODE` when anything is out of date
+fn run_config_status(args: cli::ConfigStatusArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    let cli::ConfigStatusArgs { global, agent, scope, strategy } = args;
+    let flags = SyncFlagSet {
+        scope,
+        codex_requirements: false,
+        codex_managed_config: false,
+        gemini_system: false,
+        gemini_system_defaults: false,
+    };
+    flags.validate(determine_agent(agent, app_config))?;
+    recover_interrupted_sync(true)?;
+
+    let effective_global = compute_effective_global(global, scope);
+    let agents = status_agents(effective_global, agent, scope, app_config)?;
+    let merge_strategy = resolve_merge_strategy(strategy, app_config);
+    let profile_name = resolve_profile_name(None, false)?;
+    let adjustments = SourceAdjustments {
+        profile: select_profile(profile_name.as_deref(), app_config)?,
+        app_config,
+    };
+    let mut drifted = false;
+    for agent_override in agents {
+        let (agent_context, config, paths) = setup_sync_context(
+            SyncContextRequest {
+                agent_override,
+                global: effective_global,
+                config_path: None,
+                target_config_path: None,
+                claude_code_scope: scope,
+                gemini_system_defaults: false,
+            },
+            app_config,
+        )?;
+        let status = sync_status::inspect_agent(
+            &config,
+            &paths.mcp_servers,
+            &paths.target_config,
+            agent_context,
+            merge_strategy,
+            adjustments,
+        )?;
+        let name = status_name(config.agent, scope, effective_global);
+        print!("{}", sync_status::render_status(&name, &status));
+        drifted |= status.has_drift();
+    }
+
+    if drifted {
+        println!("\nRun `claudius config sync` to bring the targets up to date.");
+        std::process::exit(sync_status::DRIFT_EXIT_CODE);
+    }
+    Ok(())
+}
+
+/// Global status without an explicit agent checks every agent with sources
+fn status_agents(
+    global: bool,
+    agent: Option<claudius::app_config::Agent>,
+    scope: Option<claudius::app_config::ClaudeCodeScope>,
+    app_config: Option<&AppConfig>,
+) -> Result<Vec<Option<claudius::app_config::Agent>>> {
+    if global
+        && agent.is_none()
+        && scope.is_none()
+        && app_config.is_none_or(|cfg| cfg.default.is_none())
+    {
+        Ok(Config::detect_available_agents()?.into_iter().map(Some).collect())
+    } else {
+        Ok(vec![agent])
+    }
+}
+
+fn status_name(
+    agent: Option<claudius::app_config::Agent>,
+    scope: Option<claudius::app_config::ClaudeCodeScope>,
+    global: bool,
+) -> String {
+    let agent_name = agent.map_or("Claude", claudius::app_config::Agent::display_name);
+    let scope_name = match scope {
+        Some(claudius::app_config::ClaudeCodeScope::Managed) => "managed",
+        Some(claudius::app_config::ClaudeCodeScope::User) => "user",
+        Some(claudius::app_config::ClaudeCodeScope::Local) => "local",
+        None if global => "global",
+        Some(claudius::app_config::ClaudeCodeScope::Project) | None => "project",
+    };
+    format!("{agent_name} ({scope_name})")
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -469,6 +1006,12 @@ fn build_sync_options(
         dry_run,
         backup,
         prune,
+        strategy,
+        profile,
+        no_profile,
+        watch,
+        all_projects: _,
+        format: _,
         target_config,
         global,
         agent,
@@ -486,8 +1029,7 @@ fn build_sync_options(
         gemini_system_defaults,
     };
 
-    let effective_agent = determine_agent(agent, app_config);
-    flags.validate(effective_agent)?;
+    flags.validate(determine_agent(agent, app_config))?;
 
     let effective_global = compute_effective_global(global, flags.scope);
     flags.validate_global_constraints(effective_global)?;
@@ -499,6 +1041,11 @@ fn build_sync_options(
         dry_run,
         backup,
         prune,
+        strategy: resolve_merge_strategy(strategy, app_config),
+        remember_profile: profile.is_some() || no_profile,
+        profile: resolve_profile_name(profile, no_profile)?,
+        watch,
+        phases: SyncPhases::ALL,
         global: effective_global,
         agent_override: agent,
         claude_code_scope: flags.scope,
@@ -509,6 +1056,16 @@ fn build_sync_options(
     })
 }
 
+/// `--strategy` wins over `[sync].strategy` in config.toml; interactive otherwise
+fn resolve_merge_strategy(
+    strategy: Option<MergeStrategy>,
+    app_config: Option<&AppConfig>,
+) -> MergeStrategy {
+    strategy
+        .or_else(|| app_config.and_then(|cfg| cfg.sync).and_then(|sync| sync.strategy))
+        .unwrap_or_default()
+}
+
 fn compute_effective_global(
     global: bool,
     scope: Option<claudius::app_config::ClaudeCodeScope>,
@@ -525,7 +1082,7 @@ fn run_config_validate(
     args: cli::ConfigValidateArgs,
     app_config: Option<&AppConfig>,
 ) -> Result<()> {
-    let cli::ConfigValidateArgs { agent, scope, strict } = args;
+    let cli::ConfigValidateArgs { agent, scope, strict, format } = args;
     let effective_agent =
         agent.or_else(|| app_config.and_then(|cfg| cfg.default.as_ref()).map(|d| d.agent));
 
@@ -537,14 +1094,21 @@ fn run_config_validate(
         Config::get_config_dir().context("Failed to determine Claudius config directory")?;
     let diagnostics = collect_config_diagnostics(&config_dir, effective_agent, scope, app_config)?;
 
-    if diagnostics.is_empty() {
-        println!("Configuration validation passed");
-        return Ok(());
-    }
-
-    println!("Configuration diagnostics ({}):", diagnostics.len());
-    for diagnostic in &diagnostics {
-        println!("  - {diagnostic}");
+    match format {
+        output::ValidationFormat::Text if diagnostics.is_empty() => {
+            println!("Configuration validation passed");
+        },
+        output::ValidationFormat::Text => {
+            println!("Configuration diagnostics ({}):", diagnostics.len());
+            for diagnostic in &diagnostics {
+                println!("  - {diagnostic}");
+            }
+        },
+        output::ValidationFormat::Json => output::print_json(
+            "config validate",
+            &serde_json::json!({ "diagnostics": diagnostics }),
+        )?,
+        output::ValidationFormat::Sarif => claudius::sarif::print(&diagnostics)?,
     }
 
     if strict
@@ -558,13 +1122,31 @@ fn run_config_validate(
     Ok(())
 }
 
-fn run_config_migrate(args: cli::ConfigMigrateArgs, _app_config: Option<&AppConfig>) -> Result<()> {
-    let cli::ConfigMigrateArgs { agent, dry_run } = args;
+fn run_config_migrate(args: cli::ConfigMigrateArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    let cli::ConfigMigrateArgs { agent, dry_run, format: _ } = args;
+    let backup_settings = backup_settings(app_config);
 
     let config_dir =
         Config::get_config_dir().context("Failed to determine Claudius config directory")?;
     let plan = claudius::config_migrate::plan_migration(&config_dir, agent)?;
 
+    if output::is_json() {
+        let backups = if dry_run {
+            Vec::new()
+        } else {
+            claudius::config_migrate::apply_migration(&plan, &backup_settings)?
+        };
+        return output::print_json(
+            "config migrate",
+            &serde_json::json!({
+                "dry_run": dry_run,
+                "files": plan.files,
+                "notes": plan.notes,
+                "backups": backups,
+            }),
+        );
+    }
+
     for note in &plan.notes {
         println!("Note: {note}");
     }
@@ -583,12 +1165,218 @@ fn run_config_migrate(args: cli::ConfigMigrateArgs, _app_config: Option<&AppConf
         return Ok(());
     }
 
-    let backups = claudius::config_migrate::apply_migration(&plan)?;
-    for backup in &backups {
-        println!("Backup created: {backup}");
+    let backups = claudius::config_migrate::apply_migration(&plan, &backup_settings)?;
+    for backup in &backups {
+        println!("Backup created: {backup}");
+    }
+    println!("Migration complete: {} file(s) updated", plan.files.len());
+    Ok(())
+}
+
+fn run_config_backups(command: cli::BackupsCommands, app_config: Option<&AppConfig>) -> Result<()> {
+    let found = discover_all_backups(app_config)?;
+    match command {
+        cli::BackupsCommands::List(args) => list_backups(&found, args.file.as_deref()),
+        cli::BackupsCommands::Restore(args) => {
+            restore_backup(&found, &args.path, &backup_settings(app_config))
+        },
+        cli::BackupsCommands::Prune(args) => prune_backups(&found, args, app_config),
+    }
+}
+
+/// Where backups go and how long they are kept, from `[backups]` in config.toml
+fn backup_settings(app_config: Option<&AppConfig>) -> backups::BackupSettings {
+    backups::BackupSettings::from_config(app_config.and_then(|cfg| cfg.backups.as_ref()))
+}
+
+/// Backups next to the sources, global targets, and known projects, plus the central directory
+fn discover_all_backups(app_config: Option<&AppConfig>) -> Result<Vec<Backup>> {
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    let base_dirs = directories::BaseDirs::new()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+    let mut project_dirs =
+        vec![std::env::current_dir().context("Failed to determine current directory")?];
+    let registry_dir = claudius::projects::registry_base_dir()?;
+    project_dirs.extend(
+        app_config
+            .into_iter()
+            .flat_map(|cfg| cfg.projects.iter())
+            .map(|project| claudius::projects::expand_project_path(&project.path, &registry_dir)),
+    );
+
+    let search_dirs = backups::default_backup_directories(
+        &config_dir,
+        base_dirs.home_dir(),
+        base_dirs.config_dir(),
+        &project_dirs,
+    );
+    Ok(backups::discover_backups(&search_dirs, backups::central_backup_dir().as_deref()))
+}
+
+fn list_backups(found: &[Backup], file: Option<&std::path::Path>) -> Result<()> {
+    let original = file.map(std::path::absolute).transpose()?;
+    let selected = found
+        .iter()
+        .filter(|backup| original.as_ref().is_none_or(|path| &backup.original == path))
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        println!("No Claudius backups found");
+        return Ok(());
+    }
+
+    let groups = selected
+        .chunk_by(|left, right| left.original == right.original)
+        .collect::<Vec<_>>();
+    for group in &groups {
+        let Some(first) = group.first() else {
+            continue;
+        };
+        println!("{}", first.original.display());
+        for backup in *group {
+            println!(
+                "  {}  {:>9} bytes  {}",
+                backup.created.format("%Y-%m-%d %H:%M:%S"),
+                backup.size(),
+                backup.path.display()
+            );
+        }
+    }
+    println!("\n{} backup(s) of {} file(s)", selected.len(), groups.len());
+    Ok(())
+}
+
+/// Restore a backup file, or the newest backup of a backed-up file
+fn restore_backup(
+    found: &[Backup],
+    requested: &std::path::Path,
+    settings: &backups::BackupSettings,
+) -> Result<()> {
+    let path = std::path::absolute(requested)
+        .with_context(|| format!("Failed to resolve {}", requested.display()))?;
+    let backup = Backup::from_path(&path, backups::central_backup_dir().as_deref())
+        .filter(|_| path.is_file())
+        .or_else(|| found.iter().find(|backup| backup.original == path).cloned())
+        .ok_or_else(|| anyhow::anyhow!("No Claudius backup found for {}", requested.display()))?;
+
+    if let Some(previous) = backups::restore_backup(&backup, settings)? {
+        println!("Backed up current {} to {previous}", backup.original.display());
+    }
+    println!("Restored {} from {}", backup.original.display(), backup.path.display());
+    Ok(())
+}
+
+fn prune_backups(
+    found: &[Backup],
+    args: cli::BackupsPruneArgs,
+    app_config: Option<&AppConfig>,
+) -> Result<()> {
+    let configured = RetentionPolicy::from_config(app_config.and_then(|cfg| cfg.backups.as_ref()));
+    let policy = RetentionPolicy {
+        keep: args.keep.map(usize::try_from).transpose()?.or(configured.keep),
+        max_age: args
+            .max_age_days
+            .map(|days| chrono::TimeDelta::days(i64::from(days)))
+            .or(configured.max_age),
+    };
+    if policy.is_unlimited() {
+        anyhow::bail!(
+            "No retention policy; set keep or max-age-days under [backups] in config.toml, or pass --keep or --max-age-days"
+        );
+    }
+
+    let expired = backups::expired_backups(found, policy, chrono::Local::now().naive_local());
+    if expired.is_empty() {
+        println!("No backups outside the retention policy");
+        return Ok(());
+    }
+    if !args.dry_run {
+        backups::remove_backups(&expired)?;
+    }
+
+    let action = if args.dry_run { "Would remove" } else { "Removed" };
+    for backup in &expired {
+        println!("{action} {}", backup.path.display());
+    }
+    println!("{} {} backup(s)", if args.dry_run { "Would prune" } else { "Pruned" }, expired.len());
+    Ok(())
+}
+
+fn run_config_import(args: cli::ConfigImportArgs) -> Result<()> {
+    let cli::ConfigImportArgs { global, agent, dry_run } = args;
+
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+    let base_dirs = directories::BaseDirs::new()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?;
+    let project_dir = std::env::current_dir().context("Failed to determine current directory")?;
+    let sources = config_import::default_import_sources(
+        global,
+        base_dirs.home_dir(),
+        &project_dir,
+        base_dirs.config_dir(),
+    )
+    .into_iter()
+    .filter(|source| agent.is_none_or(|selected| selected == source.agent))
+    .collect::<Vec<_>>();
+
+    let plan = config_import::plan_import(&config_dir, &sources)?;
+    if plan.sources_read.is_empty() {
+        println!("No agent configuration files found to import");
+        return Ok(());
+    }
+
+    print_import_plan(&plan);
+    if plan.writes.is_empty() {
+        println!("Nothing to import; Claudius sources are already up to date");
+        return Ok(());
+    }
+
+    if dry_run {
+        for write in &plan.writes {
+            print_planned_write_diff(write)?;
+        }
+        println!("\nDry run: no files were modified");
+        return Ok(());
+    }
+
+    commit_planned_writes(&plan.writes, None, &UndoRecorder::disabled())?;
+    for write in &plan.writes {
+        println!("Wrote {} ({})", write.label, write.path.display());
+    }
+    Ok(())
+}
+
+fn print_import_plan(plan: &config_import::ImportPlan) {
+    println!("Read {} agent configuration file(s):", plan.sources_read.len());
+    for path in &plan.sources_read {
+        println!("  - {}", path.display());
+    }
+
+    if !plan.servers.is_empty() {
+        println!("MCP servers:");
+    }
+    for server in &plan.servers {
+        match &server.outcome {
+            config_import::ServerOutcome::Added => {
+                println!("  + {} (from {})", server.name, server.origin);
+            },
+            config_import::ServerOutcome::AlreadyPresent => {
+                println!("  = {} (already in mcpServers.json)", server.name);
+            },
+            config_import::ServerOutcome::Duplicate { of } => {
+                println!("  = {} (from {}; same server as {of})", server.name, server.origin);
+            },
+            config_import::ServerOutcome::Conflict { kept } => println!(
+                "  ! {} (from {}) conflicts with the definition from {kept}; kept the latter",
+                server.name, server.origin
+            ),
+        }
+    }
+
+    for note in &plan.notes {
+        println!("Note: {note}");
     }
-    println!("Migration complete: {} file(s) updated", plan.files.len());
-    Ok(())
 }
 
 fn print_file_migration(file: &claudius::config_migrate::FileMigration, dry_run: bool) {
@@ -608,43 +1396,182 @@ fn collect_config_diagnostics(
     claude_scope: Option<claudius::app_config::ClaudeCodeScope>,
     app_config: Option<&AppConfig>,
 ) -> Result<Vec<claudius::validation::Diagnostic>> {
+    let app_config_path = config_dir.join("config.toml");
     let mut diagnostics = app_config
         .map(|config| claudius::validation::validate_app_config(config).diagnostics)
-        .unwrap_or_default();
+        .unwrap_or_default()
+        .into_iter()
+        .map(|diagnostic| {
+            if app_config_path.exists() {
+                diagnostic.with_path(&app_config_path)
+            } else {
+                diagnostic
+            }
+        })
+        .collect::<Vec<_>>();
 
-    diagnostics.extend(
-        validate_mcp_server_sources(config_dir)?
-            .into_iter()
-            .map(claudius::validation::Diagnostic::warning),
-    );
+    diagnostics.extend(validate_mcp_server_sources(config_dir, effective_agent, app_config)?);
     diagnostics.extend(validate_agent_sources(config_dir, effective_agent, claude_scope)?);
     diagnostics.extend(
         skills::validate_claudius_skill_sources(config_dir, effective_agent)?
             .warnings
             .into_iter()
-            .map(claudius::validation::Diagnostic::warning),
+            .map(claudius::validation::Diagnostic::from),
     );
 
     Ok(diagnostics)
 }
 
-fn validate_mcp_server_sources(config_dir: &std::path::Path) -> Result<Vec<String>> {
+fn validate_mcp_server_sources(
+    config_dir: &std::path::Path,
+    effective_agent: Option<claudius::app_config::Agent>,
+    app_config: Option<&AppConfig>,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
+    use claudius::validation::{Diagnostic, DiagnosticRule};
+
     let mcp_servers_path = config_dir.join("mcpServers.json");
     let mcp_servers = reader::read_mcp_servers_config(&mcp_servers_path).with_context(|| {
         format!("Failed to read MCP servers config: {}", mcp_servers_path.display())
     })?;
 
-    Ok(mcp_servers
+    let mut diagnostics = Vec::new();
+    for (name, server) in &mcp_servers.mcp_servers {
+        if server.command.is_none() && server.url.is_none() {
+            diagnostics.push(
+                Diagnostic::warning(format!("mcpServers.{name} must define either command or url"))
+                    .with_rule(DiagnosticRule::McpServerDefinition)
+                    .with_path(&mcp_servers_path),
+            );
+        }
+
+        let Some(targeting) = &server.targeting else {
+            continue;
+        };
+        let selectors = targeting
+            .agents
+            .iter()
+            .map(|selector| ("agents", selector))
+            .chain(targeting.exclude.iter().map(|selector| ("exclude", selector)));
+        for (field, selector) in selectors {
+            if let Err(problem) = claudius::config::parse_target_selector(selector) {
+                diagnostics.push(
+                    Diagnostic::error(format!("mcpServers.{name}.x-claudius.{field}: {problem}"))
+                        .with_rule(DiagnosticRule::TargetSelector)
+                        .with_path(&mcp_servers_path),
+                );
+            }
+        }
+    }
+
+    diagnostics.extend(claudius::mcp_lint::lint_mcp_servers(
+        &mcp_servers,
+        &mcp_servers_path,
+        std::env::var_os("PATH").as_deref(),
+    ));
+    diagnostics.extend(interpolation_diagnostics(
+        config_dir,
+        &mcp_servers,
+        &mcp_servers_path,
+        app_config,
+    ));
+    diagnostics.extend(secret_launch_diagnostics(
+        &mcp_servers,
+        &mcp_servers_path,
+        effective_agent,
+        app_config,
+    ));
+    Ok(diagnostics)
+}
+
+/// Servers with `op://` secrets, whether they can launch through `secrets run`,
+/// and whether the validated agent can start the launcher
+fn secret_launch_diagnostics(
+    mcp_servers: &claudius::McpServersConfig,
+    mcp_servers_path: &std::path::Path,
+    effective_agent: Option<claudius::app_config::Agent>,
+    app_config: Option<&AppConfig>,
+) -> Vec<claudius::validation::Diagnostic> {
+    use claudius::secret_launch::{
+        launcher_command, launcher_problems, references_secrets, wrap_problem,
+    };
+    use claudius::validation::{Diagnostic, DiagnosticRule};
+
+    let mut names = mcp_servers
         .mcp_servers
         .iter()
-        .filter(|(_, server)| server.command.is_none() && server.url.is_none())
-        .map(|(name, _)| {
-            format!(
-                "{}: mcpServers.{name} must define either command or url",
-                mcp_servers_path.display(),
+        .filter(|(_, server)| references_secrets(server))
+        .map(|(name, server)| (name, wrap_problem(server)))
+        .collect::<Vec<_>>();
+    names.sort_by_key(|(name, _)| *name);
+
+    let unconfigured =
+        !names.is_empty() && app_config.is_none_or(|config| config.secret_manager.is_none());
+    let wrapped = names
+        .iter()
+        .filter(|(_, problem)| problem.is_none())
+        .filter_map(|(name, _)| Some((name.as_str(), mcp_servers.mcp_servers.get(*name)?)))
+        .collect::<Vec<_>>();
+    let launcher_warnings = launcher_problems(
+        effective_agent,
+        &launcher_command(app_config),
+        &wrapped,
+        std::env::var_os("PATH").as_deref(),
+    );
+    names
+        .iter()
+        .map(|(name, problem)| {
+            problem.map_or_else(
+                || {
+                    Diagnostic::info(format!(
+                        "mcpServers.{name}: launches through `claudius secrets run` to resolve op:// secrets"
+                    ))
+                },
+                |reason| Diagnostic::error(format!("mcpServers.{name}: {reason}")),
+            )
+        })
+        .chain(unconfigured.then(|| {
+            Diagnostic::warning(
+                "mcpServers.json references op:// secrets, but config.toml has no [secret-manager]",
             )
+        }))
+        .chain(launcher_warnings.into_iter().map(Diagnostic::warning))
+        .map(|diagnostic| {
+            diagnostic.with_rule(DiagnosticRule::SecretReference).with_path(mcp_servers_path)
+        })
+        .collect()
+}
+
+/// Undefined names and cycles in `${NAME}` references and `[variables]`
+fn interpolation_diagnostics(
+    config_dir: &std::path::Path,
+    mcp_servers: &claudius::McpServersConfig,
+    mcp_servers_path: &std::path::Path,
+    app_config: Option<&AppConfig>,
+) -> Vec<claudius::validation::Diagnostic> {
+    use claudius::interpolation::{check_mcp_servers, Interpolator};
+    use claudius::validation::{Diagnostic, DiagnosticRule};
+
+    let variables = app_config.map(|config| config.variables.clone()).unwrap_or_default();
+    let names = variables.keys().cloned().collect::<Vec<_>>();
+    let project_root = std::env::current_dir().ok();
+    let interpolator = Interpolator::new(config_dir, project_root.as_deref(), variables);
+
+    let app_config_path = config_dir.join("config.toml");
+    names
+        .iter()
+        .filter_map(|name| {
+            interpolator.check_variable(name).err().map(|problem| {
+                Diagnostic::error(format!("config.toml variables.{name}: {problem}"))
+                    .with_path(&app_config_path)
+            })
         })
-        .collect())
+        .chain(check_mcp_servers(mcp_servers, &interpolator).into_iter().map(
+            |(location, problem)| {
+                Diagnostic::error(format!("{location}: {problem}")).with_path(mcp_servers_path)
+            },
+        ))
+        .map(|diagnostic| diagnostic.with_rule(DiagnosticRule::Interpolation))
+        .collect()
 }
 
 fn validate_agent_sources(
@@ -657,33 +1584,295 @@ fn validate_agent_sources(
     match effective_agent {
         Some(Agent::Claude) => validate_claude_settings_sources(config_dir, None),
         Some(Agent::ClaudeCode) => validate_claude_code_sources(config_dir, claude_scope),
-        Some(Agent::Codex) => validate_codex_sources(config_dir).map(warning_diagnostics),
-        Some(Agent::Gemini) => validate_gemini_sources(config_dir).map(warning_diagnostics),
+        Some(Agent::Codex) => validate_codex_sources(config_dir),
+        Some(Agent::Gemini) => validate_gemini_sources(config_dir),
         None => {
             let mut diagnostics = validate_claude_code_sources(config_dir, None)?;
-            diagnostics.extend(warning_diagnostics(validate_codex_sources(config_dir)?));
-            diagnostics.extend(warning_diagnostics(validate_gemini_sources(config_dir)?));
+            diagnostics.extend(validate_codex_sources(config_dir)?);
+            diagnostics.extend(validate_gemini_sources(config_dir)?);
             Ok(diagnostics)
         },
     }
 }
 
-fn warning_diagnostics(warnings: Vec<String>) -> Vec<claudius::validation::Diagnostic> {
-    warnings.into_iter().map(claudius::validation::Diagnostic::warning).collect()
+/// Warnings about one source file, classified under `rule`
+fn file_diagnostics(
+    warnings: Vec<String>,
+    rule: claudius::validation::DiagnosticRule,
+    path: &std::path::Path,
+) -> Vec<claudius::validation::Diagnostic> {
+    warnings
+        .into_iter()
+        .map(|warning| {
+            claudius::validation::Diagnostic::warning(warning)
+                .with_rule(rule)
+                .with_path(path)
+        })
+        .collect()
 }
 
 fn run_config_doctor(args: cli::ConfigDoctorArgs) -> Result<()> {
-    let report = run_doctor(DoctorOptions { global: args.global, agent_filter: args.agent })?;
+    let options = DoctorOptions { global: args.global, agent_filter: args.agent };
+    let report = run_doctor(options)?;
+    if args.fix {
+        return run_config_doctor_fix(&report, options, args.dry_run);
+    }
+    if output::is_json() {
+        return output::print_json("config doctor", &report);
+    }
     println!("{}", render_report(&report));
     Ok(())
 }
 
+#[derive(serde::Serialize)]
+struct DoctorFixReport<'a> {
+    dry_run: bool,
+    fixes: &'a [claudius::doctor::DoctorFixOutcome],
+    #[serde(flatten)]
+    remaining: &'a claudius::doctor::DoctorReport,
+}
+
+fn run_config_doctor_fix(
+    report: &claudius::doctor::DoctorReport,
+    options: DoctorOptions,
+    dry_run: bool,
+) -> Result<()> {
+    use claudius::doctor::DoctorFixStatus;
+
+    let fixes =
+        recording_undo(dry_run, |undo| claudius::doctor::apply_fixes(report, dry_run, undo))?;
+    let applied = fixes.iter().any(|outcome| outcome.status == DoctorFixStatus::Applied);
+    let remaining = if applied { run_doctor(options)? } else { report.clone() };
+
+    if output::is_json() {
+        output::print_json(
+            "config doctor",
+            &DoctorFixReport { dry_run, fixes: &fixes, remaining: &remaining },
+        )?;
+    } else {
+        if fixes.is_empty() {
+            println!("No findings have an automatic fix");
+        } else {
+            println!("Fixes:");
+            for outcome in &fixes {
+                println!("- {}", outcome.summary);
+                println!("  {}", outcome.detail);
+            }
+        }
+        println!();
+        println!("{}", render_report(&remaining));
+    }
+
+    let failed = fixes.iter().filter(|outcome| outcome.status == DoctorFixStatus::Failed).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} fix(es) failed");
+    }
+    Ok(())
+}
+
+fn run_mcp_add(args: cli::McpAddArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    let spec = claudius::mcp_edit::ServerSpec {
+        server_type: args.server_type,
+        command: args.command,
+        url: args.url,
+        env: args.env,
+        headers: args.headers,
+        agents: args.agents,
+        exclude: args.exclude,
+    };
+    let definition = match &args.preset {
+        Some(preset) => preset_definition(&spec, preset, &args.set)?,
+        None => spec.definition(),
+    };
+    let mut source = claudius::mcp_edit::McpSource::open(&mcp_source_path(args.edit)?)?;
+    let replaced = source.add(&args.name, definition, args.replace)?;
+    source.save()?;
+    println!(
+        "{} MCP server '{}' in {}",
+        if replaced { "Replaced" } else { "Added" },
+        args.name,
+        source.path().display()
+    );
+    sync_after_mcp_edit(args.edit, false, app_config)
+}
+
+/// Render `preset` with the `--set` values, then apply the other `mcp add` options
+fn preset_definition(
+    spec: &claudius::mcp_edit::ServerSpec,
+    preset: &str,
+    values: &[(String, String)],
+) -> Result<serde_json::Value> {
+    use claudius::mcp_presets::{record_preset, PresetCatalog, PresetRecord};
+
+    let catalog = PresetCatalog::load(&Config::get_config_dir()?)?;
+    let mut definition = catalog.render(preset, values)?;
+    spec.overlay(&mut definition);
+    let version = catalog.get(preset).map_or(0, |entry| entry.preset.version);
+    record_preset(&mut definition, &PresetRecord { name: preset.to_string(), version });
+    Ok(serde_json::Value::Object(definition))
+}
+
+fn run_mcp_remove(args: &cli::McpRemoveArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    let mut source = claudius::mcp_edit::McpSource::open(&mcp_source_path(args.edit)?)?;
+    source.remove(&args.name)?;
+    source.save()?;
+    println!("Removed MCP server '{}' from {}", args.name, source.path().display());
+    if !args.edit.sync {
+        let scope = if args.edit.project { "" } else { " --global" };
+        println!("Run `claudius config sync{scope} --prune` to remove it from agent configs");
+    }
+    sync_after_mcp_edit(args.edit, true, app_config)
+}
+
+fn run_mcp_rename(args: &cli::McpRenameArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    let mut source = claudius::mcp_edit::McpSource::open(&mcp_source_path(args.edit)?)?;
+    source.rename(&args.from, &args.to)?;
+    source.save()?;
+    println!("Renamed MCP server '{}' to '{}' in {}", args.from, args.to, source.path().display());
+    sync_after_mcp_edit(args.edit, true, app_config)
+}
+
+/// The global `mcpServers.json`, or the project layer's with `--project`
+fn mcp_source_path(edit: cli::McpEditArgs) -> Result<std::path::PathBuf> {
+    if edit.project {
+        let project_dir = std::env::current_dir().context("Failed to get current directory")?;
+        return Ok(project_dir
+            .join(claudius::source_layers::PROJECT_LAYER_DIR_NAME)
+            .join("mcpServers.json"));
+    }
+    Ok(Config::get_config_dir()?.join("mcpServers.json"))
+}
+
+/// `config sync` with its default options, after an edit made with `--sync`
+///
+/// An edit of the global file syncs the global targets; a `--project` edit runs the
+/// project-local sync.
+///
+/// With `prune`, used after `remove` and `rename`, servers no longer in the sources are
+/// pruned from the targets Claudius deployed them to; `add` leaves them alone.
+fn sync_after_mcp_edit(
+    edit: cli::McpEditArgs,
+    prune: bool,
+    app_config: Option<&AppConfig>,
+) -> Result<()> {
+    if !edit.sync {
+        return Ok(());
+    }
+    run_config_sync(cli::ConfigSyncArgs::for_mcp_edit(edit, prune), app_config)
+}
+
+fn run_mcp_presets() -> Result<()> {
+    let catalog = claudius::mcp_presets::PresetCatalog::load(&Config::get_config_dir()?)?;
+    if output::is_json() {
+        let presets = catalog.entries().collect::<Vec<_>>();
+        output::print_json("mcp presets", &serde_json::json!({ "presets": presets }))?;
+    } else {
+        println!("{}", claudius::mcp_presets::render_catalog(&catalog));
+    }
+    Ok(())
+}
+
+fn run_mcp_list() -> Result<()> {
+    let config = Config::new(false)?;
+    let project_path = config
+        .project_layer_dir
+        .as_ref()
+        .map(|layer_dir| layer_dir.join("mcpServers.json"));
+    let servers =
+        claudius::mcp_edit::list_servers(&config.mcp_servers_path, project_path.as_deref())?;
+
+    if output::is_json() {
+        output::print_json("mcp list", &serde_json::json!({ "servers": servers }))?;
+    } else {
+        println!("{}", claudius::mcp_edit::render_list(&servers));
+    }
+    Ok(())
+}
+
+fn run_mcp_show(args: &cli::McpShowArgs) -> Result<()> {
+    let config = Config::new(false)?;
+    let project_path = config
+        .project_layer_dir
+        .as_ref()
+        .map(|layer_dir| layer_dir.join("mcpServers.json"));
+    let shown = claudius::mcp_edit::find_server(
+        &args.name,
+        &config.mcp_servers_path,
+        project_path.as_deref(),
+    )?
+    .with_context(|| {
+        format!("No MCP server named '{}' in {}", args.name, config.mcp_servers_path.display())
+    })?;
+
+    if output::is_json() {
+        output::print_json("mcp show", &shown)?;
+    } else {
+        println!("{} ({} layer, {})", shown.server, shown.layer.label(), shown.source.display());
+        println!("{}", serde_json::to_string_pretty(&shown.definition)?);
+    }
+    Ok(())
+}
+
+fn run_mcp_check(args: &cli::McpCheckArgs, app_config: Option<&AppConfig>) -> Result<()> {
+    use claudius::interpolation::Interpolator;
+    use claudius::mcp_probe::{check_servers, render_reports, ProbeOptions, ProbeStatus};
+
+    let config = Config::new(false)?;
+    let servers = claudius::sync_operations::read_layered_mcp_servers(
+        &config.mcp_servers_path,
+        config.project_layer_dir.as_deref(),
+    )?;
+    let names = match &args.name {
+        Some(name) if !servers.mcp_servers.contains_key(name) => {
+            anyhow::bail!("No MCP server named '{name}' in {}", config.mcp_servers_path.display())
+        },
+        Some(name) => vec![name.clone()],
+        None => servers
+            .mcp_servers
+            .keys()
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect(),
+    };
+
+    let config_dir = config.mcp_servers_path.parent().unwrap_or_else(|| std::path::Path::new("."));
+    let project_root = std::env::current_dir().ok();
+    let variables = app_config.map(|app| app.variables.clone()).unwrap_or_default();
+    let interpolator = Interpolator::new(config_dir, project_root.as_deref(), variables);
+    let launcher = std::env::current_exe()
+        .map_or_else(|_| "claudius".to_string(), |path| path.display().to_string());
+    let reports = check_servers(
+        &servers,
+        &names,
+        ProbeOptions {
+            interpolator: &interpolator,
+            launcher: &launcher,
+            timeout: std::time::Duration::from_secs(args.timeout),
+        },
+    );
+
+    if output::is_json() {
+        output::print_json("mcp check", &serde_json::json!({ "servers": reports }))?;
+    } else if reports.is_empty() {
+        println!("No MCP servers to check");
+    } else {
+        println!("{}", render_reports(&reports));
+    }
+
+    let failed = reports.iter().filter(|report| report.status == ProbeStatus::Failed).count();
+    if failed > 0 {
+        anyhow::bail!("{failed} MCP server(s) failed the check");
+    }
+    Ok(())
+}
+
 fn validate_claude_code_sources(
     config_dir: &std::path::Path,
     scope: Option<claudius::app_config::ClaudeCodeScope>,
 ) -> Result<Vec<claudius::validation::Diagnostic>> {
     let mut diagnostics = validate_claude_settings_sources(config_dir, scope)?;
-    diagnostics.extend(warning_diagnostics(validate_claude_code_subagent_sources(config_dir)?));
+    diagnostics.extend(validate_claude_code_subagent_sources(config_dir)?);
     Ok(diagnostics)
 }
 
@@ -711,10 +1900,11 @@ fn validate_claude_settings_sources(
     let json_value: serde_json::Value = serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse JSON from {}", settings_path.display()))?;
 
-    let mut diagnostics = claudius::validation::validate_claude_settings(&json_value)
-        .into_iter()
-        .map(|warning| claudius::validation::Diagnostic::warning(warning).with_path(&settings_path))
-        .collect::<Vec<_>>();
+    let mut diagnostics = file_diagnostics(
+        claudius::validation::validate_claude_settings(&json_value),
+        claudius::validation::DiagnosticRule::DeprecatedKey,
+        &settings_path,
+    );
 
     if let Some(claude_scope) = scope {
         diagnostics.extend(
@@ -735,6 +1925,7 @@ fn validate_claude_settings_sources(
             claudius::validation::Diagnostic::info(
                 "Using legacy settings.json (consider migrating to claude.settings.json)",
             )
+            .with_rule(claudius::validation::DiagnosticRule::LegacySourceFile)
             .with_path(&settings_path),
         );
     }
@@ -742,60 +1933,71 @@ fn validate_claude_settings_sources(
     Ok(diagnostics)
 }
 
-fn validate_codex_sources(config_dir: &std::path::Path) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
+fn validate_codex_sources(
+    config_dir: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
+    let mut diagnostics = Vec::new();
 
     let codex_settings_path = config_dir.join("codex.settings.toml");
     if codex_settings_path.exists() {
-        warnings.extend(validate_codex_settings_like_file(&codex_settings_path)?);
+        diagnostics.extend(validate_codex_settings_like_file(&codex_settings_path)?);
     }
 
     let codex_requirements_path = config_dir.join("codex.requirements.toml");
     if codex_requirements_path.exists() {
-        warnings.extend(validate_codex_requirements_file(&codex_requirements_path)?);
+        diagnostics.extend(validate_codex_requirements_file(&codex_requirements_path)?);
     }
 
     if let Some((managed_config_path, is_legacy)) = select_codex_managed_config_source(config_dir) {
-        warnings.extend(validate_codex_settings_like_file(&managed_config_path)?);
+        diagnostics.extend(validate_codex_settings_like_file(&managed_config_path)?);
 
         if is_legacy {
-            warnings.push(format!(
-                "{}: Using legacy managed_config.toml (consider migrating to codex.managed_config.toml)",
-                managed_config_path.display(),
-            ));
+            diagnostics.push(
+                claudius::validation::Diagnostic::warning(
+                    "Using legacy managed_config.toml (consider migrating to codex.managed_config.toml)",
+                )
+                .with_rule(claudius::validation::DiagnosticRule::LegacySourceFile)
+                .with_path(&managed_config_path),
+            );
         }
     }
 
-    Ok(warnings)
+    Ok(diagnostics)
 }
 
-fn validate_codex_requirements_file(path: &std::path::Path) -> Result<Vec<String>> {
+fn validate_codex_requirements_file(
+    path: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read {}", path.display()))?;
     let value: toml::Value =
         toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
 
-    Ok(claudius::codex_settings::validate_codex_requirements(&value)
-        .into_iter()
-        .map(|warning| format!("{}: {warning}", path.display()))
-        .collect())
+    Ok(file_diagnostics(
+        claudius::codex_settings::validate_codex_requirements(&value),
+        claudius::validation::DiagnosticRule::DeprecatedKey,
+        path,
+    ))
 }
 
-fn validate_codex_settings_like_file(path: &std::path::Path) -> Result<Vec<String>> {
+fn validate_codex_settings_like_file(
+    path: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
     let content = std::fs::read_to_string(path)
         .with_context(|| format!("Failed to read {}", path.display()))?;
     let value: toml::Value =
         toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
 
-    let warnings = claudius::codex_settings::validate_codex_settings(&value)
-        .into_iter()
-        .map(|w| format!("{}: {w}", path.display()))
-        .collect::<Vec<_>>();
+    let diagnostics = file_diagnostics(
+        claudius::codex_settings::validate_codex_settings(&value),
+        claudius::validation::DiagnosticRule::DeprecatedKey,
+        path,
+    );
 
     let _: claudius::codex_settings::CodexSettings = toml::from_str(&content)
         .with_context(|| format!("Failed to deserialize {}", path.display()))?;
 
-    Ok(warnings)
+    Ok(diagnostics)
 }
 
 fn select_codex_managed_config_source(
@@ -812,8 +2014,12 @@ fn select_codex_managed_config_source(
         .then_some((legacy_managed_config_path, true))
 }
 
-fn validate_gemini_sources(config_dir: &std::path::Path) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
+fn validate_gemini_sources(
+    config_dir: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
+    use claudius::validation::DiagnosticRule;
+
+    let mut diagnostics = Vec::new();
     let mcp_servers_path = config_dir.join("mcpServers.json");
 
     if mcp_servers_path.exists() {
@@ -822,11 +2028,11 @@ fn validate_gemini_sources(config_dir: &std::path::Path) -> Result<Vec<String>>
                 format!("Failed to read MCP servers config: {}", mcp_servers_path.display())
             })?;
 
-        warnings.extend(
-            claudius::gemini_settings::validate_gemini_mcp_server_configs(&mcp_servers.mcp_servers)
-                .into_iter()
-                .map(|warning| format!("{}: {warning}", mcp_servers_path.display())),
-        );
+        diagnostics.extend(file_diagnostics(
+            claudius::gemini_settings::validate_gemini_mcp_server_configs(&mcp_servers.mcp_servers),
+            DiagnosticRule::GeminiMcpCompatibility,
+            &mcp_servers_path,
+        ));
     }
 
     let gemini_settings_paths =
@@ -843,77 +2049,68 @@ fn validate_gemini_sources(config_dir: &std::path::Path) -> Result<Vec<String>>
             format!("Failed to parse JSON from {}", gemini_settings_path.display())
         })?;
 
-        warnings.extend(
-            claudius::gemini_settings::validate_gemini_settings(&json_value)
-                .into_iter()
-                .map(|w| format!("{}: {w}", gemini_settings_path.display())),
-        );
+        diagnostics.extend(file_diagnostics(
+            claudius::gemini_settings::validate_gemini_settings(&json_value),
+            DiagnosticRule::UnknownGeminiField,
+            &gemini_settings_path,
+        ));
 
         let _: claudius::gemini_settings::GeminiSettings = serde_json::from_value(json_value)
             .with_context(|| format!("Failed to deserialize {}", gemini_settings_path.display()))?;
     }
 
-    warnings.extend(validate_gemini_command_sources(config_dir)?);
-    warnings.extend(validate_gemini_agent_sources(config_dir)?);
+    diagnostics.extend(validate_gemini_command_sources(config_dir)?);
+    diagnostics.extend(validate_gemini_agent_sources(config_dir)?);
 
-    Ok(warnings)
+    Ok(diagnostics)
 }
 
-fn validate_gemini_command_sources(config_dir: &std::path::Path) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
+fn validate_gemini_command_sources(
+    config_dir: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
+    let mut diagnostics = Vec::new();
     let commands_dir = config_dir.join("commands").join("gemini");
     let mut command_files = Vec::new();
     collect_files_with_extension(&commands_dir, "toml", &mut command_files)?;
 
     for command_file in command_files {
         let result = claudius::validation::validate_gemini_command_file(&command_file)?;
-        warnings.extend(
-            result
-                .diagnostics
-                .into_iter()
-                .map(|diagnostic| format!("{}: {}", command_file.display(), diagnostic.message)),
-        );
+        diagnostics.extend(result.diagnostics);
     }
 
-    Ok(warnings)
+    Ok(diagnostics)
 }
 
-fn validate_gemini_agent_sources(config_dir: &std::path::Path) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
+fn validate_gemini_agent_sources(
+    config_dir: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
+    let mut diagnostics = Vec::new();
     let agents_dir = config_dir.join("agents").join("gemini");
     let mut agent_files = Vec::new();
     collect_files_with_extension(&agents_dir, "md", &mut agent_files)?;
 
     for agent_file in agent_files {
         let result = claudius::validation::validate_gemini_agent_file(&agent_file)?;
-        warnings.extend(
-            result
-                .diagnostics
-                .into_iter()
-                .map(|diagnostic| format!("{}: {}", agent_file.display(), diagnostic.message)),
-        );
+        diagnostics.extend(result.diagnostics);
     }
 
-    Ok(warnings)
+    Ok(diagnostics)
 }
 
-fn validate_claude_code_subagent_sources(config_dir: &std::path::Path) -> Result<Vec<String>> {
-    let mut warnings = Vec::new();
+fn validate_claude_code_subagent_sources(
+    config_dir: &std::path::Path,
+) -> Result<Vec<claudius::validation::Diagnostic>> {
+    let mut diagnostics = Vec::new();
     let agents_dir = config_dir.join("agents").join("claude-code");
     let mut agent_files = Vec::new();
     collect_files_with_extension(&agents_dir, "md", &mut agent_files)?;
 
     for agent_file in agent_files {
         let result = claudius::validation::validate_claude_code_subagent_file(&agent_file)?;
-        warnings.extend(
-            result
-                .diagnostics
-                .into_iter()
-                .map(|diagnostic| format!("{}: {}", agent_file.display(), diagnostic.message)),
-        );
+        diagnostics.extend(result.diagnostics);
     }
 
-    Ok(warnings)
+    Ok(diagnostics)
 }
 
 fn collect_files_with_extension(
@@ -1120,6 +2317,15 @@ struct SyncOptions {
     dry_run: bool,
     backup: bool,
     prune: bool,
+    strategy: MergeStrategy,
+    /// Profile applied to this sync, from `--profile` or the remembered one
+    profile: Option<String>,
+    /// Whether `--profile` or `--no-profile` was given and should be remembered
+    remember_profile: bool,
+    /// Keep re-syncing on source changes after the first sync
+    watch: bool,
+    /// Phases to run; only those affected by the changes when watching
+    phases: SyncPhases,
     global: bool,
     agent_override: Option<claudius::app_config::Agent>,
     claude_code_scope: Option<claudius::app_config::ClaudeCodeScope>,
@@ -1129,7 +2335,21 @@ struct SyncOptions {
     gemini_system_defaults: bool,
 }
 
-fn run_sync(options: &SyncOptions, app_config: Option<&AppConfig>) -> Result<()> {
+fn run_sync(
+    options: &SyncOptions,
+    app_config: Option<&AppConfig>,
+    undo: &UndoRecorder,
+) -> Result<Vec<AgentSyncReport>> {
+    recover_interrupted_sync(options.dry_run)?;
+    if !options.global {
+        warn_untrusted_project_layer()?;
+    }
+
+    let adjustments = SourceAdjustments {
+        profile: select_profile(options.profile.as_deref(), app_config)?,
+        app_config,
+    };
+
     // If global mode, no agent specified, and no custom paths provided, sync all available agents
     if options.global
         && options.agent_override.is_none()
@@ -1142,7 +2362,7 @@ fn run_sync(options: &SyncOptions, app_config: Option<&AppConfig>) -> Result<()>
         && !options.gemini_system_defaults
         && app_config.is_none_or(|cfg| cfg.default.is_none())
     {
-        sync_all_available_agents(options, app_config)
+        sync_all_available_agents(options, app_config, adjustments, undo)
     } else {
         // Single agent sync (current behavior)
         let (agent_context, config, paths) = setup_sync_context(
@@ -1165,19 +2385,62 @@ fn run_sync(options: &SyncOptions, app_config: Option<&AppConfig>) -> Result<()>
             backup: options.backup,
             dry_run: options.dry_run,
             prune: options.prune,
+            strategy: options.strategy,
             codex_global: CodexGlobalSyncOptions {
                 requirements: options.codex_requirements,
                 managed_config: options.codex_managed_config,
             },
-            sync_supporting_assets: !options.gemini_system && !options.gemini_system_defaults,
+            sync_configuration: options.phases.configuration,
+            sync_supporting_assets: options.phases.assets
+                && !options.gemini_system
+                && !options.gemini_system_defaults,
+            undo,
         };
 
-        execute_sync_operation(&config, &paths, agent_context, flags)
+        Ok(vec![execute_sync_operation(&config, &paths, agent_context, flags, adjustments)?])
+    }
+}
+
+/// Restore the pre-sync state left behind by an interrupted sync
+fn recover_interrupted_sync(dry_run: bool) -> Result<()> {
+    let config_dir =
+        Config::get_config_dir().context("Failed to determine Claudius config directory")?;
+
+    if dry_run {
+        if claudius::sync_transaction::journal_path(&config_dir).exists() {
+            warn!("A previous sync was interrupted; run without --dry-run to restore its files");
+        }
+        return Ok(());
+    }
+
+    if let Some(recovery) = claudius::sync_transaction::recover_interrupted_sync(&config_dir)? {
+        output::progress(format!(
+            "Recovered from an interrupted sync; restored {} file(s):",
+            recovery.restored.len()
+        ));
+        for path in &recovery.restored {
+            output::progress(format!("  - {}", path.display()));
+        }
+        if !recovery.kept.is_empty() {
+            output::progress(format!(
+                "Kept {} file(s) changed since the interrupted sync:",
+                recovery.kept.len()
+            ));
+            for path in &recovery.kept {
+                output::progress(format!("  - {}", path.display()));
+            }
+        }
     }
+    Ok(())
 }
 
 /// Sync all available agents in global mode
-fn sync_all_available_agents(options: &SyncOptions, app_config: Option<&AppConfig>) -> Result<()> {
+fn sync_all_available_agents(
+    options: &SyncOptions,
+    app_config: Option<&AppConfig>,
+    adjustments: SourceAdjustments<'_>,
+    undo: &UndoRecorder,
+) -> Result<Vec<AgentSyncReport>> {
     // Detect available agents
     let available_agents = Config::detect_available_agents()?;
 
@@ -1185,30 +2448,26 @@ fn sync_all_available_agents(options: &SyncOptions, app_config: Option<&AppConfi
         warn!("No agent configuration files found in config directory");
         // Still sync skills if they exist
         let config = Config::new_with_agent(true, None)?;
-        let _ = sync_supporting_assets(
+        let assets = sync_supporting_assets(
             &config,
             AgentContext::new(None, None),
             SyncBehavior { dry_run: options.dry_run, prune: options.prune },
+            undo,
         );
-        return Ok(());
+        return Ok(vec![AgentSyncReport { assets: assets.assets, ..AgentSyncReport::default() }]);
     }
 
-    println!(
+    output::progress(format!(
         "Found configurations for {} agent(s): {}",
         available_agents.len(),
         available_agents.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>().join(", ")
-    );
+    ));
 
     // Sync each available agent
+    let mut reports = Vec::new();
     for agent in &available_agents {
-        let agent_name = match agent {
-            claudius::app_config::Agent::Claude => "Claude",
-            claudius::app_config::Agent::ClaudeCode => "Claude Code",
-            claudius::app_config::Agent::Codex => "Codex",
-            claudius::app_config::Agent::Gemini => "Gemini",
-        };
-        println!("\nSyncing agent: {agent_name}");
-        println!("===============================================");
+        output::progress(format!("\nSyncing agent: {}", agent.display_name()));
+        output::progress("===============================================");
 
         let (agent_context, config, paths) = setup_sync_context(
             SyncContextRequest {
@@ -1230,15 +2489,18 @@ fn sync_all_available_agents(options: &SyncOptions, app_config: Option<&AppConfi
             backup: options.backup,
             dry_run: options.dry_run,
             prune: options.prune,
+            strategy: options.strategy,
             codex_global: CodexGlobalSyncOptions::default(),
-            sync_supporting_assets: true,
+            sync_configuration: options.phases.configuration,
+            sync_supporting_assets: options.phases.assets,
+            undo,
         };
 
-        execute_sync_operation(&config, &paths, agent_context, flags)?;
+        reports.push(execute_sync_operation(&config, &paths, agent_context, flags, adjustments)?);
     }
 
-    println!("\nAll agent configurations synced successfully");
-    Ok(())
+    output::progress("\nAll agent configurations synced successfully");
+    Ok(reports)
 }
 
 /// Setup sync context with agent and paths
@@ -1307,12 +2569,16 @@ fn log_sync_paths(paths: &SyncPaths, global: bool, config: &Config) {
 }
 
 #[derive(Clone, Copy, Debug)]
-struct SyncExecutionFlags {
+struct SyncExecutionFlags<'a> {
     backup: bool,
     dry_run: bool,
     prune: bool,
+    strategy: MergeStrategy,
     codex_global: CodexGlobalSyncOptions,
+    sync_configuration: bool,
     sync_supporting_assets: bool,
+    /// Captures each file before it is written, for `claudius undo`.
+    undo: &'a UndoRecorder,
 }
 
 #[derive(Debug, Clone)]
@@ -1325,14 +2591,58 @@ struct SyncContextRequest {
     gemini_system_defaults: bool,
 }
 
+fn print_adjusted_sources(adjusted: &AdjustedSources) {
+    for warning in &adjusted.warnings {
+        output::progress(format!("Warning: {warning}"));
+    }
+    for launch in &adjusted.secret_launches {
+        output::progress(format!(
+            "MCP server '{}' launches through `claudius secrets run` to resolve {} at startup",
+            launch.server,
+            launch.secrets.join(", ")
+        ));
+    }
+}
+
+/// Report of one agent's sync, before any file outcome is known
+fn agent_sync_report(
+    config: &Config,
+    paths: &SyncPaths,
+    agent_context: AgentContext,
+) -> AgentSyncReport {
+    AgentSyncReport {
+        agent: config.agent,
+        scope: agent_context.claude_code_scope,
+        target_config: Some(paths.target_config.clone()),
+        ..AgentSyncReport::default()
+    }
+}
+
 /// Execute the main sync operation
 fn execute_sync_operation(
     config: &Config,
     paths: &SyncPaths,
     agent_context: AgentContext,
-    flags: SyncExecutionFlags,
-) -> Result<()> {
-    let read_result = read_configurations(config, &paths.mcp_servers, agent_context)?;
+    flags: SyncExecutionFlags<'_>,
+    adjustments: SourceAdjustments<'_>,
+) -> Result<AgentSyncReport> {
+    if !flags.sync_configuration {
+        let assets = collect_supporting_assets_report(config, agent_context, flags, flags.dry_run);
+        return Ok(AgentSyncReport {
+            assets: assets.map(|report| report.assets).unwrap_or_default(),
+            target_config: None,
+            ..agent_sync_report(config, paths, agent_context)
+        });
+    }
+
+    let mut read_result = read_configurations(config, &paths.mcp_servers, agent_context)?;
+    let adjusted =
+        adjust_sources(&mut read_result, config, &paths.mcp_servers, agent_context, adjustments)?;
+    print_adjusted_sources(&adjusted);
+    if flags.prune {
+        read_result.stale_mcp_servers =
+            collect_stale_mcp_servers(config, &paths.target_config, agent_context, &read_result)?;
+    }
     let mut claude_config = load_target_claude_config(config, &paths.target_config, agent_context)?;
     if flags.backup {
         handle_backup(
@@ -1341,98 +2651,114 @@ fn execute_sync_operation(
             &read_result,
             agent_context,
             flags.codex_global,
+            &backup_settings(adjustments.app_config),
         )?;
     }
-    merge_all_configs(&mut claude_config, &read_result, agent_context, config.is_global)?;
-
-    finalize_sync_operation(config, paths, agent_context, flags, &claude_config, &read_result)
-}
-
-fn load_target_claude_config(
-    config: &Config,
-    target_config: &std::path::Path,
-    agent_context: AgentContext,
-) -> Result<claudius::config::ClaudeConfig> {
-    debug!("Reading target configuration");
-
-    if config.is_global && agent_context.is_codex {
-        return Ok(claudius::config::ClaudeConfig { mcp_servers: None, other: HashMap::new() });
-    }
+    merge_all_configs(
+        &mut claude_config,
+        &read_result,
+        agent_context,
+        config.is_global,
+        flags.strategy,
+    )?;
 
-    reader::read_claude_config(target_config).context("Failed to read target configuration")
+    let report =
+        finalize_sync_operation(config, paths, agent_context, flags, &claude_config, &read_result)?;
+    Ok(AgentSyncReport { warnings: adjusted.warnings, ..report })
 }
 
 fn finalize_sync_operation(
     config: &Config,
     paths: &SyncPaths,
     agent_context: AgentContext,
-    flags: SyncExecutionFlags,
+    flags: SyncExecutionFlags<'_>,
     claude_config: &claudius::config::ClaudeConfig,
     read_result: &ReadConfigResult,
-) -> Result<()> {
+) -> Result<AgentSyncReport> {
     if flags.dry_run {
         return run_sync_dry_run(config, paths, agent_context, flags, claude_config, read_result);
     }
 
-    write_configurations(
+    let ownership =
+        stage_mcp_ownership(config, &paths.target_config, agent_context, read_result, flags.prune)?;
+    let files = write_configurations(
         config,
         claude_config,
         &paths.target_config,
         read_result,
         agent_context,
         flags.codex_global,
+        ownership,
+        flags.undo,
     )?;
-    sync_supporting_assets_if_enabled(config, agent_context, flags, false);
+    let assets = collect_supporting_assets_report(config, agent_context, flags, false);
 
-    Ok(())
+    Ok(AgentSyncReport {
+        files,
+        pruned_mcp_servers: read_result.stale_mcp_servers.iter().cloned().collect(),
+        assets: assets.map(|report| report.assets).unwrap_or_default(),
+        ..agent_sync_report(config, paths, agent_context)
+    })
 }
 
 fn run_sync_dry_run(
     config: &Config,
     paths: &SyncPaths,
     agent_context: AgentContext,
-    flags: SyncExecutionFlags,
+    flags: SyncExecutionFlags<'_>,
     claude_config: &claudius::config::ClaudeConfig,
     read_result: &ReadConfigResult,
-) -> Result<()> {
+) -> Result<AgentSyncReport> {
     let supporting_assets = collect_supporting_assets_report(config, agent_context, flags, true);
 
-    handle_dry_run(
-        config,
-        &paths.target_config,
-        claude_config,
-        read_result,
-        agent_context,
-        flags.codex_global,
-    )?;
-
-    if let Some(report) = supporting_assets {
-        print_supporting_assets_dry_run(&report);
-    }
+    let planned = if output::is_json() {
+        plan_configuration_writes(
+            config,
+            claude_config,
+            &paths.target_config,
+            read_result,
+            agent_context,
+            flags.codex_global,
+        )?
+    } else {
+        let planned = handle_dry_run(
+            config,
+            &paths.target_config,
+            claude_config,
+            read_result,
+            agent_context,
+            flags.codex_global,
+        )?;
+        if let Some(report) = &supporting_assets {
+            print_supporting_assets_dry_run(report);
+        }
+        planned
+    };
 
-    Ok(())
+    Ok(AgentSyncReport {
+        files: claudius::sync_operations::FileSyncOutcome::from_planned(&planned)?,
+        pruned_mcp_servers: read_result.stale_mcp_servers.iter().cloned().collect(),
+        assets: supporting_assets.map(|report| report.assets).unwrap_or_default(),
+        ..agent_sync_report(config, paths, agent_context)
+    })
 }
 
 fn collect_supporting_assets_report(
     config: &Config,
     agent_context: AgentContext,
-    flags: SyncExecutionFlags,
+    flags: SyncExecutionFlags<'_>,
     dry_run: bool,
 ) -> Option<SupportingAssetSyncReport> {
     flags.sync_supporting_assets.then(|| {
-        sync_supporting_assets(config, agent_context, SyncBehavior { dry_run, prune: flags.prune })
+        sync_supporting_assets(
+            config,
+            agent_context,
+            SyncBehavior { dry_run, prune: flags.prune },
+            flags.undo,
+        )
     })
 }
 
-fn sync_supporting_assets_if_enabled(
-    config: &Config,
-    agent_context: AgentContext,
-    flags: SyncExecutionFlags,
-    dry_run: bool,
-) {
-    let _ = collect_supporting_assets_report(config, agent_context, flags, dry_run);
-}
-
 fn print_skill_sync_result(reports: &[skills::SkillSyncReport], dry_run: bool) {
     if reports.iter().all(skills::SkillSyncReport::is_empty) {
         println!("No skills to sync");
@@ -1583,6 +2909,14 @@ fn run_command_inner(command: &[String]) -> Result<std::process::ExitStatus> {
 }
 
 // Helper functions for run_install_context
+fn display_paths(paths: &[std::path::PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 fn collect_md_files(
     dir: &std::path::Path,
     base_dir: &std::path::Path,
@@ -1621,22 +2955,26 @@ fn process_file_entry(path: &std::path::Path, base_dir: &std::path::Path, rules:
 
 fn copy_rules(
     rules_to_copy: &[String],
-    source_rules_dir: &std::path::Path,
+    source_rules_dirs: &[std::path::PathBuf],
     rules_dir: &std::path::Path,
 ) -> Result<Vec<String>> {
     use std::fs;
 
     let mut copied_rules = Vec::new();
     for rule_name in rules_to_copy {
-        let source_path = source_rules_dir.join(format!("{rule_name}.md"));
-        let dest_path = rules_dir.join(format!("{rule_name}.md"));
+        let file_name = format!("{rule_name}.md");
+        let dest_path = rules_dir.join(&file_name);
 
-        debug!("Checking for rule at: {}", source_path.display());
+        debug!("Checking for rule {rule_name} in: {:?}", source_rules_dirs);
 
-        if !source_path.exists() {
-            warn!("Rule '{}' not found at {}", rule_name, source_path.display());
+        let Some(source_path) = source_rules_dirs
+            .iter()
+            .map(|source_rules_dir| source_rules_dir.join(&file_name))
+            .find(|candidate| candidate.exists())
+        else {
+            warn!("Rule '{}' not found in {}", rule_name, display_paths(source_rules_dirs));
             continue;
-        }
+        };
 
         // Create subdirectories if needed
         if let Some(parent) = dest_path.parent() {
@@ -1822,31 +3160,34 @@ fn run_install_context(
     fs::create_dir_all(&rules_dir)
         .with_context(|| format!("Failed to create directory: {}", rules_dir.display()))?;
 
-    // Get config directory for source rules
-    let source_rules_dir = Config::get_config_dir()?.join("rules");
-    debug!("Looking for rules in: {}", source_rules_dir.display());
+    // Project `.claudius/rules` first, then the global rules directory
+    let source_rules_dirs = claudius::template::rule_source_dirs(&target_dir)?;
+    debug!("Looking for rules in: {:?}", source_rules_dirs);
 
     // Determine which rules to copy
     let rules_to_copy = if all {
-        // Get all .md files from the rules directory recursively
-        println!(
-            "Installing ALL rules from {} (including subdirectories)",
-            source_rules_dir.display()
-        );
+        // Get all .md files from the rules directories recursively
         let mut all_rules = Vec::new();
-        collect_md_files(&source_rules_dir, &source_rules_dir, &mut all_rules)?;
+        for source_rules_dir in &source_rules_dirs {
+            println!(
+                "Installing ALL rules from {} (including subdirectories)",
+                source_rules_dir.display()
+            );
+            collect_md_files(source_rules_dir, source_rules_dir, &mut all_rules)?;
+        }
 
         if all_rules.is_empty() {
-            return Err(anyhow::anyhow!("No rules found in {}", source_rules_dir.display()));
+            return Err(anyhow::anyhow!("No rules found in {}", display_paths(&source_rules_dirs)));
         }
         all_rules.sort(); // Sort for consistent ordering
+        all_rules.dedup();
         all_rules
     } else {
         rules
     };
 
     // Copy specified rules
-    let copied_rules = copy_rules(&rules_to_copy, &source_rules_dir, &rules_dir)?;
+    let copied_rules = copy_rules(&rules_to_copy, &source_rules_dirs, &rules_dir)?;
 
     // Add the managed rule reference section to the context file.
     add_reference_directive(&target_dir, &rules_dir, &context_filename, &copied_rules)?;
@@ -0,0 +1,474 @@
+#![allow(missing_docs)]
+
+//! Edit `mcpServers.json` for `claudius mcp add|remove|rename|list|show`.
+//!
+//! The source is edited as a JSON document rather than round-tripped through
+//! [`McpServersConfig`], so servers keep their order, members Claudius does not
+//! model survive, and [`update_json_document`] keeps the file's own
+//! formatting; a rename rewrites only the server's key. A definition is checked
+//! for its transport before it is written: stdio servers need a `command`,
+//! remote servers an `http(s)` `url`, and fields that only apply to the other
+//! transport are rejected.
+
+use crate::config::json_document::{rename_json_member, update_json_document};
+use crate::config::writer::{atomic_write, FileMode};
+use crate::config::{parse_target_selector, reader, McpServerConfig, McpServersConfig};
+use crate::mcp_probe::McpTransport;
+use crate::secret_launch;
+use crate::source_layers::SourceLayer;
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const SERVERS_KEY: &str = "mcpServers";
+
+/// Contents of a source that does not exist yet.
+const EMPTY_SOURCE: &str = "{\n  \"mcpServers\": {}\n}\n";
+
+/// Server types Claudius syncs.
+const SERVER_TYPES: &[&str] = &["stdio", "http", "sse"];
+
+/// An `mcpServers.json` source opened for editing.
+#[derive(Debug, Clone)]
+pub struct McpSource {
+    path: PathBuf,
+    text: String,
+    document: Value,
+}
+
+impl McpSource {
+    /// Open `path`, starting from an empty document when it does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, is not JSON, or has no
+    /// `mcpServers` object.
+    pub fn open(path: &Path) -> Result<Self> {
+        let text = if path.exists() {
+            fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+        } else {
+            EMPTY_SOURCE.to_string()
+        };
+        let document: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if !document.get(SERVERS_KEY).is_some_and(Value::is_object) {
+            bail!("{} has no `{SERVERS_KEY}` object", path.display());
+        }
+        Ok(Self { path: path.to_path_buf(), text, document })
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.document.get(SERVERS_KEY).and_then(|servers| servers.get(name))
+    }
+
+    /// Add `definition` as `name`, replacing an existing server only when `replace` is set.
+    ///
+    /// Returns whether an existing server was replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the definition is invalid for its transport or the
+    /// server exists and `replace` is not set.
+    pub fn add(&mut self, name: &str, definition: Value, replace: bool) -> Result<bool> {
+        validate_definition(name, &definition)?;
+        let exists = self.get(name).is_some();
+        if exists && !replace {
+            bail!(
+                "MCP server '{name}' already exists in {}; pass --replace to overwrite it",
+                self.path.display()
+            );
+        }
+        self.servers_mut()?.insert(name.to_string(), definition);
+        self.text = update_json_document(&self.text, &self.document)?;
+        Ok(exists)
+    }
+
+    /// Remove `name` and return its definition.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no such server.
+    pub fn remove(&mut self, name: &str) -> Result<Value> {
+        let path = self.path.clone();
+        let removed = self
+            .servers_mut()?
+            .shift_remove(name)
+            .with_context(|| format!("No MCP server named '{name}' in {}", path.display()))?;
+        self.text = update_json_document(&self.text, &self.document)?;
+        Ok(removed)
+    }
+
+    /// Rename `from` to `to` in place.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `from` does not exist or `to` already does.
+    pub fn rename(&mut self, from: &str, to: &str) -> Result<()> {
+        if self.get(from).is_none() {
+            bail!("No MCP server named '{from}' in {}", self.path.display());
+        }
+        if self.get(to).is_some() {
+            bail!("MCP server '{to}' already exists in {}", self.path.display());
+        }
+        self.text = rename_json_member(&self.text, &[SERVERS_KEY], from, to)?;
+        self.document = serde_json::from_str(&self.text).context("Renamed document is not JSON")?;
+        Ok(())
+    }
+
+    /// Write the edited document back to its file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn save(&self) -> Result<()> {
+        atomic_write(&self.path, self.text.as_bytes(), FileMode::Preserve)
+    }
+
+    fn servers_mut(&mut self) -> Result<&mut Map<String, Value>> {
+        self.document
+            .get_mut(SERVERS_KEY)
+            .and_then(Value::as_object_mut)
+            .with_context(|| format!("{} has no `{SERVERS_KEY}` object", self.path.display()))
+    }
+}
+
+/// What `mcp add` was given on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct ServerSpec {
+    pub server_type: Option<String>,
+    /// The program followed by its arguments.
+    pub command: Vec<String>,
+    pub url: Option<String>,
+    pub env: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    /// `x-claudius` selectors of the agents that receive the server.
+    pub agents: Vec<String>,
+    /// `x-claudius` selectors of the agents that skip the server.
+    pub exclude: Vec<String>,
+}
+
+impl ServerSpec {
+    /// The `mcpServers.json` definition; `url` servers default to `type: http`.
+    #[must_use]
+    pub fn definition(&self) -> Value {
+        let mut definition = Map::new();
+        let server_type = self
+            .server_type
+            .clone()
+            .or_else(|| self.url.as_ref().map(|_| "http".to_string()));
+        if let Some(kind) = server_type {
+            definition.insert("type".to_string(), json!(kind));
+        }
+        if let Some((program, args)) = self.command.split_first() {
+            definition.insert("command".to_string(), json!(program));
+            if !args.is_empty() {
+                definition.insert("args".to_string(), json!(args));
+            }
+        }
+        if let Some(url) = &self.url {
+            definition.insert("url".to_string(), json!(url));
+        }
+        self.overlay(&mut definition);
+        Value::Object(definition)
+    }
+
+    /// Merge the `env`, `headers`, and targeting into `definition`, such as
+    /// one rendered from a preset; given values win over existing ones.
+    pub fn overlay(&self, definition: &mut Map<String, Value>) {
+        merge_pairs(definition, "env", &self.env);
+        merge_pairs(definition, "headers", &self.headers);
+        let selectors = [("agents", &self.agents), ("exclude", &self.exclude)];
+        for (key, values) in selectors.into_iter().filter(|(_, values)| !values.is_empty()) {
+            if let Some(metadata) =
+                definition.entry("x-claudius").or_insert_with(|| json!({})).as_object_mut()
+            {
+                metadata.insert(key.to_string(), json!(values));
+            }
+        }
+    }
+}
+
+fn merge_pairs(definition: &mut Map<String, Value>, key: &str, pairs: &[(String, String)]) {
+    if pairs.is_empty() {
+        return;
+    }
+    if let Some(members) = definition.entry(key).or_insert_with(|| json!({})).as_object_mut() {
+        members.extend(pairs.iter().map(|(name, value)| (name.clone(), json!(value))));
+    }
+}
+
+/// Check that `definition` is a server its transport can run.
+///
+/// # Errors
+///
+/// Returns an error listing every problem with the definition.
+pub fn validate_definition(name: &str, definition: &Value) -> Result<()> {
+    let server: McpServerConfig = serde_json::from_value(definition.clone())
+        .with_context(|| format!("Invalid MCP server '{name}'"))?;
+    let problems = definition_problems(&server);
+    if !problems.is_empty() {
+        bail!("Invalid MCP server '{name}': {}", problems.join("; "));
+    }
+    Ok(())
+}
+
+fn definition_problems(server: &McpServerConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    match (&server.command, &server.url) {
+        (Some(_), Some(_)) => {
+            problems
+                .push("set either `command` (stdio) or `url` (http, sse), not both".to_string());
+        },
+        (None, None) => {
+            problems.push("needs a `command` (stdio) or a `url` (http, sse)".to_string());
+        },
+        _ => {},
+    }
+
+    if let Some(kind) = &server.server_type {
+        if !SERVER_TYPES.contains(&kind.as_str()) {
+            problems.push(format!("unknown type '{kind}' (expected: {})", SERVER_TYPES.join(", ")));
+        } else if kind == "stdio" && server.url.is_some() {
+            problems.push("type `stdio` takes a `command`, not a `url`".to_string());
+        } else if kind != "stdio" && server.url.is_none() {
+            problems.push(format!("type `{kind}` needs a `url`"));
+        }
+    }
+
+    if let Some(url) = &server.url {
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            problems.push(format!("url '{url}' must start with http:// or https://"));
+        }
+        if !server.args.is_empty() || !server.env.is_empty() {
+            problems.push("`args` and `env` only apply to stdio servers".to_string());
+        }
+    } else if !server.headers.is_empty() {
+        problems.push("`headers` only apply to http and sse servers".to_string());
+    }
+
+    if let Some(targeting) = &server.targeting {
+        for selector in targeting.agents.iter().chain(&targeting.exclude) {
+            if let Err(problem) = parse_target_selector(selector) {
+                problems.push(format!("x-claudius: {problem}"));
+            }
+        }
+    }
+    if let Some(problem) = secret_launch::wrap_problem(server) {
+        problems.push(problem.to_string());
+    }
+    problems
+}
+
+/// One server in `mcp list`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedServer {
+    pub name: String,
+    pub layer: SourceLayer,
+    /// A project server that replaces a global server with the same name.
+    pub overrides_global: bool,
+    pub transport: McpTransport,
+    /// `command args…` for stdio servers, the `url` for remote ones.
+    pub target: String,
+    pub agents: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+/// The servers an agent would receive, by name, with the layer each comes from.
+///
+/// # Errors
+///
+/// Returns an error if either source exists but cannot be read.
+pub fn list_servers(global_path: &Path, project_path: Option<&Path>) -> Result<Vec<ListedServer>> {
+    let global = read_source(global_path)?;
+    let project = project_path.map(read_source).transpose()?.unwrap_or_default();
+
+    let mut listed = global
+        .iter()
+        .filter(|(name, _)| !project.contains_key(*name))
+        .map(|(name, server)| listed_server(name, server, SourceLayer::Global, false))
+        .chain(project.iter().map(|(name, server)| {
+            listed_server(name, server, SourceLayer::Project, global.contains_key(name))
+        }))
+        .collect::<Vec<_>>();
+    listed.sort_by(|left, right| left.name.cmp(&right.name));
+    Ok(listed)
+}
+
+fn read_source(path: &Path) -> Result<HashMap<String, McpServerConfig>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    reader::read_mcp_servers_config(path)
+        .map(|config: McpServersConfig| config.mcp_servers)
+        .with_context(|| format!("Failed to read {}", path.display()))
+}
+
+fn listed_server(
+    name: &str,
+    server: &McpServerConfig,
+    layer: SourceLayer,
+    overrides_global: bool,
+) -> ListedServer {
+    let target = server.url.clone().unwrap_or_else(|| {
+        server.command.iter().chain(&server.args).cloned().collect::<Vec<_>>().join(" ")
+    });
+    let targeting = server.targeting.clone().unwrap_or_default();
+    ListedServer {
+        name: name.to_string(),
+        layer,
+        overrides_global,
+        transport: McpTransport::of(server),
+        target,
+        agents: targeting.agents,
+        exclude: targeting.exclude,
+    }
+}
+
+/// Text for `mcp list`.
+#[must_use]
+pub fn render_list(servers: &[ListedServer]) -> String {
+    if servers.is_empty() {
+        return "No MCP servers defined".to_string();
+    }
+    let mut lines = vec!["MCP servers:".to_string()];
+    for server in servers {
+        let mut line = format!("  {} ({}, {}", server.name, server.transport, server.layer.label());
+        if server.overrides_global {
+            line.push_str(", overrides global");
+        }
+        line.push_str("): ");
+        line.push_str(&server.target);
+        let mut targeting = Vec::new();
+        if !server.agents.is_empty() {
+            targeting.push(format!("agents: {}", server.agents.join(", ")));
+        }
+        if !server.exclude.is_empty() {
+            targeting.push(format!("exclude: {}", server.exclude.join(", ")));
+        }
+        if !targeting.is_empty() {
+            line.push_str("  [");
+            line.push_str(&targeting.join("; "));
+            line.push(']');
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// A server definition as written in its source, for `mcp show`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShownServer {
+    pub server: String,
+    pub layer: SourceLayer,
+    pub source: PathBuf,
+    pub definition: Value,
+}
+
+/// Find `name` in the project layer, then in the global source.
+///
+/// # Errors
+///
+/// Returns an error if a source exists but cannot be parsed.
+pub fn find_server(
+    name: &str,
+    global_path: &Path,
+    project_path: Option<&Path>,
+) -> Result<Option<ShownServer>> {
+    let layers = project_path
+        .map(|path| (SourceLayer::Project, path))
+        .into_iter()
+        .chain([(SourceLayer::Global, global_path)]);
+    for (layer, path) in layers {
+        if !path.exists() {
+            continue;
+        }
+        if let Some(definition) = McpSource::open(path)?.get(name) {
+            return Ok(Some(ShownServer {
+                server: name.to_string(),
+                layer,
+                source: path.to_path_buf(),
+                definition: definition.clone(),
+            }));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn source(text: &str) -> (TempDir, PathBuf) {
+        let dir = TempDir::new().expect("temp dir");
+        let path = dir.path().join("mcpServers.json");
+        fs::write(&path, text).expect("write source");
+        (dir, path)
+    }
+
+    #[test]
+    fn edits_keep_order_and_formatting() {
+        let (_dir, path) = source(
+            "{\n    \"mcpServers\": {\n        \"zeta\": {\"command\": \"z\"},\n        \"alpha\": {\"command\": \"a\"}\n    },\n    \"note\": 1\n}\n",
+        );
+        let mut edited = McpSource::open(&path).expect("open");
+
+        let spec = ServerSpec {
+            url: Some("https://example.com/mcp".to_string()),
+            ..ServerSpec::default()
+        };
+        assert!(!edited.add("beta", spec.definition(), false).expect("add"));
+        edited.rename("zeta", "omega").expect("rename");
+        edited.remove("alpha").expect("remove");
+        edited.save().expect("save");
+
+        assert_eq!(
+            fs::read_to_string(&path).expect("read"),
+            "{\n    \"mcpServers\": {\n        \"omega\": {\"command\": \"z\"},\n        \"beta\": {\n            \"type\": \"http\",\n            \"url\": \"https://example.com/mcp\"\n        }\n    },\n    \"note\": 1\n}\n"
+        );
+    }
+
+    #[test]
+    fn add_refuses_to_overwrite_without_replace() {
+        let (_dir, path) = source(r#"{"mcpServers": {"files": {"command": "old"}}}"#);
+        let mut edited = McpSource::open(&path).expect("open");
+        let spec = ServerSpec { command: vec!["new".to_string()], ..ServerSpec::default() };
+
+        assert!(edited.add("files", spec.definition(), false).is_err());
+        assert!(edited.add("files", spec.definition(), true).expect("replace"));
+        assert_eq!(edited.get("files"), Some(&json!({"command": "new"})));
+    }
+
+    #[test]
+    fn definitions_are_checked_per_transport() {
+        let problems = |definition: Value| {
+            serde_json::from_value::<McpServerConfig>(definition)
+                .map(|server| definition_problems(&server))
+                .unwrap_or_default()
+        };
+
+        assert!(problems(json!({"command": "npx", "args": ["-y", "server"]})).is_empty());
+        assert!(problems(json!({"type": "sse", "url": "https://example.com/sse"})).is_empty());
+        assert_eq!(problems(json!({})).len(), 1);
+        assert_eq!(problems(json!({"command": "npx", "url": "https://example.com"})).len(), 1);
+        assert_eq!(problems(json!({"type": "http", "command": "npx"})).len(), 1);
+        assert_eq!(problems(json!({"type": "websocket", "url": "https://example.com"})).len(), 1);
+        assert_eq!(problems(json!({"url": "ftp://example.com"})).len(), 1);
+        assert_eq!(problems(json!({"url": "https://example.com", "env": {"A": "1"}})).len(), 1);
+        assert_eq!(problems(json!({"command": "npx", "headers": {"A": "1"}})).len(), 1);
+        assert_eq!(
+            problems(json!({"command": "npx", "x-claudius": {"agents": ["vscode"]}})).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,282 @@
+#![allow(missing_docs)]
+
+//! Lint `mcpServers.json` definitions for `claudius config validate`.
+//!
+//! Parsing only proves a definition is JSON of the right shape. These checks
+//! catch definitions an agent would run differently than written, or not at
+//! all:
+//!
+//! - transport fields that contradict each other (`command` and `url`, or a
+//!   `type` that does not match them)
+//! - fields of the other transport that a sync drops
+//! - bare commands that are not on `PATH`
+//! - `npx` without `-y`, and packages pinned to `@latest`
+//! - servers that run the same command and arguments
+//! - `env` keys reserved by an agent
+
+use crate::config::{McpServerConfig, McpServersConfig};
+use crate::merge::dropped_transport_fields;
+use crate::validation::{Diagnostic, DiagnosticRule};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// `env` names an agent sets for a server itself.
+///
+/// A name ending in `_` reserves every name with that prefix.
+const RESERVED_ENV: &[(&str, &str)] = &[
+    ("CLAUDECODE", "Claude Code"),
+    ("CLAUDE_CODE_", "Claude Code"),
+    ("CODEX_HOME", "Codex"),
+    ("CODEX_SANDBOX", "Codex"),
+    ("CODEX_SANDBOX_NETWORK_DISABLED", "Codex"),
+    ("GEMINI_CLI", "Gemini CLI"),
+];
+
+/// Every finding for the servers in `mcp_servers`, attributed to `path`.
+///
+/// `path_env` is the `PATH` bare commands are looked up in; `None` skips the lookup.
+#[must_use]
+pub fn lint_mcp_servers(
+    mcp_servers: &McpServersConfig,
+    path: &Path,
+    path_env: Option<&std::ffi::OsStr>,
+) -> Vec<Diagnostic> {
+    let servers = mcp_servers.mcp_servers.iter().collect::<BTreeMap<_, _>>();
+    let mut diagnostics = Vec::new();
+    for (name, server) in &servers {
+        diagnostics.extend(transport_diagnostics(name, server));
+        diagnostics.extend(launch_diagnostics(name, server, path_env));
+        diagnostics.extend(reserved_env_diagnostics(name, server));
+    }
+    diagnostics.extend(duplicate_diagnostics(&servers));
+    diagnostics.into_iter().map(|diagnostic| diagnostic.with_path(path)).collect()
+}
+
+fn transport_diagnostics(name: &str, server: &McpServerConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let conflict = |message: String| {
+        Diagnostic::error(format!("mcpServers.{name}: {message}"))
+            .with_rule(DiagnosticRule::McpTransportConflict)
+    };
+
+    if server.command.is_some() && server.url.is_some() {
+        diagnostics.push(conflict(
+            "sets both `command` and `url`; agents pick one transport, so keep only one"
+                .to_string(),
+        ));
+    }
+    match server.server_type.as_deref() {
+        Some("stdio") if server.url.is_some() => {
+            diagnostics.push(conflict("type `stdio` takes a `command`, not a `url`".to_string()));
+        },
+        Some(kind @ ("http" | "sse")) if server.url.is_none() => {
+            diagnostics.push(conflict(format!("type `{kind}` needs a `url`")));
+        },
+        _ => {},
+    }
+
+    let transport = if server.url.is_some() { "remote" } else { "stdio" };
+    diagnostics.extend(dropped_transport_fields(server).into_iter().map(|field| {
+        Diagnostic::warning(format!(
+            "mcpServers.{name}.{field} does not apply to {transport} servers and is dropped when syncing"
+        ))
+        .with_rule(DiagnosticRule::McpDroppedField)
+    }));
+    diagnostics
+}
+
+fn launch_diagnostics(
+    name: &str,
+    server: &McpServerConfig,
+    path_env: Option<&std::ffi::OsStr>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(command) = server.command.as_deref().filter(|_| server.url.is_none()) else {
+        return diagnostics;
+    };
+
+    let is_bare = !command.contains(['/', '\\']) && !command.contains("${");
+    if let Some(search_path) = path_env.filter(|_| is_bare) {
+        if !std::env::split_paths(search_path).any(|dir| dir.join(command).is_file()) {
+            diagnostics.push(
+                Diagnostic::warning(format!(
+                    "mcpServers.{name}.command '{command}' is not on PATH; install it or use an absolute path"
+                ))
+                .with_rule(DiagnosticRule::McpCommandNotFound),
+            );
+        }
+    }
+
+    let program = Path::new(command).file_stem().and_then(|stem| stem.to_str());
+    if program == Some("npx") && !server.args.iter().any(|arg| arg == "-y" || arg == "--yes") {
+        diagnostics.push(
+            Diagnostic::warning(format!(
+                "mcpServers.{name}: npx asks before installing a missing package, which stalls the server; add `-y` to args"
+            ))
+            .with_rule(DiagnosticRule::McpNpxPrompt),
+        );
+    }
+    for package in server.args.iter().filter(|arg| arg.ends_with("@latest")) {
+        diagnostics.push(
+            Diagnostic::warning(format!(
+                "mcpServers.{name}: '{package}' installs whatever version is newest at launch; pin a version"
+            ))
+            .with_rule(DiagnosticRule::McpUnpinnedPackage),
+        );
+    }
+    diagnostics
+}
+
+fn reserved_env_diagnostics(name: &str, server: &McpServerConfig) -> Vec<Diagnostic> {
+    let mut keys = server.env.keys().collect::<Vec<_>>();
+    keys.sort();
+    keys.into_iter()
+        .filter_map(|key| {
+            let (_, owner) = RESERVED_ENV.iter().find(|(reserved, _)| {
+                if reserved.ends_with('_') {
+                    key.starts_with(reserved)
+                } else {
+                    key == reserved
+                }
+            })?;
+            Some(
+                Diagnostic::warning(format!(
+                    "mcpServers.{name}.env.{key} is reserved by {owner}; rename it"
+                ))
+                .with_rule(DiagnosticRule::McpReservedEnv),
+            )
+        })
+        .collect()
+}
+
+fn duplicate_diagnostics(servers: &BTreeMap<&String, &McpServerConfig>) -> Vec<Diagnostic> {
+    let mut launches = BTreeMap::<(&str, &[String]), Vec<&str>>::new();
+    for (name, server) in servers {
+        if let Some(command) = server.command.as_deref().filter(|_| server.url.is_none()) {
+            launches
+                .entry((command, server.args.as_slice()))
+                .or_default()
+                .push(name.as_str());
+        }
+    }
+
+    launches
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|((command, args), names)| {
+            let command_line = std::iter::once(command)
+                .chain(args.iter().map(String::as_str))
+                .collect::<Vec<_>>();
+            Diagnostic::warning(format!(
+                "mcpServers.{} run the same command (`{}`); each starts its own copy",
+                names.join(", mcpServers."),
+                command_line.join(" ")
+            ))
+            .with_rule(DiagnosticRule::McpDuplicateServer)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn lint(servers: &serde_json::Value) -> Vec<Diagnostic> {
+        let config: McpServersConfig =
+            serde_json::from_value(json!({ "mcpServers": servers })).unwrap();
+        lint_mcp_servers(&config, Path::new("mcpServers.json"), None)
+    }
+
+    fn rules(diagnostics: &[Diagnostic]) -> Vec<DiagnosticRule> {
+        diagnostics.iter().map(|diagnostic| diagnostic.rule).collect()
+    }
+
+    #[test]
+    fn transport_conflicts_are_errors() {
+        let diagnostics = lint(&json!({
+            "both": { "command": "server", "url": "https://example.com/mcp" },
+            "sse": { "type": "sse", "command": "server" },
+            "stdio": { "type": "stdio", "url": "https://example.com/mcp" },
+        }));
+
+        assert_eq!(
+            rules(&diagnostics),
+            [
+                DiagnosticRule::McpTransportConflict,
+                DiagnosticRule::McpTransportConflict,
+                DiagnosticRule::McpTransportConflict,
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == crate::validation::DiagnosticSeverity::Error));
+    }
+
+    #[test]
+    fn fields_of_the_other_transport_are_reported() {
+        let diagnostics = lint(&json!({
+            "local": { "command": "server", "headers": { "X-Team": "core" } },
+            "remote": { "url": "https://example.com/mcp", "env": { "TOKEN": "x" }, "cwd": "/srv" },
+        }));
+
+        let messages = diagnostics.iter().map(|diagnostic| diagnostic.message.as_str());
+        assert_eq!(
+            messages.collect::<Vec<_>>(),
+            [
+                "mcpServers.local.headers does not apply to stdio servers and is dropped when syncing",
+                "mcpServers.remote.env does not apply to remote servers and is dropped when syncing",
+                "mcpServers.remote.cwd does not apply to remote servers and is dropped when syncing",
+            ]
+        );
+    }
+
+    #[test]
+    fn launch_problems_are_reported() {
+        let diagnostics = lint(&json!({
+            "browser": { "command": "npx", "args": ["@playwright/mcp@latest"] },
+            "pinned": { "command": "/usr/bin/npx", "args": ["-y", "@scope/server@1.2.0"] },
+            "reserved": { "command": "/bin/server", "env": { "CODEX_HOME": "/tmp", "CLAUDIUS_SECRET_X": "y" } },
+        }));
+
+        assert_eq!(
+            rules(&diagnostics),
+            [
+                DiagnosticRule::McpNpxPrompt,
+                DiagnosticRule::McpUnpinnedPackage,
+                DiagnosticRule::McpReservedEnv,
+            ]
+        );
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic.contains("env.CODEX_HOME")));
+    }
+
+    #[test]
+    fn bare_commands_are_looked_up_on_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("present"), "").unwrap();
+        let config: McpServersConfig = serde_json::from_value(json!({ "mcpServers": {
+            "found": { "command": "present" },
+            "missing": { "command": "absent" },
+            "templated": { "command": "${TOOLS}/server" },
+        }}))
+        .unwrap();
+
+        let diagnostics =
+            lint_mcp_servers(&config, Path::new("mcpServers.json"), Some(dir.path().as_os_str()));
+        assert_eq!(rules(&diagnostics), [DiagnosticRule::McpCommandNotFound]);
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic.contains("'absent'")));
+    }
+
+    #[test]
+    fn identical_launches_are_reported_once() {
+        let diagnostics = lint(&json!({
+            "a": { "command": "/bin/server", "args": ["--port", "1"] },
+            "b": { "command": "/bin/server", "args": ["--port", "1"] },
+            "c": { "command": "/bin/server", "args": ["--port", "2"] },
+        }));
+
+        assert_eq!(rules(&diagnostics), [DiagnosticRule::McpDuplicateServer]);
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic
+            .contains("mcpServers.a, mcpServers.b run the same command (`/bin/server --port 1`)")));
+    }
+}
@@ -0,0 +1,187 @@
+#![allow(missing_docs)]
+
+//! Ownership ledger for MCP server entries deployed by Claudius.
+//!
+//! Agent target files (`.mcp.json`, `~/.claude.json`, `.codex/config.toml`,
+//! `.gemini/settings.json`) mix servers synced from `mcpServers.json` with
+//! servers users add by hand. The ledger records, per target, which server
+//! names Claudius wrote so `config sync --prune` can remove the ones that were
+//! deleted from the sources without touching hand-added entries.
+
+use crate::sync_transaction::{self, StagedFile};
+use crate::undo::UndoRecorder;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const LEDGER_FILE_NAME: &str = ".claudius-managed-mcp-servers.json";
+const LEDGER_VERSION: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpOwnershipLedger {
+    #[serde(default = "ledger_version")]
+    version: u8,
+    #[serde(default)]
+    targets: BTreeMap<String, BTreeSet<String>>,
+}
+
+const fn ledger_version() -> u8 {
+    LEDGER_VERSION
+}
+
+impl Default for McpOwnershipLedger {
+    fn default() -> Self {
+        Self { version: LEDGER_VERSION, targets: BTreeMap::new() }
+    }
+}
+
+impl McpOwnershipLedger {
+    /// Path of the ledger inside the Claudius config directory.
+    #[must_use]
+    pub fn path(config_dir: &Path) -> PathBuf {
+        config_dir.join(LEDGER_FILE_NAME)
+    }
+
+    /// Load the ledger, returning an empty one when it does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger exists but cannot be read or parsed.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let path = Self::path(config_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Persist the ledger, removing the file when no target owns any server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger cannot be written or removed.
+    pub fn save(&self, config_dir: &Path, undo: &UndoRecorder) -> Result<()> {
+        sync_transaction::commit(config_dir, &[self.staged(config_dir)?], undo)
+    }
+
+    /// The ledger file as part of a sync transaction; an empty ledger removes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ledger cannot be serialized.
+    pub fn staged(&self, config_dir: &Path) -> Result<StagedFile> {
+        let content = if self.targets.is_empty() {
+            None
+        } else {
+            let json =
+                serde_json::to_string_pretty(self).context("Failed to serialize MCP ownership")?;
+            Some(format!("{json}\n"))
+        };
+        Ok(StagedFile { path: Self::path(config_dir), content })
+    }
+
+    /// Server names Claudius owns in the given target.
+    #[must_use]
+    pub fn owned(&self, target_key: &str) -> BTreeSet<String> {
+        self.targets.get(target_key).cloned().unwrap_or_default()
+    }
+
+    /// Owned servers that are no longer defined in the sources.
+    #[must_use]
+    pub fn stale(&self, target_key: &str, desired: &BTreeSet<String>) -> BTreeSet<String> {
+        self.owned(target_key).difference(desired).cloned().collect()
+    }
+
+    /// Record the servers synced to a target.
+    ///
+    /// With `pruned` the previously owned entries were removed from the target,
+    /// so ownership is reset to `desired`. Otherwise stale entries are still
+    /// present and remain owned so a later `--prune` can remove them.
+    pub fn record(&mut self, target_key: &str, desired: &BTreeSet<String>, pruned: bool) {
+        let mut owned = if pruned { BTreeSet::new() } else { self.owned(target_key) };
+        owned.extend(desired.iter().cloned());
+
+        if owned.is_empty() {
+            self.targets.remove(target_key);
+        } else {
+            self.targets.insert(target_key.to_string(), owned);
+        }
+    }
+}
+
+/// Build the ledger key for a target file.
+///
+/// `section` distinguishes server maps nested inside a shared file, such as the
+/// per-project entries Claude Code keeps in `~/.claude.json`.
+///
+/// # Errors
+///
+/// Returns an error if the current directory cannot be determined for a
+/// relative target path.
+pub fn target_key(target_path: &Path, section: Option<&str>) -> Result<String> {
+    let absolute = std::path::absolute(target_path)
+        .with_context(|| format!("Failed to resolve {}", target_path.display()))?;
+    let path = absolute.to_string_lossy();
+    Ok(section.map_or_else(|| path.to_string(), |name| format!("{path}#{name}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn names(values: &[&str]) -> BTreeSet<String> {
+        values.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn stale_lists_only_owned_servers_missing_from_sources() {
+        let mut ledger = McpOwnershipLedger::default();
+        ledger.record("/target", &names(&["alpha", "beta"]), false);
+
+        assert_eq!(ledger.stale("/target", &names(&["alpha", "gamma"])), names(&["beta"]));
+        assert!(ledger.stale("/other", &names(&[])).is_empty());
+    }
+
+    #[test]
+    fn record_keeps_stale_entries_until_pruned() {
+        let mut ledger = McpOwnershipLedger::default();
+        ledger.record("/target", &names(&["alpha", "beta"]), false);
+
+        ledger.record("/target", &names(&["alpha"]), false);
+        assert_eq!(ledger.owned("/target"), names(&["alpha", "beta"]));
+
+        ledger.record("/target", &names(&["alpha"]), true);
+        assert_eq!(ledger.owned("/target"), names(&["alpha"]));
+    }
+
+    #[test]
+    fn save_and_load_round_trip_and_remove_empty_ledger() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let mut ledger = McpOwnershipLedger::default();
+        ledger.record("/target", &names(&["alpha"]), false);
+        ledger.save(temp_dir.path(), &UndoRecorder::disabled()).expect("save ledger");
+
+        let loaded = McpOwnershipLedger::load(temp_dir.path()).expect("load ledger");
+        assert_eq!(loaded, ledger);
+
+        ledger.record("/target", &names(&[]), true);
+        ledger
+            .save(temp_dir.path(), &UndoRecorder::disabled())
+            .expect("save empty ledger");
+        assert!(!McpOwnershipLedger::path(temp_dir.path()).exists());
+    }
+
+    #[test]
+    fn target_key_appends_section() {
+        let key = target_key(Path::new("/home/user/.claude.json"), Some("/work/project"))
+            .expect("target key");
+        assert_eq!(key, "/home/user/.claude.json#/work/project");
+    }
+}
@@ -0,0 +1,411 @@
+#![allow(missing_docs)]
+
+//! Catalog of MCP server presets for `claudius mcp add --preset`.
+//!
+//! Built-in presets are embedded in the binary. JSON files in the
+//! `mcp-presets/` directory of the config dir add presets or replace built-in
+//! ones with the same name; files are read in name order, so a later file wins.
+//! A catalog file looks like:
+//!
+//! ```json
+//! {
+//!   "presets": {
+//!     "notes": {
+//!       "version": 2,
+//!       "description": "Team notes server",
+//!       "parameters": [{ "name": "dir", "description": "Notes directory", "default": "." }],
+//!       "server": { "command": "notes-mcp", "args": ["--dir", "{{dir}}"] }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! `{{name}}` placeholders in the server's strings are replaced with the
+//! `--set` values or the parameter defaults. `${NAME}` references are left for
+//! sync-time interpolation. A rendered server records the preset's name and
+//! version under `x-claudius.preset`, so `config doctor` can point out servers
+//! whose preset has a newer version.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Directory in the config dir holding user catalog files.
+pub const CATALOG_DIR_NAME: &str = "mcp-presets";
+
+const BUILTIN_CATALOG: &str = include_str!("mcp_presets/catalog.json");
+
+static PLACEHOLDER_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
+    regex::Regex::new(r"\{\{([A-Za-z0-9_-]+)\}\}").expect("placeholder regex should compile")
+});
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogFile {
+    presets: BTreeMap<String, Preset>,
+}
+
+/// A parameterized server definition.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Preset {
+    /// Bumped whenever the definition changes.
+    pub version: u32,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Vec<PresetParameter>,
+    /// The `mcpServers.json` definition, with `{{name}}` placeholders.
+    pub server: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PresetParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Value used when `--set` does not give one; required when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// A preset and the catalog file it comes from.
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogEntry {
+    pub name: String,
+    /// The user catalog file, or `None` for a built-in preset.
+    pub source: Option<PathBuf>,
+    #[serde(flatten)]
+    pub preset: Preset,
+}
+
+/// The `x-claudius.preset` record of a server rendered from a preset.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetRecord {
+    pub name: String,
+    pub version: u32,
+}
+
+impl PresetRecord {
+    /// The record in a server definition, if it was rendered from a preset.
+    #[must_use]
+    pub fn of(definition: &Value) -> Option<Self> {
+        definition
+            .pointer("/x-claudius/preset")
+            .and_then(|record| serde_json::from_value(record.clone()).ok())
+    }
+}
+
+/// Built-in presets merged with the user catalog files.
+#[derive(Debug, Clone)]
+pub struct PresetCatalog {
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl PresetCatalog {
+    /// Load the built-in presets and the catalog files in `config_dir/mcp-presets`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a catalog file cannot be read or parsed, or a
+    /// preset's server uses a placeholder it does not declare.
+    pub fn load(config_dir: &Path) -> Result<Self> {
+        let (catalog, problems) = Self::load_skipping_invalid(config_dir)?;
+        problems.into_iter().next().map_or(Ok(catalog), Err)
+    }
+
+    /// Like [`Self::load`], but skip catalog files that cannot be loaded and
+    /// return why, in file order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error only if the built-in catalog is invalid.
+    pub fn load_skipping_invalid(config_dir: &Path) -> Result<(Self, Vec<anyhow::Error>)> {
+        let mut catalog = Self { entries: BTreeMap::new() };
+        catalog
+            .extend(BUILTIN_CATALOG, None)
+            .context("Built-in MCP preset catalog is invalid")?;
+
+        let catalog_dir = config_dir.join(CATALOG_DIR_NAME);
+        if !catalog_dir.is_dir() {
+            return Ok((catalog, Vec::new()));
+        }
+        let mut files = match fs::read_dir(&catalog_dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|found| found.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+                .collect::<Vec<_>>(),
+            Err(error) => {
+                let problem = anyhow::Error::new(error)
+                    .context(format!("Failed to read {}", catalog_dir.display()));
+                return Ok((catalog, vec![problem]));
+            },
+        };
+        files.sort();
+
+        let mut problems = Vec::new();
+        for path in files {
+            if let Err(problem) = catalog.extend_from_file(&path) {
+                problems.push(problem);
+            }
+        }
+        Ok((catalog, problems))
+    }
+
+    fn extend_from_file(&mut self, path: &Path) -> Result<()> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        // Parse into a scratch catalog first, so a bad file adds nothing.
+        let mut scratch = Self { entries: BTreeMap::new() };
+        scratch
+            .extend(&text, Some(path))
+            .with_context(|| format!("Invalid MCP preset catalog {}", path.display()))?;
+        self.entries.extend(scratch.entries);
+        Ok(())
+    }
+
+    fn extend(&mut self, text: &str, source: Option<&Path>) -> Result<()> {
+        let file: CatalogFile = serde_json::from_str(text)?;
+        for (name, preset) in file.presets {
+            if let Some(undeclared) = undeclared_placeholder(&preset) {
+                bail!("preset '{name}' uses undeclared parameter '{undeclared}'");
+            }
+            let entry =
+                CatalogEntry { name: name.clone(), source: source.map(Path::to_path_buf), preset };
+            self.entries.insert(name, entry);
+        }
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&CatalogEntry> {
+        self.entries.get(name)
+    }
+
+    /// Every preset, by name.
+    pub fn entries(&self) -> impl Iterator<Item = &CatalogEntry> {
+        self.entries.values()
+    }
+
+    /// Render preset `name`'s server with the `--set` values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the preset is unknown, a value names a parameter the
+    /// preset does not have, or a parameter without a default has no value.
+    pub fn render(&self, name: &str, values: &[(String, String)]) -> Result<Map<String, Value>> {
+        let entry = self.get(name).with_context(|| {
+            let known = self.entries.keys().map(String::as_str).collect::<Vec<_>>();
+            format!("Unknown MCP preset '{name}' (available: {})", known.join(", "))
+        })?;
+        let preset = &entry.preset;
+        let declared = preset.parameters.iter().map(|parameter| parameter.name.as_str());
+        if let Some((key, _)) =
+            values.iter().find(|(key, _)| !declared.clone().any(|known| known == key))
+        {
+            let names = declared.collect::<Vec<_>>();
+            if names.is_empty() {
+                bail!("Preset '{name}' takes no parameters, got '{key}'");
+            }
+            bail!("Preset '{name}' has no parameter '{key}' (parameters: {})", names.join(", "));
+        }
+
+        let mut resolved = BTreeMap::new();
+        let mut missing = Vec::new();
+        for parameter in &preset.parameters {
+            let given = values.iter().rev().find(|(key, _)| *key == parameter.name);
+            match given.map(|(_, value)| value).or(parameter.default.as_ref()) {
+                Some(value) => {
+                    resolved.insert(parameter.name.as_str(), value.as_str());
+                },
+                None => missing.push(format!("--set {}=...", parameter.name)),
+            }
+        }
+        if !missing.is_empty() {
+            bail!("Preset '{name}' needs {}", missing.join(" "));
+        }
+
+        let mut server = preset.server.clone();
+        for value in server.values_mut() {
+            fill_placeholders(value, &resolved);
+        }
+        Ok(server)
+    }
+}
+
+/// Record the preset a rendered server came from under `x-claudius.preset`.
+pub fn record_preset(definition: &mut Map<String, Value>, record: &PresetRecord) {
+    let entry = definition.entry("x-claudius").or_insert_with(|| json!({}));
+    if let Some(metadata) = entry.as_object_mut() {
+        metadata.insert("preset".to_string(), json!(record));
+    }
+}
+
+fn fill_placeholders(value: &mut Value, resolved: &BTreeMap<&str, &str>) {
+    match value {
+        Value::String(text) => {
+            let filled = PLACEHOLDER_RE.replace_all(text, |captures: &regex::Captures<'_>| {
+                captures
+                    .get(1)
+                    .and_then(|name| resolved.get(name.as_str()))
+                    .map_or_else(String::new, |found| (*found).to_string())
+            });
+            *text = filled.into_owned();
+        },
+        Value::Array(items) => items.iter_mut().for_each(|item| fill_placeholders(item, resolved)),
+        Value::Object(members) => {
+            members.values_mut().for_each(|member| fill_placeholders(member, resolved));
+        },
+        Value::Null | Value::Bool(_) | Value::Number(_) => {},
+    }
+}
+
+fn undeclared_placeholder(preset: &Preset) -> Option<String> {
+    let mut strings = Vec::new();
+    for value in preset.server.values() {
+        collect_strings(value, &mut strings);
+    }
+    strings
+        .iter()
+        .flat_map(|text| PLACEHOLDER_RE.captures_iter(text))
+        .filter_map(|captures| captures.get(1).map(|name| name.as_str().to_string()))
+        .find(|name| !preset.parameters.iter().any(|parameter| parameter.name == *name))
+}
+
+fn collect_strings<'a>(value: &'a Value, strings: &mut Vec<&'a str>) {
+    match value {
+        Value::String(text) => strings.push(text),
+        Value::Array(items) => items.iter().for_each(|item| collect_strings(item, strings)),
+        Value::Object(members) => {
+            members.values().for_each(|member| collect_strings(member, strings));
+        },
+        Value::Null | Value::Bool(_) | Value::Number(_) => {},
+    }
+}
+
+/// A server whose recorded preset version is older than the catalog's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutdatedServer {
+    pub server: String,
+    pub source: PathBuf,
+    pub preset: String,
+    pub recorded: u32,
+    pub current: u32,
+}
+
+/// Servers in `sources` rendered from an older version of their preset.
+///
+/// Missing or unparsable sources are skipped; `config validate` reports them.
+#[must_use]
+pub fn outdated_servers(catalog: &PresetCatalog, sources: &[PathBuf]) -> Vec<OutdatedServer> {
+    let mut outdated = Vec::new();
+    for source in sources {
+        let Some(document) = fs::read_to_string(source)
+            .ok()
+            .and_then(|text| serde_json::from_str::<Value>(&text).ok())
+        else {
+            continue;
+        };
+        let Some(servers) = document.get("mcpServers").and_then(Value::as_object) else {
+            continue;
+        };
+        outdated.extend(servers.iter().filter_map(|(server, definition)| {
+            let record = PresetRecord::of(definition)?;
+            let current = catalog.get(&record.name)?.preset.version;
+            (record.version < current).then(|| OutdatedServer {
+                server: server.clone(),
+                source: source.clone(),
+                preset: record.name,
+                recorded: record.version,
+                current,
+            })
+        }));
+    }
+    outdated
+}
+
+/// Text listing of the catalog for `claudius mcp presets`.
+#[must_use]
+pub fn render_catalog(catalog: &PresetCatalog) -> String {
+    let mut lines = vec!["MCP server presets:".to_string()];
+    for entry in catalog.entries() {
+        let origin = entry
+            .source
+            .as_ref()
+            .map_or_else(|| "built-in".to_string(), |path| path.display().to_string());
+        lines.push(format!(
+            "  {} (version {}, {origin}): {}",
+            entry.name, entry.preset.version, entry.preset.description
+        ));
+        for parameter in &entry.preset.parameters {
+            let default = parameter
+                .default
+                .as_ref()
+                .map_or_else(|| " (required)".to_string(), |value| format!(" (default: {value})"));
+            lines.push(format!(
+                "    --set {}=...  {}{default}",
+                parameter.name, parameter.description
+            ));
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn builtin() -> PresetCatalog {
+        let mut catalog = PresetCatalog { entries: BTreeMap::new() };
+        catalog.extend(BUILTIN_CATALOG, None).unwrap();
+        catalog
+    }
+
+    #[test]
+    fn builtin_presets_render_with_defaults() {
+        let catalog = builtin();
+        for name in ["fetch", "filesystem", "git", "github", "memory", "playwright"] {
+            assert!(catalog.render(name, &[]).is_ok(), "{name} should render");
+        }
+        let server = catalog.render("github", &[]).unwrap();
+        assert_eq!(
+            Value::Object(server).pointer("/headers/Authorization").cloned(),
+            Some(json!("Bearer $${GITHUB_TOKEN}"))
+        );
+    }
+
+    #[test]
+    fn render_checks_parameters() {
+        let catalog = builtin();
+        let set = |key: &str, value: &str| vec![(key.to_string(), value.to_string())];
+
+        let server = catalog.render("postgres", &set("url", "postgresql://localhost/app")).unwrap();
+        assert_eq!(
+            server.get("args"),
+            Some(&json!([
+                "-y",
+                "@modelcontextprotocol/server-postgres",
+                "postgresql://localhost/app"
+            ]))
+        );
+        let missing = catalog.render("postgres", &[]).unwrap_err().to_string();
+        assert!(missing.contains("needs --set url=..."), "{missing}");
+        let unknown = catalog.render("git", &set("repo", ".")).unwrap_err().to_string();
+        assert!(unknown.contains("no parameter 'repo' (parameters: repository)"), "{unknown}");
+        let preset = catalog.render("nope", &[]).unwrap_err().to_string();
+        assert!(preset.contains("available: fetch, filesystem"), "{preset}");
+    }
+
+    #[test]
+    fn catalogs_reject_undeclared_placeholders() {
+        let mut catalog = builtin();
+        let file = r#"{"presets": {"x": {"version": 1, "server": {"command": "{{bin}}"}}}}"#;
+        let error = catalog.extend(file, None).unwrap_err().to_string();
+        assert!(error.contains("undeclared parameter 'bin'"), "{error}");
+    }
+}
@@ -0,0 +1,603 @@
+#![allow(missing_docs)]
+
+//! Connect to MCP servers and run the MCP handshake for `claudius mcp check`.
+//!
+//! A stdio server is started the way an agent starts it: with its `env` and
+//! `cwd`, after `${NAME}` interpolation, and through `claudius secrets run`
+//! when its `env` references `op://` secrets, so the configured secret manager
+//! resolves them. A `url` server is reached over streamable HTTP, or over the
+//! legacy HTTP+SSE transport when its `type` is `sse`, with its configured
+//! `headers`. The probe sends `initialize`, the `notifications/initialized`
+//! notification, and `tools/list`, then stops the server or ends the session.
+//! The whole exchange shares one timeout; when it fails, the tail of a stdio
+//! server's stderr is reported alongside the error. A remote server answering
+//! HTTP 401 is reported as requiring authorization rather than as broken.
+
+mod http;
+mod stdio;
+
+use crate::app_config::Agent;
+use crate::config::{McpServerConfig, McpServersConfig};
+use crate::interpolation::{self, Interpolator};
+use crate::secret_launch;
+use crate::sync_operations::AgentContext;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// MCP protocol revision requested in `initialize`.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Lines of stderr kept in the report of a failed probe.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// `tools/list` pages followed before a cursor loop is reported as an error.
+const MAX_TOOL_PAGES: usize = 100;
+
+/// How an agent talks to a server, derived from its definition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpTransport {
+    Stdio,
+    Http,
+    Sse,
+}
+
+impl McpTransport {
+    /// `url` servers are streamable HTTP unless `type` says `sse`.
+    #[must_use]
+    pub fn of(server: &McpServerConfig) -> Self {
+        if server.url.is_none() {
+            Self::Stdio
+        } else if server.server_type.as_deref() == Some("sse") {
+            Self::Sse
+        } else {
+            Self::Http
+        }
+    }
+}
+
+impl std::fmt::Display for McpTransport {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(match self {
+            Self::Stdio => "stdio",
+            Self::Http => "http",
+            Self::Sse => "sse",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProbeStatus {
+    Ok,
+    Failed,
+    /// The server answered HTTP 401 and no credentials were configured.
+    AuthRequired,
+}
+
+/// `serverInfo` from the `initialize` result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ServerInfo {
+    pub name: String,
+    pub version: String,
+}
+
+/// Outcome of probing one server.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProbeReport {
+    pub server: String,
+    pub transport: McpTransport,
+    pub status: ProbeStatus,
+    pub server_info: Option<ServerInfo>,
+    pub protocol_version: Option<String>,
+    /// Capabilities the server advertised in `initialize`, such as `tools`.
+    pub capabilities: Vec<String>,
+    /// Tools from `tools/list`; `None` when the server has no tools capability.
+    pub tool_count: Option<usize>,
+    /// Round trip of the `initialize` request.
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+    /// Tail of the server's stderr, kept when the probe fails.
+    pub stderr: Option<String>,
+}
+
+impl ProbeReport {
+    #[must_use]
+    pub fn failed(server: &str, transport: McpTransport, error: impl std::fmt::Display) -> Self {
+        Self {
+            server: server.to_string(),
+            transport,
+            status: ProbeStatus::Failed,
+            server_info: None,
+            protocol_version: None,
+            capabilities: Vec::new(),
+            tool_count: None,
+            latency_ms: None,
+            error: Some(error.to_string()),
+            stderr: None,
+        }
+    }
+
+    fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(info) = &self.server_info {
+            parts.push(format!("{} {}", info.name, info.version).trim().to_string());
+        }
+        if let Some(version) = &self.protocol_version {
+            parts.push(format!("protocol {version}"));
+        }
+        parts.push(
+            self.tool_count.map_or_else(
+                || "no tools capability".to_string(),
+                |count| format!("{count} tool(s)"),
+            ),
+        );
+        if !self.capabilities.is_empty() {
+            parts.push(format!("capabilities [{}]", self.capabilities.join(", ")));
+        }
+        if let Some(latency) = self.latency_ms {
+            parts.push(format!("{latency} ms"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// What the probes share.
+#[derive(Debug, Clone, Copy)]
+pub struct ProbeOptions<'a> {
+    /// Resolves `${NAME}` references in the server definitions.
+    pub interpolator: &'a Interpolator,
+    /// Program that runs `secrets run` for servers with `op://` secrets.
+    pub launcher: &'a str,
+    /// Limit for each server's whole exchange.
+    pub timeout: Duration,
+}
+
+/// Probe the servers called `names` in parallel, in the order given.
+#[must_use]
+pub fn check_servers(
+    servers: &McpServersConfig,
+    names: &[String],
+    options: ProbeOptions<'_>,
+) -> Vec<ProbeReport> {
+    names
+        .par_iter()
+        .filter_map(|name| servers.mcp_servers.get(name).map(|server| (name, server)))
+        .map(|(name, server)| {
+            match launch_definition(name, server, options.interpolator, options.launcher) {
+                Ok(definition) => probe_server(name, &definition, options.timeout),
+                Err(error) => {
+                    ProbeReport::failed(name, McpTransport::of(server), format!("{error:#}"))
+                },
+            }
+        })
+        .collect()
+}
+
+/// The definition an agent launches for `server`: interpolated, and wrapped in
+/// `claudius secrets run` when its `env` references secrets.
+///
+/// # Errors
+///
+/// Returns an error if a value cannot be interpolated or the server's secrets
+/// cannot be passed through `claudius secrets run`.
+pub fn launch_definition(
+    name: &str,
+    server: &McpServerConfig,
+    interpolator: &Interpolator,
+    launcher: &str,
+) -> Result<McpServerConfig> {
+    let mut single =
+        McpServersConfig { mcp_servers: HashMap::from([(name.to_string(), server.clone())]) };
+    // The probe launches the server itself, so it resolves every reference
+    // like an agent that does not expand them; warnings are not shown.
+    interpolation::interpolate_mcp_servers(
+        &mut single,
+        interpolator,
+        AgentContext::new(Some(Agent::Codex), None),
+    )?;
+    secret_launch::wrap_secret_servers(&mut single, launcher)?;
+    single
+        .mcp_servers
+        .remove(name)
+        .context("server vanished while preparing its launch")
+}
+
+/// Probe `server`, giving the whole exchange `timeout`.
+#[must_use]
+pub fn probe_server(name: &str, server: &McpServerConfig, timeout: Duration) -> ProbeReport {
+    let transport = McpTransport::of(server);
+    let started = Instant::now();
+    let (outcome, stderr) = match transport {
+        McpTransport::Stdio => converse(stdio::StdioChannel::spawn(server), started, timeout),
+        McpTransport::Http => converse(http::StreamableHttp::new(server), started, timeout),
+        McpTransport::Sse => converse(http::LegacySse::connect(server, timeout), started, timeout),
+    };
+
+    match outcome {
+        Ok(handshake) => ProbeReport {
+            status: ProbeStatus::Ok,
+            server_info: handshake.server_info,
+            protocol_version: handshake.protocol_version,
+            capabilities: handshake.capabilities,
+            tool_count: handshake.tool_count,
+            latency_ms: Some(handshake.latency_ms),
+            error: None,
+            ..ProbeReport::failed(name, transport, "")
+        },
+        Err(error) if error.is::<Unauthorized>() => {
+            if sends_authorization(server) {
+                ProbeReport::failed(
+                    name,
+                    transport,
+                    format!("server rejected the configured credentials: {error}"),
+                )
+            } else {
+                ProbeReport {
+                    status: ProbeStatus::AuthRequired,
+                    ..ProbeReport::failed(name, transport, error)
+                }
+            }
+        },
+        Err(error) => ProbeReport {
+            stderr: stderr_tail(&stderr),
+            ..ProbeReport::failed(name, transport, format!("{error:#}"))
+        },
+    }
+}
+
+/// Text report of `mcp check`, one line per server and the totals.
+#[must_use]
+pub fn render_reports(reports: &[ProbeReport]) -> String {
+    let mut lines = vec!["MCP server check:".to_string()];
+    for report in reports {
+        let label = format!("{} ({})", report.server, report.transport);
+        let error = report.error.as_deref().unwrap_or_default();
+        match report.status {
+            ProbeStatus::Ok => lines.push(format!("  ok      {label}: {}", report.summary())),
+            ProbeStatus::AuthRequired => lines.push(format!("  auth    {label}: {error}")),
+            ProbeStatus::Failed => {
+                lines.push(format!("  FAILED  {label}: {error}"));
+                if let Some(stderr) = &report.stderr {
+                    lines.push("          stderr:".to_string());
+                    lines.extend(stderr.lines().map(|line| format!("            {line}")));
+                }
+            },
+        }
+    }
+
+    let count = |status| reports.iter().filter(|report| report.status == status).count();
+    lines.push(format!(
+        "{} ok, {} failed, {} require authorization",
+        count(ProbeStatus::Ok),
+        count(ProbeStatus::Failed),
+        count(ProbeStatus::AuthRequired)
+    ));
+    lines.join("\n")
+}
+
+/// The server answered HTTP 401.
+#[derive(Debug, thiserror::Error)]
+#[error("server requires authorization (HTTP 401{})", challenge_note(.challenge.as_deref()))]
+pub(crate) struct Unauthorized {
+    /// The `WWW-Authenticate` header, which names the OAuth metadata to use.
+    challenge: Option<String>,
+}
+
+fn challenge_note(challenge: Option<&str>) -> String {
+    challenge.map_or_else(String::new, |header| format!(", WWW-Authenticate: {header}"))
+}
+
+/// A transport gave up waiting because the exchange ran out of time.
+#[derive(Debug, thiserror::Error)]
+#[error("timed out")]
+pub(crate) struct TimedOut;
+
+/// What a transport delivered while the probe waited for a response.
+enum Received {
+    Message(Value),
+    TimedOut,
+    /// The server can send nothing more; the reason reads "… before answering".
+    Closed(String),
+}
+
+/// One way of exchanging JSON-RPC messages with a server.
+trait Channel {
+    fn send(&mut self, message: &Value, remaining: Duration) -> Result<()>;
+
+    fn receive(&mut self, remaining: Duration) -> Result<Received>;
+
+    /// Called once `initialize` settled the protocol revision.
+    fn negotiated(&mut self, _protocol_version: &str) {}
+
+    /// End the session and return everything the server wrote to stderr.
+    fn close(self) -> String;
+}
+
+/// Run the handshake over `channel`, then close it.
+fn converse<C: Channel>(
+    opened: Result<C>,
+    started: Instant,
+    timeout: Duration,
+) -> (Result<Handshake>, String) {
+    match opened {
+        Ok(channel) => {
+            let mut session = Session { channel, started, timeout, next_id: 1 };
+            let outcome = handshake(&mut session);
+            (outcome, session.channel.close())
+        },
+        Err(error) => (Err(error), String::new()),
+    }
+}
+
+/// JSON-RPC requests over a channel, sharing one deadline.
+struct Session<C> {
+    channel: C,
+    started: Instant,
+    timeout: Duration,
+    next_id: u64,
+}
+
+impl<C: Channel> Session<C> {
+    fn remaining(&self) -> Duration {
+        self.timeout.saturating_sub(self.started.elapsed())
+    }
+
+    fn notify(&mut self, method: &str) -> Result<()> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method }), method)
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id = self.next_id.saturating_add(1);
+        self.send(
+            &json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }),
+            method,
+        )?;
+
+        loop {
+            let received = self
+                .channel
+                .receive(self.remaining())
+                .map_err(|error| self.explain(error, method))?;
+            let message = match received {
+                Received::Message(message) => message,
+                Received::TimedOut => return Err(self.timed_out(method)),
+                Received::Closed(reason) => anyhow::bail!("{reason} before answering `{method}`"),
+            };
+            match classify(&message, id) {
+                Incoming::Response(Ok(result)) => return Ok(result),
+                Incoming::Response(Err(error)) => anyhow::bail!("`{method}` failed: {error}"),
+                Incoming::Request(reply) => self.send(&reply, method)?,
+                Incoming::Other => {},
+            }
+        }
+    }
+
+    fn send(&mut self, message: &Value, method: &str) -> Result<()> {
+        let remaining = self.remaining();
+        self.channel
+            .send(message, remaining)
+            .map_err(|error| self.explain(error, method))
+    }
+
+    fn explain(&self, error: anyhow::Error, method: &str) -> anyhow::Error {
+        if error.is::<TimedOut>() {
+            self.timed_out(method)
+        } else {
+            error
+        }
+    }
+
+    fn timed_out(&self, method: &str) -> anyhow::Error {
+        anyhow::anyhow!("timed out after {:?} waiting for the `{method}` response", self.timeout)
+    }
+}
+
+struct Handshake {
+    server_info: Option<ServerInfo>,
+    protocol_version: Option<String>,
+    capabilities: Vec<String>,
+    tool_count: Option<usize>,
+    latency_ms: u64,
+}
+
+fn handshake<C: Channel>(session: &mut Session<C>) -> Result<Handshake> {
+    let sent = Instant::now();
+    let initialized = session.request(
+        "initialize",
+        &json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": { "name": "claudius", "version": env!("CARGO_PKG_VERSION") },
+        }),
+    )?;
+    let latency_ms = u64::try_from(sent.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let protocol_version =
+        initialized.get("protocolVersion").and_then(Value::as_str).map(str::to_string);
+    if let Some(version) = &protocol_version {
+        session.channel.negotiated(version);
+    }
+    session.notify("notifications/initialized")?;
+
+    let server_info = initialized.get("serverInfo").map(|info| ServerInfo {
+        name: string_field(info, "name"),
+        version: string_field(info, "version"),
+    });
+    let capabilities = initialized
+        .get("capabilities")
+        .and_then(Value::as_object)
+        .map(|advertised| advertised.keys().cloned().collect())
+        .unwrap_or_default();
+    let tool_count = if initialized.pointer("/capabilities/tools").is_some() {
+        Some(count_tools(session)?)
+    } else {
+        None
+    };
+
+    Ok(Handshake { server_info, protocol_version, capabilities, tool_count, latency_ms })
+}
+
+fn count_tools<C: Channel>(session: &mut Session<C>) -> Result<usize> {
+    let mut count = 0_usize;
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_TOOL_PAGES {
+        let params = cursor.map_or_else(|| json!({}), |next| json!({ "cursor": next }));
+        let page = session.request("tools/list", &params)?;
+        let tools = page.get("tools").and_then(Value::as_array).map_or(0, Vec::len);
+        count = count.saturating_add(tools);
+        cursor = page.get("nextCursor").and_then(Value::as_str).map(str::to_string);
+        if cursor.is_none() {
+            return Ok(count);
+        }
+    }
+    anyhow::bail!("`tools/list` returned more than {MAX_TOOL_PAGES} pages")
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value.get(key).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+/// Whether the definition already sends credentials, so a 401 means they were refused.
+fn sends_authorization(server: &McpServerConfig) -> bool {
+    server.headers.keys().any(|name| name.eq_ignore_ascii_case("authorization"))
+}
+
+/// The last lines the server wrote to stderr, if any.
+fn stderr_tail(stderr: &str) -> Option<String> {
+    let skipped = stderr.lines().count().saturating_sub(STDERR_TAIL_LINES);
+    let tail = stderr.lines().skip(skipped).collect::<Vec<_>>().join("\n");
+    let trimmed = tail.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+/// A message read from the server while waiting for a response.
+#[derive(Debug, PartialEq)]
+enum Incoming {
+    /// The `result` of the awaited request, or the `error` it failed with.
+    Response(std::result::Result<Value, String>),
+    /// A request from the server, with the reply to send back.
+    Request(Value),
+    /// Notifications and responses to other requests.
+    Other,
+}
+
+fn classify(message: &Value, awaited_id: u64) -> Incoming {
+    match (message.get("id"), message.get("method").and_then(Value::as_str)) {
+        (Some(id), Some(method)) => Incoming::Request(reply_to(id, method)),
+        (Some(id), None) if id.as_u64() == Some(awaited_id) => {
+            Incoming::Response(message.get("error").map_or_else(
+                || Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                |error| Err(error_message(error)),
+            ))
+        },
+        _ => Incoming::Other,
+    }
+}
+
+/// Answer `ping` and refuse every other server request, so the server never waits on us.
+fn reply_to(id: &Value, method: &str) -> Value {
+    if method == "ping" {
+        json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+    } else {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32601, "message": format!("Method not found: {method}") },
+        })
+    }
+}
+
+fn error_message(error: &Value) -> String {
+    let message = error.get("message").and_then(Value::as_str).unwrap_or("unknown error");
+    error
+        .get("code")
+        .and_then(Value::as_i64)
+        .map_or_else(|| message.to_string(), |code| format!("{message} (code {code})"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_answers_server_requests_and_matches_responses() {
+        assert_eq!(
+            classify(&json!({"jsonrpc": "2.0", "id": 7, "result": {"tools": []}}), 7),
+            Incoming::Response(Ok(json!({"tools": []})))
+        );
+        assert_eq!(
+            classify(
+                &json!({"jsonrpc": "2.0", "id": 7, "error": {"code": -32602, "message": "bad"}}),
+                7
+            ),
+            Incoming::Response(Err("bad (code -32602)".to_string()))
+        );
+        assert_eq!(classify(&json!({"jsonrpc": "2.0", "id": 3, "result": {}}), 7), Incoming::Other);
+        assert_eq!(
+            classify(&json!({"jsonrpc": "2.0", "method": "notifications/message"}), 7),
+            Incoming::Other
+        );
+        assert_eq!(
+            classify(&json!({"jsonrpc": "2.0", "id": "s1", "method": "ping"}), 7),
+            Incoming::Request(json!({"jsonrpc": "2.0", "id": "s1", "result": {}}))
+        );
+        let roots = classify(&json!({"jsonrpc": "2.0", "id": 9, "method": "roots/list"}), 7);
+        assert!(matches!(
+            &roots,
+            Incoming::Request(reply) if reply.pointer("/error/code") == Some(&json!(-32601))
+        ));
+    }
+
+    #[test]
+    fn stderr_tail_keeps_the_last_lines() {
+        let stderr = (1..=25).map(|line| format!("line {line}")).collect::<Vec<_>>().join("\n");
+
+        let tail = stderr_tail(&stderr).unwrap_or_default();
+
+        assert!(tail.starts_with("line 6\n"));
+        assert!(tail.ends_with("line 25"));
+        assert_eq!(stderr_tail("  \n"), None);
+    }
+
+    #[test]
+    fn transport_follows_url_and_type() {
+        let stdio = McpServerConfig {
+            command: Some("server".to_string()),
+            args: Vec::new(),
+            env: HashMap::new(),
+            server_type: None,
+            url: None,
+            headers: HashMap::new(),
+            targeting: None,
+            extra: HashMap::new(),
+        };
+        let http =
+            McpServerConfig { url: Some("https://example.com/mcp".to_string()), ..stdio.clone() };
+        let sse = McpServerConfig { server_type: Some("sse".to_string()), ..http.clone() };
+
+        assert_eq!(McpTransport::of(&stdio), McpTransport::Stdio);
+        assert_eq!(McpTransport::of(&http), McpTransport::Http);
+        assert_eq!(McpTransport::of(&sse), McpTransport::Sse);
+    }
+
+    #[test]
+    fn unauthorized_quotes_the_challenge() {
+        let challenge = Unauthorized {
+            challenge: Some("Bearer resource_metadata=\"https://auth/.well-known\"".to_string()),
+        };
+
+        assert_eq!(
+            challenge.to_string(),
+            "server requires authorization (HTTP 401, WWW-Authenticate: Bearer resource_metadata=\"https://auth/.well-known\")"
+        );
+        assert_eq!(
+            Unauthorized { challenge: None }.to_string(),
+            "server requires authorization (HTTP 401)"
+        );
+    }
+}
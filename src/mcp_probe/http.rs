@@ -0,0 +1,394 @@
+//! Streamable HTTP and the legacy HTTP+SSE transport.
+
+use super::{Channel, Received, TimedOut, Unauthorized};
+use crate::config::McpServerConfig;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::Duration;
+use ureq::http::{Response, StatusCode};
+use ureq::{Agent, Body, RequestBuilder};
+
+/// Session a streamable HTTP server assigns in its `initialize` response.
+const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+/// Negotiated protocol revision, sent on every request after `initialize`.
+const PROTOCOL_HEADER: &str = "MCP-Protocol-Version";
+
+/// Limit for the request that ends a streamable HTTP session.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Characters of an error response body quoted in the report.
+const BODY_EXCERPT_CHARS: usize = 200;
+
+/// Streamable HTTP: each message is a POST to the server URL, and the answers
+/// come back in the POST response as JSON or as an event stream.
+pub(super) struct StreamableHttp {
+    agent: Agent,
+    url: String,
+    headers: HashMap<String, String>,
+    session_id: Option<String>,
+    protocol_version: Option<String>,
+    inbox: VecDeque<Value>,
+}
+
+impl StreamableHttp {
+    pub(super) fn new(server: &McpServerConfig) -> Result<Self> {
+        Ok(Self {
+            agent: agent(),
+            url: server.url.clone().context("server has no url")?,
+            headers: server.headers.clone(),
+            session_id: None,
+            protocol_version: None,
+            inbox: VecDeque::new(),
+        })
+    }
+}
+
+impl Channel for StreamableHttp {
+    fn send(&mut self, message: &Value, remaining: Duration) -> Result<()> {
+        let mut request = with_headers(self.agent.post(&self.url), &self.headers)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream");
+        if let Some(session_id) = &self.session_id {
+            request = request.header(SESSION_HEADER, session_id);
+        }
+        if let Some(version) = &self.protocol_version {
+            request = request.header(PROTOCOL_HEADER, version);
+        }
+        let sent = request
+            .config()
+            .timeout_global(Some(remaining))
+            .build()
+            .send(serde_json::to_string(message)?)
+            .map_err(|error| transport_error(error, &self.url))?;
+        let response = check_status(sent)?;
+
+        if let Some(session_id) = header(&response, SESSION_HEADER) {
+            self.session_id = Some(session_id);
+        }
+        if response.status() == StatusCode::ACCEPTED {
+            return Ok(());
+        }
+        let is_stream = header(&response, "Content-Type")
+            .is_some_and(|kind| kind.starts_with("text/event-stream"));
+        let mut body = response.into_body();
+        if is_stream {
+            return queue_stream(BufReader::new(body.into_reader()), &mut self.inbox, &self.url);
+        }
+
+        let text = body.read_to_string().map_err(|error| transport_error(error, &self.url))?;
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+        match parse_message(&text)? {
+            Value::Array(batch) => self.inbox.extend(batch),
+            single => self.inbox.push_back(single),
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, _remaining: Duration) -> Result<Received> {
+        Ok(self.inbox.pop_front().map_or_else(
+            || Received::Closed("the HTTP response ended".to_string()),
+            Received::Message,
+        ))
+    }
+
+    fn negotiated(&mut self, protocol_version: &str) {
+        self.protocol_version = Some(protocol_version.to_string());
+    }
+
+    /// End the server's session, if it started one; remote servers have no stderr.
+    fn close(self) -> String {
+        if let Some(session_id) = &self.session_id {
+            let _ = with_headers(self.agent.delete(&self.url), &self.headers)
+                .header(SESSION_HEADER, session_id)
+                .config()
+                .timeout_global(Some(CLOSE_TIMEOUT))
+                .build()
+                .call();
+        }
+        String::new()
+    }
+}
+
+/// Legacy HTTP+SSE: answers arrive on a long-lived GET event stream, and
+/// messages are sent as POST requests to the endpoint that stream announces first.
+pub(super) struct LegacySse {
+    agent: Agent,
+    endpoint: String,
+    headers: HashMap<String, String>,
+    events: Receiver<SseEvent>,
+}
+
+impl LegacySse {
+    pub(super) fn connect(server: &McpServerConfig, timeout: Duration) -> Result<Self> {
+        let url = server.url.as_deref().context("server has no url")?;
+        let agent = agent();
+        let response = with_headers(agent.get(url), &server.headers)
+            .header("Accept", "text/event-stream")
+            .config()
+            .timeout_global(Some(timeout))
+            .build()
+            .call()
+            .map_err(|error| transport_error(error, url))?;
+        let reader = BufReader::new(check_status(response)?.into_body().into_reader());
+
+        let (sender, events) = mpsc::channel();
+        std::thread::spawn(move || {
+            let mut parser = SseParser::default();
+            let mut parsed = reader
+                .lines()
+                .map_while(std::result::Result::ok)
+                .filter_map(|line| parser.line(&line));
+            while parsed.next().is_some_and(|event| sender.send(event).is_ok()) {}
+        });
+
+        let endpoint = match events.recv_timeout(timeout) {
+            Ok(event) if event.event == "endpoint" => resolve_endpoint(url, event.data.trim())?,
+            Ok(event) => anyhow::bail!(
+                "server sent a `{}` event before announcing its `endpoint`",
+                event.event
+            ),
+            Err(RecvTimeoutError::Timeout) => {
+                anyhow::bail!("timed out after {timeout:?} waiting for the `endpoint` event")
+            },
+            Err(RecvTimeoutError::Disconnected) => {
+                anyhow::bail!("server closed the event stream before announcing its `endpoint`")
+            },
+        };
+        Ok(Self { agent, endpoint, headers: server.headers.clone(), events })
+    }
+}
+
+impl Channel for LegacySse {
+    fn send(&mut self, message: &Value, remaining: Duration) -> Result<()> {
+        let response = with_headers(self.agent.post(&self.endpoint), &self.headers)
+            .header("Content-Type", "application/json")
+            .config()
+            .timeout_global(Some(remaining))
+            .build()
+            .send(serde_json::to_string(message)?)
+            .map_err(|error| transport_error(error, &self.endpoint))?;
+        check_status(response).map(drop)
+    }
+
+    fn receive(&mut self, remaining: Duration) -> Result<Received> {
+        loop {
+            match self.events.recv_timeout(remaining) {
+                Ok(event) if event.event == "message" => {
+                    return parse_message(&event.data).map(Received::Message);
+                },
+                Ok(_) => {},
+                Err(RecvTimeoutError::Timeout) => return Ok(Received::TimedOut),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Ok(Received::Closed("server closed the event stream".to_string()));
+                },
+            }
+        }
+    }
+
+    /// Dropping the receiver ends the reader thread with the stream.
+    fn close(self) -> String {
+        String::new()
+    }
+}
+
+fn agent() -> Agent {
+    Agent::config_builder().http_status_as_error(false).build().into()
+}
+
+fn with_headers<B>(
+    request: RequestBuilder<B>,
+    headers: &HashMap<String, String>,
+) -> RequestBuilder<B> {
+    headers
+        .iter()
+        .fold(request, |builder, (name, value)| builder.header(name.as_str(), value.as_str()))
+}
+
+fn header(response: &Response<Body>, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+fn transport_error(error: ureq::Error, url: &str) -> anyhow::Error {
+    match error {
+        ureq::Error::Timeout(_) => TimedOut.into(),
+        other => anyhow::Error::new(other).context(format!("Failed to reach {url}")),
+    }
+}
+
+/// Turn 401 into [`Unauthorized`] and any other non-success status into an error.
+fn check_status(mut response: Response<Body>) -> Result<Response<Body>> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(Unauthorized { challenge: header(&response, "WWW-Authenticate") }.into());
+    }
+    let body = response.body_mut().read_to_string().unwrap_or_default();
+    let excerpt = body.trim().chars().take(BODY_EXCERPT_CHARS).collect::<String>();
+    if excerpt.is_empty() {
+        anyhow::bail!("server returned HTTP {status}");
+    }
+    anyhow::bail!("server returned HTTP {status}: {excerpt}")
+}
+
+fn parse_message(text: &str) -> Result<Value> {
+    serde_json::from_str(text)
+        .with_context(|| format!("server sent a message that is not JSON-RPC: {text}"))
+}
+
+/// Queue the messages of a POST response stream, up to the first response.
+fn queue_stream(reader: impl BufRead, inbox: &mut VecDeque<Value>, url: &str) -> Result<()> {
+    let mut parser = SseParser::default();
+    for read in reader.lines() {
+        let line = read.map_err(|error| transport_error(error.into(), url))?;
+        let Some(event) = parser.line(&line) else {
+            continue;
+        };
+        if event.event != "message" || event.data.trim().is_empty() {
+            continue;
+        }
+        let message = parse_message(&event.data)?;
+        let is_response = message.get("method").is_none();
+        inbox.push_back(message);
+        if is_response {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// `endpoint` as announced by a legacy SSE server, made absolute against `base`.
+///
+/// Messages carry the configured headers, so an absolute endpoint on another
+/// origin is rejected rather than sent the server's credentials.
+fn resolve_endpoint(base: &str, endpoint: &str) -> Result<String> {
+    let (scheme, rest) = base.split_once("://").unwrap_or(("http", base));
+    let (authority, path) =
+        rest.find(['/', '?', '#']).map_or((rest, ""), |split| rest.split_at(split));
+    if endpoint.contains("://") {
+        let same_origin = endpoint.split_once("://").is_some_and(|(other_scheme, other)| {
+            let other_authority = other.split(['/', '?', '#']).next().unwrap_or_default();
+            other_scheme.eq_ignore_ascii_case(scheme)
+                && other_authority.eq_ignore_ascii_case(authority)
+        });
+        anyhow::ensure!(
+            same_origin,
+            "server announced an endpoint on another origin ({endpoint}); refusing to send it \
+             the configured headers"
+        );
+        return Ok(endpoint.to_string());
+    }
+    if endpoint.starts_with('/') {
+        return Ok(format!("{scheme}://{authority}{endpoint}"));
+    }
+    let base_path = path.split(['?', '#']).next().unwrap_or_default();
+    let directory = base_path.rfind('/').map_or("", |slash| base_path.split_at(slash).0);
+    Ok(format!("{scheme}://{authority}{directory}/{endpoint}"))
+}
+
+/// One server-sent event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+/// Assembles server-sent events from the lines of a `text/event-stream`.
+#[derive(Debug, Default)]
+struct SseParser {
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed one line; a blank line completes the pending event.
+    fn line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            if self.data.is_empty() {
+                self.event = None;
+                return None;
+            }
+            return Some(SseEvent {
+                event: self.event.take().unwrap_or_else(|| "message".to_string()),
+                data: std::mem::take(&mut self.data).join("\n"),
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, raw) = line.split_once(':').unwrap_or((line, ""));
+        let value = raw.strip_prefix(' ').unwrap_or(raw);
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            _ => {},
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(stream: &str) -> Vec<SseEvent> {
+        let mut parser = SseParser::default();
+        stream.split('\n').filter_map(|line| parser.line(line)).collect()
+    }
+
+    #[test]
+    fn sse_parser_assembles_events() {
+        let parsed = events(
+            ": keep-alive\nevent: endpoint\ndata: /messages?session=1\n\ndata: {\"a\":\ndata: 1}\n\n\n",
+        );
+
+        assert_eq!(
+            parsed,
+            vec![
+                SseEvent { event: "endpoint".to_string(), data: "/messages?session=1".to_string() },
+                SseEvent { event: "message".to_string(), data: "{\"a\":\n1}".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn resolve_endpoint_follows_the_base_url() {
+        let resolve = |base, endpoint| resolve_endpoint(base, endpoint).unwrap();
+        assert_eq!(
+            resolve("http://127.0.0.1:8080/sse", "/messages?session=1"),
+            "http://127.0.0.1:8080/messages?session=1"
+        );
+        assert_eq!(
+            resolve("https://example.com/mcp/sse?x=1", "messages"),
+            "https://example.com/mcp/messages"
+        );
+        assert_eq!(resolve("https://example.com", "messages"), "https://example.com/messages");
+        assert_eq!(
+            resolve("https://example.com/sse", "https://EXAMPLE.com/post"),
+            "https://EXAMPLE.com/post"
+        );
+    }
+
+    #[test]
+    fn resolve_endpoint_rejects_other_origins() {
+        for endpoint in [
+            "https://other.example.com/post",
+            "http://example.com/post",
+            "https://example.com:8443/post",
+        ] {
+            let error = resolve_endpoint("https://example.com/sse", endpoint).unwrap_err();
+            assert!(error.to_string().contains("another origin"), "{endpoint}");
+        }
+    }
+}
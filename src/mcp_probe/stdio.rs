@@ -0,0 +1,129 @@
+//! Newline-delimited JSON-RPC over a spawned server's stdin and stdout.
+
+use super::{Channel, Received};
+use crate::config::McpServerConfig;
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a killed server gets to close its stderr before the probe stops reading.
+const STDERR_DRAIN: Duration = Duration::from_millis(500);
+
+/// A running stdio server and the threads draining its output.
+pub(super) struct StdioChannel {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: Receiver<String>,
+    stderr: Arc<Mutex<Vec<u8>>>,
+    stderr_closed: Receiver<()>,
+}
+
+impl StdioChannel {
+    pub(super) fn spawn(server: &McpServerConfig) -> Result<Self> {
+        let program = server.command.as_deref().context("server has no command")?;
+        let mut command = Command::new(program);
+        command
+            .args(&server.args)
+            .envs(&server.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        if let Some(cwd) = server.extra.get("cwd").and_then(Value::as_str) {
+            command.current_dir(cwd);
+        }
+        let mut child = command.spawn().with_context(|| format!("Failed to start `{program}`"))?;
+
+        let stdin = child.stdin.take();
+        let stdout_pipe = child.stdout.take().context("server stdout is not captured")?;
+        let stderr_pipe = child.stderr.take().context("server stderr is not captured")?;
+
+        let (line_sender, stdout) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout_pipe).lines().map_while(std::result::Result::ok) {
+                if line_sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let stderr = Arc::new(Mutex::new(Vec::new()));
+        let (closed_sender, stderr_closed) = mpsc::channel();
+        let buffer = Arc::clone(&stderr);
+        std::thread::spawn(move || {
+            let mut pipe = stderr_pipe;
+            let mut chunk = [0_u8; 4096];
+            while let Ok(read) = pipe.read(&mut chunk) {
+                let Some(bytes) = chunk.get(..read).filter(|bytes| !bytes.is_empty()) else {
+                    break;
+                };
+                if let Ok(mut collected) = buffer.lock() {
+                    collected.extend_from_slice(bytes);
+                }
+            }
+            let _ = closed_sender.send(());
+        });
+
+        Ok(Self { child, stdin, stdout, stderr, stderr_closed })
+    }
+
+    /// ` (exit status: N)` once the server has exited, for errors about a closed pipe.
+    fn exit_note(&mut self) -> String {
+        let started = Instant::now();
+        while started.elapsed() < STDERR_DRAIN {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                return format!(" ({status})");
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        String::new()
+    }
+}
+
+impl Channel for StdioChannel {
+    fn send(&mut self, message: &Value, _remaining: Duration) -> Result<()> {
+        let stdin = self.stdin.as_mut().context("server stdin is closed")?;
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        let written = stdin.write_all(line.as_bytes()).and_then(|()| stdin.flush());
+        // A server that already exited is reported by `receive`, with its exit status.
+        if written.is_err() && !self.exit_note().is_empty() {
+            return Ok(());
+        }
+        written.context("Failed to write to the server's stdin")
+    }
+
+    fn receive(&mut self, remaining: Duration) -> Result<Received> {
+        loop {
+            let line = match self.stdout.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => return Ok(Received::TimedOut),
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Ok(Received::Closed(format!("server exited{}", self.exit_note())));
+                },
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            return serde_json::from_str(&line)
+                .map(Received::Message)
+                .with_context(|| format!("server wrote a non-JSON-RPC line to stdout: {line}"));
+        }
+    }
+
+    /// Stop the server and return everything it wrote to stderr.
+    fn close(mut self) -> String {
+        drop(self.stdin.take());
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        // A grandchild may keep stderr open; do not wait on it for long.
+        let _ = self.stderr_closed.recv_timeout(STDERR_DRAIN);
+        self.stderr
+            .lock()
+            .map(|collected| String::from_utf8_lossy(&collected).into_owned())
+            .unwrap_or_default()
+    }
+}
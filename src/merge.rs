@@ -71,22 +71,15 @@ where
 /// # Errors
 ///
 /// Returns an error if:
-/// - Standard output cannot be flushed
+/// - Prompting is disabled (`--no-input`) or stdin is not a terminal
 /// - Reading from standard input fails
 pub fn prompt_resolve_conflict(conflict: &MergeConflict) -> Result<bool> {
-    use std::io::{self, Write};
+    eprintln!("\n=== Configuration conflict detected ===");
+    eprintln!("  Field: {}", conflict.field_name);
+    eprintln!("  Current value: {}", conflict.existing_value);
+    eprintln!("  New value: {}", conflict.new_value);
 
-    println!("\n=== Configuration conflict detected ===");
-    println!("  Field: {}", conflict.field_name);
-    println!("  Current value: {}", conflict.existing_value);
-    println!("  New value: {}", conflict.new_value);
-    print!("\nOverwrite with new value? [y/N] ");
-    io::stdout().flush()?;
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-
-    Ok(input.trim().eq_ignore_ascii_case("y"))
+    crate::interaction::confirm(&format!("Overwrite {} with the new value?", conflict.field_name))
 }
 
 /// Merges MCP server configurations into Claude configuration.
@@ -189,6 +182,31 @@ fn has_transport_specific_conflict(
     }
 }
 
+/// Fields of `server` that belong to the other transport.
+///
+/// [`merge_configs`] drops them when it reconciles a server whose transport
+/// changed. `command`, `url`, and `type`, which decide the transport, are not
+/// listed.
+#[must_use]
+pub fn dropped_transport_fields(server: &crate::config::McpServerConfig) -> Vec<&'static str> {
+    let (fields, extra_fields): (Vec<(&'static str, bool)>, &[&'static str]) =
+        match transport_for_mcp_server(server) {
+            Some(McpTransport::Remote) => (
+                vec![("args", !server.args.is_empty()), ("env", !server.env.is_empty())],
+                MCP_JSON_STDIO_ONLY_EXTRA_FIELDS,
+            ),
+            Some(McpTransport::Stdio) => {
+                (vec![("headers", !server.headers.is_empty())], MCP_JSON_REMOTE_ONLY_EXTRA_FIELDS)
+            },
+            None => return Vec::new(),
+        };
+    fields
+        .into_iter()
+        .filter_map(|(field, present)| present.then_some(field))
+        .chain(extra_fields.iter().copied().filter(|key| server.extra.contains_key(*key)))
+        .collect()
+}
+
 fn sync_transport_specific_fields(
     existing: &mut crate::config::McpServerConfig,
     overlay: &crate::config::McpServerConfig,
@@ -461,6 +479,7 @@ mod tests {
             url: None,
             headers: HashMap::new(),
             extra: HashMap::new(),
+            targeting: None,
         }
     }
 
@@ -478,6 +497,7 @@ mod tests {
             url: Some(url.to_string()),
             headers,
             extra,
+            targeting: None,
         }
     }
 
@@ -595,6 +615,7 @@ mod tests {
                 ("cwd".to_string(), json!("/tmp")),
                 ("startupTimeoutSec".to_string(), json!(30)),
             ]),
+            targeting: None,
         };
 
         let overlay = create_remote_server_config(
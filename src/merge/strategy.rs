@@ -1,12 +1,19 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
 pub enum MergeStrategy {
     /// Replace all existing MCP servers with new ones
     Replace,
     /// Merge new servers, overwriting existing ones with same name
     Merge,
     /// Merge new servers, preserving existing ones with same name
+    #[serde(rename = "preserve-existing")]
+    #[value(name = "preserve-existing")]
     MergePreserveExisting,
     /// Merge with interactive conflict resolution
     #[default]
+    #[serde(rename = "interactive")]
+    #[value(name = "interactive")]
     InteractiveMerge,
 }
@@ -0,0 +1,167 @@
+#![allow(missing_docs)]
+
+//! Output format of the reporting commands.
+//!
+//! With `--format json`, `config validate`, `config doctor`, `config sync`,
+//! `config migrate`, `skills validate`, `skills sync`, `mcp check`, `mcp list`,
+//! `mcp show`, and `mcp presets` print a single JSON document on stdout instead
+//! of their text report. Every document starts with `schema_version` and
+//! `command`; fields may be added within a schema version but are never renamed
+//! or removed, so consumers should ignore unknown keys.
+//! Progress lines the text format prints alongside the report go to stderr.
+//! The validation commands also accept `--format sarif`; see [`crate::sarif`].
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Version of the JSON documents; bumped on incompatible changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// How a reporting command prints its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable report.
+    #[default]
+    Text,
+    /// One versioned JSON document on stdout.
+    Json,
+}
+
+/// How `config validate` and `skills validate` print their diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ValidationFormat {
+    /// Human-readable report.
+    #[default]
+    Text,
+    /// One versioned JSON document on stdout.
+    Json,
+    /// A SARIF 2.1.0 log on stdout, for code scanning.
+    Sarif,
+}
+
+impl From<ValidationFormat> for OutputFormat {
+    /// SARIF reserves stdout just like JSON does.
+    fn from(format: ValidationFormat) -> Self {
+        match format {
+            ValidationFormat::Text => Self::Text,
+            ValidationFormat::Json | ValidationFormat::Sarif => Self::Json,
+        }
+    }
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Select the output format for the rest of the process.
+pub fn set_format(format: OutputFormat) {
+    JSON.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// Whether stdout is reserved for a JSON document.
+#[must_use]
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Print a progress line: on stdout for text output, on stderr under `--format json`.
+pub fn progress(line: impl std::fmt::Display) {
+    if is_json() {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct JsonDocument<'a, T> {
+    schema_version: u32,
+    command: &'a str,
+    #[serde(flatten)]
+    report: &'a T,
+}
+
+/// Render `report` as the JSON document of `command`, e.g. `config validate`.
+///
+/// # Errors
+///
+/// Returns an error if the report cannot be serialized.
+pub fn render_json<T: Serialize>(command: &str, report: &T) -> Result<String> {
+    let document = JsonDocument { schema_version: SCHEMA_VERSION, command, report };
+    serde_json::to_string_pretty(&document).context("Failed to serialize JSON output")
+}
+
+/// Print `report` as the JSON document of `command` on stdout.
+///
+/// # Errors
+///
+/// Returns an error if the report cannot be serialized.
+pub fn print_json<T: Serialize>(command: &str, report: &T) -> Result<()> {
+    println!("{}", render_json(command, report)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doctor::{DoctorFinding, DoctorStatus};
+    use crate::validation::Diagnostic;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Diagnostics {
+        diagnostics: Vec<Diagnostic>,
+        findings: Vec<DoctorFinding>,
+    }
+
+    #[test]
+    fn json_documents_keep_the_versioned_schema() {
+        let report = Diagnostics {
+            diagnostics: vec![
+                Diagnostic::warning("Unknown key")
+                    .with_rule(crate::validation::DiagnosticRule::UnknownGeminiField)
+                    .with_path("/cfg/claude.settings.json"),
+                Diagnostic::info("No Gemini settings"),
+            ],
+            findings: vec![DoctorFinding {
+                status: DoctorStatus::BestEffort,
+                summary: "Claude Desktop target".to_string(),
+                path: None,
+                detail: Some("legacy".to_string()),
+                recommendation: "Use Claude Code".to_string(),
+                fix: None,
+            }],
+        };
+
+        let rendered: serde_json::Value =
+            serde_json::from_str(&render_json("config validate", &report).unwrap()).unwrap();
+        assert_eq!(
+            rendered,
+            json!({
+                "schema_version": 1,
+                "command": "config validate",
+                "diagnostics": [
+                    {
+                        "severity": "warning",
+                        "rule": "unknown-gemini-field",
+                        "message": "Unknown key",
+                        "path": "/cfg/claude.settings.json",
+                    },
+                    {
+                        "severity": "info",
+                        "rule": "general",
+                        "message": "No Gemini settings",
+                        "path": null,
+                    },
+                ],
+                "findings": [{
+                    "status": "best-effort",
+                    "summary": "Claude Desktop target",
+                    "path": null,
+                    "detail": "legacy",
+                    "recommendation": "Use Claude Code",
+                    "fix": null,
+                }],
+            })
+        );
+    }
+}
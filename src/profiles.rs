@@ -0,0 +1,236 @@
+#![allow(missing_docs)]
+
+//! Named profiles defined in `config.toml`.
+//!
+//! A profile selects a subset of `mcpServers.json` and overlays agent settings
+//! on top of the shared source files:
+//!
+//! ```toml
+//! [profiles.work]
+//! servers = ["github", "jira"]
+//!
+//! [profiles.work.codex-settings]
+//! model = "o3"
+//! ```
+//!
+//! `config sync --profile <name>` applies a profile and remembers it, so later
+//! syncs keep using it until another profile or `--no-profile` is given.
+
+use crate::app_config::{AppConfig, ProfileConfig};
+use crate::json_merge::merge_serialized;
+use crate::sync_operations::{AgentContext, ReadConfigResult};
+use crate::undo::UndoRecorder;
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ACTIVE_PROFILE_FILE_NAME: &str = ".claudius-active-profile";
+
+/// Path of the file that remembers the active profile.
+#[must_use]
+pub fn active_profile_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(ACTIVE_PROFILE_FILE_NAME)
+}
+
+/// Name of the profile remembered by the last `config sync --profile`.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_active_profile(config_dir: &Path) -> Result<Option<String>> {
+    let path = active_profile_path(config_dir);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let name = content.trim();
+    Ok((!name.is_empty()).then(|| name.to_string()))
+}
+
+/// Remember `name` as the active profile, or forget it when `None`.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written or removed.
+pub fn save_active_profile(
+    config_dir: &Path,
+    name: Option<&str>,
+    undo: &UndoRecorder,
+) -> Result<()> {
+    let path = active_profile_path(config_dir);
+    undo.record(&path);
+    match name {
+        Some(profile) => {
+            fs::create_dir_all(config_dir)
+                .with_context(|| format!("Failed to create {}", config_dir.display()))?;
+            fs::write(&path, format!("{profile}\n"))
+                .with_context(|| format!("Failed to write {}", path.display()))
+        },
+        None if path.exists() => {
+            fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))
+        },
+        None => Ok(()),
+    }
+}
+
+/// Look up a profile by name in `config.toml`.
+///
+/// # Errors
+///
+/// Returns an error naming the defined profiles when `name` is not one of them.
+pub fn find_profile<'a>(
+    app_config: Option<&'a AppConfig>,
+    name: &str,
+) -> Result<&'a ProfileConfig> {
+    let profiles = app_config.map(|config| &config.profiles);
+    if let Some(profile) = profiles.and_then(|defined| defined.get(name)) {
+        return Ok(profile);
+    }
+
+    let available = profiles
+        .map(|defined| defined.keys().map(String::as_str).collect::<Vec<_>>().join(", "))
+        .filter(|names| !names.is_empty())
+        .unwrap_or_else(|| "none".to_string());
+    anyhow::bail!(
+        "Profile '{name}' is not defined in config.toml (available: {available}); \
+         use --profile <NAME> to pick another or --no-profile to clear it"
+    )
+}
+
+/// Restrict MCP servers and overlay settings for the agent being synced.
+///
+/// # Errors
+///
+/// Returns an error if the profile names a server missing from `mcpServers.json`
+/// or an overlay does not fit the agent's settings format.
+pub fn apply_profile(
+    read_result: &mut ReadConfigResult,
+    profile: &ProfileConfig,
+    agent_context: AgentContext,
+) -> Result<()> {
+    if let Some(servers) = &profile.servers {
+        if let Some(unknown) = servers
+            .iter()
+            .find(|name| !read_result.mcp_servers.mcp_servers.contains_key(*name))
+        {
+            anyhow::bail!("Profile lists MCP server '{unknown}', which is not in mcpServers.json");
+        }
+        read_result.mcp_servers.mcp_servers.retain(|name, _| servers.contains(name));
+    }
+
+    if agent_context.is_codex {
+        if let Some(overlay) = &profile.codex_settings {
+            read_result.codex_settings =
+                Some(overlay_settings(read_result.codex_settings.as_ref(), overlay)?);
+        }
+        return Ok(());
+    }
+
+    let overlay = if agent_context.is_gemini {
+        profile.gemini_settings.as_ref()
+    } else if agent_context.is_claude_desktop {
+        None
+    } else {
+        profile.claude_settings.as_ref()
+    };
+    if let Some(table) = overlay {
+        read_result.settings = Some(overlay_settings(read_result.settings.as_ref(), table)?);
+    }
+
+    Ok(())
+}
+
+fn overlay_settings<T: Serialize + DeserializeOwned>(
+    base: Option<&T>,
+    overlay: &toml::Table,
+) -> Result<T> {
+    merge_serialized(base, overlay)
+        .context("Profile settings overlay does not match the settings format")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{McpServerConfig, McpServersConfig, Settings};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn server(command: &str) -> McpServerConfig {
+        McpServerConfig {
+            command: Some(command.to_string()),
+            args: Vec::new(),
+            env: HashMap::new(),
+            server_type: None,
+            url: None,
+            headers: HashMap::new(),
+            extra: HashMap::new(),
+            targeting: None,
+        }
+    }
+
+    fn read_result() -> ReadConfigResult {
+        ReadConfigResult {
+            mcp_servers: McpServersConfig {
+                mcp_servers: HashMap::from([
+                    ("github".to_string(), server("gh")),
+                    ("jira".to_string(), server("jira")),
+                ]),
+            },
+            settings: Some(
+                serde_json::from_value::<Settings>(serde_json::json!({
+                    "env": {"SHARED": "1"},
+                    "theme": "dark"
+                }))
+                .unwrap(),
+            ),
+            codex_settings: None,
+            stale_mcp_servers: std::collections::BTreeSet::new(),
+        }
+    }
+
+    #[test]
+    fn apply_profile_filters_servers_and_overlays_claude_settings() {
+        let profile: ProfileConfig =
+            toml::from_str("servers = [\"github\"]\n[claude-settings.env]\nTEAM = \"oss\"\n")
+                .unwrap();
+        let mut result = read_result();
+
+        apply_profile(
+            &mut result,
+            &profile,
+            AgentContext::new(Some(crate::app_config::Agent::ClaudeCode), None),
+        )
+        .unwrap();
+
+        assert_eq!(result.mcp_servers.mcp_servers.keys().collect::<Vec<_>>(), ["github"]);
+        let settings = serde_json::to_value(result.settings.unwrap()).unwrap();
+        assert_eq!(settings.pointer("/env/SHARED").unwrap(), "1");
+        assert_eq!(settings.pointer("/env/TEAM").unwrap(), "oss");
+        assert_eq!(settings.pointer("/theme").unwrap(), "dark");
+    }
+
+    #[test]
+    fn apply_profile_rejects_unknown_servers() {
+        let profile = ProfileConfig { servers: Some(vec!["missing".into()]), ..Default::default() };
+
+        let error =
+            apply_profile(&mut read_result(), &profile, AgentContext::new(None, None)).unwrap_err();
+
+        assert!(error.to_string().contains("'missing'"));
+    }
+
+    #[test]
+    fn active_profile_round_trips_and_clears() {
+        let config_dir = TempDir::new().unwrap();
+
+        save_active_profile(config_dir.path(), Some("work"), &UndoRecorder::disabled()).unwrap();
+        assert_eq!(load_active_profile(config_dir.path()).unwrap().as_deref(), Some("work"));
+
+        save_active_profile(config_dir.path(), None, &UndoRecorder::disabled()).unwrap();
+        assert_eq!(load_active_profile(config_dir.path()).unwrap(), None);
+    }
+}
@@ -0,0 +1,291 @@
+#![allow(missing_docs)]
+
+//! Batch project-local syncs over the `[[projects]]` registry in `config.toml`.
+//!
+//! ```toml
+//! [[projects]]
+//! path = "~/src/api"
+//! agents = ["claude-code", "codex"]
+//! scope = "local"
+//! ```
+//!
+//! Relative paths are resolved against the directory holding `config.toml`,
+//! not the directory the command runs in.
+//!
+//! `config sync --all-projects` runs one `claudius config sync` child process
+//! per project and agent, started in the project directory, so project
+//! discovery, the `.claudius/` layer, and `CLAUDIUS_PROJECT_ROOT` behave
+//! exactly as they do when the sync is run by hand.
+//!
+//! Dry runs only read and run in parallel. Writing syncs run one at a time,
+//! because the project files are not the only ones they write:
+//!
+//! - every sync journals its commit in the same `.claudius-sync-journal.json`
+//!   in the config directory, and a concurrent sync would overwrite the
+//!   recovery record of another one still committing;
+//! - the MCP ownership ledger in the config directory is read, updated, and
+//!   written back by each sync, so concurrent updates would drop entries;
+//! - Claude Code `local` scope syncs patch the per-project entries of the
+//!   shared `~/.claude.json`.
+//!
+//! Running them in parallel would need a lock around all three; the child
+//! processes do not take one, so the batch serializes them instead.
+
+use crate::app_config::{Agent, AppConfig, ClaudeCodeScope, ProjectConfig};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// One project-local sync to run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectSync {
+    /// Path as written in `config.toml`, for reports.
+    pub path: String,
+    pub directory: PathBuf,
+    /// Agent to sync; the default agent when `None`.
+    pub agent: Option<Agent>,
+    pub scope: Option<ClaudeCodeScope>,
+}
+
+impl ProjectSync {
+    /// Report label such as `~/src/api (Claude Code)`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        let agent = self.agent.map_or("default agent", Agent::display_name);
+        format!("{} ({agent})", self.path)
+    }
+
+    /// `--agent` and `--scope` arguments for the child sync.
+    fn target_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(name) = self.agent.as_ref().and_then(value_name) {
+            args.extend(["--agent".to_string(), name]);
+        }
+        if let Some(name) = self.scope.as_ref().and_then(value_name) {
+            args.extend(["--scope".to_string(), name]);
+        }
+        args
+    }
+}
+
+fn value_name(value: &impl ValueEnum) -> Option<String> {
+    value.to_possible_value().map(|possible| possible.get_name().to_string())
+}
+
+/// Result of one project-local sync.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectSyncOutcome {
+    pub sync: ProjectSync,
+    pub success: bool,
+    /// Combined stdout and stderr of the child sync.
+    pub output: String,
+}
+
+impl ProjectSyncOutcome {
+    /// Last non-empty output line, which carries the error of a failed sync.
+    #[must_use]
+    pub fn reason(&self) -> &str {
+        self.output
+            .lines()
+            .rev()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("")
+    }
+}
+
+/// Directory that relative `[[projects]]` paths are resolved against: the one
+/// holding `config.toml`.
+///
+/// # Errors
+///
+/// Returns an error if the config directory cannot be determined.
+pub fn registry_base_dir() -> Result<PathBuf> {
+    let config_path = AppConfig::config_path()?;
+    config_path
+        .parent()
+        .map(Path::to_path_buf)
+        .with_context(|| format!("{} has no parent directory", config_path.display()))
+}
+
+/// Expand a leading `~/` in a registered project path and resolve a relative
+/// path against `base_dir`.
+#[must_use]
+pub fn expand_project_path(path: &str, base_dir: &Path) -> PathBuf {
+    let home = || std::env::var_os("HOME").map(PathBuf::from);
+    let expanded = if path == "~" {
+        home().unwrap_or_else(|| PathBuf::from(path))
+    } else {
+        path.strip_prefix("~/")
+            .and_then(|relative| home().map(|dir| dir.join(relative)))
+            .unwrap_or_else(|| PathBuf::from(path))
+    };
+    base_dir.join(expanded)
+}
+
+/// Syncs for every registered project, one per agent.
+///
+/// `agent` and `scope` from the command line override each project's own
+/// choices. The scope is only passed to Claude Code syncs and syncs of the
+/// default agent. Relative paths are resolved against `base_dir`.
+#[must_use]
+pub fn plan_project_syncs(
+    projects: &[ProjectConfig],
+    base_dir: &Path,
+    agent: Option<Agent>,
+    scope: Option<ClaudeCodeScope>,
+) -> Vec<ProjectSync> {
+    projects
+        .iter()
+        .flat_map(|project| {
+            let agents = match agent {
+                Some(selected) => vec![Some(selected)],
+                None if project.agents.is_empty() => vec![None],
+                None => project.agents.iter().copied().map(Some).collect(),
+            };
+            agents.into_iter().map(|project_agent| ProjectSync {
+                path: project.path.clone(),
+                directory: expand_project_path(&project.path, base_dir),
+                agent: project_agent,
+                scope: scope
+                    .or(project.scope)
+                    .filter(|_| project_agent.is_none_or(|name| name == Agent::ClaudeCode)),
+            })
+        })
+        .collect()
+}
+
+/// Run `program` with `args` plus each sync's `--agent`/`--scope` in its project directory.
+///
+/// Outcomes keep the order of `syncs`. A missing directory or a child that
+/// cannot be started is reported as a failed outcome.
+#[must_use]
+pub fn run_project_syncs(
+    program: &Path,
+    args: &[String],
+    syncs: Vec<ProjectSync>,
+    parallel: bool,
+) -> Vec<ProjectSyncOutcome> {
+    if parallel {
+        syncs
+            .into_par_iter()
+            .map(|sync| run_project_sync(program, args, sync))
+            .collect()
+    } else {
+        syncs.into_iter().map(|sync| run_project_sync(program, args, sync)).collect()
+    }
+}
+
+fn run_project_sync(program: &Path, args: &[String], sync: ProjectSync) -> ProjectSyncOutcome {
+    if !sync.directory.is_dir() {
+        let output = format!("Project directory not found: {}", sync.directory.display());
+        return ProjectSyncOutcome { sync, success: false, output };
+    }
+
+    let result = Command::new(program)
+        .current_dir(&sync.directory)
+        .args(args)
+        .args(sync.target_args())
+        .env_remove("TARGET_CONFIG_PATH")
+        .stdin(std::process::Stdio::null())
+        .output();
+    match result {
+        Ok(finished) => {
+            let mut output = String::from_utf8_lossy(&finished.stdout).into_owned();
+            output.push_str(&String::from_utf8_lossy(&finished.stderr));
+            ProjectSyncOutcome { sync, success: finished.status.success(), output }
+        },
+        Err(error) => ProjectSyncOutcome {
+            sync,
+            success: false,
+            output: format!("Failed to start {}: {error}", program.display()),
+        },
+    }
+}
+
+/// Consolidated report listing every sync and the totals.
+#[must_use]
+pub fn summarize_outcomes(outcomes: &[ProjectSyncOutcome]) -> String {
+    let mut lines = vec!["Project sync summary:".to_string()];
+    for outcome in outcomes {
+        if outcome.success {
+            lines.push(format!("  ok      {}", outcome.sync.label()));
+        } else {
+            lines.push(format!("  FAILED  {}: {}", outcome.sync.label(), outcome.reason()));
+        }
+    }
+    let failed = outcomes.iter().filter(|outcome| !outcome.success).count();
+    lines.push(format!("{} succeeded, {failed} failed", outcomes.len().saturating_sub(failed)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project(path: &str, agents: Vec<Agent>, scope: Option<ClaudeCodeScope>) -> ProjectConfig {
+        ProjectConfig { path: path.to_string(), agents, scope }
+    }
+
+    #[test]
+    fn plan_project_syncs_expands_agents_and_applies_overrides() {
+        let projects = [
+            project(
+                "/work/api",
+                vec![Agent::ClaudeCode, Agent::Codex],
+                Some(ClaudeCodeScope::Local),
+            ),
+            project("/work/web", Vec::new(), None),
+        ];
+
+        let planned = plan_project_syncs(&projects, Path::new("/config"), None, None);
+        let labels = planned.iter().map(ProjectSync::label).collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            ["/work/api (Claude Code)", "/work/api (Codex)", "/work/web (default agent)"]
+        );
+        assert_eq!(
+            planned.first().map(ProjectSync::target_args),
+            Some(vec!["--agent".into(), "claude-code".into(), "--scope".into(), "local".into()])
+        );
+        assert_eq!(planned.get(1).and_then(|sync| sync.scope), None);
+
+        let overridden =
+            plan_project_syncs(&projects, Path::new("/config"), Some(Agent::Gemini), None);
+        assert_eq!(overridden.len(), 2);
+        assert!(overridden.iter().all(|sync| sync.agent == Some(Agent::Gemini)));
+    }
+
+    #[test]
+    fn relative_project_paths_resolve_against_the_config_directory() {
+        let base_dir = Path::new("/home/user/.config/claudius");
+
+        assert_eq!(expand_project_path("../../src/api", base_dir), base_dir.join("../../src/api"));
+        assert_eq!(expand_project_path("/work/api", base_dir), Path::new("/work/api"));
+    }
+
+    #[test]
+    fn summarize_outcomes_reports_failure_reasons_and_totals() {
+        let sync = |path: &str| ProjectSync {
+            path: path.to_string(),
+            directory: PathBuf::from(path),
+            agent: Some(Agent::Codex),
+            scope: None,
+        };
+        let outcomes = [
+            ProjectSyncOutcome { sync: sync("/work/api"), success: true, output: "done\n".into() },
+            ProjectSyncOutcome {
+                sync: sync("/work/web"),
+                success: false,
+                output: "Syncing\nError: merge conflict on 'github'\n\n".into(),
+            },
+        ];
+
+        assert_eq!(
+            summarize_outcomes(&outcomes),
+            "Project sync summary:\n  ok      /work/api (Codex)\n  FAILED  /work/web (Codex): Error: merge conflict on 'github'\n1 succeeded, 1 failed"
+        );
+    }
+}
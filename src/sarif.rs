@@ -0,0 +1,302 @@
+#![allow(missing_docs)]
+
+//! SARIF 2.1.0 logs of validation diagnostics, for code scanning.
+//!
+//! `config validate --format sarif` and `skills validate --format sarif` print
+//! one run whose driver lists every [`DiagnosticRule`], so rule IDs stay
+//! stable between releases. Paths below the source root (the working
+//! directory) become URIs relative to `%SRCROOT%`, which code scanning
+//! resolves against the repository checkout; other paths are `file://` URIs.
+
+use crate::validation::{Diagnostic, DiagnosticRule, DiagnosticSeverity};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Component, Path};
+
+/// SARIF specification version of the emitted logs.
+pub const SARIF_VERSION: &str = "2.1.0";
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SOURCE_ROOT_ID: &str = "%SRCROOT%";
+
+#[derive(Debug, Serialize)]
+struct Log {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run; 1],
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    original_uri_base_ids: BTreeMap<&'static str, ArtifactLocation>,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    level: &'static str,
+    message: Message,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    locations: Vec<Location>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    uri_base_id: Option<&'static str>,
+}
+
+/// SARIF `level` of a diagnostic severity.
+#[must_use]
+pub const fn level(severity: DiagnosticSeverity) -> &'static str {
+    match severity {
+        DiagnosticSeverity::Info => "note",
+        DiagnosticSeverity::Warning => "warning",
+        DiagnosticSeverity::Error => "error",
+    }
+}
+
+/// Render `diagnostics` as a SARIF log, with locations relative to `source_root`.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be serialized.
+pub fn render(diagnostics: &[Diagnostic], source_root: Option<&Path>) -> Result<String> {
+    let rules = DiagnosticRule::ALL
+        .iter()
+        .map(|rule| Rule {
+            id: rule.id(),
+            short_description: Message { text: rule.description().to_string() },
+        })
+        .collect();
+    let results = diagnostics
+        .iter()
+        .map(|diagnostic| SarifResult {
+            rule_id: diagnostic.rule.id(),
+            level: level(diagnostic.severity),
+            message: Message { text: diagnostic.message.clone() },
+            locations: diagnostic
+                .path
+                .iter()
+                .map(|path| Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: artifact_location(path, source_root),
+                    },
+                })
+                .collect(),
+        })
+        .collect();
+    let original_uri_base_ids = source_root
+        .map(|root| {
+            (SOURCE_ROOT_ID, ArtifactLocation { uri: directory_uri(root), uri_base_id: None })
+        })
+        .into_iter()
+        .collect();
+
+    let log = Log {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: [Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "claudius",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules,
+                },
+            },
+            original_uri_base_ids,
+            results,
+        }],
+    };
+    serde_json::to_string_pretty(&log).context("Failed to serialize SARIF output")
+}
+
+/// Print `diagnostics` as a SARIF log on stdout, relative to the working directory.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be serialized.
+pub fn print(diagnostics: &[Diagnostic]) -> Result<()> {
+    let source_root = std::env::current_dir().ok();
+    println!("{}", render(diagnostics, source_root.as_deref())?);
+    Ok(())
+}
+
+/// The working directory is already resolved, so resolve symlinks in `path`
+/// too before looking for it below the source root.
+fn artifact_location(path: &Path, source_root: Option<&Path>) -> ArtifactLocation {
+    let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    match source_root.and_then(|root| resolved.strip_prefix(root).ok()) {
+        Some(relative) if resolved.is_absolute() => {
+            ArtifactLocation { uri: encode_path(relative), uri_base_id: Some(SOURCE_ROOT_ID) }
+        },
+        _ => ArtifactLocation { uri: file_uri(path), uri_base_id: None },
+    }
+}
+
+fn file_uri(path: &Path) -> String {
+    if path.is_absolute() {
+        format!("file:///{}", encode_path(path))
+    } else {
+        encode_path(path)
+    }
+}
+
+/// Base URIs must end with a slash to resolve relative references below them.
+fn directory_uri(path: &Path) -> String {
+    let uri = file_uri(path);
+    if uri.ends_with('/') {
+        uri
+    } else {
+        format!("{uri}/")
+    }
+}
+
+/// Percent-encode each normal component and join them with `/`.
+fn encode_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(percent_encode(&part.to_string_lossy())),
+            Component::ParentDir => Some("..".to_string()),
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn percent_encode(segment: &str) -> String {
+    use std::fmt::Write as _;
+
+    segment.bytes().fold(String::with_capacity(segment.len()), |mut encoded, byte| {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            let _ = write!(encoded, "%{byte:02X}");
+        }
+        encoded
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn rendered(diagnostics: &[Diagnostic], source_root: Option<&Path>) -> Value {
+        serde_json::from_str(&render(diagnostics, source_root).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn results_carry_rule_level_message_and_relative_location() {
+        let diagnostics = [
+            Diagnostic::warning("includeCoAuthoredBy is deprecated")
+                .with_rule(DiagnosticRule::DeprecatedKey)
+                .with_path("/repo/claudius/claude settings.json"),
+            Diagnostic::info("launches through secrets run")
+                .with_rule(DiagnosticRule::SecretReference),
+            Diagnostic::error("undefined variable").with_path("/elsewhere/mcpServers.json"),
+        ];
+
+        let log = rendered(&diagnostics, Some(Path::new("/repo")));
+
+        assert_eq!(log.pointer("/version"), Some(&json!("2.1.0")));
+        assert_eq!(
+            log.pointer("/runs/0/originalUriBaseIds/%SRCROOT%/uri"),
+            Some(&json!("file:///repo/"))
+        );
+        assert_eq!(
+            log.pointer("/runs/0/results"),
+            Some(&json!([
+                {
+                    "ruleId": "deprecated-key",
+                    "level": "warning",
+                    "message": {"text": "includeCoAuthoredBy is deprecated"},
+                    "locations": [{"physicalLocation": {"artifactLocation": {
+                        "uri": "claudius/claude%20settings.json",
+                        "uriBaseId": "%SRCROOT%",
+                    }}}],
+                },
+                {
+                    "ruleId": "secret-reference",
+                    "level": "note",
+                    "message": {"text": "launches through secrets run"},
+                },
+                {
+                    "ruleId": "general",
+                    "level": "error",
+                    "message": {"text": "undefined variable"},
+                    "locations": [{"physicalLocation": {"artifactLocation": {
+                        "uri": "file:///elsewhere/mcpServers.json",
+                    }}}],
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn driver_lists_every_rule_once() {
+        let log = rendered(&[], None);
+
+        let ids = log
+            .pointer("/runs/0/tool/driver/rules")
+            .and_then(Value::as_array)
+            .map_or_else(Vec::new, |rules| {
+                rules.iter().filter_map(|rule| rule.get("id")?.as_str()).collect()
+            });
+        let expected = DiagnosticRule::ALL.iter().map(|rule| rule.id()).collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+        assert_eq!(
+            ids.iter().collect::<std::collections::BTreeSet<_>>().len(),
+            DiagnosticRule::ALL.len()
+        );
+        assert!(log.pointer("/runs/0/originalUriBaseIds").is_none());
+    }
+}
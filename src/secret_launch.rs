@@ -0,0 +1,350 @@
+#![allow(missing_docs)]
+
+//! Launch MCP servers with `op://` secrets through `claudius secrets run`.
+//!
+//! A stdio server whose `env` references `op://...` is rewritten so the agent
+//! starts `claudius secrets run -- <command> <args>` instead. Each secret entry
+//! is passed as `CLAUDIUS_SECRET_<NAME>`, which `secrets run` resolves with the
+//! configured secret manager and hands to the server as `<NAME>`. Deployed
+//! agent files therefore hold secret references, never resolved values.
+//!
+//! Servers that already start through `claudius secrets run` keep their
+//! command; only their secret `env` names are prefixed. Remote servers cannot
+//! be wrapped, so secret references in their `env` or `headers` are rejected.
+
+use crate::app_config::{Agent, AppConfig};
+use crate::config::{McpServerConfig, McpServersConfig};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::Path;
+
+/// Prefix that `claudius secrets run` resolves and strips.
+pub const SECRET_ENV_PREFIX: &str = "CLAUDIUS_SECRET_";
+
+/// Launcher used when `config.toml` does not configure one.
+const DEFAULT_LAUNCHER: &str = "claudius";
+
+/// Marker of a 1Password secret reference (`op://` or `{{op://...}}`).
+const SECRET_REFERENCE_MARKER: &str = "op://";
+
+/// A server rewritten to launch through `claudius secrets run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretLaunch {
+    pub server: String,
+    /// `env` names resolved at launch, without the `CLAUDIUS_SECRET_` prefix.
+    pub secrets: Vec<String>,
+}
+
+#[must_use]
+pub fn is_secret_reference(value: &str) -> bool {
+    value.contains(SECRET_REFERENCE_MARKER)
+}
+
+/// Whether the server's `env` or `headers` reference a secret.
+#[must_use]
+pub fn references_secrets(server: &McpServerConfig) -> bool {
+    server
+        .env
+        .values()
+        .chain(server.headers.values())
+        .any(|value| is_secret_reference(value))
+}
+
+/// Program the agent starts instead of the server.
+///
+/// `launcher` under `[secret-manager]` in `config.toml`, or plain `claudius`
+/// from `PATH`. The running binary's path is never used: it changes with every
+/// upgrade of a versioned install, which would break the deployed servers.
+#[must_use]
+pub fn launcher_command(app_config: Option<&AppConfig>) -> String {
+    app_config
+        .and_then(|config| config.secret_manager.as_ref())
+        .and_then(|secret_manager| secret_manager.launcher.clone())
+        .unwrap_or_else(|| DEFAULT_LAUNCHER.to_string())
+}
+
+/// Whether `server` already starts through `claudius secrets run`.
+///
+/// The command is `launcher` or any `claudius` binary, and the arguments begin
+/// with `secrets run --`.
+#[must_use]
+pub fn is_wrapped(server: &McpServerConfig, launcher: &str) -> bool {
+    let launches_claudius = server.command.as_deref().is_some_and(|command| {
+        command == launcher
+            || Path::new(command).file_stem().and_then(OsStr::to_str) == Some("claudius")
+    });
+    launches_claudius && server.args.iter().take(3).eq(["secrets", "run", "--"].iter())
+}
+
+/// Why an agent may fail to start the wrapped servers named in `wrapped`.
+///
+/// `agent` is the target being validated; `None` stands for every agent.
+/// A bare `launcher` must be on `path_env`, and Claude Desktop, which starts
+/// servers without the login shell's `PATH`, needs an absolute launcher and
+/// absolute server commands. `path_env` of `None` skips the `PATH` lookup.
+#[must_use]
+pub fn launcher_problems(
+    agent: Option<Agent>,
+    launcher: &str,
+    wrapped: &[(&str, &McpServerConfig)],
+    path_env: Option<&OsStr>,
+) -> Vec<String> {
+    if wrapped.is_empty() {
+        return Vec::new();
+    }
+    let names = wrapped
+        .iter()
+        .map(|(name, _)| format!("mcpServers.{name}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut problems = Vec::new();
+
+    if !is_bare_command(launcher) {
+        if !Path::new(launcher).is_file() {
+            problems.push(format!(
+                "{names} launch through '{launcher}', which does not exist; fix `launcher` \
+                 under [secret-manager] in config.toml"
+            ));
+        }
+    } else if agent == Some(Agent::Claude) {
+        problems.push(format!(
+            "{names}: Claude Desktop starts servers without your shell's PATH, so it may not \
+             find '{launcher}'; set `launcher` under [secret-manager] in config.toml to its \
+             absolute path"
+        ));
+    } else if let Some(search_path) = path_env {
+        if !std::env::split_paths(search_path).any(|dir| dir.join(launcher).is_file()) {
+            problems.push(format!(
+                "{names} launch through '{launcher}', which is not on PATH; install it on PATH \
+                 or set `launcher` under [secret-manager] in config.toml"
+            ));
+        }
+    }
+
+    if agent == Some(Agent::Claude) {
+        problems.extend(wrapped.iter().filter_map(|(name, server)| {
+            let command = server.command.as_deref()?;
+            is_bare_command(command).then(|| {
+                format!(
+                    "mcpServers.{name}: Claude Desktop starts servers without your shell's \
+                     PATH, so `claudius secrets run` may not find '{command}'; use an \
+                     absolute path"
+                )
+            })
+        }));
+    }
+    problems
+}
+
+fn is_bare_command(command: &str) -> bool {
+    !command.contains(['/', '\\']) && !command.contains("${")
+}
+
+/// Why `server` cannot launch through `claudius secrets run`, if it references secrets.
+#[must_use]
+pub fn wrap_problem(server: &McpServerConfig) -> Option<&'static str> {
+    if !references_secrets(server) {
+        return None;
+    }
+    if server.command.is_none() || server.url.is_some() {
+        return Some(
+            "references op:// secrets, but only stdio servers can launch through `claudius secrets run`",
+        );
+    }
+    server
+        .headers
+        .values()
+        .any(|value| is_secret_reference(value))
+        .then_some("references op:// secrets in headers, which stdio servers do not send")
+}
+
+/// Rewrite every stdio server with secret `env` entries to launch through `launcher`.
+///
+/// # Errors
+///
+/// Returns an error listing servers that reference secrets but cannot be wrapped.
+pub fn wrap_secret_servers(
+    servers: &mut McpServersConfig,
+    launcher: &str,
+) -> Result<Vec<SecretLaunch>> {
+    let mut problems = servers
+        .mcp_servers
+        .iter()
+        .filter_map(|(name, server)| {
+            wrap_problem(server).map(|problem| format!("mcpServers.{name}: {problem}"))
+        })
+        .collect::<Vec<_>>();
+    if !problems.is_empty() {
+        problems.sort();
+        anyhow::bail!("Cannot keep secrets out of agent configs:\n  {}", problems.join("\n  "));
+    }
+
+    let mut wrapped = Vec::new();
+    for (name, server) in &mut servers.mcp_servers {
+        if references_secrets(server) {
+            let secrets = prefix_secret_env(server);
+            if !is_wrapped(server, launcher) {
+                wrap_command(server, launcher);
+            }
+            wrapped.push(SecretLaunch { server: name.clone(), secrets });
+        }
+    }
+    wrapped.sort_by(|left, right| left.server.cmp(&right.server));
+    Ok(wrapped)
+}
+
+fn prefix_secret_env(server: &mut McpServerConfig) -> Vec<String> {
+    let mut secrets = Vec::new();
+    let env = std::mem::take(&mut server.env);
+    server.env = env
+        .into_iter()
+        .map(|(key, value)| {
+            if !is_secret_reference(&value) {
+                return (key, value);
+            }
+            let name = key.strip_prefix(SECRET_ENV_PREFIX).unwrap_or(&key).to_string();
+            secrets.push(name.clone());
+            (format!("{SECRET_ENV_PREFIX}{name}"), value)
+        })
+        .collect::<HashMap<_, _>>();
+    secrets.sort();
+    secrets
+}
+
+fn wrap_command(server: &mut McpServerConfig, launcher: &str) {
+    let command = server.command.replace(launcher.to_string()).unwrap_or_default();
+    let original_args = std::mem::take(&mut server.args);
+    server.args = ["secrets", "run", "--"]
+        .into_iter()
+        .map(str::to_string)
+        .chain(std::iter::once(command))
+        .chain(original_args)
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(json: serde_json::Value) -> McpServerConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn wrap_secret_servers_rewrites_stdio_servers_with_secret_env() {
+        let mut servers = McpServersConfig {
+            mcp_servers: HashMap::from([
+                (
+                    "github".to_string(),
+                    server(serde_json::json!({
+                        "command": "npx",
+                        "args": ["-y", "github-mcp"],
+                        "env": {"GITHUB_TOKEN": "op://dev/github/token", "LOG_LEVEL": "info"}
+                    })),
+                ),
+                ("plain".to_string(), server(serde_json::json!({"command": "plain"}))),
+            ]),
+        };
+
+        let launches = wrap_secret_servers(&mut servers, "/usr/bin/claudius").unwrap();
+
+        assert_eq!(
+            launches,
+            [SecretLaunch { server: "github".into(), secrets: vec!["GITHUB_TOKEN".into()] }]
+        );
+        let github = servers.mcp_servers.get("github").unwrap();
+        assert_eq!(github.command.as_deref(), Some("/usr/bin/claudius"));
+        assert_eq!(github.args, ["secrets", "run", "--", "npx", "-y", "github-mcp"]);
+        assert_eq!(
+            github.env.get("CLAUDIUS_SECRET_GITHUB_TOKEN").map(String::as_str),
+            Some("op://dev/github/token")
+        );
+        assert_eq!(github.env.get("LOG_LEVEL").map(String::as_str), Some("info"));
+        assert!(!github.env.contains_key("GITHUB_TOKEN"));
+        assert_eq!(servers.mcp_servers.get("plain").unwrap().command.as_deref(), Some("plain"));
+    }
+
+    #[test]
+    fn wrap_secret_servers_keeps_servers_that_already_launch_through_secrets_run() {
+        let mut servers = McpServersConfig {
+            mcp_servers: HashMap::from([(
+                "github".to_string(),
+                server(serde_json::json!({
+                    "command": "/opt/bin/claudius",
+                    "args": ["secrets", "run", "--", "npx", "github-mcp"],
+                    "env": {"GITHUB_TOKEN": "op://dev/github/token"}
+                })),
+            )]),
+        };
+
+        let launches = wrap_secret_servers(&mut servers, "claudius").unwrap();
+
+        assert_eq!(
+            launches,
+            [SecretLaunch { server: "github".into(), secrets: vec!["GITHUB_TOKEN".into()] }]
+        );
+        let github = servers.mcp_servers.get("github").unwrap();
+        assert_eq!(github.command.as_deref(), Some("/opt/bin/claudius"));
+        assert_eq!(github.args, ["secrets", "run", "--", "npx", "github-mcp"]);
+        assert!(github.env.contains_key("CLAUDIUS_SECRET_GITHUB_TOKEN"));
+    }
+
+    #[test]
+    fn launcher_command_never_uses_the_running_binary() {
+        assert_eq!(launcher_command(None), "claudius");
+
+        let app_config: AppConfig = toml::from_str(
+            "[secret-manager]\ntype = \"1password\"\nlauncher = \"/usr/local/bin/claudius\"\n",
+        )
+        .unwrap();
+        assert_eq!(launcher_command(Some(&app_config)), "/usr/local/bin/claudius");
+    }
+
+    #[test]
+    fn launcher_problems_depend_on_the_agent() {
+        let github = server(serde_json::json!({"command": "npx"}));
+        let pinned = server(serde_json::json!({"command": "/usr/bin/server"}));
+        let wrapped = [("github", &github), ("pinned", &pinned)];
+        let bin = tempfile::TempDir::new().unwrap();
+        let path_env = bin.path().as_os_str();
+        let launcher = bin.path().join("claudius");
+
+        let desktop = launcher_problems(Some(Agent::Claude), "claudius", &wrapped, Some(path_env));
+        assert!(matches!(desktop.as_slice(), [launcher_problem, command_problem]
+            if launcher_problem.contains("set `launcher` under [secret-manager]")
+                && command_problem.starts_with("mcpServers.github: Claude Desktop")));
+
+        let codex = launcher_problems(Some(Agent::Codex), "claudius", &wrapped, Some(path_env));
+        assert!(matches!(codex.as_slice(), [problem]
+            if problem.starts_with("mcpServers.github, mcpServers.pinned launch through 'claudius', which is not on PATH")));
+
+        let configured = launcher.display().to_string();
+        let missing = launcher_problems(None, &configured, &wrapped, Some(path_env));
+        assert!(
+            matches!(missing.as_slice(), [problem] if problem.contains("which does not exist"))
+        );
+
+        std::fs::write(&launcher, "").unwrap();
+        assert!(launcher_problems(None, "claudius", &wrapped, Some(path_env)).is_empty());
+        assert!(launcher_problems(None, &configured, &wrapped, Some(path_env)).is_empty());
+    }
+
+    #[test]
+    fn wrap_secret_servers_rejects_remote_servers_with_secrets() {
+        let mut servers = McpServersConfig {
+            mcp_servers: HashMap::from([(
+                "remote".to_string(),
+                server(serde_json::json!({
+                    "type": "http",
+                    "url": "https://example.com/mcp",
+                    "headers": {"Authorization": "Bearer {{op://dev/api/token}}"}
+                })),
+            )]),
+        };
+
+        let error = wrap_secret_servers(&mut servers, "claudius").unwrap_err();
+
+        assert!(error.to_string().contains("mcpServers.remote: references op:// secrets"));
+    }
+}
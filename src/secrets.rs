@@ -863,8 +863,11 @@ mod tests {
         let resolver = SecretResolver::new(None);
         assert!(resolver.config.is_none());
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::Vault, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::Vault,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver_with_config = SecretResolver::new(Some(config));
         assert!(resolver_with_config.config.is_some());
         assert_eq!(
@@ -949,8 +952,11 @@ mod tests {
 
     #[test]
     fn test_resolve_value_vault_config() {
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::Vault, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::Vault,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         let result = resolver
@@ -961,8 +967,11 @@ mod tests {
 
     #[test]
     fn test_resolve_value_onepassword_non_reference() {
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         let result = resolver
@@ -988,6 +997,7 @@ mod tests {
                 mode: Some(OnePasswordMode::Manual),
                 service_account_token_path: None,
             }),
+            launcher: None,
         };
         let resolver = SecretResolver::new(Some(config));
 
@@ -1018,6 +1028,7 @@ mod tests {
                 mode: Some(OnePasswordMode::Manual),
                 service_account_token_path: None,
             }),
+            launcher: None,
         };
         let resolver = SecretResolver::new(Some(config));
 
@@ -1048,6 +1059,7 @@ mod tests {
                 mode: Some(OnePasswordMode::Manual),
                 service_account_token_path: None,
             }),
+            launcher: None,
         };
         let resolver = SecretResolver::new(Some(config));
 
@@ -1086,6 +1098,7 @@ mod tests {
                 mode: Some(OnePasswordMode::ServiceAccount),
                 service_account_token_path: Some(token_path.to_string_lossy().to_string()),
             }),
+            launcher: None,
         };
         let resolver = SecretResolver::new(Some(config));
 
@@ -1126,6 +1139,7 @@ mod tests {
                 mode: Some(OnePasswordMode::Manual),
                 service_account_token_path: None,
             }),
+            launcher: None,
         };
         let resolver = SecretResolver::new(Some(config));
 
@@ -1148,8 +1162,11 @@ mod tests {
         std::env::set_var("CLAUDIUS_SECRET_API_KEY", "op://vault/test-item/api-key");
         std::env::set_var(ONEPASSWORD_MODE_ENV, "unsupported");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         let error = resolver.resolve_env_vars().expect_err("invalid mode override should fail");
@@ -1246,8 +1263,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Set op:// references
@@ -1277,8 +1297,11 @@ mod tests {
         std::env::set_var("CLAUDIUS_SECRET_A", "op://vault/item1/field1");
         std::env::set_var("CLAUDIUS_SECRET_B", "op://vault/item1/field1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         let resolved = resolver.resolve_env_vars().expect("resolve_env_vars should succeed");
@@ -1306,8 +1329,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Set invalid reference (must have 3 segments to match regex)
@@ -1335,8 +1361,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Set up inline op:// references (Cloudflare AI Gateway example)
@@ -1383,8 +1412,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Mix of inline op:// and variable references
@@ -1449,8 +1481,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Set value with duplicate op:// references
@@ -1485,8 +1520,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Test with delimited references in URL
@@ -1520,8 +1558,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Mix of delimited and bare references
@@ -1552,8 +1593,11 @@ mod tests {
         // Enable mock mode
         std::env::set_var("CLAUDIUS_TEST_MOCK_OP", "1");
 
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Use delimited syntax for clarity
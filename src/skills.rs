@@ -1,6 +1,8 @@
 use crate::{
     app_config::Agent,
     asset_sync::{self, ManagedTreeSyncReport, SourceFileMapping, SyncBehavior},
+    undo::UndoRecorder,
+    validation::{Diagnostic, DiagnosticRule},
 };
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
@@ -42,7 +44,7 @@ static YAML_FRONTMATTER_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
         .expect("frontmatter regex should compile")
 });
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct SkillSyncReport {
     pub target_dir: PathBuf,
     pub synced_skills: Vec<String>,
@@ -54,13 +56,39 @@ pub struct SkillSyncReport {
 pub struct SkillSourceSet {
     pub mappings: Vec<SourceFileMapping>,
     pub includes_legacy_commands: bool,
-    pub warnings: Vec<String>,
-    _render_workspace: Option<TempDir>,
+    pub warnings: Vec<SkillWarning>,
+    render_workspaces: Vec<TempDir>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SkillValidationReport {
-    pub warnings: Vec<String>,
+    pub warnings: Vec<SkillWarning>,
+}
+
+/// A skill source problem and the file or directory it concerns.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SkillWarning {
+    pub message: String,
+    pub path: PathBuf,
+    pub rule: DiagnosticRule,
+}
+
+impl SkillWarning {
+    fn new(rule: DiagnosticRule, path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Self { message: message.into(), path: path.into(), rule }
+    }
+}
+
+impl std::fmt::Display for SkillWarning {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(&self.message)
+    }
+}
+
+impl From<SkillWarning> for Diagnostic {
+    fn from(warning: SkillWarning) -> Self {
+        Self::warning(warning.message).with_rule(warning.rule).with_path(warning.path)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -212,6 +240,7 @@ pub fn sync_skills(source_dir: &Path, target_dir: &Path) -> Result<Vec<String>>
         Some(source_dir),
         target_dir,
         SyncBehavior { dry_run: false, prune: false },
+        &UndoRecorder::disabled(),
     )?;
 
     Ok(report.synced_skills)
@@ -227,9 +256,10 @@ pub fn sync_skills_with_options(
     source_dir_opt: Option<&Path>,
     target_dir: &Path,
     behavior: SyncBehavior,
+    undo: &UndoRecorder,
 ) -> Result<SkillSyncReport> {
     let mappings = collect_skill_mappings(source_dir_opt)?;
-    sync_skill_mappings_with_options(&mappings, target_dir, behavior)
+    sync_skill_mappings_with_options(&mappings, target_dir, behavior, undo)
 }
 
 /// Sync already-collected skill mappings into a target directory.
@@ -242,10 +272,11 @@ pub fn sync_skill_mappings_with_options(
     mappings: &[SourceFileMapping],
     target_dir: &Path,
     behavior: SyncBehavior,
+    undo: &UndoRecorder,
 ) -> Result<SkillSyncReport> {
     let synced_skills = skill_names_from_mappings(mappings);
     let ManagedTreeSyncReport { target_dir: synced_target_dir, synced_files, pruned_files } =
-        asset_sync::sync_managed_tree(target_dir, mappings, behavior)?;
+        asset_sync::sync_managed_tree(target_dir, mappings, behavior, undo)?;
 
     Ok(SkillSyncReport { target_dir: synced_target_dir, synced_skills, synced_files, pruned_files })
 }
@@ -284,6 +315,9 @@ pub fn validate_claudius_skill_sources(
 /// into canonical shared `skill.yaml` target overlays plus optional
 /// `targets/<agent>.md` body overrides.
 ///
+/// The files it rewrites and the files of the override directories it removes
+/// are captured by `undo`.
+///
 /// # Errors
 ///
 /// Returns an error if a deprecated override cannot be represented safely in
@@ -292,6 +326,7 @@ pub fn migrate_deprecated_agent_overrides(
     config_dir: &Path,
     agent_filter: Option<Agent>,
     dry_run: bool,
+    undo: &UndoRecorder,
 ) -> Result<SkillMigrationReport> {
     let skills_root = config_dir.join("skills");
     let candidates = discover_deprecated_override_candidates(&skills_root, agent_filter)?;
@@ -308,7 +343,7 @@ pub fn migrate_deprecated_agent_overrides(
     let plans = plan_deprecated_override_migrations(&skills_root, &candidates)?;
 
     if !dry_run {
-        apply_planned_skill_migrations(&plans)?;
+        apply_planned_skill_migrations(&plans, undo)?;
         prune_empty_agent_override_dirs(&skills_root)?;
     }
 
@@ -939,9 +974,13 @@ fn serialize_yaml_struct<T: Serialize>(value: &T) -> Result<String> {
     Ok(serialized.strip_prefix("---\n").unwrap_or(&serialized).to_string())
 }
 
-fn apply_planned_skill_migrations(plans: &[PlannedSkillMigration]) -> Result<()> {
+fn apply_planned_skill_migrations(
+    plans: &[PlannedSkillMigration],
+    undo: &UndoRecorder,
+) -> Result<()> {
     for plan in plans {
         for (path, content) in &plan.updated_files {
+            undo.record(path);
             if let Some(parent) = path.parent() {
                 fs::create_dir_all(parent)
                     .with_context(|| format!("Failed to create {}", parent.display()))?;
@@ -953,6 +992,9 @@ fn apply_planned_skill_migrations(plans: &[PlannedSkillMigration]) -> Result<()>
 
     for plan in plans {
         for path in &plan.removed_directories {
+            for file in crate::file_walk::collect_files(path) {
+                undo.record(&file);
+            }
             fs::remove_dir_all(path)
                 .with_context(|| format!("Failed to remove {}", path.display()))?;
         }
@@ -1154,7 +1196,7 @@ fn render_candidate_to_mappings(
     candidate: &SkillCandidate,
     render_agent: Agent,
     render_workspace: &Path,
-    warnings: &mut BTreeSet<String>,
+    warnings: &mut BTreeSet<SkillWarning>,
 ) -> Result<Vec<SourceFileMapping>> {
     let bundle = match candidate.kind {
         SkillCandidateKind::CanonicalDirectory => {
@@ -1172,10 +1214,11 @@ fn render_candidate_to_mappings(
 fn render_canonical_skill_bundle(
     candidate: &SkillCandidate,
     render_agent: Agent,
-    warnings: &mut BTreeSet<String>,
+    warnings: &mut BTreeSet<SkillWarning>,
 ) -> Result<RenderedSkillBundle> {
     let definition = load_canonical_skill_definition(candidate)?;
     warnings.extend(collect_canonical_layout_warnings(&candidate.path, &definition)?);
+    let definition_path = candidate.path.join(CANONICAL_SKILL_FILE_NAME);
     let target_name = canonical_target_for_agent(render_agent);
     let target_overlay = definition.targets.get(&target_name).cloned().unwrap_or_default();
     let instructions = load_canonical_instructions(&candidate.path, &definition, target_name)?;
@@ -1183,10 +1226,10 @@ fn render_canonical_skill_bundle(
     match target_name {
         SkillTargetName::Codex => {
             if target_overlay.invocation.is_some() {
-                warnings.insert(format!(
+                warnings.insert(SkillWarning::new(DiagnosticRule::SkillTargetOverlay, &definition_path, format!(
                     "Codex target overlay for skill `{}` uses `invocation`, but Codex keeps implicit skill discovery enabled unless `allow-implicit-invocation` is set explicitly; `invocation` is ignored for Codex.",
                     definition.name
-                ));
+                )));
             }
             if target_overlay.disable_model_invocation.is_some()
                 || target_overlay.user_invocable.is_some()
@@ -1196,10 +1239,10 @@ fn render_canonical_skill_bundle(
                 || target_overlay.context.is_some()
                 || target_overlay.agent.is_some()
             {
-                warnings.insert(format!(
+                warnings.insert(SkillWarning::new(DiagnosticRule::SkillTargetOverlay, &definition_path, format!(
                     "Codex target overlay for skill `{}` contains Claude-specific fields that will be ignored during rendering.",
                     definition.name
-                ));
+                )));
             }
         },
         SkillTargetName::Claude | SkillTargetName::ClaudeCode | SkillTargetName::Gemini => {
@@ -1207,11 +1250,11 @@ fn render_canonical_skill_bundle(
                 || target_overlay.interface.is_some()
                 || target_overlay.dependencies.is_some()
             {
-                warnings.insert(format!(
+                warnings.insert(SkillWarning::new(DiagnosticRule::SkillTargetOverlay, &definition_path, format!(
                     "{} target overlay for skill `{}` contains Codex-only fields that will be ignored during rendering.",
                     target_name_label(target_name),
                     definition.name
-                ));
+                )));
             }
         },
     }
@@ -1329,7 +1372,7 @@ fn validate_canonical_reserved_directories(skill_root: &Path, skill_name: &str)
 fn collect_canonical_layout_warnings(
     skill_root: &Path,
     definition: &CanonicalSkillDefinition,
-) -> Result<Vec<String>> {
+) -> Result<Vec<SkillWarning>> {
     let allowed_entries = allowed_canonical_top_level_entries(definition);
     let mut warnings = Vec::new();
     let mut entries = fs::read_dir(skill_root)
@@ -1360,11 +1403,11 @@ fn collect_canonical_layout_warnings(
             continue;
         }
 
-        warnings.push(format!(
+        warnings.push(SkillWarning::new(DiagnosticRule::SkillLayout, entry.path(), format!(
             "Canonical skill `{}` contains unsupported top-level entry `{entry_name}`; only {} are rendered, so this entry will be ignored.",
             definition.name,
             format_canonical_top_level_entries(&allowed_entries),
-        ));
+        )));
     }
 
     Ok(warnings)
@@ -1373,7 +1416,7 @@ fn collect_canonical_layout_warnings(
 fn collect_canonical_target_entry_warnings(
     targets_dir: &Path,
     skill_name: &str,
-) -> Result<Vec<String>> {
+) -> Result<Vec<SkillWarning>> {
     if !targets_dir.exists() {
         return Ok(Vec::new());
     }
@@ -1392,10 +1435,10 @@ fn collect_canonical_target_entry_warnings(
                 return None;
             }
 
-            Some(format!(
+            Some(SkillWarning::new(DiagnosticRule::SkillLayout, entry.path(), format!(
                 "Canonical skill `{skill_name}` contains unsupported targets entry `targets/{entry_name}`; keep per-agent metadata in skill.yaml and limit Markdown fragments to {}.",
                 format_canonical_target_entries(&allowed_entries),
-            ))
+            )))
         })
         .collect::<Vec<_>>();
 
@@ -1417,9 +1460,9 @@ fn collect_canonical_target_entry_warnings(
                 return None;
             }
 
-            Some(format!(
+            Some(SkillWarning::new(DiagnosticRule::SkillLayout, full_override, format!(
                 "Canonical skill `{skill_name}` defines `targets/{label}.md` together with prepend/append fragments; the full override takes precedence and the fragments will be ignored."
-            ))
+            )))
         }),
     );
 
@@ -1542,13 +1585,17 @@ fn render_codex_generated_files(
 fn render_legacy_skill_bundle(
     candidate: &SkillCandidate,
     render_agent: Agent,
-    warnings: &mut BTreeSet<String>,
+    warnings: &mut BTreeSet<SkillWarning>,
 ) -> Result<RenderedSkillBundle> {
     let skill_path = legacy_skill_markdown_path(candidate);
     let document = parse_legacy_skill_document(&skill_path)?;
 
     if let Some(parse_warning) = &document.parse_warning {
-        warnings.insert(parse_warning.clone());
+        warnings.insert(SkillWarning::new(
+            DiagnosticRule::LegacySkill,
+            &skill_path,
+            parse_warning.clone(),
+        ));
     }
 
     let mut generated_files = Vec::new();
@@ -1565,10 +1612,10 @@ fn render_legacy_skill_bundle(
     if render_agent == Agent::Codex {
         if let Some(frontmatter) = &document.frontmatter {
             let Some((name, description)) = extract_name_and_description(frontmatter) else {
-                warnings.insert(format!(
+                warnings.insert(SkillWarning::new(DiagnosticRule::LegacySkill, &skill_path, format!(
                     "Legacy skill `{}` contains YAML frontmatter without valid `name` and `description`; preserving the original SKILL.md for Codex.",
                     candidate.name
-                ));
+                )));
                 generated_files.push(RenderedTextFile {
                     relative_path: SKILL_FILE_NAME.to_string(),
                     content: document.raw_content.clone(),
@@ -1583,10 +1630,10 @@ fn render_legacy_skill_bundle(
             if frontmatter_contains_any(frontmatter, CLAUDE_ONLY_FRONTMATTER_KEYS)
                 && candidate.origin != SkillSourceOrigin::AgentOverride
             {
-                warnings.insert(format!(
+                warnings.insert(SkillWarning::new(DiagnosticRule::LegacySkill, &skill_path, format!(
                     "Legacy shared skill `{}` contains Claude-specific metadata that will be dropped when rendering for Codex.",
                     candidate.name
-                ));
+                )));
             }
 
             generated_files.push(RenderedTextFile {
@@ -2023,12 +2070,12 @@ pub fn collect_claudius_skill_source_set(
     let mut mappings = Vec::new();
     for candidate in &candidates {
         if candidate.origin == SkillSourceOrigin::AgentOverride {
-            warnings.insert(format!(
+            warnings.insert(SkillWarning::new(DiagnosticRule::DeprecatedSkillOverride, &candidate.path, format!(
                 "Deprecated full agent override directory detected for skill `{}` under skills/{}/{}; prefer canonical target overlays in skill.yaml and migrate it with `claudius skills migrate`.",
                 candidate.name,
                 agent_skill_subdir(render_agent),
                 candidate.name,
-            ));
+            )));
         }
 
         mappings.extend(render_candidate_to_mappings(
@@ -2044,10 +2091,55 @@ pub fn collect_claudius_skill_source_set(
         mappings,
         includes_legacy_commands,
         warnings: warnings.into_iter().collect(),
-        _render_workspace: Some(render_workspace),
+        render_workspaces: vec![render_workspace],
+    })
+}
+
+/// Collect skills from the global config root and, when given, a project
+/// `.claudius/` layer whose skills replace global skills with the same name.
+///
+/// # Errors
+///
+/// Returns an error if either skill tree cannot be read or rendered.
+pub fn collect_layered_skill_source_set(
+    config_dir: &Path,
+    project_layer: Option<&Path>,
+    agent: Option<Agent>,
+) -> Result<SkillSourceSet> {
+    let global = collect_claudius_skill_source_set(config_dir, agent)?;
+    let Some(layer_dir) = project_layer else {
+        return Ok(global);
+    };
+
+    let project = collect_claudius_skill_source_set(layer_dir, agent)?;
+    let project_skills = project.mappings.iter().map(skill_name_of).collect::<BTreeSet<_>>();
+
+    let mut mappings = global
+        .mappings
+        .into_iter()
+        .filter(|mapping| !project_skills.contains(skill_name_of(mapping)))
+        .chain(project.mappings.iter().cloned())
+        .collect::<Vec<_>>();
+    mappings.sort_by(|left, right| left.relative_path.cmp(&right.relative_path));
+
+    let warnings = global.warnings.into_iter().chain(project.warnings).collect::<BTreeSet<_>>();
+    Ok(SkillSourceSet {
+        mappings,
+        includes_legacy_commands: global.includes_legacy_commands
+            || project.includes_legacy_commands,
+        warnings: warnings.into_iter().collect(),
+        render_workspaces: global
+            .render_workspaces
+            .into_iter()
+            .chain(project.render_workspaces)
+            .collect(),
     })
 }
 
+fn skill_name_of(mapping: &SourceFileMapping) -> &str {
+    mapping.relative_path.split('/').next().unwrap_or_default()
+}
+
 /// Collect mappings for shared skills in `skills/`, excluding agent-specific
 /// subdirectories such as `skills/gemini/`.
 ///
@@ -2278,6 +2370,7 @@ mod tests {
             Some(&source_dir),
             &target_dir,
             SyncBehavior { dry_run: false, prune: false },
+            &UndoRecorder::disabled(),
         )
         .expect("initial sync should succeed");
 
@@ -2286,6 +2379,7 @@ mod tests {
             Some(&source_dir),
             &target_dir,
             SyncBehavior { dry_run: false, prune: true },
+            &UndoRecorder::disabled(),
         )
         .expect("prune sync should succeed");
 
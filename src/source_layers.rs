@@ -0,0 +1,313 @@
+#![allow(missing_docs)]
+
+//! Project-local `.claudius/` source layer.
+//!
+//! A repository can ship its own Claudius sources in a `.claudius/` directory
+//! at the project root, laid out like `~/.config/claudius`. Project-local
+//! commands layer it over the user's global sources:
+//!
+//! - `mcpServers.json`: project servers replace global servers with the same name
+//! - `claude.settings.json`, `codex.settings.toml`, `gemini.settings.json`:
+//!   deep-merged over the global file, project values win
+//! - `skills/`: a project skill replaces the global skill with the same name
+//! - `commands/`, `agents/`, `rules/`: project files replace global files with
+//!   the same relative path
+//!
+//! Global syncs never read the project layer.
+//!
+//! A cloned repository decides what its layer contains, including the commands
+//! its servers run and the environment and secrets they receive. The layer is
+//! therefore only applied to projects listed under `trusted-projects` in
+//! `config.toml`; other projects get the global sources alone.
+
+use crate::app_config::AppConfig;
+use crate::asset_sync::{self, SourceFileMapping};
+use crate::config::reader;
+use crate::projects::{expand_project_path, registry_base_dir};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the project layer directory at the project root.
+pub const PROJECT_LAYER_DIR_NAME: &str = ".claudius";
+
+/// Settings sources that are merged across layers.
+const SETTINGS_FILE_NAMES: &[&str] =
+    &["claude.settings.json", "codex.settings.toml", "gemini.settings.json"];
+
+/// Auxiliary trees whose files are layered by relative path.
+const TREE_SOURCES: &[(&str, &str)] = &[
+    ("Gemini command", "commands/gemini"),
+    ("Gemini agent", "agents/gemini"),
+    ("Claude Code subagent", "agents/claude-code"),
+    ("rule", "rules"),
+];
+
+/// Agent-specific skill subdirectories that are not skills themselves.
+const AGENT_SKILL_DIRS: &[&str] = &["claude", "claude-code", "codex", "gemini"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceLayer {
+    Global,
+    Project,
+}
+
+impl SourceLayer {
+    #[must_use]
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Global => "global",
+            Self::Project => "project",
+        }
+    }
+}
+
+/// One source asset and the layer it is taken from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LayeredAsset {
+    pub kind: &'static str,
+    pub name: String,
+    pub layer: SourceLayer,
+    /// The project layer replaces (or, for settings, merges over) a global asset.
+    pub overrides_global: bool,
+}
+
+/// `.claudius/` in `project_dir`, unless missing, the global config directory
+/// itself, or in a project that is not listed under `trusted-projects`.
+#[must_use]
+pub fn discover_project_layer(project_dir: &Path, config_dir: &Path) -> Option<PathBuf> {
+    find_project_layer(project_dir, config_dir).filter(|_| is_trusted_project(project_dir))
+}
+
+/// `.claudius/` in `project_dir` when it exists but is ignored because the
+/// project is not listed under `trusted-projects`.
+#[must_use]
+pub fn untrusted_project_layer(project_dir: &Path, config_dir: &Path) -> Option<PathBuf> {
+    find_project_layer(project_dir, config_dir).filter(|_| !is_trusted_project(project_dir))
+}
+
+fn is_trusted_project(project_dir: &Path) -> bool {
+    let (Ok(Some(app_config)), Ok(base_dir)) = (AppConfig::load(), registry_base_dir()) else {
+        return false;
+    };
+    is_listed(project_dir, &app_config.trusted_projects, &base_dir)
+}
+
+/// Whether `project_dir` is one of the `trusted` paths, resolved like
+/// `[[projects]]` paths against `base_dir`.
+fn is_listed(project_dir: &Path, trusted: &[String], base_dir: &Path) -> bool {
+    let Ok(project_root) = project_dir.canonicalize() else {
+        return false;
+    };
+    trusted.iter().any(|path| {
+        expand_project_path(path, base_dir)
+            .canonicalize()
+            .is_ok_and(|trusted_dir| trusted_dir == project_root)
+    })
+}
+
+fn find_project_layer(project_dir: &Path, config_dir: &Path) -> Option<PathBuf> {
+    let layer_dir = project_dir.join(PROJECT_LAYER_DIR_NAME);
+    if !layer_dir.is_dir() {
+        return None;
+    }
+
+    let same_as_config_dir = match (layer_dir.canonicalize(), config_dir.canonicalize()) {
+        (Ok(layer), Ok(config)) => layer == config,
+        _ => false,
+    };
+    (!same_as_config_dir).then_some(layer_dir)
+}
+
+/// Combine source trees in precedence order; later layers replace files with
+/// the same relative path.
+#[must_use]
+pub fn layer_tree_mappings(layers: Vec<Vec<SourceFileMapping>>) -> Vec<SourceFileMapping> {
+    let mut merged = BTreeMap::new();
+    for mapping in layers.into_iter().flatten() {
+        merged.insert(mapping.relative_path.clone(), mapping);
+    }
+    merged.into_values().collect()
+}
+
+/// List every source asset with the layer a project-local sync takes it from.
+///
+/// # Errors
+///
+/// Returns an error if a source file or directory exists but cannot be read.
+pub fn describe_layers(config_dir: &Path, project_layer: &Path) -> Result<Vec<LayeredAsset>> {
+    let mut assets = Vec::new();
+
+    let global_servers = mcp_server_names(config_dir)?;
+    let project_servers = mcp_server_names(project_layer)?;
+    push_named(&mut assets, "MCP server", global_servers, project_servers);
+
+    for file_name in SETTINGS_FILE_NAMES {
+        let global = config_dir.join(file_name).exists();
+        let project = project_layer.join(file_name).exists();
+        if global || project {
+            assets.push(LayeredAsset {
+                kind: "settings",
+                name: (*file_name).to_string(),
+                layer: if project { SourceLayer::Project } else { SourceLayer::Global },
+                overrides_global: global && project,
+            });
+        }
+    }
+
+    push_named(
+        &mut assets,
+        "skill",
+        skill_names(&config_dir.join("skills"))?,
+        skill_names(&project_layer.join("skills"))?,
+    );
+
+    for (kind, relative_dir) in TREE_SOURCES {
+        push_named(
+            &mut assets,
+            kind,
+            tree_file_names(&config_dir.join(relative_dir))?,
+            tree_file_names(&project_layer.join(relative_dir))?,
+        );
+    }
+
+    Ok(assets)
+}
+
+fn push_named(
+    assets: &mut Vec<LayeredAsset>,
+    kind: &'static str,
+    global: Vec<String>,
+    project: Vec<String>,
+) {
+    let mut layers = BTreeMap::new();
+    for name in global {
+        layers.insert(name, (SourceLayer::Global, false));
+    }
+    for name in project {
+        let overrides_global = layers.contains_key(&name);
+        layers.insert(name, (SourceLayer::Project, overrides_global));
+    }
+
+    assets.extend(layers.into_iter().map(|(name, (layer, overrides_global))| LayeredAsset {
+        kind,
+        name,
+        layer,
+        overrides_global,
+    }));
+}
+
+fn mcp_server_names(root: &Path) -> Result<Vec<String>> {
+    let path = root.join("mcpServers.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(reader::read_mcp_servers_config(&path)?.mcp_servers.into_keys().collect())
+}
+
+fn skill_names(skills_dir: &Path) -> Result<Vec<String>> {
+    if !skills_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut names = Vec::new();
+    for entry in fs::read_dir(skills_dir)? {
+        let path = entry?.path();
+        let name = if path.is_dir() {
+            path.file_name()
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            path.file_stem()
+        } else {
+            None
+        };
+        if let Some(skill) = name.and_then(|value| value.to_str()) {
+            if !AGENT_SKILL_DIRS.contains(&skill) {
+                names.push(skill.to_string());
+            }
+        }
+    }
+    Ok(names)
+}
+
+fn tree_file_names(source_dir: &Path) -> Result<Vec<String>> {
+    Ok(asset_sync::collect_directory_tree_mappings(source_dir)?
+        .into_iter()
+        .map(|mapping| mapping.relative_path)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn mapping(root: &str, relative_path: &str) -> SourceFileMapping {
+        SourceFileMapping {
+            source_path: Path::new(root).join(relative_path),
+            relative_path: relative_path.to_string(),
+        }
+    }
+
+    #[test]
+    fn layer_tree_mappings_prefers_later_layers() {
+        let merged = layer_tree_mappings(vec![
+            vec![mapping("/global", "a.md"), mapping("/global", "b.md")],
+            vec![mapping("/project", "b.md"), mapping("/project", "c.md")],
+        ]);
+
+        let sources = merged
+            .iter()
+            .map(|entry| entry.source_path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(sources, ["/global/a.md", "/project/b.md", "/project/c.md"]);
+    }
+
+    #[test]
+    fn only_listed_projects_are_trusted() {
+        let root = TempDir::new().expect("temp dir");
+        let config_dir = root.path().join("config");
+        let project_dir = root.path().join("src/api");
+        fs::create_dir_all(&config_dir).expect("config dir");
+        fs::create_dir_all(project_dir.join(PROJECT_LAYER_DIR_NAME)).expect("project layer");
+        let other_dir = root.path().join("src/web");
+        fs::create_dir_all(&other_dir).expect("other project");
+
+        let trusted = ["../src/api/".to_string()];
+        assert!(is_listed(&project_dir, &trusted, &config_dir));
+        assert!(!is_listed(&other_dir, &trusted, &config_dir));
+        assert!(!is_listed(&project_dir, &[], &config_dir));
+    }
+
+    #[test]
+    fn describe_layers_marks_project_overrides() {
+        let root = TempDir::new().expect("temp dir");
+        let config_dir = root.path().join("config");
+        let layer_dir = root.path().join("project/.claudius");
+        fs::create_dir_all(config_dir.join("skills/review")).expect("global skill");
+        fs::create_dir_all(layer_dir.join("skills/review")).expect("project skill");
+        fs::write(config_dir.join("mcpServers.json"), r#"{"mcpServers": {"a": {}, "b": {}}}"#)
+            .expect("global servers");
+        fs::write(layer_dir.join("mcpServers.json"), r#"{"mcpServers": {"b": {}}}"#)
+            .expect("project servers");
+        fs::write(layer_dir.join("codex.settings.toml"), "model = \"o3\"\n").expect("settings");
+
+        let assets = describe_layers(&config_dir, &layer_dir).expect("describe");
+
+        let summary = assets
+            .iter()
+            .map(|asset| (asset.kind, asset.name.as_str(), asset.layer, asset.overrides_global))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("MCP server", "a", SourceLayer::Global, false),
+                ("MCP server", "b", SourceLayer::Project, true),
+                ("settings", "codex.settings.toml", SourceLayer::Project, false),
+                ("skill", "review", SourceLayer::Project, true),
+            ]
+        );
+    }
+}
@@ -1,18 +1,31 @@
 #![allow(missing_docs)]
 
 use crate::agent_paths;
-use crate::app_config::{Agent, AppConfig, ClaudeCodeScope};
+use crate::app_config::{Agent, AppConfig, ClaudeCodeScope, ProfileConfig};
 use crate::asset_sync::{self, SyncBehavior};
+use crate::backups::BackupSettings;
 use crate::codex_settings::{convert_mcp_to_toml, CodexSettings, ModelProvider};
-use crate::config::{reader, writer, ClaudeConfig, Config, McpServersConfig, Settings};
+use crate::config::json_document::render_json;
+use crate::config::toml_document::render_toml;
+use crate::config::{
+    reader, writer, ClaudeConfig, Config, McpServerConfig, McpServersConfig, Settings,
+};
 use crate::gemini_settings::sanitize_claude_config_for_gemini;
-use crate::json_merge::deep_merge_json_maps;
+use crate::interpolation::{self, Interpolator};
+use crate::json_merge::{deep_merge_json_maps, merge_serialized};
+use crate::mcp_ownership::{self, McpOwnershipLedger};
 use crate::merge::{merge_configs, merge_settings, strategy::MergeStrategy};
+use crate::profiles;
+use crate::secret_launch::{self, SecretLaunch};
 use crate::skills;
+use crate::source_layers;
+use crate::sync_transaction;
+use crate::undo::UndoRecorder;
 use crate::validation::{pre_validate_settings, prompt_continue};
 use anyhow::{Context, Result};
+use serde::Serialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::path::{Path, PathBuf};
 use toml::Value as TomlValue;
 use tracing::{debug, info, warn};
@@ -35,6 +48,9 @@ pub struct ReadConfigResult {
     pub mcp_servers: McpServersConfig,
     pub settings: Option<Settings>,
     pub codex_settings: Option<CodexSettings>,
+    /// Previously synced MCP servers that are no longer defined in the sources.
+    /// Only populated when pruning; these entries are removed from the target.
+    pub stale_mcp_servers: BTreeSet<String>,
 }
 
 /// Optional extra files for Codex in global mode
@@ -62,7 +78,7 @@ impl SupportingAssetSyncReport {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SupportingAssetReport {
     pub label: &'static str,
     pub target_dir: PathBuf,
@@ -138,8 +154,8 @@ pub fn read_configurations(
 ) -> Result<ReadConfigResult> {
     // Read MCP servers
     debug!("Reading MCP servers configuration");
-    let mcp_servers = reader::read_mcp_servers_config(mcp_servers_path)
-        .context("Failed to read MCP servers configuration")?;
+    let mcp_servers =
+        read_layered_mcp_servers(mcp_servers_path, config.project_layer_dir.as_deref())?;
 
     debug!("Found {} MCP server(s) to sync", mcp_servers.mcp_servers.len());
     for name in mcp_servers.mcp_servers.keys() {
@@ -147,15 +163,306 @@ pub fn read_configurations(
     }
 
     // Read settings based on agent type
-    let (settings, codex_settings) = if agent_context.is_codex {
+    let (global_settings, global_codex_settings) = if agent_context.is_codex {
         read_codex_settings(&config.settings_path)?
     } else if agent_context.is_claude_desktop {
         (None, None)
     } else {
         read_regular_settings(&config.settings_path)?
     };
+    let (settings, codex_settings) =
+        layer_project_settings(config, agent_context, global_settings, global_codex_settings)?;
 
-    Ok(ReadConfigResult { mcp_servers, settings, codex_settings })
+    Ok(ReadConfigResult {
+        mcp_servers,
+        settings,
+        codex_settings,
+        stale_mcp_servers: BTreeSet::new(),
+    })
+}
+
+/// Per-run adjustments applied to sources right after they are read.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SourceAdjustments<'a> {
+    /// Active profile, if any.
+    pub profile: Option<&'a ProfileConfig>,
+    /// `config.toml`, for its `[variables]`.
+    pub app_config: Option<&'a AppConfig>,
+}
+
+/// Notes from `adjust_sources` for the caller to show.
+#[derive(Debug, Clone, Default)]
+pub struct AdjustedSources {
+    pub warnings: Vec<String>,
+    /// Servers rewritten to launch through `claudius secrets run`.
+    pub secret_launches: Vec<SecretLaunch>,
+}
+
+/// Apply the active profile, then `${NAME}` interpolation of the servers this
+/// target receives, then wrap servers with `op://` secrets in
+/// `claudius secrets run`.
+///
+/// # Errors
+///
+/// Returns an error if the profile does not fit the sources, a server value
+/// cannot be interpolated, or a server with secrets cannot be wrapped.
+pub fn adjust_sources(
+    read_result: &mut ReadConfigResult,
+    config: &Config,
+    mcp_servers_path: &Path,
+    agent_context: AgentContext,
+    adjustments: SourceAdjustments<'_>,
+) -> Result<AdjustedSources> {
+    if let Some(profile) = adjustments.profile {
+        profiles::apply_profile(read_result, profile, agent_context)?;
+    }
+
+    read_result.mcp_servers =
+        servers_for_target(&read_result.mcp_servers, agent_context, config.is_global);
+
+    let config_dir = mcp_servers_path.parent().unwrap_or_else(|| Path::new("."));
+    let project_root = if config.is_global { None } else { std::env::current_dir().ok() };
+    let variables = adjustments
+        .app_config
+        .map(|app_config| app_config.variables.clone())
+        .unwrap_or_default();
+    let interpolator = Interpolator::new(config_dir, project_root.as_deref(), variables);
+    let warnings = interpolation::interpolate_mcp_servers(
+        &mut read_result.mcp_servers,
+        &interpolator,
+        agent_context,
+    )?;
+
+    let launcher = secret_launch::launcher_command(adjustments.app_config);
+    let secret_launches =
+        secret_launch::wrap_secret_servers(&mut read_result.mcp_servers, &launcher)?;
+
+    Ok(AdjustedSources { warnings, secret_launches })
+}
+
+/// Global MCP servers with the project layer's servers replacing same-named ones.
+///
+/// # Errors
+///
+/// Returns an error if either `mcpServers.json` cannot be read or parsed.
+pub fn read_layered_mcp_servers(
+    mcp_servers_path: &Path,
+    project_layer: Option<&Path>,
+) -> Result<McpServersConfig> {
+    let project_path = project_layer
+        .map(|layer_dir| layer_dir.join("mcpServers.json"))
+        .filter(|path| path.exists());
+
+    let mut mcp_servers = if project_path.is_some() && !mcp_servers_path.exists() {
+        McpServersConfig { mcp_servers: HashMap::new() }
+    } else {
+        reader::read_mcp_servers_config(mcp_servers_path)
+            .context("Failed to read MCP servers configuration")?
+    };
+
+    if let Some(path) = project_path {
+        debug!("Layering project MCP servers from {}", path.display());
+        let project = reader::read_mcp_servers_config(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        mcp_servers.mcp_servers.extend(project.mcp_servers);
+    }
+
+    Ok(mcp_servers)
+}
+
+/// Deep-merge the project layer's settings file over the global one.
+fn layer_project_settings(
+    config: &Config,
+    agent_context: AgentContext,
+    settings: Option<Settings>,
+    codex_settings: Option<CodexSettings>,
+) -> Result<(Option<Settings>, Option<CodexSettings>)> {
+    let Some(path) = config.project_layer_settings_path() else {
+        return Ok((settings, codex_settings));
+    };
+    debug!("Layering project settings from {}", path.display());
+
+    if agent_context.is_codex {
+        let merged = match reader::read_codex_settings(&path)? {
+            Some(project) => Some(
+                merge_serialized(codex_settings.as_ref(), &project)
+                    .with_context(|| format!("Failed to merge {}", path.display()))?,
+            ),
+            None => codex_settings,
+        };
+        return Ok((settings, merged));
+    }
+
+    if agent_context.is_claude_desktop {
+        return Ok((settings, codex_settings));
+    }
+
+    let merged = match reader::read_settings(&path)? {
+        Some(project) => Some(
+            merge_serialized(settings.as_ref(), &project)
+                .with_context(|| format!("Failed to merge {}", path.display()))?,
+        ),
+        None => settings,
+    };
+    Ok((merged, codex_settings))
+}
+
+/// Previously synced MCP servers that the sources no longer define.
+///
+/// # Errors
+///
+/// Returns an error if the ownership ledger cannot be read.
+pub fn collect_stale_mcp_servers(
+    config: &Config,
+    target_config_path: &Path,
+    agent_context: AgentContext,
+    read_result: &ReadConfigResult,
+) -> Result<BTreeSet<String>> {
+    let config_dir = Config::get_config_dir()?;
+    let ledger = McpOwnershipLedger::load(&config_dir)?;
+    let target_key = mcp_ownership_target_key(config, target_config_path, agent_context)?;
+    let desired = desired_mcp_server_names(read_result, agent_context, config.is_global);
+    Ok(ledger.stale(&target_key, &desired))
+}
+
+/// The ownership ledger updated with the MCP servers a sync writes, staged so
+/// it is committed in the same transaction as the target files.
+///
+/// # Errors
+///
+/// Returns an error if the ownership ledger cannot be read or serialized.
+pub fn stage_mcp_ownership(
+    config: &Config,
+    target_config_path: &Path,
+    agent_context: AgentContext,
+    read_result: &ReadConfigResult,
+    pruned: bool,
+) -> Result<sync_transaction::StagedFile> {
+    let config_dir = Config::get_config_dir()?;
+    let mut ledger = McpOwnershipLedger::load(&config_dir)?;
+    let target_key = mcp_ownership_target_key(config, target_config_path, agent_context)?;
+    let desired = desired_mcp_server_names(read_result, agent_context, config.is_global);
+    ledger.record(&target_key, &desired, pruned);
+    ledger.staged(&config_dir)
+}
+
+fn desired_mcp_server_names(
+    read_result: &ReadConfigResult,
+    agent_context: AgentContext,
+    global: bool,
+) -> BTreeSet<String> {
+    let mut names: BTreeSet<String> =
+        servers_for_target(&read_result.mcp_servers, agent_context, global)
+            .mcp_servers
+            .into_keys()
+            .collect();
+
+    if let Some(servers) = read_result.settings.as_ref().and_then(|s| s.mcp_servers.as_ref()) {
+        names.extend(servers.keys().cloned());
+    }
+    if let Some(servers) = read_result.codex_settings.as_ref().and_then(|s| s.mcp_servers.as_ref())
+    {
+        names.extend(servers.keys().cloned());
+    }
+
+    names
+}
+
+/// MCP servers whose `x-claudius` block selects this agent and scope.
+///
+/// The block is dropped from the returned servers, so they compare equal to
+/// what the target already holds.
+#[must_use]
+pub fn servers_for_target(
+    mcp_servers: &McpServersConfig,
+    agent_context: AgentContext,
+    global: bool,
+) -> McpServersConfig {
+    let agent = agent_context.agent.unwrap_or(Agent::Claude);
+    let scopes = target_scopes(agent_context, global);
+
+    McpServersConfig {
+        mcp_servers: mcp_servers
+            .mcp_servers
+            .iter()
+            .filter(|(name, server)| {
+                let included =
+                    server.targeting.as_ref().is_none_or(|target| target.includes(agent, scopes));
+                if !included {
+                    debug!(
+                        "Skipping MCP server {name} for {}: excluded by x-claudius",
+                        agent.display_name()
+                    );
+                }
+                included
+            })
+            .map(|(name, server)| {
+                (name.clone(), McpServerConfig { targeting: None, ..server.clone() })
+            })
+            .collect(),
+    }
+}
+
+/// Scope names an `x-claudius` selector can use to address this sync target.
+const fn target_scopes(agent_context: AgentContext, global: bool) -> &'static [&'static str] {
+    match (agent_context.is_claude_code, agent_context.claude_code_scope) {
+        (true, Some(ClaudeCodeScope::Managed)) => &["managed", "global"],
+        (true, Some(ClaudeCodeScope::Local)) => &["local", "project"],
+        (true, Some(ClaudeCodeScope::Project)) => &["project"],
+        (true, Some(ClaudeCodeScope::User)) => &["user", "global"],
+        (true, None) if global => &["user", "global"],
+        _ if global => &["global"],
+        _ => &["project"],
+    }
+}
+
+/// Project-local Codex syncs read `.mcp.json` but write `.codex/config.toml`.
+fn mcp_server_target_path(
+    config: &Config,
+    target_config_path: &Path,
+    agent_context: AgentContext,
+) -> PathBuf {
+    if agent_context.is_codex && !config.is_global {
+        if let Some(settings_path) = config.project_settings_path.as_ref() {
+            return settings_path.clone();
+        }
+    }
+    target_config_path.to_path_buf()
+}
+
+fn mcp_ownership_target_key(
+    config: &Config,
+    target_config_path: &Path,
+    agent_context: AgentContext,
+) -> Result<String> {
+    let target_path = mcp_server_target_path(config, target_config_path, agent_context);
+    if is_claude_code_local_scope(agent_context, config.is_global) {
+        let project_key = claude_code_local_project_key()?;
+        return mcp_ownership::target_key(&target_path, Some(&project_key));
+    }
+    mcp_ownership::target_key(&target_path, None)
+}
+
+fn is_claude_code_local_scope(agent_context: AgentContext, global: bool) -> bool {
+    agent_context.is_claude_code
+        && !global
+        && agent_context.claude_code_scope == Some(ClaudeCodeScope::Local)
+}
+
+fn claude_code_local_project_key() -> Result<String> {
+    let project_dir = std::env::current_dir().context("Failed to determine current directory")?;
+    Ok(project_dir.to_string_lossy().to_string())
+}
+
+fn remove_stale_mcp_servers(claude_config: &mut ClaudeConfig, stale: &BTreeSet<String>) {
+    let Some(servers) = claude_config.mcp_servers.as_mut() else {
+        return;
+    };
+    for name in stale {
+        if servers.remove(name).is_some() {
+            debug!("Pruned stale MCP server: {name}");
+        }
+    }
 }
 
 /// Read Codex-specific settings
@@ -243,7 +550,7 @@ fn log_settings_fields(s: &Settings) {
     }
 }
 
-/// Create backup if requested and file exists
+/// Create backup if requested and file exists, placed and pruned per `backups`
 ///
 /// # Errors
 ///
@@ -256,6 +563,7 @@ pub fn handle_backup(
     read_result: &ReadConfigResult,
     agent_context: AgentContext,
     codex_global: CodexGlobalSyncOptions,
+    backups: &BackupSettings,
 ) -> Result<()> {
     let backup_paths =
         collect_backup_paths(config, target_config_path, read_result, agent_context, codex_global)?;
@@ -266,7 +574,7 @@ pub fn handle_backup(
         }
 
         debug!("Creating backup of {}", path.display());
-        match writer::backup_file(&path) {
+        match writer::backup_file(&path, backups) {
             Ok(Some(backup_path)) => {
                 debug!("Backup created: {backup_path}");
             },
@@ -334,20 +642,7 @@ fn collect_backup_paths(
 }
 
 fn should_write_claude_project_settings(read_result: &ReadConfigResult) -> bool {
-    let Some(settings) = read_result.settings.as_ref() else {
-        return false;
-    };
-
-    let mut settings_to_write = settings.clone();
-    settings_to_write.mcp_servers = None;
-
-    settings_to_write.api_key_helper.is_some()
-        || settings_to_write.cleanup_period_days.is_some()
-        || settings_to_write.env.is_some()
-        || settings_to_write.include_co_authored_by.is_some()
-        || settings_to_write.permissions.is_some()
-        || settings_to_write.preferred_notif_channel.is_some()
-        || !settings_to_write.extra.is_empty()
+    read_result.settings.as_ref().and_then(settings_without_mcp_servers).is_some()
 }
 
 /// Merge configurations and settings
@@ -360,18 +655,23 @@ pub fn merge_all_configs(
     read_result: &ReadConfigResult,
     agent_context: AgentContext,
     global: bool,
+    strategy: MergeStrategy,
 ) -> Result<()> {
     // Merge MCP servers
     debug!("Merging configurations");
-    if agent_context.is_claude_code
-        && !global
-        && agent_context.claude_code_scope == Some(ClaudeCodeScope::Local)
-    {
-        merge_claude_code_local_mcp_servers(claude_config, &read_result.mcp_servers)?;
+    let mcp_servers = servers_for_target(&read_result.mcp_servers, agent_context, global);
+    if is_claude_code_local_scope(agent_context, global) {
+        merge_claude_code_local_mcp_servers(
+            claude_config,
+            &mcp_servers,
+            &read_result.stale_mcp_servers,
+            strategy,
+        )?;
     } else {
         let original_count =
             claude_config.mcp_servers.as_ref().map_or(0, std::collections::HashMap::len);
-        merge_configs(claude_config, &read_result.mcp_servers, MergeStrategy::default())?;
+        merge_configs(claude_config, &mcp_servers, strategy)?;
+        remove_stale_mcp_servers(claude_config, &read_result.stale_mcp_servers);
         let new_count =
             claude_config.mcp_servers.as_ref().map_or(0, std::collections::HashMap::len);
         debug!("Merged configuration: {} -> {} server(s)", original_count, new_count);
@@ -380,7 +680,7 @@ pub fn merge_all_configs(
     if agent_context.is_gemini {
         if let Some(settings) = read_result.settings.as_ref() {
             debug!("Merging Gemini settings");
-            merge_gemini_settings_into_config(claude_config, settings)?;
+            merge_gemini_settings_into_config(claude_config, settings, strategy)?;
             debug!("Gemini settings merged successfully");
         }
     }
@@ -407,9 +707,10 @@ pub fn merge_all_configs(
 fn merge_claude_code_local_mcp_servers(
     claude_config: &mut ClaudeConfig,
     mcp_servers: &McpServersConfig,
+    stale_mcp_servers: &BTreeSet<String>,
+    strategy: MergeStrategy,
 ) -> Result<()> {
-    let project_dir = std::env::current_dir().context("Failed to determine current directory")?;
-    let project_key = project_dir.to_string_lossy().to_string();
+    let project_key = claude_code_local_project_key()?;
 
     let mut project_config = claude_config
         .other
@@ -420,7 +721,8 @@ fn merge_claude_code_local_mcp_servers(
 
     let original_count =
         project_config.mcp_servers.as_ref().map_or(0, std::collections::HashMap::len);
-    merge_configs(&mut project_config, mcp_servers, MergeStrategy::default())?;
+    merge_configs(&mut project_config, mcp_servers, strategy)?;
+    remove_stale_mcp_servers(&mut project_config, stale_mcp_servers);
     let new_count = project_config.mcp_servers.as_ref().map_or(0, std::collections::HashMap::len);
 
     debug!(
@@ -439,10 +741,11 @@ fn merge_claude_code_local_mcp_servers(
 fn merge_gemini_settings_into_config(
     claude_config: &mut ClaudeConfig,
     settings: &Settings,
+    strategy: MergeStrategy,
 ) -> Result<()> {
     if let Some(mcp_servers) = settings.mcp_servers.as_ref() {
         let settings_mcp = McpServersConfig { mcp_servers: mcp_servers.clone() };
-        merge_configs(claude_config, &settings_mcp, MergeStrategy::default())?;
+        merge_configs(claude_config, &settings_mcp, strategy)?;
     }
 
     let mut settings_value: Value = serde_json::to_value(settings)
@@ -511,11 +814,163 @@ fn migrate_key_into_category(
     }
 }
 
+const MCP_SERVERS_LABEL: &str = "MCP servers";
+const SETTINGS_LABEL: &str = "Settings";
+const CODEX_SETTINGS_LABEL: &str = "Settings with MCP servers";
+const GEMINI_SETTINGS_LABEL: &str = "Gemini settings";
+const CLAUDE_CONFIG_LABEL: &str = "Configuration";
+
+/// A file the sync would write, rendered exactly as it lands on disk.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedWrite {
+    pub label: &'static str,
+    pub path: PathBuf,
+    pub content: String,
+}
+
+/// How a planned write compares with the file currently on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlannedWriteStatus {
+    Created,
+    Modified,
+    Unchanged,
+}
+
+impl PlannedWriteStatus {
+    #[must_use]
+    pub const fn marker(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Modified => "modified",
+            Self::Unchanged => "unchanged",
+        }
+    }
+}
+
+impl PlannedWrite {
+    /// Render a JSON target, patching only the members whose data changed so the
+    /// rest of an existing file keeps its bytes, indentation and key order.
+    fn json<T: serde::Serialize>(label: &'static str, path: PathBuf, value: &T) -> Result<Self> {
+        let existing = std::fs::read_to_string(&path).ok();
+        let content = render_json(existing.as_deref(), value)
+            .with_context(|| format!("Failed to serialize {}", path.display()))?;
+        Ok(Self { label, path, content })
+    }
+
+    /// Render a TOML target, editing the existing document in place when there
+    /// is one so comments, key order and inline tables survive the sync.
+    fn toml<T: serde::Serialize>(label: &'static str, path: PathBuf, value: &T) -> Result<Self> {
+        let existing = std::fs::read_to_string(&path).ok();
+        let content = render_toml(existing.as_deref(), value)
+            .with_context(|| format!("Failed to serialize {}", path.display()))?;
+        Ok(Self { label, path, content })
+    }
+
+    /// Content currently on disk, or `None` when the file does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing file cannot be read.
+    pub fn current_content(&self) -> Result<Option<String>> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => Ok(Some(content)),
+            Err(error)
+                if matches!(
+                    error.kind(),
+                    std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory
+                ) =>
+            {
+                Ok(None)
+            },
+            Err(error) => {
+                Err(error).with_context(|| format!("Failed to read {}", self.path.display()))
+            },
+        }
+    }
+
+    /// Compare the planned content with the file currently on disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing file cannot be read.
+    pub fn status(&self) -> Result<PlannedWriteStatus> {
+        Ok(match self.current_content()? {
+            None => PlannedWriteStatus::Created,
+            Some(current) if current == self.content => PlannedWriteStatus::Unchanged,
+            Some(_) => PlannedWriteStatus::Modified,
+        })
+    }
+}
+
+/// Outcome of one configuration file written (or, in a dry run, planned) by a sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileSyncOutcome {
+    pub label: &'static str,
+    pub path: PathBuf,
+    pub status: PlannedWriteStatus,
+}
+
+impl FileSyncOutcome {
+    /// Outcomes of `planned`, compared with the files currently on disk.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a current file cannot be read.
+    pub fn from_planned(planned: &[PlannedWrite]) -> Result<Vec<Self>> {
+        planned
+            .iter()
+            .map(|write| {
+                Ok(Self { label: write.label, path: write.path.clone(), status: write.status()? })
+            })
+            .collect()
+    }
+}
+
+/// Everything one agent's sync changed, as reported by `config sync --format json`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AgentSyncReport {
+    pub agent: Option<Agent>,
+    pub scope: Option<ClaudeCodeScope>,
+    /// Target the MCP servers are merged into; `None` when only assets were synced.
+    pub target_config: Option<PathBuf>,
+    pub files: Vec<FileSyncOutcome>,
+    pub pruned_mcp_servers: Vec<String>,
+    pub assets: Vec<SupportingAssetReport>,
+    pub warnings: Vec<String>,
+}
+
+/// Read the target file the MCP servers are merged into.
+///
+/// Global Codex sync renders a fresh TOML document, so it starts from an empty
+/// configuration instead of reading `~/.codex/config.toml` as JSON.
+///
+/// # Errors
+///
+/// Returns an error if the target exists but cannot be read or parsed.
+pub fn load_target_claude_config(
+    config: &Config,
+    target_config: &Path,
+    agent_context: AgentContext,
+) -> Result<ClaudeConfig> {
+    debug!("Reading target configuration");
+
+    if config.is_global && agent_context.is_codex {
+        return Ok(ClaudeConfig { mcp_servers: None, other: HashMap::new() });
+    }
+
+    reader::read_claude_config(target_config).context("Failed to read target configuration")
+}
+
 /// Handle dry run output
 ///
+/// Prints a unified diff against the current target for every file the sync
+/// would write, marking files that would be created or stay unchanged, and
+/// returns the planned writes.
+///
 /// # Errors
 ///
-/// Returns an error if serialization fails
+/// Returns an error if serialization fails or a current target cannot be read
 pub fn handle_dry_run(
     config: &Config,
     target_config_path: &Path,
@@ -523,95 +978,59 @@ pub fn handle_dry_run(
     read_result: &ReadConfigResult,
     agent_context: AgentContext,
     codex_global: CodexGlobalSyncOptions,
-) -> Result<()> {
+) -> Result<Vec<PlannedWrite>> {
     info!("Dry run mode - not writing changes");
 
-    if config.is_global {
-        print_global_dry_run(
-            target_config_path,
-            claude_config,
-            read_result,
-            agent_context,
-            codex_global.requirements,
-            codex_global.managed_config,
-        )?;
-    } else if agent_context.is_claude_code
-        && agent_context.claude_code_scope == Some(ClaudeCodeScope::Local)
-    {
-        print_claude_code_local_dry_run(
-            target_config_path,
-            claude_config,
-            read_result.settings.as_ref(),
-        )?;
-    } else {
-        print_project_local_dry_run(claude_config, read_result, agent_context)?;
-    }
+    let planned = plan_configuration_writes(
+        config,
+        claude_config,
+        target_config_path,
+        read_result,
+        agent_context,
+        codex_global,
+    )?;
+    for write in &planned {
+        print_planned_write_diff(write)?;
+    }
+
+    print_pruned_mcp_servers_dry_run(
+        &mcp_server_target_path(config, target_config_path, agent_context),
+        &read_result.stale_mcp_servers,
+    );
 
-    Ok(())
+    Ok(planned)
 }
 
-fn print_global_dry_run(
-    target_config_path: &Path,
-    claude_config: &ClaudeConfig,
-    read_result: &ReadConfigResult,
-    agent_context: AgentContext,
-    include_codex_requirements: bool,
-    include_codex_managed_config: bool,
-) -> Result<()> {
-    if agent_context.is_codex {
-        print_codex_global_dry_run(
-            target_config_path,
-            claude_config,
-            read_result.codex_settings.as_ref(),
-            include_codex_requirements,
-            include_codex_managed_config,
-        )?;
-        return Ok(());
-    }
-
-    if agent_context.is_claude_code {
-        print_claude_code_global_dry_run(
-            target_config_path,
-            claude_config,
-            read_result.settings.as_ref(),
-            agent_context.claude_code_scope,
-        )?;
-        return Ok(());
-    }
+/// Print a planned write as a unified diff against the file on disk.
+///
+/// # Errors
+///
+/// Returns an error if the current file cannot be read.
+pub fn print_planned_write_diff(write: &PlannedWrite) -> Result<()> {
+    let current = write.current_content()?;
+    let status = write.status()?;
+    println!("\n--- {} ({}) [{}] ---", write.label, write.path.display(), status.marker());
 
-    if agent_context.is_gemini {
-        let (gemini_config, _) = sanitize_claude_config_for_gemini(claude_config);
-        println!("\n--- Result (dry run): {} ---", target_config_path.display());
-        println!("{}", serde_json::to_string_pretty(&gemini_config)?);
+    if status == PlannedWriteStatus::Unchanged {
         return Ok(());
     }
 
-    println!("\n--- Result (dry run): {} ---", target_config_path.display());
-    println!("{}", serde_json::to_string_pretty(&claude_config)?);
+    let original = current.unwrap_or_default();
+    let diff = similar::TextDiff::from_lines(&original, &write.content);
+    print!("{}", diff.unified_diff().context_radius(3).header("current", "synced"));
     Ok(())
 }
 
-/// Print dry run output for project-local mode
-fn print_project_local_dry_run(
-    claude_config: &ClaudeConfig,
-    read_result: &ReadConfigResult,
-    agent_context: AgentContext,
-) -> Result<()> {
-    if agent_context.is_codex {
-        print_codex_dry_run(claude_config, read_result.codex_settings.as_ref())?;
-    } else if agent_context.is_gemini {
-        print_gemini_dry_run(claude_config)?;
-    } else {
-        print_other_agent_dry_run(claude_config, read_result.settings.as_ref())?;
+fn print_pruned_mcp_servers_dry_run(target_path: &Path, stale_mcp_servers: &BTreeSet<String>) {
+    if stale_mcp_servers.is_empty() {
+        return;
     }
-    Ok(())
-}
 
-fn print_gemini_dry_run(claude_config: &ClaudeConfig) -> Result<()> {
-    let (gemini_config, _) = sanitize_claude_config_for_gemini(claude_config);
-    println!("\n--- Gemini settings (.gemini/settings.json) ---");
-    println!("{}", serde_json::to_string_pretty(&gemini_config)?);
-    Ok(())
+    println!("\n--- Pruned MCP servers ({}) ---", target_path.display());
+    println!("Would prune {} stale MCP server(s):", stale_mcp_servers.len());
+    for name in stale_mcp_servers {
+        println!("  - {name}");
+    }
 }
 
 pub fn print_supporting_assets_dry_run(report: &SupportingAssetSyncReport) {
@@ -638,94 +1057,6 @@ pub fn print_supporting_assets_dry_run(report: &SupportingAssetSyncReport) {
     }
 }
 
-fn print_claude_code_global_dry_run(
-    target_config_path: &Path,
-    claude_config: &ClaudeConfig,
-    source_settings: Option<&Settings>,
-    scope: Option<ClaudeCodeScope>,
-) -> Result<()> {
-    let claude_settings_path = claude_code_settings_path(scope)?;
-    let settings_to_write =
-        build_claude_code_settings_to_write(&claude_settings_path, source_settings)?;
-
-    println!("\n--- MCP servers ({}) ---", target_config_path.display());
-    println!("{}", serde_json::to_string_pretty(&claude_config)?);
-
-    println!("\n--- Settings ({}) ---", claude_settings_path.display());
-    println!("{}", serde_json::to_string_pretty(&settings_to_write)?);
-
-    Ok(())
-}
-
-fn print_claude_code_local_dry_run(
-    target_config_path: &Path,
-    claude_config: &ClaudeConfig,
-    source_settings: Option<&Settings>,
-) -> Result<()> {
-    let local_settings_path = claude_code_local_settings_path()?;
-    let settings_to_write =
-        build_claude_code_settings_to_write(&local_settings_path, source_settings)?;
-
-    println!("\n--- MCP servers ({}) ---", target_config_path.display());
-    println!("{}", serde_json::to_string_pretty(&claude_config)?);
-
-    println!("\n--- Settings ({}) ---", local_settings_path.display());
-    println!("{}", serde_json::to_string_pretty(&settings_to_write)?);
-
-    Ok(())
-}
-
-/// Print Codex-specific dry run output
-fn print_codex_dry_run(
-    claude_config: &ClaudeConfig,
-    codex_settings: Option<&CodexSettings>,
-) -> Result<()> {
-    let settings_location = ".codex/config.toml";
-    println!("\n--- Settings with MCP servers ({settings_location}) ---");
-
-    if let Some(mut codex_settings_copy) = codex_settings.cloned() {
-        if let Some(ref mcp_servers) = claude_config.mcp_servers {
-            codex_settings_copy.mcp_servers = Some(convert_mcp_to_toml(mcp_servers));
-        }
-        println!("{}", toml::to_string_pretty(&codex_settings_copy)?);
-    } else {
-        let new_codex_settings = create_codex_settings_with_mcp_servers(claude_config);
-        println!("{}", toml::to_string_pretty(&new_codex_settings)?);
-    }
-    Ok(())
-}
-
-fn print_codex_global_dry_run(
-    target_config_path: &Path,
-    claude_config: &ClaudeConfig,
-    codex_settings: Option<&CodexSettings>,
-    include_codex_requirements: bool,
-    include_codex_managed_config: bool,
-) -> Result<()> {
-    println!("\n--- Settings with MCP servers ({}) ---", target_config_path.display());
-
-    let codex_to_write =
-        build_codex_settings_for_global(target_config_path, claude_config, codex_settings)?;
-    println!("{}", toml::to_string_pretty(&codex_to_write)?);
-
-    if include_codex_requirements {
-        let (_, requirements) = read_codex_requirements_from_config_dir()?;
-        let target_path = agent_paths::codex_requirements_path();
-
-        println!("\n--- requirements.toml ({}) ---", target_path.display());
-        println!("{}", toml::to_string_pretty(&requirements)?);
-    }
-
-    if include_codex_managed_config {
-        let (_, managed_config) = read_codex_managed_config_from_config_dir()?;
-        let target_path = agent_paths::codex_managed_config_path();
-
-        println!("\n--- managed_config.toml ({}) ---", target_path.display());
-        println!("{}", toml::to_string_pretty(&managed_config)?);
-    }
-    Ok(())
-}
-
 fn read_codex_requirements_from_config_dir() -> Result<(PathBuf, TomlValue)> {
     let config_dir =
         Config::get_config_dir().context("Failed to determine Claudius config directory")?;
@@ -758,23 +1089,6 @@ fn read_codex_requirements_from_config_dir() -> Result<(PathBuf, TomlValue)> {
     Ok((source_path, requirements))
 }
 
-fn write_codex_requirements() -> Result<()> {
-    let (source_path, requirements) = read_codex_requirements_from_config_dir()?;
-    let target_path = agent_paths::codex_requirements_path();
-
-    info!("Writing Codex requirements from {} to {}", source_path.display(), target_path.display(),);
-
-    if let Some(parent) = target_path.parent() {
-        std::fs::create_dir_all(parent)
-            .context("Failed to create requirements.toml parent directory")?;
-    }
-
-    std::fs::write(&target_path, toml::to_string_pretty(&requirements)?)
-        .with_context(|| format!("Failed to write {}", target_path.display()))?;
-
-    Ok(())
-}
-
 fn read_codex_managed_config_from_config_dir() -> Result<(PathBuf, TomlValue)> {
     let config_dir =
         Config::get_config_dir().context("Failed to determine Claudius config directory")?;
@@ -808,51 +1122,6 @@ fn read_codex_managed_config_from_config_dir() -> Result<(PathBuf, TomlValue)> {
     Ok((source_path, managed_config))
 }
 
-fn write_codex_managed_config() -> Result<()> {
-    let (source_path, managed_config) = read_codex_managed_config_from_config_dir()?;
-    let target_path = agent_paths::codex_managed_config_path();
-
-    info!(
-        "Writing Codex managed_config from {} to {}",
-        source_path.display(),
-        target_path.display(),
-    );
-
-    if let Some(parent) = target_path.parent() {
-        std::fs::create_dir_all(parent)
-            .context("Failed to create managed_config.toml parent directory")?;
-    }
-
-    std::fs::write(&target_path, toml::to_string_pretty(&managed_config)?)
-        .with_context(|| format!("Failed to write {}", target_path.display()))?;
-
-    Ok(())
-}
-
-/// Print dry run output for non-Claude, non-Codex agents
-fn print_other_agent_dry_run(
-    claude_config: &ClaudeConfig,
-    settings: Option<&Settings>,
-) -> Result<()> {
-    // Print MCP servers
-    println!("\n--- MCP servers (.mcp.json) ---");
-    let mcp_only =
-        McpServersConfig { mcp_servers: claude_config.mcp_servers.clone().unwrap_or_default() };
-    println!("{}", serde_json::to_string_pretty(&mcp_only)?);
-
-    // Print settings if present
-    if let Some(settings_ref) = settings {
-        let mut settings_copy = settings_ref.clone();
-        settings_copy.mcp_servers = None;
-
-        let settings_location = ".claude/settings.json";
-        println!("\n--- Settings ({settings_location}) ---");
-        println!("{}", serde_json::to_string_pretty(&settings_copy)?);
-    }
-
-    Ok(())
-}
-
 /// Merge source Codex settings into existing settings
 /// Only non-None fields from source override existing fields
 fn merge_codex_settings(target: &mut CodexSettings, source: &CodexSettings) {
@@ -1001,12 +1270,17 @@ fn create_codex_settings_with_mcp_servers(claude_config: &ClaudeConfig) -> Codex
 
 /// Write configurations to disk
 ///
+/// All files are staged and validated first, then committed atomically as one
+/// transaction together with the `ownership` ledger; see [`sync_transaction`].
+/// Returns how each file compared with its previous content.
+///
 /// # Errors
 ///
 /// Returns an error if:
 /// - Unable to write configuration files
 /// - Unable to create parent directories
 /// - Serialization fails
+#[allow(clippy::too_many_arguments)]
 pub fn write_configurations(
     config: &Config,
     claude_config: &ClaudeConfig,
@@ -1014,89 +1288,167 @@ pub fn write_configurations(
     read_result: &ReadConfigResult,
     agent_context: AgentContext,
     codex_global: CodexGlobalSyncOptions,
-) -> Result<()> {
+    ownership: sync_transaction::StagedFile,
+    undo: &UndoRecorder,
+) -> Result<Vec<FileSyncOutcome>> {
+    let planned = plan_configuration_writes(
+        config,
+        claude_config,
+        target_config_path,
+        read_result,
+        agent_context,
+        codex_global,
+    )?;
+    let outcomes = FileSyncOutcome::from_planned(&planned)?;
+    commit_planned_writes(&planned, Some(ownership), undo)?;
+
+    info!("Configuration updated successfully");
+    Ok(outcomes)
+}
+
+/// Render every file the sync would write without touching the disk.
+///
+/// # Errors
+///
+/// Returns an error if existing targets or source files cannot be read, or
+/// serialization fails.
+pub fn plan_configuration_writes(
+    config: &Config,
+    claude_config: &ClaudeConfig,
+    target_config_path: &Path,
+    read_result: &ReadConfigResult,
+    agent_context: AgentContext,
+    codex_global: CodexGlobalSyncOptions,
+) -> Result<Vec<PlannedWrite>> {
     if config.is_global {
-        write_global_configurations(
+        plan_global_writes(
             claude_config,
             target_config_path,
             read_result,
             agent_context,
-            codex_global.requirements,
-            codex_global.managed_config,
-        )?;
+            codex_global,
+        )
     } else {
-        write_project_local_configurations(
+        plan_project_local_writes(
             config,
             claude_config,
             target_config_path,
             read_result,
             agent_context,
-        )?;
+        )
     }
+}
 
-    info!("Configuration updated successfully");
-    Ok(())
+/// Commit the planned files, and `ownership` when given, as a single
+/// transaction, rolling back on failure.
+///
+/// # Errors
+///
+/// Returns an error if staging or writing fails; files already written are
+/// restored before the error is returned.
+pub fn commit_planned_writes(
+    planned: &[PlannedWrite],
+    ownership: Option<sync_transaction::StagedFile>,
+    undo: &UndoRecorder,
+) -> Result<()> {
+    let staged = planned
+        .iter()
+        .map(|write| {
+            info!("Writing {} to {}", write.label, write.path.display());
+            sync_transaction::StagedFile {
+                path: write.path.clone(),
+                content: Some(write.content.clone()),
+            }
+        })
+        .chain(ownership)
+        .collect::<Vec<_>>();
+    let config_dir = Config::get_config_dir()?;
+    sync_transaction::commit(&config_dir, &staged, undo)
 }
 
-/// Write configurations in global mode
-fn write_global_configurations(
+/// Plan writes in global mode
+fn plan_global_writes(
     claude_config: &ClaudeConfig,
     target_config_path: &Path,
     read_result: &ReadConfigResult,
     agent_context: AgentContext,
-    include_codex_requirements: bool,
-    include_codex_managed_config: bool,
-) -> Result<()> {
+    codex_global: CodexGlobalSyncOptions,
+) -> Result<Vec<PlannedWrite>> {
     if agent_context.is_claude_code {
-        write_claude_code_global(
+        let settings_path = claude_code_settings_path(agent_context.claude_code_scope)?;
+        let settings_to_write =
+            build_claude_code_settings_to_write(&settings_path, read_result.settings.as_ref())?;
+        return Ok(vec![
+            PlannedWrite::json(MCP_SERVERS_LABEL, target_config_path.to_path_buf(), claude_config)?,
+            PlannedWrite::json(SETTINGS_LABEL, settings_path, &settings_to_write)?,
+        ]);
+    }
+
+    if agent_context.is_codex {
+        return plan_codex_global_writes(
             claude_config,
             target_config_path,
-            read_result.settings.as_ref(),
-            agent_context.claude_code_scope,
-        )?;
-    } else if agent_context.is_codex {
-        write_codex_global(claude_config, target_config_path, read_result.codex_settings.as_ref())?;
-        if include_codex_requirements {
-            write_codex_requirements()?;
-        }
-        if include_codex_managed_config {
-            write_codex_managed_config()?;
-        }
-    } else if agent_context.is_gemini {
+            read_result,
+            codex_global,
+        );
+    }
+
+    if agent_context.is_gemini {
         let (gemini_config, _) = sanitize_claude_config_for_gemini(claude_config);
-        info!("Writing updated configuration");
-        writer::write_claude_config(target_config_path, &gemini_config)
-            .context("Failed to write Gemini configuration")?;
-    } else {
-        info!("Writing updated configuration");
-        writer::write_claude_config(target_config_path, claude_config)
-            .context("Failed to write Claude configuration")?;
+        return Ok(vec![PlannedWrite::json(
+            GEMINI_SETTINGS_LABEL,
+            target_config_path.to_path_buf(),
+            &gemini_config,
+        )?]);
     }
-    Ok(())
+
+    Ok(vec![PlannedWrite::json(
+        CLAUDE_CONFIG_LABEL,
+        target_config_path.to_path_buf(),
+        claude_config,
+    )?])
 }
 
-/// Write Claude Code configuration in global mode
-fn write_claude_code_global(
+/// Plan Codex writes in global mode, including optional admin files
+fn plan_codex_global_writes(
     claude_config: &ClaudeConfig,
     target_config_path: &Path,
-    settings: Option<&Settings>,
-    scope: Option<ClaudeCodeScope>,
-) -> Result<()> {
-    let claude_settings_path = claude_code_settings_path(scope)?;
-    let settings_to_write = build_claude_code_settings_to_write(&claude_settings_path, settings)?;
-
-    info!("Writing MCP servers to {}", target_config_path.display());
-    writer::write_claude_config(target_config_path, claude_config)
-        .context("Failed to write ~/.claude.json")?;
-
-    info!("Writing settings to {}", claude_settings_path.display());
-    if let Some(parent) = claude_settings_path.parent() {
-        std::fs::create_dir_all(parent).context("Failed to create ~/.claude directory")?;
-    }
-    writer::write_settings(&claude_settings_path, &settings_to_write)
-        .context("Failed to write ~/.claude/settings.json")?;
-
-    Ok(())
+    read_result: &ReadConfigResult,
+    codex_global: CodexGlobalSyncOptions,
+) -> Result<Vec<PlannedWrite>> {
+    let codex_to_write = build_codex_settings_for_global(
+        target_config_path,
+        claude_config,
+        read_result.codex_settings.as_ref(),
+        &read_result.stale_mcp_servers,
+    )?;
+    let mut planned = vec![PlannedWrite::toml(
+        CODEX_SETTINGS_LABEL,
+        target_config_path.to_path_buf(),
+        &codex_to_write,
+    )?];
+
+    if codex_global.requirements {
+        let (source_path, requirements) = read_codex_requirements_from_config_dir()?;
+        debug!("Rendering Codex requirements from {}", source_path.display());
+        planned.push(PlannedWrite::toml(
+            "requirements.toml",
+            agent_paths::codex_requirements_path(),
+            &requirements,
+        )?);
+    }
+
+    if codex_global.managed_config {
+        let (source_path, managed_config) = read_codex_managed_config_from_config_dir()?;
+        debug!("Rendering Codex managed_config from {}", source_path.display());
+        planned.push(PlannedWrite::toml(
+            "managed_config.toml",
+            agent_paths::codex_managed_config_path(),
+            &managed_config,
+        )?);
+    }
+
+    Ok(planned)
 }
 
 fn claude_code_settings_path(scope: Option<ClaudeCodeScope>) -> Result<PathBuf> {
@@ -1128,16 +1480,7 @@ fn build_claude_code_settings_to_write(
         },
         (Some(existing), None) => existing,
         (None, Some(source)) => source.clone(),
-        (None, None) => Settings {
-            api_key_helper: None,
-            cleanup_period_days: None,
-            env: None,
-            include_co_authored_by: None,
-            permissions: None,
-            preferred_notif_channel: None,
-            mcp_servers: None,
-            extra: HashMap::new(),
-        },
+        (None, None) => empty_settings(),
     };
 
     settings_to_write.mcp_servers = None;
@@ -1168,30 +1511,11 @@ fn merge_claude_code_settings(target: &mut Settings, source: &Settings) {
     deep_merge_json_maps(&mut target.extra, &source.extra);
 }
 
-/// Write Codex configuration in global mode
-fn write_codex_global(
-    claude_config: &ClaudeConfig,
-    target_config_path: &Path,
-    codex_settings: Option<&CodexSettings>,
-) -> Result<()> {
-    let codex_to_write =
-        build_codex_settings_for_global(target_config_path, claude_config, codex_settings)?;
-    info!("Writing settings to {}", target_config_path.display());
-
-    if let Some(parent) = target_config_path.parent() {
-        std::fs::create_dir_all(parent).context("Failed to create .codex directory")?;
-    }
-
-    writer::write_codex_settings(target_config_path, &codex_to_write)
-        .context("Failed to write Codex settings")?;
-
-    Ok(())
-}
-
 fn build_codex_settings_for_global(
     target_config_path: &Path,
     claude_config: &ClaudeConfig,
     codex_settings: Option<&CodexSettings>,
+    stale_mcp_servers: &BTreeSet<String>,
 ) -> Result<CodexSettings> {
     // Read existing Codex configuration from target location.
     let existing_codex = reader::read_codex_settings(target_config_path)
@@ -1221,159 +1545,116 @@ fn build_codex_settings_for_global(
         merge_mcp_server_toml_maps(&mut merged_mcp_servers, &new_toml_servers);
     }
 
+    merged_mcp_servers.retain(|name, _| !stale_mcp_servers.contains(name));
     codex_to_write.mcp_servers = (!merged_mcp_servers.is_empty()).then_some(merged_mcp_servers);
     Ok(codex_to_write)
 }
 
-/// Write configurations in project-local mode
-fn write_project_local_configurations(
+/// Plan writes in project-local mode
+fn plan_project_local_writes(
     config: &Config,
     claude_config: &ClaudeConfig,
     target_config_path: &Path,
     read_result: &ReadConfigResult,
     agent_context: AgentContext,
-) -> Result<()> {
-    if agent_context.is_claude_code
-        && agent_context.claude_code_scope == Some(ClaudeCodeScope::Local)
-    {
-        write_claude_code_local(target_config_path, claude_config, read_result.settings.as_ref())?;
+) -> Result<Vec<PlannedWrite>> {
+    if is_claude_code_local_scope(agent_context, false) {
+        plan_claude_code_local_writes(
+            target_config_path,
+            claude_config,
+            read_result.settings.as_ref(),
+        )
     } else if agent_context.is_claude {
-        write_claude_project_local(
+        plan_claude_project_local_writes(
             config,
             target_config_path,
             claude_config,
             read_result.settings.as_ref(),
-        )?;
+        )
     } else if agent_context.is_gemini {
-        write_gemini_project_local(target_config_path, claude_config)?;
+        let (gemini_config, _) = sanitize_claude_config_for_gemini(claude_config);
+        Ok(vec![PlannedWrite::json(
+            GEMINI_SETTINGS_LABEL,
+            target_config_path.to_path_buf(),
+            &gemini_config,
+        )?])
     } else if agent_context.is_codex {
-        write_codex_project_local(config, claude_config, read_result.codex_settings.as_ref())?;
+        plan_codex_project_local_writes(config, claude_config, read_result.codex_settings.as_ref())
     } else {
-        write_other_agent_project_local(
+        plan_other_agent_project_local_writes(
             config,
             claude_config,
             target_config_path,
             read_result.settings.as_ref(),
-        )?;
+        )
     }
-    Ok(())
-}
-
-fn write_gemini_project_local(
-    target_config_path: &Path,
-    claude_config: &ClaudeConfig,
-) -> Result<()> {
-    let (gemini_config, _) = sanitize_claude_config_for_gemini(claude_config);
-    info!("Writing Gemini settings to {}", target_config_path.display());
-    writer::write_claude_config(target_config_path, &gemini_config)
-        .context("Failed to write .gemini/settings.json")?;
-    Ok(())
 }
 
-fn write_claude_code_local(
+fn plan_claude_code_local_writes(
     target_config_path: &Path,
     claude_config: &ClaudeConfig,
     settings: Option<&Settings>,
-) -> Result<()> {
-    info!("Writing local-scoped MCP servers to {}", target_config_path.display());
-    writer::write_claude_config(target_config_path, claude_config)
-        .context("Failed to write ~/.claude.json")?;
-
-    let Some(settings_ref) = settings else {
-        return Ok(());
+) -> Result<Vec<PlannedWrite>> {
+    let mut planned = vec![PlannedWrite::json(
+        MCP_SERVERS_LABEL,
+        target_config_path.to_path_buf(),
+        claude_config,
+    )?];
+
+    let Some(settings_candidate) = settings.and_then(settings_without_mcp_servers) else {
+        return Ok(planned);
     };
 
-    let mut settings_candidate = settings_ref.clone();
-    settings_candidate.mcp_servers = None;
-
-    let has_settings = settings_candidate.api_key_helper.is_some()
-        || settings_candidate.cleanup_period_days.is_some()
-        || settings_candidate.env.is_some()
-        || settings_candidate.include_co_authored_by.is_some()
-        || settings_candidate.permissions.is_some()
-        || settings_candidate.preferred_notif_channel.is_some()
-        || !settings_candidate.extra.is_empty();
-
-    if !has_settings {
-        return Ok(());
-    }
-
     let local_settings_path = claude_code_local_settings_path()?;
     let settings_to_write =
         build_claude_code_settings_to_write(&local_settings_path, Some(&settings_candidate))?;
+    planned.push(PlannedWrite::json(SETTINGS_LABEL, local_settings_path, &settings_to_write)?);
 
-    info!("Writing settings to {}", local_settings_path.display());
-    ensure_parent_directory_exists(&local_settings_path)?;
-    writer::write_settings(&local_settings_path, &settings_to_write)
-        .context("Failed to write .claude/settings.local.json")?;
-
-    Ok(())
+    Ok(planned)
 }
 
-/// Write Claude configuration in project-local mode
-fn write_claude_project_local(
+/// Plan Claude writes in project-local mode
+fn plan_claude_project_local_writes(
     config: &Config,
     target_config_path: &Path,
     claude_config: &ClaudeConfig,
     settings: Option<&Settings>,
-) -> Result<()> {
-    // Write MCP servers to .mcp.json
+) -> Result<Vec<PlannedWrite>> {
+    let mut planned = Vec::new();
+
+    // MCP servers go to .mcp.json
     if let Some(ref mcp_servers) = claude_config.mcp_servers {
-        info!("Writing MCP servers to {}", target_config_path.display());
         let mcp_config = McpServersConfig { mcp_servers: mcp_servers.clone() };
-        writer::write_mcp_servers_config(target_config_path, &mcp_config)
-            .with_context(|| format!("Failed to write {}", target_config_path.display()))?;
-    }
-
-    // Write settings (without MCP servers) to .claude/settings.json
-    if let Some(ref settings_path) = config.project_settings_path {
-        if let Some(mut settings_to_write) = settings.cloned() {
-            // Remove MCP servers from settings - they go in .mcp.json
-            settings_to_write.mcp_servers = None;
-
-            // Check if we have any actual settings to write
-            let has_settings = settings_to_write.api_key_helper.is_some()
-                || settings_to_write.cleanup_period_days.is_some()
-                || settings_to_write.env.is_some()
-                || settings_to_write.include_co_authored_by.is_some()
-                || settings_to_write.permissions.is_some()
-                || settings_to_write.preferred_notif_channel.is_some()
-                || !settings_to_write.extra.is_empty();
-
-            if has_settings {
-                let existing_settings = reader::read_settings(settings_path)
-                    .context("Failed to read existing .claude/settings.json")?;
-
-                let mut merged_settings = existing_settings.unwrap_or_else(|| Settings {
-                    api_key_helper: None,
-                    cleanup_period_days: None,
-                    env: None,
-                    include_co_authored_by: None,
-                    permissions: None,
-                    preferred_notif_channel: None,
-                    mcp_servers: None,
-                    extra: HashMap::new(),
-                });
+        planned.push(PlannedWrite::json(
+            MCP_SERVERS_LABEL,
+            target_config_path.to_path_buf(),
+            &mcp_config,
+        )?);
+    }
+
+    // Settings (without MCP servers) go to .claude/settings.json
+    let (Some(settings_path), Some(settings_to_write)) =
+        (config.project_settings_path.as_ref(), settings.and_then(settings_without_mcp_servers))
+    else {
+        return Ok(planned);
+    };
 
-                merge_claude_code_settings(&mut merged_settings, &settings_to_write);
-                merged_settings.mcp_servers = None;
+    let existing_settings = reader::read_settings(settings_path)
+        .context("Failed to read existing .claude/settings.json")?;
+    let mut merged_settings = existing_settings.unwrap_or_else(empty_settings);
+    merge_claude_code_settings(&mut merged_settings, &settings_to_write);
+    merged_settings.mcp_servers = None;
+    planned.push(PlannedWrite::json(SETTINGS_LABEL, settings_path.clone(), &merged_settings)?);
 
-                info!("Writing settings to .claude/settings.json");
-                ensure_parent_directory_exists(settings_path)?;
-                writer::write_settings(settings_path, &merged_settings)
-                    .context("Failed to write .claude/settings.json")?;
-            }
-        }
-    }
-    Ok(())
+    Ok(planned)
 }
 
-/// Write Codex configuration in project-local mode
-fn write_codex_project_local(
+/// Plan Codex writes in project-local mode
+fn plan_codex_project_local_writes(
     config: &Config,
     claude_config: &ClaudeConfig,
     codex_settings: Option<&CodexSettings>,
-) -> Result<()> {
+) -> Result<Vec<PlannedWrite>> {
     let mut codex_to_write = codex_settings
         .map_or_else(|| create_codex_settings_with_mcp_servers(claude_config), Clone::clone);
 
@@ -1384,50 +1665,64 @@ fn write_codex_project_local(
         codex_to_write.mcp_servers = (!merged_mcp_servers.is_empty()).then_some(merged_mcp_servers);
     }
 
-    if let Some(ref settings_path) = config.project_settings_path {
-        info!("Writing merged settings and MCP servers to {}", settings_path.display());
-        ensure_parent_directory_exists(settings_path)?;
-        writer::write_codex_settings(settings_path, &codex_to_write)
-            .with_context(|| format!("Failed to write {}", settings_path.display()))?;
-    }
-    Ok(())
+    let Some(settings_path) = config.project_settings_path.as_ref() else {
+        return Ok(Vec::new());
+    };
+    Ok(vec![PlannedWrite::toml(CODEX_SETTINGS_LABEL, settings_path.clone(), &codex_to_write)?])
 }
 
-/// Write configuration for non-Claude, non-Codex agents in project-local mode
-fn write_other_agent_project_local(
+/// Plan writes for non-Claude, non-Codex agents in project-local mode
+fn plan_other_agent_project_local_writes(
     config: &Config,
     claude_config: &ClaudeConfig,
     target_config_path: &Path,
     settings: Option<&Settings>,
-) -> Result<()> {
-    // Write MCP servers
-    info!("Writing MCP servers to .mcp.json");
+) -> Result<Vec<PlannedWrite>> {
     let mcp_only =
         McpServersConfig { mcp_servers: claude_config.mcp_servers.clone().unwrap_or_default() };
-    writer::write_mcp_servers_config(target_config_path, &mcp_only)
-        .context("Failed to write .mcp.json")?;
-
-    // Write settings if present
-    if let Some(settings_ref) = settings {
-        if let Some(ref settings_path) = config.project_settings_path {
-            let mut settings_to_write = settings_ref.clone();
-            settings_to_write.mcp_servers = None;
-
-            info!("Writing settings to {}", settings_path.display());
-            ensure_parent_directory_exists(settings_path)?;
-            writer::write_settings(settings_path, &settings_to_write)
-                .context("Failed to write settings")?;
-        }
+    let mut planned =
+        vec![PlannedWrite::json(MCP_SERVERS_LABEL, target_config_path.to_path_buf(), &mcp_only)?];
+
+    if let (Some(settings_ref), Some(settings_path)) = (settings, &config.project_settings_path) {
+        let mut settings_to_write = settings_ref.clone();
+        settings_to_write.mcp_servers = None;
+        planned.push(PlannedWrite::json(
+            SETTINGS_LABEL,
+            settings_path.clone(),
+            &settings_to_write,
+        )?);
     }
-    Ok(())
+
+    Ok(planned)
 }
 
-/// Ensure parent directory exists
-fn ensure_parent_directory_exists(path: &Path) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).context("Failed to create parent directory")?;
+/// Settings without MCP servers, or `None` when nothing else is set
+fn settings_without_mcp_servers(settings: &Settings) -> Option<Settings> {
+    let mut settings_to_write = settings.clone();
+    settings_to_write.mcp_servers = None;
+
+    let has_settings = settings_to_write.api_key_helper.is_some()
+        || settings_to_write.cleanup_period_days.is_some()
+        || settings_to_write.env.is_some()
+        || settings_to_write.include_co_authored_by.is_some()
+        || settings_to_write.permissions.is_some()
+        || settings_to_write.preferred_notif_channel.is_some()
+        || !settings_to_write.extra.is_empty();
+
+    has_settings.then_some(settings_to_write)
+}
+
+fn empty_settings() -> Settings {
+    Settings {
+        api_key_helper: None,
+        cleanup_period_days: None,
+        env: None,
+        include_co_authored_by: None,
+        permissions: None,
+        preferred_notif_channel: None,
+        mcp_servers: None,
+        extra: HashMap::new(),
     }
-    Ok(())
 }
 
 fn deep_merge_toml_maps(
@@ -1516,36 +1811,86 @@ fn deep_merge_toml_value(target: &mut TomlValue, overlay: &TomlValue) {
     }
 }
 
+/// Source files for one auxiliary asset tree, resolved exactly as `config sync` resolves them.
+#[derive(Debug)]
+pub struct SupportingAssetSource {
+    pub label: &'static str,
+    pub log_label: &'static str,
+    pub target_dir: PathBuf,
+    pub mappings: Vec<asset_sync::SourceFileMapping>,
+    /// Keeps the rendered skill workspace alive while `mappings` point into it.
+    _skill_sources: Option<skills::SkillSourceSet>,
+}
+
+/// Resolve the skills, Gemini commands and agents, and Claude Code subagents
+/// that a sync would deploy for this agent context.
+#[must_use]
+pub fn collect_supporting_asset_sources(
+    config: &Config,
+    agent_context: AgentContext,
+) -> Vec<SupportingAssetSource> {
+    [
+        skills_source(config, agent_context),
+        directory_tree_source(
+            "Gemini commands",
+            "Gemini command",
+            &config.layered_source_dirs(
+                config.resolve_gemini_commands_source_dir(),
+                "commands/gemini",
+            ),
+            config.gemini_commands_target_dir(),
+        ),
+        directory_tree_source(
+            "Gemini agents",
+            "Gemini agent",
+            &config.layered_source_dirs(config.resolve_gemini_agents_source_dir(), "agents/gemini"),
+            config.gemini_agents_target_dir(),
+        ),
+        claude_code_agents_source(config, agent_context),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
 /// Sync skills and other agent-specific auxiliary content.
 #[must_use]
 pub fn sync_supporting_assets(
     config: &Config,
     agent_context: AgentContext,
     behavior: SyncBehavior,
+    undo: &UndoRecorder,
 ) -> SupportingAssetSyncReport {
     let mut report = SupportingAssetSyncReport::default();
 
-    if let Some(asset) = sync_skills_if_exists(config, agent_context, behavior) {
-        report.push(asset);
-    }
-    if let Some(asset) = sync_gemini_commands_if_exists(config, behavior) {
-        report.push(asset);
-    }
-    if let Some(asset) = sync_gemini_agents_if_exists(config, behavior) {
-        report.push(asset);
-    }
-    if let Some(asset) = sync_claude_code_agents_if_exists(config, agent_context, behavior) {
-        report.push(asset);
+    for source in collect_supporting_asset_sources(config, agent_context) {
+        debug!("Syncing {}s", source.log_label);
+        debug!("Source mappings: {}", source.mappings.len());
+        debug!("Target: {}", source.target_dir.display());
+
+        match asset_sync::sync_managed_tree(&source.target_dir, &source.mappings, behavior, undo) {
+            Ok(sync_report) => {
+                log_supporting_asset_result(
+                    source.log_label,
+                    &sync_report.synced_files,
+                    &sync_report.pruned_files,
+                    behavior.dry_run,
+                );
+                report.push(SupportingAssetReport {
+                    label: source.label,
+                    target_dir: sync_report.target_dir,
+                    synced_files: sync_report.synced_files,
+                    pruned_files: sync_report.pruned_files,
+                });
+            },
+            Err(e) => warn!("Failed to sync {}s: {}", source.log_label, e),
+        }
     }
 
     report
 }
 
-fn sync_skills_if_exists(
-    config: &Config,
-    agent_context: AgentContext,
-    behavior: SyncBehavior,
-) -> Option<SupportingAssetReport> {
+fn skills_source(config: &Config, agent_context: AgentContext) -> Option<SupportingAssetSource> {
     if config.agent == Some(Agent::Codex) {
         debug!(
             "Skipping Codex skills during config sync; use `claudius skills sync --agent codex`"
@@ -1565,39 +1910,22 @@ fn sync_skills_if_exists(
 
     let source_set = collect_skill_source_set(config)?;
     log_skill_source_details(config, &source_set);
-    debug!("Syncing skills");
-    debug!("Source mappings: {}", source_set.mappings.len());
-    debug!("Target: {}", config.skills_target_dir.display());
-
-    match skills::sync_skill_mappings_with_options(
-        &source_set.mappings,
-        &config.skills_target_dir,
-        behavior,
-    ) {
-        Ok(sync_report) => {
-            log_supporting_asset_result(
-                "skill",
-                &sync_report.synced_files,
-                &sync_report.pruned_files,
-                behavior.dry_run,
-            );
-            Some(SupportingAssetReport {
-                label: "Skills",
-                target_dir: sync_report.target_dir,
-                synced_files: sync_report.synced_files,
-                pruned_files: sync_report.pruned_files,
-            })
-        },
-        Err(e) => {
-            warn!("Failed to sync skills: {}", e);
-            None
-        },
-    }
+    Some(SupportingAssetSource {
+        label: "Skills",
+        log_label: "skill",
+        target_dir: config.skills_target_dir.clone(),
+        mappings: source_set.mappings.clone(),
+        _skill_sources: Some(source_set),
+    })
 }
 
 fn collect_skill_source_set(config: &Config) -> Option<skills::SkillSourceSet> {
     match config.config_root_dir().and_then(|config_root| {
-        skills::collect_claudius_skill_source_set(config_root, config.agent)
+        skills::collect_layered_skill_source_set(
+            config_root,
+            config.project_layer_dir.as_deref(),
+            config.agent,
+        )
     }) {
         Ok(source_set) => Some(source_set),
         Err(e) => {
@@ -1630,57 +1958,10 @@ fn log_skill_source_details(config: &Config, source_set: &skills::SkillSourceSet
     }
 }
 
-fn sync_gemini_commands_if_exists(
-    config: &Config,
-    behavior: SyncBehavior,
-) -> Option<SupportingAssetReport> {
-    let source_dir = config.resolve_gemini_commands_source_dir();
-    let target_dir = match config.gemini_commands_target_dir() {
-        Ok(Some(path)) => path,
-        Ok(None) => return None,
-        Err(e) => {
-            warn!("Failed to determine Gemini commands target directory: {}", e);
-            return None;
-        },
-    };
-
-    sync_directory_tree_if_exists(
-        "Gemini command",
-        "Gemini commands",
-        source_dir.as_deref(),
-        &target_dir,
-        behavior,
-    )
-}
-
-fn sync_gemini_agents_if_exists(
-    config: &Config,
-    behavior: SyncBehavior,
-) -> Option<SupportingAssetReport> {
-    let source_dir = config.resolve_gemini_agents_source_dir();
-    let target_dir = match config.gemini_agents_target_dir() {
-        Ok(Some(path)) => path,
-        Ok(None) => return None,
-        Err(e) => {
-            warn!("Failed to determine Gemini agents target directory: {}", e);
-            return None;
-        },
-    };
-
-    sync_directory_tree_if_exists(
-        "Gemini agent",
-        "Gemini agents",
-        source_dir.as_deref(),
-        &target_dir,
-        behavior,
-    )
-}
-
-fn sync_claude_code_agents_if_exists(
+fn claude_code_agents_source(
     config: &Config,
     agent_context: AgentContext,
-    behavior: SyncBehavior,
-) -> Option<SupportingAssetReport> {
+) -> Option<SupportingAssetSource> {
     if !agent_context.is_claude_code
         || matches!(
             agent_context.claude_code_scope,
@@ -1690,53 +1971,47 @@ fn sync_claude_code_agents_if_exists(
         return None;
     }
 
-    let source_dir = config.resolve_claude_code_agents_source_dir();
-    let target_dir = match config.claude_code_agents_target_dir() {
+    directory_tree_source(
+        "Claude Code subagents",
+        "Claude Code subagent",
+        &config.layered_source_dirs(
+            config.resolve_claude_code_agents_source_dir(),
+            "agents/claude-code",
+        ),
+        config.claude_code_agents_target_dir(),
+    )
+}
+
+fn directory_tree_source(
+    label: &'static str,
+    log_label: &'static str,
+    source_dirs: &[PathBuf],
+    target: Result<Option<PathBuf>>,
+) -> Option<SupportingAssetSource> {
+    let target_dir = match target {
         Ok(Some(path)) => path,
         Ok(None) => return None,
         Err(e) => {
-            warn!("Failed to determine Claude Code agents target directory: {}", e);
+            warn!("Failed to determine {log_label}s target directory: {}", e);
             return None;
         },
     };
 
-    sync_directory_tree_if_exists(
-        "Claude Code subagent",
-        "Claude Code subagents",
-        source_dir.as_deref(),
-        &target_dir,
-        behavior,
-    )
-}
-
-fn sync_directory_tree_if_exists(
-    log_label: &str,
-    report_label: &'static str,
-    source_dir: Option<&Path>,
-    target_dir: &Path,
-    behavior: SyncBehavior,
-) -> Option<SupportingAssetReport> {
-    debug!("Syncing {log_label}s");
-    if let Some(path) = source_dir {
-        debug!("Source: {}", path.display());
-    }
-    debug!("Target: {}", target_dir.display());
-
-    match sync_directory_tree(source_dir, target_dir, behavior) {
-        Ok(sync_report) => {
-            log_supporting_asset_result(
-                log_label,
-                &sync_report.synced_files,
-                &sync_report.pruned_files,
-                behavior.dry_run,
-            );
-            Some(SupportingAssetReport {
-                label: report_label,
-                target_dir: sync_report.target_dir,
-                synced_files: sync_report.synced_files,
-                pruned_files: sync_report.pruned_files,
-            })
-        },
+    for path in source_dirs {
+        debug!("{label} source: {}", path.display());
+    }
+    let collected = source_dirs
+        .iter()
+        .map(|path| asset_sync::collect_directory_tree_mappings(path))
+        .collect::<Result<Vec<_>>>();
+    match collected {
+        Ok(layers) => Some(SupportingAssetSource {
+            label,
+            log_label,
+            target_dir,
+            mappings: source_layers::layer_tree_mappings(layers),
+            _skill_sources: None,
+        }),
         Err(e) => {
             warn!("Failed to sync {log_label}s: {}", e);
             None
@@ -1744,16 +2019,6 @@ fn sync_directory_tree_if_exists(
     }
 }
 
-fn sync_directory_tree(
-    source_dir: Option<&Path>,
-    target_dir: &Path,
-    behavior: SyncBehavior,
-) -> Result<asset_sync::ManagedTreeSyncReport> {
-    let mappings =
-        source_dir.map_or_else(|| Ok(Vec::new()), asset_sync::collect_directory_tree_mappings)?;
-    asset_sync::sync_managed_tree(target_dir, &mappings, behavior)
-}
-
 fn log_supporting_asset_result(
     label: &str,
     synced_files: &[String],
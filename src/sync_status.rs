@@ -0,0 +1,407 @@
+#![allow(missing_docs)]
+
+//! Read-only drift detection for `claudius config status`.
+//!
+//! Status runs the same read/merge/plan pipeline as `config sync`, then
+//! compares every planned file and every managed asset tree with what is on
+//! disk. MCP servers Claudius synced earlier that the sources no longer define
+//! are reported while they remain in a target, since `config sync --prune`
+//! would remove them. Nothing is written, so the command is safe to run from
+//! hooks and cron.
+
+use crate::asset_sync::inspect_managed_tree;
+use crate::config::Config;
+use crate::merge::MergeStrategy;
+use crate::sync_operations::{
+    adjust_sources, collect_stale_mcp_servers, collect_supporting_asset_sources,
+    load_target_claude_config, merge_all_configs, plan_configuration_writes, read_configurations,
+    AgentContext, CodexGlobalSyncOptions, PlannedWrite, PlannedWriteStatus, ReadConfigResult,
+    SourceAdjustments, SupportingAssetSource,
+};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Process exit code used by `config status` when any target is out of date.
+///
+/// Distinct from `1` (runtime error) and `2` (usage error) so scripts can tell
+/// drift apart from a broken invocation.
+pub const DRIFT_EXIT_CODE: i32 = 3;
+
+/// Top-level keys whose members are reported individually.
+const MCP_SERVER_KEYS: &[&str] = &["mcpServers", "mcp_servers"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl ChangeKind {
+    #[must_use]
+    pub const fn marker(self) -> char {
+        match self {
+            Self::Added => '+',
+            Self::Modified => '~',
+            Self::Removed => '-',
+        }
+    }
+}
+
+/// A single key that a sync would add, change or remove.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChange {
+    pub key: String,
+    pub kind: ChangeKind,
+}
+
+/// How one configuration file compares with what a sync would write.
+#[derive(Debug, Clone)]
+pub struct FileDrift {
+    pub label: &'static str,
+    pub path: PathBuf,
+    pub status: PlannedWriteStatus,
+    pub changes: Vec<EntryChange>,
+}
+
+/// How one managed asset tree compares with its source files.
+#[derive(Debug, Clone)]
+pub struct AssetDrift {
+    pub label: &'static str,
+    pub target_dir: PathBuf,
+    /// Source files that have not been deployed yet.
+    pub missing: Vec<String>,
+    /// Deployed files whose content no longer matches the source.
+    pub modified: Vec<String>,
+    /// Previously deployed files that no longer exist in the source tree.
+    pub stale: Vec<String>,
+}
+
+impl AssetDrift {
+    #[must_use]
+    pub fn has_drift(&self) -> bool {
+        !self.missing.is_empty() || !self.modified.is_empty() || !self.stale.is_empty()
+    }
+}
+
+/// Drift report for one agent and scope.
+#[derive(Debug, Clone)]
+pub struct AgentStatus {
+    pub files: Vec<FileDrift>,
+    pub assets: Vec<AssetDrift>,
+    /// Servers Claudius synced earlier that the sources no longer define but a
+    /// target still contains.
+    pub stale_mcp_servers: Vec<String>,
+}
+
+impl AgentStatus {
+    #[must_use]
+    pub fn has_drift(&self) -> bool {
+        self.files.iter().any(|file| file.status != PlannedWriteStatus::Unchanged)
+            || self.assets.iter().any(AssetDrift::has_drift)
+            || !self.stale_mcp_servers.is_empty()
+    }
+}
+
+/// Compute what `config sync` would change for one agent without writing.
+///
+/// Interactive merges are evaluated as `merge`, i.e. as if every conflict were
+/// accepted, so status never prompts. `adjustments` carries the active profile
+/// and `config.toml` variables.
+///
+/// # Errors
+///
+/// Returns an error if sources or targets cannot be read or parsed.
+pub fn inspect_agent(
+    config: &Config,
+    mcp_servers_path: &Path,
+    target_config: &Path,
+    agent_context: AgentContext,
+    strategy: MergeStrategy,
+    adjustments: SourceAdjustments<'_>,
+) -> Result<AgentStatus> {
+    let effective_strategy = match strategy {
+        MergeStrategy::InteractiveMerge => MergeStrategy::Merge,
+        other => other,
+    };
+
+    let mut read_result = read_configurations(config, mcp_servers_path, agent_context)?;
+    adjust_sources(&mut read_result, config, mcp_servers_path, agent_context, adjustments)?;
+    let plan = |sources: &ReadConfigResult| {
+        let mut claude_config = load_target_claude_config(config, target_config, agent_context)?;
+        merge_all_configs(
+            &mut claude_config,
+            sources,
+            agent_context,
+            config.is_global,
+            effective_strategy,
+        )?;
+        plan_configuration_writes(
+            config,
+            &claude_config,
+            target_config,
+            sources,
+            agent_context,
+            CodexGlobalSyncOptions::default(),
+        )
+    };
+    let planned = plan(&read_result)?;
+
+    let stale = collect_stale_mcp_servers(config, target_config, agent_context, &read_result)?;
+    let stale_mcp_servers = if stale.is_empty() {
+        Vec::new()
+    } else {
+        read_result.stale_mcp_servers = stale;
+        let pruned = plan(&read_result)?;
+        read_result
+            .stale_mcp_servers
+            .iter()
+            .filter(|name| removed_by_prune(&planned, &pruned, name))
+            .cloned()
+            .collect()
+    };
+
+    let files = planned.iter().map(inspect_planned_write).collect::<Result<Vec<_>>>()?;
+    let assets = collect_supporting_asset_sources(config, agent_context)
+        .iter()
+        .map(inspect_asset_source)
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(AgentStatus { files, assets, stale_mcp_servers })
+}
+
+/// Whether pruning drops server `name` from one of the planned files.
+fn removed_by_prune(planned: &[PlannedWrite], pruned: &[PlannedWrite], name: &str) -> bool {
+    planned.iter().any(|write| {
+        let kept = pruned
+            .iter()
+            .find(|candidate| candidate.path == write.path)
+            .and_then(|candidate| parse_document(&candidate.path, &candidate.content))
+            .map_or(0, |document| count_server_entries(&document, name));
+        parse_document(&write.path, &write.content)
+            .is_some_and(|document| count_server_entries(&document, name) > kept)
+    })
+}
+
+/// How many MCP server maps in `document`, at any depth, define `name`.
+fn count_server_entries(document: &Value, name: &str) -> usize {
+    let Some(members) = document.as_object() else {
+        return 0;
+    };
+    members
+        .iter()
+        .map(|(key, value)| {
+            let defined = MCP_SERVER_KEYS.contains(&key.as_str())
+                && value.as_object().is_some_and(|servers| servers.contains_key(name));
+            count_server_entries(value, name).saturating_add(usize::from(defined))
+        })
+        .fold(0, usize::saturating_add)
+}
+
+/// Compare a planned write with the file on disk, listing changed entries.
+///
+/// # Errors
+///
+/// Returns an error if the current file cannot be read.
+pub fn inspect_planned_write(write: &PlannedWrite) -> Result<FileDrift> {
+    let status = write.status()?;
+    let changes = match (status, write.current_content()?) {
+        (PlannedWriteStatus::Modified, Some(current)) => {
+            match (
+                parse_document(&write.path, &current),
+                parse_document(&write.path, &write.content),
+            ) {
+                (Some(before), Some(after)) => diff_entries(&before, &after),
+                _ => Vec::new(),
+            }
+        },
+        _ => Vec::new(),
+    };
+
+    Ok(FileDrift { label: write.label, path: write.path.clone(), status, changes })
+}
+
+/// Compare a managed asset tree with the source files a sync would deploy.
+///
+/// # Errors
+///
+/// Returns an error if the manifest or a deployed file cannot be read.
+pub fn inspect_asset_source(source: &SupportingAssetSource) -> Result<AssetDrift> {
+    let mut missing = Vec::new();
+    let mut modified = Vec::new();
+
+    for mapping in &source.mappings {
+        let destination = source.target_dir.join(&mapping.relative_path);
+        if !destination.exists() {
+            missing.push(mapping.relative_path.clone());
+            continue;
+        }
+
+        let expected = fs::read(&mapping.source_path)
+            .with_context(|| format!("Failed to read {}", mapping.source_path.display()))?;
+        let actual = fs::read(&destination)
+            .with_context(|| format!("Failed to read {}", destination.display()))?;
+        if expected != actual {
+            modified.push(mapping.relative_path.clone());
+        }
+    }
+
+    let stale = inspect_managed_tree(&source.target_dir, &source.mappings)?
+        .stale_files
+        .into_iter()
+        .filter(|relative_path| source.target_dir.join(relative_path).exists())
+        .collect();
+
+    Ok(AssetDrift {
+        label: source.label,
+        target_dir: source.target_dir.clone(),
+        missing,
+        modified,
+        stale,
+    })
+}
+
+fn parse_document(path: &Path, content: &str) -> Option<Value> {
+    if path.extension().is_some_and(|ext| ext == "toml") {
+        let table = toml::from_str::<toml::Table>(content).ok()?;
+        serde_json::to_value(table).ok()
+    } else {
+        serde_json::from_str(content).ok()
+    }
+}
+
+fn diff_entries(before: &Value, after: &Value) -> Vec<EntryChange> {
+    let mut changes = Vec::new();
+    diff_object(before, after, None, &mut changes);
+    changes
+}
+
+fn diff_object(
+    before: &Value,
+    after: &Value,
+    prefix: Option<&str>,
+    changes: &mut Vec<EntryChange>,
+) {
+    let empty = serde_json::Map::new();
+    let before_map = before.as_object().unwrap_or(&empty);
+    let after_map = after.as_object().unwrap_or(&empty);
+    let keys = before_map.keys().chain(after_map.keys()).collect::<BTreeSet<_>>();
+
+    for key in keys {
+        let path = prefix.map_or_else(|| key.clone(), |parent| format!("{parent}.{key}"));
+        match (before_map.get(key), after_map.get(key)) {
+            (None, Some(_)) => changes.push(EntryChange { key: path, kind: ChangeKind::Added }),
+            (Some(_), None) => changes.push(EntryChange { key: path, kind: ChangeKind::Removed }),
+            (Some(old), Some(new)) if old != new => {
+                if prefix.is_none() && MCP_SERVER_KEYS.contains(&key.as_str()) {
+                    diff_object(old, new, Some(&path), changes);
+                } else {
+                    changes.push(EntryChange { key: path, kind: ChangeKind::Modified });
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+/// Render a human-readable drift report, listing only what is out of date.
+#[must_use]
+pub fn render_status(name: &str, status: &AgentStatus) -> String {
+    let mut output = String::new();
+    if !status.has_drift() {
+        let _ = writeln!(output, "{name}: up to date");
+        return output;
+    }
+
+    let _ = writeln!(output, "{name}: out of date");
+    for file in status.files.iter().filter(|file| file.status != PlannedWriteStatus::Unchanged) {
+        let _ = writeln!(
+            output,
+            "  {} ({}) [{}]",
+            file.label,
+            file.path.display(),
+            file.status.marker()
+        );
+        for change in &file.changes {
+            let _ = writeln!(output, "    {} {}", change.kind.marker(), change.key);
+        }
+    }
+
+    if !status.stale_mcp_servers.is_empty() {
+        let _ = writeln!(output, "  MCP servers");
+        for server in &status.stale_mcp_servers {
+            let _ = writeln!(output, "    - {server} (stale; removed by `config sync --prune`)");
+        }
+    }
+
+    for asset in status.assets.iter().filter(|asset| asset.has_drift()) {
+        let _ = writeln!(output, "  {} ({})", asset.label, asset.target_dir.display());
+        for path in &asset.missing {
+            let _ = writeln!(output, "    + {path} (not deployed)");
+        }
+        for path in &asset.modified {
+            let _ = writeln!(output, "    ~ {path} (differs from source)");
+        }
+        for path in &asset.stale {
+            let _ = writeln!(output, "    - {path} (stale; removed by `config sync --prune`)");
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_entries_reports_individual_mcp_servers() {
+        let before = json!({
+            "mcpServers": { "kept": {"command": "a"}, "edited": {"command": "b"}, "gone": {} },
+            "theme": "dark"
+        });
+        let after = json!({
+            "mcpServers": { "kept": {"command": "a"}, "edited": {"command": "c"}, "new": {} },
+            "theme": "light"
+        });
+
+        let changes = diff_entries(&before, &after);
+
+        assert_eq!(
+            changes,
+            vec![
+                EntryChange { key: "mcpServers.edited".to_string(), kind: ChangeKind::Modified },
+                EntryChange { key: "mcpServers.gone".to_string(), kind: ChangeKind::Removed },
+                EntryChange { key: "mcpServers.new".to_string(), kind: ChangeKind::Added },
+                EntryChange { key: "theme".to_string(), kind: ChangeKind::Modified },
+            ]
+        );
+    }
+
+    #[test]
+    fn count_server_entries_looks_into_nested_server_maps() {
+        let document = json!({
+            "mcpServers": { "old": {} },
+            "/work/project": { "mcpServers": { "old": {}, "kept": {} } },
+            "/work/other": { "mcpServers": { "kept": {} } },
+            "theme": "old"
+        });
+
+        assert_eq!(count_server_entries(&document, "old"), 2);
+        assert_eq!(count_server_entries(&document, "kept"), 2);
+        assert_eq!(count_server_entries(&document, "theme"), 0);
+    }
+
+    #[test]
+    fn parse_document_reads_toml_targets() {
+        let value = parse_document(Path::new("config.toml"), "[mcp_servers.x]\ncommand = \"y\"\n")
+            .expect("toml should parse");
+
+        assert_eq!(value, json!({ "mcp_servers": { "x": { "command": "y" } } }));
+    }
+}
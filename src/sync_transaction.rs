@@ -0,0 +1,446 @@
+#![allow(missing_docs)]
+
+//! All-or-nothing commit of the configuration files produced by `config sync`.
+//!
+//! Every output is staged in memory and validated before anything touches the
+//! disk. The original content of each target is recorded in a journal inside
+//! the Claudius config directory, files are then replaced atomically one by
+//! one, and a failure part-way through restores the files that were already
+//! committed. If the process dies mid-commit the journal survives, and the
+//! next sync restores the pre-sync state before doing anything else. Files
+//! edited after the interrupted commit wrote them are left alone.
+//!
+//! The transaction covers the agent configuration files and the MCP ownership
+//! ledger that records which of their servers Claudius wrote. Skill, command,
+//! and agent trees are deliberately outside it: they are synced file by file
+//! after the commit succeeds, so an interrupted asset sync can leave a partly
+//! updated tree, which the next sync completes.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config::writer::{atomic_write, FileMode};
+use crate::undo::{fingerprint, UndoRecorder};
+
+const JOURNAL_FILE_NAME: &str = ".claudius-sync-journal.json";
+const JOURNAL_VERSION: u8 = 2;
+
+/// A fully rendered output file waiting to be committed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StagedFile {
+    pub path: PathBuf,
+    /// New content; `None` removes the file.
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SyncJournal {
+    #[serde(default = "journal_version")]
+    version: u8,
+    #[serde(default)]
+    entries: Vec<JournalEntry>,
+}
+
+/// Pre-sync state of one target; `original` is `None` for files the sync creates.
+///
+/// `staged` fingerprints the content the sync writes. It is absent for files
+/// the sync removes and in journals from version 1, and such entries are
+/// restored unconditionally.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    path: PathBuf,
+    original: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    staged: Option<String>,
+}
+
+/// What recovering from an interrupted commit did to each journaled file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recovery {
+    /// Files put back to their pre-sync content.
+    pub restored: Vec<PathBuf>,
+    /// Files changed since the interrupted commit wrote them, left as they are.
+    pub kept: Vec<PathBuf>,
+}
+
+const fn journal_version() -> u8 {
+    JOURNAL_VERSION
+}
+
+/// Path of the sync journal inside the Claudius config directory.
+#[must_use]
+pub fn journal_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(JOURNAL_FILE_NAME)
+}
+
+/// Check that every staged file parses in the format implied by its extension.
+///
+/// # Errors
+///
+/// Returns an error naming the first file whose content is not valid JSON or
+/// TOML.
+pub fn validate_staged_files(files: &[StagedFile]) -> Result<()> {
+    for file in files {
+        let Some(content) = file.content.as_deref() else {
+            continue;
+        };
+        match file.path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => {
+                serde_json::from_str::<serde_json::Value>(content).with_context(|| {
+                    format!("Staged output for {} is not valid JSON", file.path.display())
+                })?;
+            },
+            Some("toml") => {
+                toml::from_str::<toml::Table>(content).with_context(|| {
+                    format!("Staged output for {} is not valid TOML", file.path.display())
+                })?;
+            },
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+/// Validate and atomically commit the staged files as one unit.
+///
+/// Files whose content is already up to date are left untouched. When a write
+/// fails, files committed earlier in the same call are restored before the
+/// error is returned. Each file is captured by `undo` before it is written.
+///
+/// # Errors
+///
+/// Returns an error if validation fails, the journal cannot be written, or a
+/// file cannot be committed. Rollback failures are included in the error.
+pub fn commit(config_dir: &Path, files: &[StagedFile], undo: &UndoRecorder) -> Result<()> {
+    validate_staged_files(files)?;
+
+    let mut entries = Vec::new();
+    let mut pending = Vec::new();
+    for file in files {
+        let original = read_optional(&file.path)?;
+        if original == file.content {
+            debug!("{} is already up to date", file.path.display());
+            continue;
+        }
+        entries.push(JournalEntry {
+            path: file.path.clone(),
+            original,
+            staged: file.content.as_deref().map(|content| fingerprint(content.as_bytes())),
+        });
+        pending.push(file);
+    }
+
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let journal = SyncJournal { version: JOURNAL_VERSION, entries };
+    write_journal(config_dir, &journal)?;
+
+    for (index, file) in pending.iter().enumerate() {
+        undo.record(&file.path);
+        if let Err(error) = write_or_remove(&file.path, file.content.as_deref()) {
+            let committed = journal.entries.get(..index).unwrap_or_default();
+            return Err(rollback_after_failure(config_dir, committed, error, &file.path));
+        }
+    }
+
+    remove_journal(config_dir)
+}
+
+/// Restore the pre-sync state recorded by an interrupted commit.
+///
+/// A file is restored only while it still holds the content the interrupted
+/// commit wrote. Files already back at their original content need nothing,
+/// and files edited since are kept so recovery never discards later work.
+/// Returns `None` when no journal was left behind.
+///
+/// # Errors
+///
+/// Returns an error if the journal cannot be parsed or a file cannot be
+/// restored. The journal is kept in that case so recovery can be retried.
+pub fn recover_interrupted_sync(config_dir: &Path) -> Result<Option<Recovery>> {
+    let path = journal_path(config_dir);
+    let Some(content) = read_optional(&path)? else {
+        return Ok(None);
+    };
+
+    let journal: SyncJournal = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let mut recovery = Recovery::default();
+    let mut to_restore = Vec::new();
+    for entry in journal.entries {
+        let current = read_optional(&entry.path)?;
+        if current == entry.original {
+            continue;
+        }
+        let written_by_sync = entry.staged.as_deref().is_none_or(|staged| {
+            current
+                .as_deref()
+                .is_some_and(|written| fingerprint(written.as_bytes()) == staged)
+        });
+        if written_by_sync {
+            recovery.restored.push(entry.path.clone());
+            to_restore.push(entry);
+        } else {
+            warn!("{} changed after the interrupted sync; keeping it", entry.path.display());
+            recovery.kept.push(entry.path);
+        }
+    }
+    restore_entries(&to_restore)?;
+    remove_journal(config_dir)?;
+
+    Ok(Some(recovery))
+}
+
+fn rollback_after_failure(
+    config_dir: &Path,
+    committed: &[JournalEntry],
+    error: anyhow::Error,
+    failed_path: &Path,
+) -> anyhow::Error {
+    let write_error = error.context(format!("Failed to write {}", failed_path.display()));
+    match restore_entries(committed).and_then(|()| remove_journal(config_dir)) {
+        Ok(()) => {
+            warn!("Rolled back {} file(s) after a failed sync", committed.len());
+            write_error.context("Sync failed; all target files were restored")
+        },
+        Err(rollback_error) => write_error.context(format!(
+            "Sync failed and rollback was incomplete ({rollback_error:#}); run the sync again to \
+             restore the recorded state from {}",
+            journal_path(config_dir).display()
+        )),
+    }
+}
+
+fn restore_entries(entries: &[JournalEntry]) -> Result<()> {
+    for entry in entries.iter().rev() {
+        write_or_remove(&entry.path, entry.original.as_deref())
+            .with_context(|| format!("Failed to restore {}", entry.path.display()))?;
+    }
+    Ok(())
+}
+
+/// Atomically replace `path` with `content`, or remove it when `content` is `None`.
+fn write_or_remove(path: &Path, content: Option<&str>) -> Result<()> {
+    let Some(text) = content else {
+        return match fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(error) => {
+                Err(error).with_context(|| format!("Failed to remove {}", path.display()))
+            },
+        };
+    };
+    atomic_write(path, text.as_bytes(), FileMode::Preserve)
+}
+
+fn read_optional(path: &Path) -> Result<Option<String>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error)
+            if matches!(
+                error.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory
+            ) =>
+        {
+            Ok(None)
+        },
+        Err(error) => Err(error).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+fn write_journal(config_dir: &Path, journal: &SyncJournal) -> Result<()> {
+    let path = journal_path(config_dir);
+    let content = serde_json::to_string(journal).context("Failed to serialize sync journal")?;
+    atomic_write(&path, content.as_bytes(), FileMode::Private)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn remove_journal(config_dir: &Path) -> Result<()> {
+    let path = journal_path(config_dir);
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error).with_context(|| format!("Failed to remove {}", path.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn staged(path: PathBuf, content: &str) -> StagedFile {
+        StagedFile { path, content: Some(content.to_string()) }
+    }
+
+    #[test]
+    fn commit_writes_all_files_and_removes_journal() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let existing = temp_dir.path().join("existing.json");
+        fs::write(&existing, "{}").expect("write existing");
+        let created = temp_dir.path().join("nested").join("created.toml");
+
+        commit(
+            temp_dir.path(),
+            &[staged(existing.clone(), "{\"a\": 1}"), staged(created.clone(), "a = 1\n")],
+            &UndoRecorder::disabled(),
+        )
+        .expect("commit should succeed");
+
+        assert_eq!(fs::read_to_string(existing).expect("read existing"), "{\"a\": 1}");
+        assert_eq!(fs::read_to_string(created).expect("read created"), "a = 1\n");
+        assert!(!journal_path(temp_dir.path()).exists());
+    }
+
+    #[test]
+    fn commit_removes_files_staged_without_content_and_restores_them_on_failure() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let removed = temp_dir.path().join("removed.json");
+        fs::write(&removed, "{}").expect("write removed");
+        let blocker = temp_dir.path().join("blocker");
+        fs::write(&blocker, "").expect("write blocker");
+
+        let error = commit(
+            temp_dir.path(),
+            &[
+                StagedFile { path: removed.clone(), content: None },
+                staged(blocker.join("target.json"), "{}"),
+            ],
+            &UndoRecorder::disabled(),
+        )
+        .expect_err("write under a regular file should fail");
+        assert!(format!("{error:#}").contains("all target files were restored"));
+        assert_eq!(fs::read_to_string(&removed).expect("read restored"), "{}");
+
+        commit(
+            temp_dir.path(),
+            &[StagedFile { path: removed.clone(), content: None }],
+            &UndoRecorder::disabled(),
+        )
+        .expect("commit should succeed");
+        assert!(!removed.exists());
+    }
+
+    #[test]
+    fn commit_rejects_invalid_staged_content_before_writing() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let first = temp_dir.path().join("first.json");
+        let second = temp_dir.path().join("second.toml");
+
+        let error = commit(
+            temp_dir.path(),
+            &[staged(first.clone(), "{}"), staged(second, "not = [valid")],
+            &UndoRecorder::disabled(),
+        )
+        .expect_err("invalid TOML should be rejected");
+
+        assert!(error.to_string().contains("not valid TOML"));
+        assert!(!first.exists());
+    }
+
+    #[test]
+    fn commit_rolls_back_committed_files_when_a_later_write_fails() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let existing = temp_dir.path().join("existing.json");
+        fs::write(&existing, "{\"old\": true}").expect("write existing");
+        let created = temp_dir.path().join("created.json");
+        let blocker = temp_dir.path().join("blocker");
+        fs::write(&blocker, "").expect("write blocker");
+        let unwritable = blocker.join("target.json");
+
+        let error = commit(
+            temp_dir.path(),
+            &[
+                staged(existing.clone(), "{\"new\": true}"),
+                staged(created.clone(), "{}"),
+                staged(unwritable, "{}"),
+            ],
+            &UndoRecorder::disabled(),
+        )
+        .expect_err("write under a regular file should fail");
+
+        assert!(format!("{error:#}").contains("all target files were restored"));
+        assert_eq!(fs::read_to_string(existing).expect("read existing"), "{\"old\": true}");
+        assert!(!created.exists());
+        assert!(!journal_path(temp_dir.path()).exists());
+    }
+
+    fn journal_entry(path: &Path, original: Option<&str>, staged: &str) -> JournalEntry {
+        JournalEntry {
+            path: path.to_path_buf(),
+            original: original.map(str::to_string),
+            staged: Some(fingerprint(staged.as_bytes())),
+        }
+    }
+
+    #[test]
+    fn recover_restores_state_recorded_by_interrupted_commit() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let existing = temp_dir.path().join("existing.json");
+        let created = temp_dir.path().join("created.json");
+        let untouched = temp_dir.path().join("untouched.json");
+        fs::write(&existing, "{\"half\": \"written\"}").expect("write existing");
+        fs::write(&created, "{}").expect("write created");
+        fs::write(&untouched, "{\"old\": true}").expect("write untouched");
+
+        let journal = SyncJournal {
+            version: JOURNAL_VERSION,
+            entries: vec![
+                journal_entry(&existing, Some("{}"), "{\"half\": \"written\"}"),
+                journal_entry(&created, None, "{}"),
+                journal_entry(&untouched, Some("{\"old\": true}"), "{\"new\": true}"),
+            ],
+        };
+        write_journal(temp_dir.path(), &journal).expect("write journal");
+
+        let recovery = recover_interrupted_sync(temp_dir.path())
+            .expect("recovery should succeed")
+            .expect("journal should be found");
+
+        assert_eq!(recovery.restored, vec![existing.clone(), created.clone()]);
+        assert!(recovery.kept.is_empty());
+        assert_eq!(fs::read_to_string(existing).expect("read existing"), "{}");
+        assert!(!created.exists());
+        assert!(recover_interrupted_sync(temp_dir.path()).expect("second recovery").is_none());
+    }
+
+    #[test]
+    fn recover_keeps_files_edited_after_the_interruption() {
+        let temp_dir = TempDir::new().expect("temp dir");
+        let edited = temp_dir.path().join("edited.json");
+        fs::write(&edited, "{\"edited\": true}").expect("write edited");
+
+        let journal = SyncJournal {
+            version: JOURNAL_VERSION,
+            entries: vec![journal_entry(&edited, Some("{}"), "{\"synced\": true}")],
+        };
+        write_journal(temp_dir.path(), &journal).expect("write journal");
+
+        let recovery = recover_interrupted_sync(temp_dir.path())
+            .expect("recovery should succeed")
+            .expect("journal should be found");
+
+        assert_eq!(recovery, Recovery { restored: Vec::new(), kept: vec![edited.clone()] });
+        assert_eq!(fs::read_to_string(edited).expect("read edited"), "{\"edited\": true}");
+        assert!(!journal_path(temp_dir.path()).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn journal_is_private_to_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().expect("temp dir");
+        let journal = SyncJournal { version: JOURNAL_VERSION, entries: Vec::new() };
+        write_journal(temp_dir.path(), &journal).expect("write journal");
+
+        let mode = fs::metadata(journal_path(temp_dir.path())).expect("journal metadata");
+        assert_eq!(mode.permissions().mode() & 0o777, 0o600);
+    }
+}
@@ -103,7 +103,7 @@ pub fn append_rules_to_claude_md(rule_names: &[String], target_dir: Option<&Path
     };
     let claude_md_path = base_dir.join("CLAUDE.md");
 
-    let (combined_content, found_rules) = collect_rule_contents(rule_names)?;
+    let (combined_content, found_rules) = collect_rule_contents(rule_names, &base_dir)?;
 
     if combined_content.is_empty() {
         return Err(anyhow::anyhow!("No valid rules found"));
@@ -112,14 +112,42 @@ pub fn append_rules_to_claude_md(rule_names: &[String], target_dir: Option<&Path
     write_rules_to_file(&claude_md_path, &combined_content, &found_rules)
 }
 
+/// Rule source directories in precedence order: the project `.claudius/rules`
+/// layer of `project_dir` (when present), then the global rules directory.
+///
+/// # Errors
+///
+/// Returns an error if the Claudius config directory cannot be determined.
+pub fn rule_source_dirs(project_dir: &Path) -> Result<Vec<PathBuf>> {
+    let config_dir = Config::get_config_dir()?;
+    let project_rules = crate::source_layers::discover_project_layer(project_dir, &config_dir)
+        .map(|layer_dir| layer_dir.join("rules"))
+        .filter(|rules_dir| rules_dir.is_dir());
+
+    Ok(project_rules
+        .into_iter()
+        .chain(std::iter::once(config_dir.join("rules")))
+        .collect())
+}
+
 /// Collect contents from rule files
-fn collect_rule_contents(rule_names: &[String]) -> Result<(String, Vec<String>)> {
-    let rules_dir = Config::get_config_dir()?.join("rules");
+fn collect_rule_contents(
+    rule_names: &[String],
+    project_dir: &Path,
+) -> Result<(String, Vec<String>)> {
+    let rules_dirs = rule_source_dirs(project_dir)?;
     let mut combined_content = String::new();
     let mut found_rules = Vec::new();
 
     for rule_name in rule_names {
-        if let Some(content) = read_rule_file(&rules_dir, rule_name)? {
+        let mut found = None;
+        for rules_dir in &rules_dirs {
+            found = read_rule_file(rules_dir, rule_name)?;
+            if found.is_some() {
+                break;
+            }
+        }
+        if let Some(content) = found {
             if !combined_content.is_empty() {
                 combined_content.push_str("\n\n");
             }
@@ -305,7 +333,8 @@ fn get_filename_display(path: &Path) -> &str {
 /// - Unable to write to context file
 /// - No valid rules found
 pub fn append_rules_to_context_file(rule_names: &[String], context_file_path: &Path) -> Result<()> {
-    let (combined_content, found_rules) = collect_rule_contents(rule_names)?;
+    let project_dir = context_file_path.parent().unwrap_or_else(|| Path::new("."));
+    let (combined_content, found_rules) = collect_rule_contents(rule_names, project_dir)?;
 
     if combined_content.is_empty() {
         return Err(anyhow::anyhow!("No valid rules found"));
@@ -0,0 +1,498 @@
+#![allow(missing_docs)]
+
+//! Undo history for `config sync`, `skills sync`, and `config doctor --fix`.
+//!
+//! While a sync runs, an [`UndoRecorder`] captures every file it is about to
+//! write or delete once, before the first change: configuration targets
+//! committed by [`crate::sync_transaction`], skill/command/agent files and
+//! manifests written by [`crate::asset_sync`], the MCP ownership ledger, and
+//! the active profile. The recorder is passed explicitly to each function that
+//! writes. When the sync finishes, files whose content actually changed are
+//! stored as an undo record under `.claudius-undo/<id>/` in the config
+//! directory: the pre-sync content of each file plus a fingerprint of the
+//! content the sync left behind. The history directory is private to the
+//! owner, since the saved content may include credentials.
+//!
+//! `claudius undo` restores the newest record, refusing when any of its files
+//! no longer matches that fingerprint, i.e. was edited after the sync.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use tracing::warn;
+
+use crate::config::writer::{atomic_write, create_private_dir_all, FileMode};
+
+const UNDO_DIR_NAME: &str = ".claudius-undo";
+const RECORD_FILE_NAME: &str = "record.json";
+const RECORD_VERSION: u8 = 1;
+
+/// Undo records kept; older ones are removed when a new one is saved.
+pub const HISTORY_LIMIT: usize = 20;
+
+/// Files changed by one sync, as stored on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoRecord {
+    #[serde(default = "record_version")]
+    version: u8,
+    /// Command line of the sync, e.g. `config sync --agent codex`.
+    pub command: String,
+    /// Local time the sync finished, in RFC 3339.
+    pub created: String,
+    pub entries: Vec<UndoEntry>,
+}
+
+const fn record_version() -> u8 {
+    RECORD_VERSION
+}
+
+/// One file changed by a sync.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub path: PathBuf,
+    /// Blob holding the pre-sync content; `None` when the sync created the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous: Option<String>,
+    /// Unix mode of the pre-sync file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+    /// Fingerprint of the content the sync left; `None` when it removed the file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    /// Highest directory the sync created for a new file, removed again on undo if empty.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_root: Option<PathBuf>,
+}
+
+impl UndoEntry {
+    /// Whether undo deletes the file rather than restoring content.
+    #[must_use]
+    pub const fn removes_file(&self) -> bool {
+        self.previous.is_none()
+    }
+}
+
+/// A saved undo record and the directory holding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedUndo {
+    pub dir: PathBuf,
+    pub record: UndoRecord,
+}
+
+#[derive(Debug)]
+struct Capture {
+    path: PathBuf,
+    previous: Option<Vec<u8>>,
+    mode: Option<u32>,
+    created_root: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct Recording {
+    config_dir: PathBuf,
+    command: String,
+    seen: BTreeSet<PathBuf>,
+    captures: Vec<Capture>,
+}
+
+/// Directory holding the undo history inside the Claudius config directory.
+#[must_use]
+pub fn history_dir(config_dir: &Path) -> PathBuf {
+    config_dir.join(UNDO_DIR_NAME)
+}
+
+/// Captures the files one command changes, for a single undo record.
+///
+/// Functions that write on behalf of a sync take the recorder as an argument.
+/// A [`disabled`](Self::disabled) recorder captures nothing, for dry runs and
+/// commands that are not undoable.
+#[derive(Debug, Default)]
+pub struct UndoRecorder {
+    recording: Mutex<Option<Recording>>,
+}
+
+impl UndoRecorder {
+    /// Record the files changed by `command`, saving under `config_dir`.
+    #[must_use]
+    pub fn new(config_dir: &Path, command: &str) -> Self {
+        Self {
+            recording: Mutex::new(Some(Recording {
+                config_dir: config_dir.to_path_buf(),
+                command: command.to_string(),
+                seen: BTreeSet::new(),
+                captures: Vec::new(),
+            })),
+        }
+    }
+
+    /// A recorder that captures nothing.
+    #[must_use]
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Capture the current state of `path` before it is written or deleted.
+    ///
+    /// Only the first call per path counts.
+    pub fn record(&self, path: &Path) {
+        let mut active = self.recording.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(recording) = active.as_mut() {
+            capture(recording, path);
+        }
+    }
+
+    /// Save the captured files that changed as the newest undo record.
+    ///
+    /// Returns `None` when nothing changed or the recorder is disabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record cannot be written.
+    pub fn finish(self) -> Result<Option<SavedUndo>> {
+        let recording = self.recording.into_inner().unwrap_or_else(PoisonError::into_inner);
+        let Some(Recording { config_dir, command, captures, .. }) = recording else {
+            return Ok(None);
+        };
+
+        let mut changed = Vec::new();
+        for capture in captures {
+            let current = read_optional(&capture.path)?;
+            if current != capture.previous {
+                changed.push((capture, current.as_deref().map(fingerprint)));
+            }
+        }
+        if changed.is_empty() {
+            return Ok(None);
+        }
+
+        let saved = save_record(&config_dir, &command, changed)?;
+        prune_history(&config_dir)?;
+        Ok(Some(saved))
+    }
+}
+
+fn capture(recording: &mut Recording, path: &Path) {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    if !recording.seen.insert(absolute.clone()) {
+        return;
+    }
+
+    match read_optional(&absolute) {
+        Ok(previous) => {
+            let mode = previous.as_ref().and_then(|_| file_mode(&absolute));
+            let created_root =
+                previous.is_none().then(|| highest_missing_ancestor(&absolute)).flatten();
+            recording
+                .captures
+                .push(Capture { path: absolute, previous, mode, created_root });
+        },
+        Err(error) => warn!("Cannot record {} for undo: {error:#}", absolute.display()),
+    }
+}
+
+fn save_record(
+    config_dir: &Path,
+    command: &str,
+    changed: Vec<(Capture, Option<String>)>,
+) -> Result<SavedUndo> {
+    let now = chrono::Local::now();
+    create_private_dir_all(&history_dir(config_dir))?;
+    let dir = unique_record_dir(config_dir, &now.format("%Y%m%d_%H%M%S_%6f").to_string());
+    create_private_dir_all(&dir)?;
+
+    let mut entries = Vec::new();
+    for (index, (capture, after)) in changed.into_iter().enumerate() {
+        let previous = match capture.previous {
+            Some(content) => {
+                let blob = format!("{index}.blob");
+                atomic_write(&dir.join(&blob), &content, FileMode::Private).with_context(|| {
+                    format!("Failed to write undo content in {}", dir.display())
+                })?;
+                Some(blob)
+            },
+            None => None,
+        };
+        entries.push(UndoEntry {
+            path: capture.path,
+            previous,
+            mode: capture.mode,
+            after,
+            created_root: capture.created_root,
+        });
+    }
+
+    let record = UndoRecord {
+        version: RECORD_VERSION,
+        command: command.to_string(),
+        created: now.to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        entries,
+    };
+    let content =
+        serde_json::to_string_pretty(&record).context("Failed to serialize undo record")?;
+    atomic_write(
+        &dir.join(RECORD_FILE_NAME),
+        format!("{content}\n").as_bytes(),
+        FileMode::Preserve,
+    )?;
+    Ok(SavedUndo { dir, record })
+}
+
+fn unique_record_dir(config_dir: &Path, stamp: &str) -> PathBuf {
+    let history = history_dir(config_dir);
+    let mut candidate = history.join(stamp);
+    let mut counter = 0_u32;
+    while candidate.exists() && counter < u32::MAX {
+        counter = counter.saturating_add(1);
+        candidate = history.join(format!("{stamp}_{counter}"));
+    }
+    candidate
+}
+
+/// Saved undo records, newest first.
+///
+/// # Errors
+///
+/// Returns an error if the history directory or a record cannot be read.
+pub fn history(config_dir: &Path) -> Result<Vec<SavedUndo>> {
+    let dir = history_dir(config_dir);
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => {
+            return Err(error).with_context(|| format!("Failed to read {}", dir.display()))
+        },
+    };
+
+    let mut record_dirs = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(RECORD_FILE_NAME).is_file())
+        .collect::<Vec<_>>();
+    record_dirs.sort_by(|left, right| right.cmp(left));
+
+    record_dirs
+        .into_iter()
+        .map(|record_dir| {
+            let path = record_dir.join(RECORD_FILE_NAME);
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let record = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?;
+            Ok(SavedUndo { dir: record_dir, record })
+        })
+        .collect()
+}
+
+fn prune_history(config_dir: &Path) -> Result<()> {
+    for expired in history(config_dir)?.iter().skip(HISTORY_LIMIT) {
+        fs::remove_dir_all(&expired.dir)
+            .with_context(|| format!("Failed to remove {}", expired.dir.display()))?;
+    }
+    Ok(())
+}
+
+/// Files in `record` whose content differs from what the sync left behind.
+///
+/// # Errors
+///
+/// Returns an error if a file exists but cannot be read.
+pub fn modified_files(record: &UndoRecord) -> Result<Vec<PathBuf>> {
+    let mut modified = Vec::new();
+    for entry in &record.entries {
+        let current = read_optional(&entry.path)?;
+        if current.as_deref().map(fingerprint) != entry.after {
+            modified.push(entry.path.clone());
+        }
+    }
+    Ok(modified)
+}
+
+/// Restore every file in `saved` to its pre-sync state and drop the record.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be restored or removed; the record is
+/// kept so the undo can be retried.
+pub fn apply(saved: &SavedUndo) -> Result<()> {
+    for entry in saved.record.entries.iter().rev() {
+        match &entry.previous {
+            Some(blob) => restore_entry(&saved.dir.join(blob), entry)?,
+            None => remove_created_file(entry)?,
+        }
+    }
+    fs::remove_dir_all(&saved.dir)
+        .with_context(|| format!("Failed to remove {}", saved.dir.display()))
+}
+
+fn restore_entry(blob: &Path, entry: &UndoEntry) -> Result<()> {
+    let content = fs::read(blob)
+        .with_context(|| format!("Failed to read undo content for {}", entry.path.display()))?;
+    atomic_write(&entry.path, &content, FileMode::Preserve)
+        .with_context(|| format!("Failed to restore {}", entry.path.display()))?;
+    if let Some(mode) = entry.mode {
+        set_file_mode(&entry.path, mode)?;
+    }
+    Ok(())
+}
+
+fn remove_created_file(entry: &UndoEntry) -> Result<()> {
+    match fs::remove_file(&entry.path) {
+        Ok(()) => {},
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {},
+        Err(error) => {
+            return Err(error).with_context(|| format!("Failed to remove {}", entry.path.display()))
+        },
+    }
+
+    let Some(root) = &entry.created_root else {
+        return Ok(());
+    };
+    let mut current = entry.path.parent();
+    while let Some(dir) = current.filter(|dir| dir.starts_with(root)) {
+        if fs::remove_dir(dir).is_err() {
+            break;
+        }
+        current = dir.parent();
+    }
+    Ok(())
+}
+
+/// Highest ancestor of `path` that does not exist yet.
+fn highest_missing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|ancestor| !ancestor.exists())
+        .last()
+        .map(Path::to_path_buf)
+}
+
+/// Content fingerprint: 64-bit FNV-1a hash and length.
+pub(crate) fn fingerprint(content: &[u8]) -> String {
+    let hash = content.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("fnv1a64:{hash:016x}:{}", content.len())
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(error)
+            if matches!(
+                error.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::NotADirectory
+            ) =>
+        {
+            Ok(None)
+        },
+        Err(error) => Err(error).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+#[cfg(unix)]
+fn file_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).ok().map(|metadata| metadata.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn file_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .with_context(|| format!("Failed to restore permissions of {}", path.display()))
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn finish_records_changed_files_and_apply_restores_them() {
+        let config_dir = TempDir::new().unwrap();
+        let target_dir = TempDir::new().unwrap();
+        let edited = target_dir.path().join("settings.json");
+        let created = target_dir.path().join("skills").join("review").join("SKILL.md");
+        let untouched = target_dir.path().join("same.json");
+        fs::write(&edited, "{\"old\": true}").unwrap();
+        fs::write(&untouched, "{}").unwrap();
+
+        let recorder = UndoRecorder::new(config_dir.path(), "skills sync");
+        recorder.record(&edited);
+        recorder.record(&created);
+        recorder.record(&untouched);
+        fs::write(&edited, "{\"new\": true}").unwrap();
+        fs::create_dir_all(created.parent().unwrap()).unwrap();
+        fs::write(&created, "# Review").unwrap();
+        recorder.record(&edited);
+        let saved = recorder.finish().unwrap().unwrap();
+
+        assert_eq!(saved.record.entries.len(), 2);
+        assert_eq!(history(config_dir.path()).unwrap(), std::slice::from_ref(&saved));
+        assert!(modified_files(&saved.record).unwrap().is_empty());
+
+        apply(&saved).unwrap();
+        assert_eq!(fs::read_to_string(&edited).unwrap(), "{\"old\": true}");
+        assert!(!target_dir.path().join("skills").exists());
+        assert!(history(config_dir.path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn modified_files_reports_edits_after_the_sync() {
+        let config_dir = TempDir::new().unwrap();
+        let target = config_dir.path().join("target.json");
+
+        let recorder = UndoRecorder::new(config_dir.path(), "config sync");
+        recorder.record(&target);
+        fs::write(&target, "{}").unwrap();
+        let saved = recorder.finish().unwrap().unwrap();
+
+        fs::write(&target, "{\"edited\": true}").unwrap();
+        assert_eq!(modified_files(&saved.record).unwrap(), [target]);
+    }
+
+    #[test]
+    fn saved_content_is_private_to_the_owner() {
+        let config_dir = TempDir::new().unwrap();
+        let target = config_dir.path().join("claude.json");
+        fs::write(&target, "{\"oauthAccount\": {}}").unwrap();
+
+        let recorder = UndoRecorder::new(config_dir.path(), "config sync");
+        recorder.record(&target);
+        fs::write(&target, "{}").unwrap();
+        let saved = recorder.finish().unwrap().unwrap();
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&history_dir(config_dir.path())), 0o700);
+            assert_eq!(mode(&saved.dir), 0o700);
+            assert_eq!(mode(&saved.dir.join("0.blob")), 0o600);
+        }
+    }
+
+    #[test]
+    fn disabled_recorder_saves_nothing() {
+        let config_dir = TempDir::new().unwrap();
+        let recorder = UndoRecorder::disabled();
+        recorder.record(&config_dir.path().join("target.json"));
+        fs::write(config_dir.path().join("target.json"), "{}").unwrap();
+
+        assert!(recorder.finish().unwrap().is_none());
+        assert!(!history_dir(config_dir.path()).exists());
+    }
+}
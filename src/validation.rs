@@ -1,12 +1,14 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs;
 use std::path::Path;
 use std::sync::LazyLock;
 use toml::Value as TomlValue;
 
-use crate::app_config::{AppConfig, CodexSkillTargetMode, SecretManagerType};
+use crate::app_config::{
+    AppConfig, ClaudeCodeScope, CodexSkillTargetMode, ProjectConfig, SecretManagerType,
+};
 use crate::config::Settings;
 use crate::gemini_settings::{validate_gemini_settings, GeminiSettings};
 
@@ -15,7 +17,8 @@ static YAML_FRONTMATTER_RE: LazyLock<regex::Regex> = LazyLock::new(|| {
         .expect("frontmatter regex should compile")
 });
 
-#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "lowercase")]
 /// Severity assigned to a configuration diagnostic.
 pub enum DiagnosticSeverity {
     /// Advisory information that does not fail strict validation.
@@ -36,11 +39,186 @@ impl std::fmt::Display for DiagnosticSeverity {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Kind of check that produced a diagnostic.
+///
+/// The kebab-case identifiers are stable: code scanning and other consumers key
+/// suppressions and history on them, so variants may be added but never renamed.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DiagnosticRule {
+    /// Finding without a more specific classification.
+    #[default]
+    General,
+    /// A `config.toml` setting that has no effect as written.
+    IgnoredAppSetting,
+    /// `[codex].skill-target` publishes to the legacy `.codex/skills` path.
+    LegacySkillTarget,
+    /// A `[[projects]]` entry that `config sync --all-projects` cannot use.
+    ProjectRegistry,
+    /// A deprecated or legacy agent setting.
+    DeprecatedKey,
+    /// A Claude Code setting ignored in the validated scope.
+    ScopeRestriction,
+    /// A Claude Code setting honored only in managed settings.
+    ManagedOnlySetting,
+    /// A field unknown to the Gemini settings schema.
+    UnknownGeminiField,
+    /// An MCP server field Gemini cannot represent as written.
+    GeminiMcpCompatibility,
+    /// A source file in a legacy location or under a legacy name.
+    LegacySourceFile,
+    /// An incomplete MCP server definition in `mcpServers.json`.
+    McpServerDefinition,
+    /// An invalid `x-claudius` target selector.
+    TargetSelector,
+    /// An `op://` secret reference and how it is resolved at launch.
+    SecretReference,
+    /// An undefined or cyclic `${NAME}` reference.
+    Interpolation,
+    /// An incomplete Gemini custom command.
+    GeminiCommand,
+    /// An incomplete subagent or Gemini agent definition.
+    AgentDefinition,
+    /// A file or directory a canonical skill does not render.
+    SkillLayout,
+    /// A skill target overlay field the target agent ignores.
+    SkillTargetOverlay,
+    /// Legacy skill metadata that cannot be rendered as written.
+    LegacySkill,
+    /// A deprecated full agent override skill directory.
+    DeprecatedSkillOverride,
+    /// MCP server transport fields that contradict each other.
+    McpTransportConflict,
+    /// An MCP server field of the other transport, dropped when syncing.
+    McpDroppedField,
+    /// A bare MCP server command that is not on `PATH`.
+    McpCommandNotFound,
+    /// An `npx` server that may stop at an install prompt.
+    McpNpxPrompt,
+    /// An MCP server package pinned to `@latest`.
+    McpUnpinnedPackage,
+    /// MCP servers that run the same command and arguments.
+    McpDuplicateServer,
+    /// An MCP server `env` key reserved by an agent or `claudius secrets run`.
+    McpReservedEnv,
+}
+
+impl DiagnosticRule {
+    /// Every rule, in the order code scanning tools list them.
+    pub const ALL: [Self; 27] = [
+        Self::General,
+        Self::IgnoredAppSetting,
+        Self::LegacySkillTarget,
+        Self::ProjectRegistry,
+        Self::DeprecatedKey,
+        Self::ScopeRestriction,
+        Self::ManagedOnlySetting,
+        Self::UnknownGeminiField,
+        Self::GeminiMcpCompatibility,
+        Self::LegacySourceFile,
+        Self::McpServerDefinition,
+        Self::TargetSelector,
+        Self::SecretReference,
+        Self::Interpolation,
+        Self::GeminiCommand,
+        Self::AgentDefinition,
+        Self::SkillLayout,
+        Self::SkillTargetOverlay,
+        Self::LegacySkill,
+        Self::DeprecatedSkillOverride,
+        Self::McpTransportConflict,
+        Self::McpDroppedField,
+        Self::McpCommandNotFound,
+        Self::McpNpxPrompt,
+        Self::McpUnpinnedPackage,
+        Self::McpDuplicateServer,
+        Self::McpReservedEnv,
+    ];
+
+    /// Stable identifier, e.g. `deprecated-key`.
+    #[must_use]
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::General => "general",
+            Self::IgnoredAppSetting => "ignored-app-setting",
+            Self::LegacySkillTarget => "legacy-skill-target",
+            Self::ProjectRegistry => "project-registry",
+            Self::DeprecatedKey => "deprecated-key",
+            Self::ScopeRestriction => "scope-restriction",
+            Self::ManagedOnlySetting => "managed-only-setting",
+            Self::UnknownGeminiField => "unknown-gemini-field",
+            Self::GeminiMcpCompatibility => "gemini-mcp-compatibility",
+            Self::LegacySourceFile => "legacy-source-file",
+            Self::McpServerDefinition => "mcp-server-definition",
+            Self::TargetSelector => "target-selector",
+            Self::SecretReference => "secret-reference",
+            Self::Interpolation => "interpolation",
+            Self::GeminiCommand => "gemini-command",
+            Self::AgentDefinition => "agent-definition",
+            Self::SkillLayout => "skill-layout",
+            Self::SkillTargetOverlay => "skill-target-overlay",
+            Self::LegacySkill => "legacy-skill",
+            Self::DeprecatedSkillOverride => "deprecated-skill-override",
+            Self::McpTransportConflict => "mcp-transport-conflict",
+            Self::McpDroppedField => "mcp-dropped-field",
+            Self::McpCommandNotFound => "mcp-command-not-found",
+            Self::McpNpxPrompt => "mcp-npx-prompt",
+            Self::McpUnpinnedPackage => "mcp-unpinned-package",
+            Self::McpDuplicateServer => "mcp-duplicate-server",
+            Self::McpReservedEnv => "mcp-reserved-env",
+        }
+    }
+
+    /// One-line description of what the rule checks.
+    #[must_use]
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::General => "Configuration finding",
+            Self::IgnoredAppSetting => "config.toml setting has no effect",
+            Self::LegacySkillTarget => {
+                "Codex skills are published to the legacy .codex/skills path"
+            },
+            Self::ProjectRegistry => "[[projects]] entry cannot be synced",
+            Self::DeprecatedKey => "Deprecated or legacy agent setting",
+            Self::ScopeRestriction => "Claude Code setting is ignored in this scope",
+            Self::ManagedOnlySetting => "Claude Code setting is honored only in managed settings",
+            Self::UnknownGeminiField => "Field is unknown to the Gemini settings schema",
+            Self::GeminiMcpCompatibility => "MCP server field is not supported by Gemini",
+            Self::LegacySourceFile => "Source file uses a legacy name or location",
+            Self::McpServerDefinition => "MCP server definition is incomplete",
+            Self::TargetSelector => "Invalid x-claudius target selector",
+            Self::SecretReference => "op:// secret reference",
+            Self::Interpolation => "Undefined or cyclic ${NAME} reference",
+            Self::GeminiCommand => "Gemini custom command is incomplete",
+            Self::AgentDefinition => "Agent definition is incomplete",
+            Self::SkillLayout => "Skill entry is not rendered",
+            Self::SkillTargetOverlay => "Skill target overlay field is ignored by the target agent",
+            Self::LegacySkill => "Legacy skill metadata cannot be rendered as written",
+            Self::DeprecatedSkillOverride => "Deprecated full agent override skill directory",
+            Self::McpTransportConflict => "MCP server transport fields contradict each other",
+            Self::McpDroppedField => "MCP server field does not apply to its transport",
+            Self::McpCommandNotFound => "MCP server command is not on PATH",
+            Self::McpNpxPrompt => "npx may prompt before installing the MCP server package",
+            Self::McpUnpinnedPackage => "MCP server package is pinned to @latest",
+            Self::McpDuplicateServer => "MCP servers run the same command",
+            Self::McpReservedEnv => "MCP server env key is reserved",
+        }
+    }
+}
+
+impl std::fmt::Display for DiagnosticRule {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str(self.id())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 /// Structured finding emitted while validating configuration.
 pub struct Diagnostic {
     /// Finding severity.
     pub severity: DiagnosticSeverity,
+    /// Kind of check that produced the finding.
+    pub rule: DiagnosticRule,
     /// Human-readable explanation and remediation guidance.
     pub message: String,
     /// Source file associated with the finding, when available.
@@ -51,19 +229,30 @@ impl Diagnostic {
     #[must_use]
     /// Create an informational diagnostic.
     pub fn info(message: impl Into<String>) -> Self {
-        Self { severity: DiagnosticSeverity::Info, message: message.into(), path: None }
+        Self::new(DiagnosticSeverity::Info, message)
     }
 
     #[must_use]
     /// Create a warning diagnostic.
     pub fn warning(message: impl Into<String>) -> Self {
-        Self { severity: DiagnosticSeverity::Warning, message: message.into(), path: None }
+        Self::new(DiagnosticSeverity::Warning, message)
     }
 
     #[must_use]
     /// Create an error diagnostic.
     pub fn error(message: impl Into<String>) -> Self {
-        Self { severity: DiagnosticSeverity::Error, message: message.into(), path: None }
+        Self::new(DiagnosticSeverity::Error, message)
+    }
+
+    fn new(severity: DiagnosticSeverity, message: impl Into<String>) -> Self {
+        Self { severity, rule: DiagnosticRule::General, message: message.into(), path: None }
+    }
+
+    #[must_use]
+    /// Classify this diagnostic under a rule.
+    pub const fn with_rule(mut self, rule: DiagnosticRule) -> Self {
+        self.rule = rule;
+        self
     }
 
     #[must_use]
@@ -117,31 +306,72 @@ pub enum JsonConfigKind {
 /// Validates Claudius app configuration and returns semantic warnings.
 #[must_use]
 pub fn validate_app_config(config: &AppConfig) -> ValidationResult {
-    let mut warnings = Vec::new();
+    let mut diagnostics = Vec::new();
 
     if let Some(secret_manager) = &config.secret_manager {
         if secret_manager.onepassword.is_some()
             && secret_manager.manager_type != SecretManagerType::OnePassword
         {
-            warnings.push(
-                "[secret-manager.onepassword] is configured but [secret-manager].type is not \"1password\"; these settings will be ignored".to_string(),
+            diagnostics.push(
+                Diagnostic::warning(
+                    "[secret-manager.onepassword] is configured but [secret-manager].type is not \"1password\"; these settings will be ignored",
+                )
+                .with_rule(DiagnosticRule::IgnoredAppSetting),
             );
         }
     }
 
     if let Some(codex) = &config.codex {
-        match codex.skill_target {
-            Some(CodexSkillTargetMode::Codex) => warnings.push(
-                "[codex].skill-target = \"codex\" publishes only to the legacy .codex/skills path; prefer \"agents\" (or leave it unset) for the official Codex search path".to_string(),
+        let warning = match codex.skill_target {
+            Some(CodexSkillTargetMode::Codex) => Some(
+                "[codex].skill-target = \"codex\" publishes only to the legacy .codex/skills path; prefer \"agents\" (or leave it unset) for the official Codex search path",
             ),
-            Some(CodexSkillTargetMode::Both) => warnings.push(
-                "[codex].skill-target = \"both\" also publishes compatibility copies to .codex/skills; prefer \"agents\" (or leave it unset) unless you still need the legacy path".to_string(),
+            Some(CodexSkillTargetMode::Both) => Some(
+                "[codex].skill-target = \"both\" also publishes compatibility copies to .codex/skills; prefer \"agents\" (or leave it unset) unless you still need the legacy path",
             ),
-            Some(CodexSkillTargetMode::Auto | CodexSkillTargetMode::Agents) | None => {},
-        }
+            Some(CodexSkillTargetMode::Auto | CodexSkillTargetMode::Agents) | None => None,
+        };
+        diagnostics.extend(warning.map(|message| {
+            Diagnostic::warning(message).with_rule(DiagnosticRule::LegacySkillTarget)
+        }));
     }
 
-    ValidationResult { diagnostics: warnings.into_iter().map(Diagnostic::warning).collect() }
+    if config.backups.is_some_and(|backups| backups.keep == Some(0)) {
+        diagnostics.push(
+            Diagnostic::warning(
+                "[backups].keep = 0 would remove every backup and is ignored; use a positive count",
+            )
+            .with_rule(DiagnosticRule::IgnoredAppSetting),
+        );
+    }
+
+    diagnostics.extend(
+        project_registry_warnings(&config.projects)
+            .into_iter()
+            .map(|warning| Diagnostic::warning(warning).with_rule(DiagnosticRule::ProjectRegistry)),
+    );
+
+    ValidationResult { diagnostics }
+}
+
+fn project_registry_warnings(projects: &[ProjectConfig]) -> Vec<String> {
+    let base_dir = crate::projects::registry_base_dir().unwrap_or_default();
+    let mut warnings = Vec::new();
+    for project in projects {
+        if !crate::projects::expand_project_path(&project.path, &base_dir).is_dir() {
+            warnings.push(format!(
+                "[[projects]] path \"{}\" is not a directory; config sync --all-projects will report it as failed",
+                project.path
+            ));
+        }
+        if matches!(project.scope, Some(ClaudeCodeScope::Managed | ClaudeCodeScope::User)) {
+            warnings.push(format!(
+                "[[projects]] path \"{}\" sets a global Claude Code scope; use \"project\" or \"local\"",
+                project.path
+            ));
+        }
+    }
+    warnings
 }
 
 #[derive(Debug, Deserialize)]
@@ -203,16 +433,21 @@ fn parse_and_validate_json_file<P: AsRef<Path>>(
         format!("Failed to parse JSON from {}: Invalid JSON syntax", path_ref.display())
     })?;
 
-    let warnings = match kind {
-        Some(JsonConfigKind::Claude) => validate_claude_settings(&json_value),
-        Some(JsonConfigKind::Gemini) => validate_gemini_settings(&json_value),
-        None => Vec::new(),
+    let (warnings, rule) = match kind {
+        Some(JsonConfigKind::Claude) => {
+            (validate_claude_settings(&json_value), DiagnosticRule::DeprecatedKey)
+        },
+        Some(JsonConfigKind::Gemini) => {
+            (validate_gemini_settings(&json_value), DiagnosticRule::UnknownGeminiField)
+        },
+        None => (Vec::new(), DiagnosticRule::General),
     };
+    let diagnostics = warnings
+        .into_iter()
+        .map(|warning| Diagnostic::warning(warning).with_rule(rule).with_path(path_ref))
+        .collect();
 
-    Ok((
-        json_value,
-        ValidationResult { diagnostics: warnings.into_iter().map(Diagnostic::warning).collect() },
-    ))
+    Ok((json_value, ValidationResult { diagnostics }))
 }
 
 fn infer_json_config_kind(path: &Path) -> Option<JsonConfigKind> {
@@ -332,7 +567,16 @@ pub fn validate_gemini_command_file<P: AsRef<Path>>(path: P) -> Result<Validatio
         warnings.push("Optional field 'description' should not be empty when present".to_string());
     }
 
-    Ok(ValidationResult { diagnostics: warnings.into_iter().map(Diagnostic::warning).collect() })
+    Ok(ValidationResult {
+        diagnostics: warnings
+            .into_iter()
+            .map(|warning| {
+                Diagnostic::warning(warning)
+                    .with_rule(DiagnosticRule::GeminiCommand)
+                    .with_path(path_ref)
+            })
+            .collect(),
+    })
 }
 
 /// Validates a Claude Code subagent definition file.
@@ -369,7 +613,7 @@ pub fn validate_claude_code_subagent_file<P: AsRef<Path>>(path: P) -> Result<Val
             format!("Failed to parse Claude Code subagent frontmatter: {}", path_ref.display())
         })?;
 
-    Ok(validate_markdown_agent_metadata(&metadata, body))
+    Ok(validate_markdown_agent_metadata(&metadata, body, path_ref))
 }
 
 /// Validates a Gemini custom agent definition file.
@@ -405,12 +649,13 @@ pub fn validate_gemini_agent_file<P: AsRef<Path>>(path: P) -> Result<ValidationR
             format!("Failed to parse Gemini agent frontmatter: {}", path_ref.display())
         })?;
 
-    Ok(validate_markdown_agent_metadata(&metadata, body))
+    Ok(validate_markdown_agent_metadata(&metadata, body, path_ref))
 }
 
 fn validate_markdown_agent_metadata(
     metadata: &MarkdownAgentFrontmatter,
     body: &str,
+    path: &Path,
 ) -> ValidationResult {
     let mut warnings = Vec::new();
     if metadata.name.trim().is_empty() {
@@ -423,7 +668,16 @@ fn validate_markdown_agent_metadata(
         warnings.push("Agent Markdown body should not be empty".to_string());
     }
 
-    ValidationResult { diagnostics: warnings.into_iter().map(Diagnostic::warning).collect() }
+    ValidationResult {
+        diagnostics: warnings
+            .into_iter()
+            .map(|warning| {
+                Diagnostic::warning(warning)
+                    .with_rule(DiagnosticRule::AgentDefinition)
+                    .with_path(path)
+            })
+            .collect(),
+    }
 }
 
 /// Prompt user to continue after a warning
@@ -431,18 +685,10 @@ fn validate_markdown_agent_metadata(
 /// # Errors
 ///
 /// Returns an error if:
-/// - Unable to flush stdout
+/// - Prompting is disabled (`--no-input`) or stdin is not a terminal
 /// - Unable to read from stdin
 pub fn prompt_continue() -> Result<bool> {
-    use std::io::{self, Write};
-
-    print!("Continue anyway? [y/N] ");
-    io::stdout().flush()?;
-
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
-
-    Ok(input.trim().eq_ignore_ascii_case("y"))
+    crate::interaction::confirm("Continue anyway?")
 }
 
 #[cfg(test)]
@@ -453,6 +699,7 @@ mod tests {
         SecretManagerType,
     };
     use serde_json::json;
+    use std::collections::BTreeMap;
     use std::fs;
     use tempfile::TempDir;
 
@@ -463,6 +710,13 @@ mod tests {
         assert_eq!(diagnostic.to_string(), "[warning] settings.json: setting is deprecated");
     }
 
+    #[test]
+    fn rule_ids_match_their_serialized_form() {
+        for rule in DiagnosticRule::ALL {
+            assert_eq!(serde_json::to_value(rule).unwrap(), json!(rule.id()));
+        }
+    }
+
     #[test]
     fn only_warning_and_error_diagnostics_are_actionable() {
         let info = ValidationResult { diagnostics: vec![Diagnostic::info("migration available")] };
@@ -484,9 +738,16 @@ mod tests {
                     mode: Some(OnePasswordMode::ServiceAccount),
                     service_account_token_path: Some("~/.config/op/service-account.token".into()),
                 }),
+                launcher: None,
             }),
             default: None,
             codex: None,
+            sync: None,
+            backups: None,
+            profiles: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            projects: Vec::new(),
+            trusted_projects: Vec::new(),
         };
 
         let result = validate_app_config(&config);
@@ -506,9 +767,16 @@ mod tests {
                     mode: Some(OnePasswordMode::ServiceAccount),
                     service_account_token_path: Some("~/.config/op/service-account.token".into()),
                 }),
+                launcher: None,
             }),
             default: None,
             codex: None,
+            sync: None,
+            backups: None,
+            profiles: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            projects: Vec::new(),
+            trusted_projects: Vec::new(),
         };
 
         let result = validate_app_config(&config);
@@ -521,6 +789,12 @@ mod tests {
             secret_manager: None,
             default: None,
             codex: Some(CodexConfig { skill_target: Some(CodexSkillTargetMode::Both) }),
+            sync: None,
+            backups: None,
+            profiles: BTreeMap::new(),
+            variables: BTreeMap::new(),
+            projects: Vec::new(),
+            trusted_projects: Vec::new(),
         };
 
         let result = validate_app_config(&config);
@@ -531,6 +805,35 @@ mod tests {
             .is_some_and(|warning| warning.contains(".codex/skills")));
     }
 
+    #[test]
+    fn test_validate_app_config_warns_about_unusable_project_entries() {
+        let existing = TempDir::new().unwrap();
+        let config = AppConfig {
+            projects: vec![
+                ProjectConfig {
+                    path: existing.path().display().to_string(),
+                    agents: Vec::new(),
+                    scope: Some(ClaudeCodeScope::User),
+                },
+                ProjectConfig {
+                    path: existing.path().join("missing").display().to_string(),
+                    agents: Vec::new(),
+                    scope: None,
+                },
+            ],
+            ..AppConfig::default()
+        };
+
+        let warnings = validate_app_config(&config)
+            .diagnostics
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|warning| warning.contains("global Claude Code scope")));
+        assert!(warnings.iter().any(|warning| warning.contains("missing\" is not a directory")));
+    }
+
     #[test]
     fn test_validate_claude_settings_known_fields() {
         let json = json!({
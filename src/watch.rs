@@ -0,0 +1,314 @@
+#![allow(missing_docs)]
+
+//! Source watching for `config sync --watch` and `skills sync --watch`.
+//!
+//! The watcher polls the Claudius config directory (and the project
+//! `.claudius/` layer, when present) for changes to `mcpServers.json`,
+//! `*.settings.*`, `skills/`, `commands/`, and `agents/`. Only those entries
+//! are scanned; the rest of each root, including `config.toml`, is not watched.
+//! A burst of edits is reported as one batch once the sources have been quiet
+//! for the debounce window, so an editor's save sequence triggers a single
+//! re-sync.
+
+use crate::file_walk::collect_files;
+use crate::sync_status::ChangeKind;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the sources are scanned.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long the sources must stay unchanged before a batch is reported.
+pub const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Source area a watched file belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SourceArea {
+    McpServers,
+    Settings,
+    Skills,
+    Commands,
+    Agents,
+}
+
+impl SourceArea {
+    /// Whether a change here needs the configuration phase (MCP targets and settings).
+    #[must_use]
+    pub const fn affects_configuration(self) -> bool {
+        matches!(self, Self::McpServers | Self::Settings)
+    }
+
+    /// Whether a change here needs the supporting asset phase (skills, commands, agents).
+    #[must_use]
+    pub const fn affects_assets(self) -> bool {
+        !self.affects_configuration()
+    }
+}
+
+/// Which sync phases a batch of changes needs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncPhases {
+    pub configuration: bool,
+    pub assets: bool,
+}
+
+impl SyncPhases {
+    pub const ALL: Self = Self { configuration: true, assets: true };
+
+    #[must_use]
+    pub fn for_changes(changes: &[SourceChange]) -> Self {
+        Self {
+            configuration: changes.iter().any(|change| change.area.affects_configuration()),
+            assets: changes.iter().any(|change| change.area.affects_assets()),
+        }
+    }
+}
+
+/// One added, modified, or removed source file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceChange {
+    /// Path relative to its watched root.
+    pub path: String,
+    pub area: SourceArea,
+    pub kind: ChangeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+/// Watched files and their modification stamps.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceSnapshot {
+    files: BTreeMap<String, (SourceArea, FileStamp)>,
+}
+
+impl SourceSnapshot {
+    /// Scan `roots` for watched files in the areas `include` accepts.
+    #[must_use]
+    pub fn scan(roots: &[PathBuf], include: fn(SourceArea) -> bool) -> Self {
+        let mut files = BTreeMap::new();
+        for (index, root) in roots.iter().enumerate() {
+            for path in watched_files(root, include) {
+                let Some(relative) = path.strip_prefix(root).ok().map(display_relative) else {
+                    continue;
+                };
+                let Some(area) = classify(&relative).filter(|area| include(*area)) else {
+                    continue;
+                };
+                let Ok(metadata) = fs::metadata(&path) else {
+                    continue;
+                };
+                let key = match root.file_name().filter(|_| index > 0) {
+                    Some(layer) => format!("{}/{relative}", layer.to_string_lossy()),
+                    None => relative,
+                };
+                let stamp = FileStamp { modified: metadata.modified().ok(), len: metadata.len() };
+                files.insert(key, (area, stamp));
+            }
+        }
+        Self { files }
+    }
+
+    /// Changes from `self` to `current`, in path order.
+    #[must_use]
+    pub fn changes_to(&self, current: &Self) -> Vec<SourceChange> {
+        let mut changes = Vec::new();
+        for (path, (area, stamp)) in &current.files {
+            let kind = match self.files.get(path) {
+                None => ChangeKind::Added,
+                Some((_, previous)) if previous != stamp => ChangeKind::Modified,
+                Some(_) => continue,
+            };
+            changes.push(SourceChange { path: path.clone(), area: *area, kind });
+        }
+        for (path, (area, _)) in &self.files {
+            if !current.files.contains_key(path) {
+                changes.push(SourceChange {
+                    path: path.clone(),
+                    area: *area,
+                    kind: ChangeKind::Removed,
+                });
+            }
+        }
+        changes.sort_by(|left, right| left.path.cmp(&right.path));
+        changes
+    }
+}
+
+/// Area of a path relative to a config root, or `None` if it is not watched.
+#[must_use]
+pub fn classify(relative_path: &str) -> Option<SourceArea> {
+    let (first, rest) = relative_path.split_once('/').unwrap_or((relative_path, ""));
+    match first {
+        "skills" => Some(SourceArea::Skills),
+        "commands" => Some(SourceArea::Commands),
+        "agents" => Some(SourceArea::Agents),
+        "mcpServers.json" if rest.is_empty() => Some(SourceArea::McpServers),
+        name if rest.is_empty() && (name.contains(".settings.") || name == "settings.json") => {
+            Some(SourceArea::Settings)
+        },
+        _ => None,
+    }
+}
+
+/// Files in the watched entries of `root`, without walking anything else.
+fn watched_files(root: &Path, include: fn(SourceArea) -> bool) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut paths = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if !classify(&name.to_string_lossy()).is_some_and(include) {
+            continue;
+        }
+        if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+            paths.extend(collect_files(&entry.path()));
+        } else {
+            paths.push(entry.path());
+        }
+    }
+    paths
+}
+
+fn display_relative(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Polls sources and reports debounced batches of changes.
+#[derive(Debug)]
+pub struct SourceWatcher {
+    roots: Vec<PathBuf>,
+    include: fn(SourceArea) -> bool,
+    snapshot: SourceSnapshot,
+}
+
+impl SourceWatcher {
+    /// Start watching `roots` from their current state.
+    #[must_use]
+    pub fn new(roots: Vec<PathBuf>, include: fn(SourceArea) -> bool) -> Self {
+        let snapshot = SourceSnapshot::scan(&roots, include);
+        Self { roots, include, snapshot }
+    }
+
+    /// Block until sources change, then until they have been quiet for [`DEBOUNCE`].
+    ///
+    /// Roots after the first are reported under their directory name, e.g.
+    /// `.claudius/mcpServers.json`. Unreadable files are skipped.
+    #[must_use]
+    pub fn wait_for_changes(&mut self) -> Vec<SourceChange> {
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let mut latest = SourceSnapshot::scan(&self.roots, self.include);
+            if latest == self.snapshot {
+                continue;
+            }
+
+            let mut quiet_since = std::time::Instant::now();
+            while quiet_since.elapsed() < DEBOUNCE {
+                std::thread::sleep(POLL_INTERVAL);
+                let next = SourceSnapshot::scan(&self.roots, self.include);
+                if next != latest {
+                    latest = next;
+                    quiet_since = std::time::Instant::now();
+                }
+            }
+
+            let changes = self.snapshot.changes_to(&latest);
+            self.snapshot = latest;
+            if !changes.is_empty() {
+                return changes;
+            }
+        }
+    }
+}
+
+/// One-line summary such as `~ mcpServers.json, + skills/review/SKILL.md`.
+#[must_use]
+pub fn summarize_changes(changes: &[SourceChange]) -> String {
+    changes
+        .iter()
+        .map(|change| format!("{} {}", change.kind.marker(), change.path))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn classify_matches_watched_sources_only() {
+        assert_eq!(classify("mcpServers.json"), Some(SourceArea::McpServers));
+        assert_eq!(classify("codex.settings.toml"), Some(SourceArea::Settings));
+        assert_eq!(classify("skills/review/SKILL.md"), Some(SourceArea::Skills));
+        assert_eq!(classify("agents/claude-code/reviewer.md"), Some(SourceArea::Agents));
+        assert_eq!(classify("commands/gemini/review.toml"), Some(SourceArea::Commands));
+        assert_eq!(classify("config.toml"), None);
+        assert_eq!(classify("rules/style.md"), None);
+        assert_eq!(classify(".claudius-sync-journal.json"), None);
+    }
+
+    #[test]
+    fn snapshot_changes_report_added_modified_and_removed_files() {
+        let root = TempDir::new().unwrap();
+        let roots = vec![root.path().to_path_buf()];
+        fs::write(root.path().join("mcpServers.json"), "{}").unwrap();
+        fs::write(root.path().join("claude.settings.json"), "{}").unwrap();
+        let before = SourceSnapshot::scan(&roots, |_| true);
+
+        fs::write(root.path().join("mcpServers.json"), r#"{"mcpServers": {}}"#).unwrap();
+        fs::remove_file(root.path().join("claude.settings.json")).unwrap();
+        fs::create_dir_all(root.path().join("skills/review")).unwrap();
+        fs::write(root.path().join("skills/review/SKILL.md"), "# Review").unwrap();
+        let after = SourceSnapshot::scan(&roots, |_| true);
+
+        let changes = before.changes_to(&after);
+        assert_eq!(
+            summarize_changes(&changes),
+            "- claude.settings.json, ~ mcpServers.json, + skills/review/SKILL.md"
+        );
+        assert_eq!(SyncPhases::for_changes(&changes), SyncPhases::ALL);
+    }
+
+    #[test]
+    fn snapshot_scan_ignores_unwatched_entries() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("config.toml"), "").unwrap();
+        fs::create_dir_all(root.path().join("undo-history/1")).unwrap();
+        fs::write(root.path().join("undo-history/1/record.json"), "{}").unwrap();
+        fs::write(root.path().join("mcpServers.json"), "{}").unwrap();
+
+        let snapshot = SourceSnapshot::scan(&[root.path().to_path_buf()], |_| true);
+        let changes = SourceSnapshot::default().changes_to(&snapshot);
+
+        assert_eq!(summarize_changes(&changes), "+ mcpServers.json");
+    }
+
+    #[test]
+    fn snapshot_scan_skips_excluded_areas() {
+        let root = TempDir::new().unwrap();
+        fs::write(root.path().join("mcpServers.json"), "{}").unwrap();
+        fs::create_dir_all(root.path().join("skills/review")).unwrap();
+        fs::write(root.path().join("skills/review/SKILL.md"), "# Review").unwrap();
+
+        let snapshot =
+            SourceSnapshot::scan(&[root.path().to_path_buf()], |area| area == SourceArea::Skills);
+        let changes = SourceSnapshot::default().changes_to(&snapshot);
+
+        assert_eq!(summarize_changes(&changes), "+ skills/review/SKILL.md");
+        assert_eq!(
+            SyncPhases::for_changes(&changes),
+            SyncPhases { configuration: false, assets: true }
+        );
+    }
+}
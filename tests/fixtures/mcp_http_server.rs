@@ -0,0 +1,229 @@
+// Local HTTP stand-in for remote MCP servers in `claudius mcp check` tests.
+//
+// Each connection is served on its own thread and closed after one response,
+// except the legacy SSE stream, which stays open and carries the answers to
+// the messages POSTed to the endpoint it announces.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Session id handed out by the streamable HTTP scenarios.
+pub const SESSION_ID: &str = "session-1";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scenario {
+    /// Streamable HTTP answering with JSON bodies.
+    Streamable,
+    /// Streamable HTTP answering with event streams.
+    StreamableSse,
+    /// Every request is refused with 401 and an OAuth challenge.
+    Unauthorized,
+    /// Legacy HTTP+SSE: `GET /sse` announces `/messages`, answers arrive on the stream.
+    LegacySse,
+}
+
+/// A request the stand-in received, with lowercase header names.
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// The JSON-RPC method in the body, if any.
+    pub fn rpc_method(&self) -> Option<String> {
+        serde_json::from_str::<Value>(&self.body)
+            .ok()
+            .and_then(|message| message.get("method").and_then(Value::as_str).map(str::to_string))
+    }
+}
+
+struct Shared {
+    scenario: Scenario,
+    requests: Mutex<Vec<RecordedRequest>>,
+    /// Feeds the open legacy SSE stream.
+    stream: Mutex<Option<Sender<Value>>>,
+}
+
+pub struct McpHttpServer {
+    /// `http://127.0.0.1:<port>`, without a path.
+    pub base_url: String,
+    shared: Arc<Shared>,
+}
+
+impl McpHttpServer {
+    pub fn start(scenario: Scenario) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let shared = Arc::new(Shared {
+            scenario,
+            requests: Mutex::new(Vec::new()),
+            stream: Mutex::new(None),
+        });
+        let accepted = Arc::clone(&shared);
+        thread::spawn(move || {
+            for connection in listener.incoming().map_while(Result::ok) {
+                let state = Arc::clone(&accepted);
+                thread::spawn(move || serve(connection, &state));
+            }
+        });
+        Self { base_url, shared }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(mut connection: TcpStream, shared: &Shared) {
+    let Some(request) = read_request(&mut BufReader::new(connection.try_clone().unwrap())) else {
+        return;
+    };
+    shared.requests.lock().unwrap().push(request.clone());
+    let message = serde_json::from_str::<Value>(&request.body).unwrap_or(Value::Null);
+
+    match shared.scenario {
+        Scenario::Unauthorized => respond(
+            &mut connection,
+            "401 Unauthorized",
+            &[(
+                "WWW-Authenticate",
+                "Bearer resource_metadata=\"http://127.0.0.1/.well-known/oauth-protected-resource\"",
+            )],
+            "unauthorized",
+        ),
+        Scenario::LegacySse if request.method == "GET" => open_stream(connection, shared),
+        Scenario::LegacySse => {
+            if let Some(reply) = reply_to(&message) {
+                if let Some(stream) = shared.stream.lock().unwrap().as_ref() {
+                    let _ = stream.send(reply);
+                }
+            }
+            respond(&mut connection, "202 Accepted", &[], "");
+        },
+        Scenario::Streamable | Scenario::StreamableSse => {
+            serve_streamable(&mut connection, shared.scenario, &request, &message);
+        },
+    }
+}
+
+fn serve_streamable(
+    connection: &mut TcpStream,
+    scenario: Scenario,
+    request: &RecordedRequest,
+    message: &Value,
+) {
+    let initializing = message.get("method").and_then(Value::as_str) == Some("initialize");
+    let session = request.headers.get("mcp-session-id").map(String::as_str);
+    if request.method == "DELETE" {
+        return respond(connection, "200 OK", &[], "");
+    }
+    if !initializing && session != Some(SESSION_ID) {
+        return respond(connection, "400 Bad Request", &[], "missing Mcp-Session-Id");
+    }
+    let Some(reply) = reply_to(message) else {
+        return respond(connection, "202 Accepted", &[], "");
+    };
+    let session_header = [("Mcp-Session-Id", SESSION_ID)];
+    let headers: &[(&str, &str)] = if initializing { &session_header } else { &[] };
+
+    if scenario == Scenario::StreamableSse {
+        let log = json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": {} });
+        let body = format!("event: message\ndata: {log}\n\nevent: message\ndata: {reply}\n\n");
+        let mut all = headers.to_vec();
+        all.push(("Content-Type", "text/event-stream"));
+        respond(connection, "200 OK", &all, &body);
+    } else {
+        let mut all = headers.to_vec();
+        all.push(("Content-Type", "application/json"));
+        respond(connection, "200 OK", &all, &reply.to_string());
+    }
+}
+
+fn open_stream(mut connection: TcpStream, shared: &Shared) {
+    let (sender, messages) = mpsc::channel::<Value>();
+    *shared.stream.lock().unwrap() = Some(sender);
+    let opened = write!(
+        connection,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n\
+         : connected\n\nevent: endpoint\ndata: /messages?session={SESSION_ID}\n\n"
+    )
+    .and_then(|()| connection.flush());
+    if opened.is_err() {
+        return;
+    }
+    for message in messages {
+        let sent = write!(connection, "event: message\ndata: {message}\n\n")
+            .and_then(|()| connection.flush());
+        if sent.is_err() {
+            break;
+        }
+    }
+}
+
+/// The stand-in's answer to a JSON-RPC request; notifications get none.
+fn reply_to(message: &Value) -> Option<Value> {
+    let id = message.get("id")?;
+    let result = match message.get("method").and_then(Value::as_str)? {
+        "initialize" => json!({
+            "protocolVersion": "2025-06-18",
+            "capabilities": { "prompts": {}, "tools": {} },
+            "serverInfo": { "name": "remote", "version": "2.0.0" },
+        }),
+        "tools/list" => json!({
+            "tools": [{ "name": "search" }, { "name": "fetch" }, { "name": "summarize" }],
+        }),
+        _ => json!({}),
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn respond(connection: &mut TcpStream, status: &str, headers: &[(&str, &str)], body: &str) {
+    let mut head = format!(
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        head.push_str(name);
+        head.push_str(": ");
+        head.push_str(value);
+        head.push_str("\r\n");
+    }
+    head.push_str("\r\n");
+    head.push_str(body);
+    let _ = connection.write_all(head.as_bytes()).and_then(|()| connection.flush());
+}
+
+fn read_request(reader: &mut impl BufRead) -> Option<RecordedRequest> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).ok()?;
+        let Some((name, value)) = line.trim_end().split_once(':') else {
+            break;
+        };
+        headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+    }
+
+    let length = headers.get("content-length").and_then(|value| value.parse().ok()).unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).ok()?;
+    Some(RecordedRequest { method, path, headers, body: String::from_utf8_lossy(&body).into_owned() })
+}
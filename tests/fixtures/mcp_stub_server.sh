@@ -0,0 +1,43 @@
+#!/bin/sh
+# Stub MCP stdio server for `claudius mcp check` tests.
+#
+# Usage: mcp_stub_server.sh [ok|no-tools|error|crash|hang|noisy]
+#   ok        answer initialize and tools/list (2 tools)
+#   no-tools  answer initialize without a tools capability
+#   error     answer initialize with a JSON-RPC error
+#   crash     write to stderr and exit 3 before answering
+#   hang      read requests but never answer
+#   noisy     write a log line to stdout before answering
+# The server name is taken from $STUB_NAME (default: stub).
+
+mode="${1:-ok}"
+name="${STUB_NAME:-stub}"
+
+if [ "$mode" = "crash" ]; then
+    echo "stub: missing API token" >&2
+    exit 3
+fi
+
+if [ "$mode" = "noisy" ]; then
+    echo "Starting stub server..."
+fi
+
+while IFS= read -r line; do
+    id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9][0-9]*\).*/\1/p')
+    case "$mode:$line" in
+        hang:*) ;;
+        error:*'"method":"initialize"'*)
+            printf '{"jsonrpc":"2.0","id":%s,"error":{"code":-32602,"message":"unsupported protocol version"}}\n' "$id"
+            ;;
+        no-tools:*'"method":"initialize"'*)
+            printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{},"serverInfo":{"name":"%s","version":"0.1.0"}}}\n' "$id" "$name"
+            ;;
+        *'"method":"initialize"'*)
+            printf '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info","data":"booting"}}\n'
+            printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"%s","version":"1.2.3"}}}\n' "$id" "$name"
+            ;;
+        *'"method":"tools/list"'*)
+            printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"read","inputSchema":{"type":"object"}},{"name":"write","inputSchema":{"type":"object"}}]}}\n' "$id"
+            ;;
+    esac
+done
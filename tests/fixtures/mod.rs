@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+use assert_cmd::Command;
 use std::fs;
 use std::path::PathBuf;
 use tempfile::{tempdir, TempDir};
@@ -215,6 +216,27 @@ impl TestFixture {
         self.temp.path().join("home")
     }
 
+    /// List the project directory under `trusted-projects` in config.toml so its
+    /// `.claudius/` layer is applied
+    pub fn trust_project(&self) -> std::io::Result<&Self> {
+        let path = self.config.join("config.toml");
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let trusted = format!("trusted-projects = ['{}']\n", self.project.display());
+        fs::write(path, trusted + &existing)?;
+        Ok(self)
+    }
+
+    /// The claudius binary, run from the project directory with the fixture's
+    /// config and home directories
+    pub fn claudius(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_claudius"));
+        cmd.current_dir(&self.project)
+            .env("XDG_CONFIG_HOME", self.config_home())
+            .env("HOME", self.home_dir())
+            .env_remove("XDG_STATE_HOME");
+        cmd
+    }
+
     /// Set environment variables for the test
     pub fn setup_env(&self) {
         std::env::set_var("XDG_CONFIG_HOME", self.config_home());
@@ -0,0 +1,137 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECTS: &str = r#"
+[[projects]]
+path = "~/src/api"
+agents = ["claude-code", "codex"]
+
+[[projects]]
+path = "~/src/web"
+agents = ["claude-code"]
+scope = "local"
+"#;
+
+    fn projects_fixture(registry: &str) -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"github": {"command": "github-mcp"}}}"#)
+            .unwrap();
+        fs::write(fixture.config.join("config.toml"), registry).unwrap();
+        for project in ["src/api", "src/web"] {
+            fs::create_dir_all(fixture.home_dir().join(project)).unwrap();
+        }
+        fixture
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_projects_syncs_every_registered_project() {
+        let fixture = projects_fixture(PROJECTS);
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--all-projects"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Syncing 3 project target(s) one at a time"))
+            .stdout(predicate::str::contains("ok      ~/src/api (Claude Code)"))
+            .stdout(predicate::str::contains("ok      ~/src/api (Codex)"))
+            .stdout(predicate::str::contains("3 succeeded, 0 failed"));
+
+        let api = fixture.home_dir().join("src/api");
+        assert!(fs::read_to_string(api.join(".mcp.json")).unwrap().contains("github-mcp"));
+        assert!(fs::read_to_string(api.join(".codex/config.toml"))
+            .unwrap()
+            .contains("github-mcp"));
+        let claude_json = fs::read_to_string(fixture.home_dir().join(".claude.json")).unwrap();
+        assert!(claude_json.contains("src/web"));
+        assert!(!fixture.home_dir().join("src/web/.mcp.json").exists());
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_projects_reports_failures_and_continues() {
+        let registry = format!("{PROJECTS}\n[[projects]]\npath = \"~/src/missing\"\n");
+        let fixture = projects_fixture(&registry);
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--all-projects", "--agent", "claude-code"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "FAILED  ~/src/missing (Claude Code): Project directory not found",
+            ))
+            .stdout(predicate::str::contains("2 succeeded, 1 failed"))
+            .stderr(predicate::str::contains("1 of 3 project sync(s) failed"));
+
+        assert!(fixture.home_dir().join("src/api/.mcp.json").exists());
+        assert!(!fixture.home_dir().join("src/api/.codex/config.toml").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_projects_dry_run_runs_in_parallel_without_writing() {
+        let fixture = projects_fixture(PROJECTS);
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--all-projects", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("in parallel"))
+            .stdout(predicate::str::contains("== ~/src/api (Codex) =="))
+            .stdout(predicate::str::contains("3 succeeded, 0 failed"));
+
+        assert!(!fixture.home_dir().join("src/api/.mcp.json").exists());
+        assert!(!fixture.home_dir().join("src/api/.codex").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_projects_children_do_not_prompt_on_conflicts() {
+        let fixture = projects_fixture(PROJECTS);
+        fs::write(
+            fixture.home_dir().join("src/api/.mcp.json"),
+            r#"{"mcpServers": {"github": {"command": "hand-edited"}}}"#,
+        )
+        .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--all-projects", "--agent", "claude-code"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("FAILED  ~/src/api (Claude Code)"));
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--all-projects", "--agent", "claude-code"])
+            .args(["--strategy", "merge"])
+            .assert()
+            .success();
+        let api = fs::read_to_string(fixture.home_dir().join("src/api/.mcp.json")).unwrap();
+        assert!(api.contains("github-mcp"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_all_projects_requires_registered_projects() {
+        let fixture = projects_fixture("");
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--all-projects"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No projects registered"));
+    }
+}
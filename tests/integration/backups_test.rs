@@ -0,0 +1,169 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND_WRITTEN: &str = r#"{"mcpServers": {"legacy": {"command": "legacy-mcp"}}}"#;
+
+    fn backup_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"github": {"command": "github-mcp"}}}"#)
+            .unwrap();
+        fs::write(fixture.project.join(".mcp.json"), HAND_WRITTEN).unwrap();
+        fixture
+    }
+
+    fn project_backups(fixture: &TestFixture) -> Vec<String> {
+        let mut names = fs::read_dir(&fixture.project)
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| name.starts_with(".mcp.json.backup."))
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    fn central_dir(fixture: &TestFixture) -> PathBuf {
+        let project = fixture.project.canonicalize().unwrap();
+        let mirrored = project.strip_prefix("/").unwrap().to_path_buf();
+        fixture.home_dir().join(".local/state/claudius/backups").join(mirrored)
+    }
+
+    #[test]
+    #[serial]
+    fn test_list_shows_sync_and_source_backups() {
+        let fixture = backup_fixture();
+        fs::write(fixture.config.join("claude.settings.json.backup.20260101_000000"), "{}")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "replace", "--backup"])
+            .assert()
+            .success();
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(".mcp.json.backup."))
+            .stdout(predicate::str::contains("claude.settings.json.backup.20260101_000000"))
+            .stdout(predicate::str::contains("2 backup(s) of 2 file(s)"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_restore_newest_backup_backs_up_current_file() {
+        let fixture = backup_fixture();
+        fs::write(fixture.project.join(".mcp.json.backup.20260101_000000"), "{}").unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "replace", "--backup"])
+            .assert()
+            .success();
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("github-mcp"));
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "restore", ".mcp.json"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Backed up current"))
+            .stdout(predicate::str::contains("Restored"));
+
+        assert_eq!(fixture.read_project_file(".mcp.json").unwrap(), HAND_WRITTEN);
+        assert_eq!(project_backups(&fixture).len(), 3);
+    }
+
+    #[test]
+    #[serial]
+    fn test_restore_specific_backup_file() {
+        let fixture = backup_fixture();
+        fs::write(fixture.project.join(".mcp.json.backup.20260101_000000"), "{\"old\": true}")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "restore", ".mcp.json.backup.20260101_000000"])
+            .assert()
+            .success();
+
+        assert_eq!(fixture.read_project_file(".mcp.json").unwrap(), "{\"old\": true}");
+    }
+
+    #[test]
+    #[serial]
+    fn test_prune_keeps_newest_backups_per_file() {
+        let fixture = backup_fixture();
+        for timestamp in ["20260101_000000", "20260201_000000", "20260301_000000"] {
+            fs::write(fixture.project.join(format!(".mcp.json.backup.{timestamp}")), "{}").unwrap();
+        }
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "prune", "--keep", "1", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would prune 2 backup(s)"));
+        assert_eq!(project_backups(&fixture).len(), 3);
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "prune", "--keep", "1"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Pruned 2 backup(s)"));
+        assert_eq!(project_backups(&fixture), [".mcp.json.backup.20260301_000000"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_prune_requires_a_retention_policy() {
+        let fixture = backup_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "prune"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No retention policy"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_central_backups_apply_configured_retention() {
+        let fixture = backup_fixture();
+        fs::write(fixture.config.join("config.toml"), "[backups]\nkeep = 1\ncentral = true\n")
+            .unwrap();
+        let central = central_dir(&fixture);
+        fs::create_dir_all(&central).unwrap();
+        fs::write(central.join(".mcp.json.backup.20260101_000000"), "{}").unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "replace", "--backup"])
+            .assert()
+            .success();
+
+        assert!(project_backups(&fixture).is_empty());
+        let stored = fs::read_dir(&central).unwrap().flatten().collect::<Vec<_>>();
+        assert_eq!(stored.len(), 1);
+        let newest = stored.first().map(fs::DirEntry::path).unwrap_or_default();
+        assert_eq!(fs::read_to_string(newest).unwrap(), HAND_WRITTEN);
+
+        fixture
+            .claudius()
+            .args(["config", "backups", "list", "--file", ".mcp.json"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("1 backup(s) of 1 file(s)"));
+    }
+}
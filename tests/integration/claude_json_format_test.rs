@@ -0,0 +1,86 @@
+use crate::fixtures::TestFixture;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING_CLAUDE_JSON: &str = r#"{
+    "numStartups": 12,
+    "theme": "dark",
+    "tipsHistory": {"memory": 3},
+    "mcpServers": {
+        "alpha": {
+            "command": "alpha-server",
+            "args": [],
+            "env": {}
+        }
+    }
+}
+"#;
+
+    const SYNCED_CLAUDE_JSON: &str = r#"{
+    "numStartups": 12,
+    "theme": "dark",
+    "tipsHistory": {"memory": 3},
+    "mcpServers": {
+        "alpha": {
+            "command": "alpha-server",
+            "args": [],
+            "env": {}
+        },
+        "beta": {
+            "command": "beta-server",
+            "args": [],
+            "env": {}
+        }
+    }
+}
+"#;
+
+    const TWO_SERVERS: &str = r#"{
+        "mcpServers": {
+            "alpha": {"command": "alpha-server"},
+            "beta": {"command": "beta-server"}
+        }
+    }"#;
+
+    #[test]
+    #[serial]
+    fn test_global_sync_patches_only_mcp_servers_in_claude_json() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TWO_SERVERS).unwrap();
+        fixture.with_existing_global_config(EXISTING_CLAUDE_JSON).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--global", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let content = fixture.read_home_file(".claude.json").unwrap();
+        assert_eq!(content, SYNCED_CLAUDE_JSON);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn test_global_sync_preserves_claude_json_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TWO_SERVERS).unwrap();
+        fixture.with_existing_global_config(EXISTING_CLAUDE_JSON).unwrap();
+        let claude_json = fixture.home_dir().join(".claude.json");
+        std::fs::set_permissions(&claude_json, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--global", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let mode = std::fs::metadata(&claude_json).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
@@ -0,0 +1,73 @@
+use crate::fixtures::TestFixture;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING_CODEX_CONFIG: &str = r#"# Personal Codex setup
+model = "o3" # keep in sync with the team default
+
+[mcp_servers.alpha]
+# launched through the wrapper script
+command = "alpha-server"
+env = { ALPHA_MODE = "fast" }
+
+[mcp_servers.manual]
+command = "manual-server"
+"#;
+
+    #[test]
+    #[serial]
+    fn test_global_codex_sync_preserves_comments_and_inline_tables() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {
+                    "alpha": {"command": "alpha-server", "env": {"ALPHA_MODE": "fast"}},
+                    "beta": {"command": "beta-server"}
+                }}"#,
+            )
+            .unwrap();
+        let codex_path = fixture.home_dir().join(".codex").join("config.toml");
+        std::fs::create_dir_all(codex_path.parent().unwrap()).unwrap();
+        std::fs::write(&codex_path, EXISTING_CODEX_CONFIG).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--global", "--agent", "codex"])
+            .assert()
+            .success();
+
+        let content = fixture.read_home_file(".codex/config.toml").unwrap();
+        assert!(content.starts_with(EXISTING_CODEX_CONFIG), "unexpected rewrite:\n{content}");
+        assert!(content.contains("[mcp_servers.beta]\ncommand = \"beta-server\""));
+    }
+
+    #[test]
+    #[serial]
+    fn test_project_codex_sync_only_touches_changed_values() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-v2"}}}"#)
+            .unwrap();
+        std::fs::create_dir_all(fixture.project.join(".codex")).unwrap();
+        std::fs::write(
+            fixture.project.join(".codex").join("config.toml"),
+            "# project overrides\n[mcp_servers.alpha]\ncommand = \"alpha-v1\" # pinned\n",
+        )
+        .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "codex"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".codex/config.toml").unwrap();
+        assert_eq!(
+            content,
+            "# project overrides\n[mcp_servers.alpha]\ncommand = \"alpha-v2\" # pinned\n"
+        );
+    }
+}
@@ -773,9 +773,9 @@ base_url = "https://proxy.openai.com/v1"
             "extra provider field should be preserved from existing config"
         );
 
-        // Headers should be merged (not replaced)
+        // Headers should be merged (not replaced), keeping the existing key spelling
         anyhow::ensure!(
-            codex_config_content.contains("X-Existing = \"keep\""),
+            codex_config_content.contains("\"X-Existing\" = \"keep\""),
             "existing header should be preserved"
         );
         anyhow::ensure!(
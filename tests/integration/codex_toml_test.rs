@@ -152,6 +152,7 @@ env_key = "OPENAI_API_KEY"
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -165,6 +166,7 @@ env_key = "OPENAI_API_KEY"
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -188,6 +190,7 @@ env_key = "OPENAI_API_KEY"
                 url: Some("https://example.com/mcp".to_string()),
                 headers: remote_headers,
                 extra: remote_extra,
+                targeting: None,
             },
         );
 
@@ -406,6 +409,7 @@ key = "value"
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -0,0 +1,155 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_sources(fixture: &TestFixture, name: &str) -> serde_json::Value {
+        let content = fs::read_to_string(fixture.config.join(name)).unwrap();
+        serde_json::from_str(&content).unwrap()
+    }
+
+    fn project_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_existing_mcp_json(
+                r#"{"mcpServers": {
+                    "github": {"type": "stdio", "command": "npx", "args": ["-y", "github-mcp"], "env": {}},
+                    "shared": {"command": "shared-a"}
+                }}"#,
+            )
+            .unwrap();
+        fs::create_dir_all(fixture.project.join(".codex")).unwrap();
+        fs::write(
+            fixture.project.join(".codex/config.toml"),
+            r#"# Preferred model
+model = "o3"
+
+[mcp_servers.gh]
+command = "npx"
+args = ["-y", "github-mcp"]
+
+[mcp_servers.shared]
+command = "shared-b"
+
+[mcp_servers.docs]
+url = "https://docs.example.com/mcp"
+http_headers = { "X-Team" = "core" }
+"#,
+        )
+        .unwrap();
+        fixture
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_dedupes_and_reports_conflicts() {
+        let fixture = project_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "import"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("+ github"))
+            .stdout(predicate::str::contains("= gh (from Codex"))
+            .stdout(predicate::str::contains("same server as github"))
+            .stdout(predicate::str::contains("! shared (from Codex"))
+            .stdout(predicate::str::contains("+ docs"));
+
+        let sources = read_sources(&fixture, "mcpServers.json");
+        let servers = sources.get("mcpServers").and_then(|value| value.as_object()).unwrap();
+        assert_eq!(servers.keys().collect::<Vec<_>>(), ["github", "shared", "docs"]);
+        assert_eq!(
+            *servers.get("github").unwrap(),
+            serde_json::json!({"command": "npx", "args": ["-y", "github-mcp"]})
+        );
+        assert_eq!(
+            servers.get("shared").and_then(|server| server.get("command")).unwrap(),
+            "shared-a"
+        );
+        assert_eq!(
+            *servers.get("docs").unwrap(),
+            serde_json::json!({
+                "type": "http",
+                "url": "https://docs.example.com/mcp",
+                "headers": {"X-Team": "core"}
+            })
+        );
+
+        let codex_settings =
+            fs::read_to_string(fixture.config.join("codex.settings.toml")).unwrap();
+        assert_eq!(codex_settings, "# Preferred model\nmodel = \"o3\"\n");
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_dry_run_writes_nothing() {
+        let fixture = project_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "import", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[created]"))
+            .stdout(predicate::str::contains("+    \"docs\""))
+            .stdout(predicate::str::contains("Dry run: no files were modified"));
+
+        assert!(!fixture.config.join("mcpServers.json").exists());
+        assert!(!fixture.config.join("codex.settings.toml").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_keeps_existing_sources() {
+        let fixture = project_fixture();
+        let existing = "{\n  \"mcpServers\": {\n    \"shared\": {\"command\": \"mine\"}\n  }\n}";
+        fixture.with_mcp_servers(existing).unwrap();
+        fixture.with_codex_settings("model = \"gpt-5\"\n").unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "import", "--agent", "codex"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("conflicts with the definition from mcpServers.json"))
+            .stdout(predicate::str::contains("Kept existing codex.settings.toml"));
+
+        let sources = read_sources(&fixture, "mcpServers.json");
+        assert_eq!(*sources.pointer("/mcpServers/shared/command").unwrap(), "mine");
+        assert_eq!(*sources.pointer("/mcpServers/gh/command").unwrap(), "npx");
+        assert!(sources.pointer("/mcpServers/github").is_none());
+        assert_eq!(
+            fs::read_to_string(fixture.config.join("codex.settings.toml")).unwrap(),
+            "model = \"gpt-5\"\n"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_import_global_gemini_restores_http_url() {
+        let fixture = TestFixture::new().unwrap();
+        fs::create_dir_all(fixture.home_dir().join(".gemini")).unwrap();
+        fs::write(
+            fixture.home_dir().join(".gemini/settings.json"),
+            r#"{"theme": "dark", "mcpServers": {"remote": {"httpUrl": "https://remote.example.com", "trust": true}}}"#,
+        )
+        .unwrap();
+
+        fixture.claudius().args(["config", "import", "--global"]).assert().success();
+
+        let sources = read_sources(&fixture, "mcpServers.json");
+        assert_eq!(
+            *sources.pointer("/mcpServers/remote").unwrap(),
+            serde_json::json!({"type": "http", "url": "https://remote.example.com", "trust": true})
+        );
+        assert_eq!(
+            read_sources(&fixture, "gemini.settings.json"),
+            serde_json::json!({"theme": "dark"})
+        );
+    }
+}
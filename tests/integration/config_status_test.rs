@@ -0,0 +1,131 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn synced_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"alpha": {"command": "alpha"}, "beta": {"command": "beta"}}}"#,
+            )
+            .unwrap();
+        fixture.with_claude_code_agent("reviewer", "# Reviewer\n").unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+        fixture
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_reports_up_to_date_after_sync() {
+        let fixture = synced_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Claude Code (project): up to date"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_reports_missing_targets_without_writing() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha"}}}"#)
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .code(3)
+            .stdout(predicate::str::contains("[created]"));
+
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_detects_hand_edited_mcp_entries() {
+        let fixture = synced_fixture();
+        let edited = fixture
+            .read_project_file(".mcp.json")
+            .unwrap()
+            .replace("\"command\": \"alpha\"", "\"command\": \"hand-edited\"");
+        fixture.with_existing_mcp_json(&edited).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .code(3)
+            .stdout(predicate::str::contains("Claude Code (project): out of date"))
+            .stdout(predicate::str::contains("~ mcpServers.alpha"))
+            .stdout(predicate::str::contains("mcpServers.beta").not());
+
+        assert_eq!(fixture.read_project_file(".mcp.json").unwrap(), edited);
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_detects_modified_and_stale_subagents() {
+        let fixture = synced_fixture();
+        std::fs::write(fixture.project.join(".claude/agents/reviewer.md"), "# Edited\n").unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .code(3)
+            .stdout(predicate::str::contains("~ reviewer.md (differs from source)"));
+
+        std::fs::remove_file(fixture.config.join("agents/claude-code/reviewer.md")).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .code(3)
+            .stdout(predicate::str::contains("- reviewer.md (stale"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_status_reports_servers_removed_from_sources_until_pruned() {
+        let fixture = synced_fixture();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha"}}}"#)
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .code(3)
+            .stdout(predicate::str::contains("- beta (stale; removed by `config sync --prune`)"))
+            .stdout(predicate::str::contains("alpha (stale").not());
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--prune"])
+            .assert()
+            .success();
+
+        fixture
+            .claudius()
+            .args(["config", "status", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Claude Code (project): up to date"));
+    }
+}
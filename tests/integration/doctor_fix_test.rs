@@ -0,0 +1,222 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Value at a JSON pointer such as `/fixes/0/status`, or null when absent.
+    fn field(document: &Value, pointer: &str) -> Value {
+        document.pointer(pointer).cloned().unwrap_or(Value::Null)
+    }
+
+    const CODEX_OVERRIDE: &str = "---\nname: setup-review\ndescription: Review the repository.\ndisable-model-invocation: true\n---\n\nCodex specific review instructions.\n";
+
+    fn override_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture
+            .with_canonical_skill(
+                "setup-review",
+                "version: 1\nname: setup-review\ndescription: Review the repository.\n",
+                "Shared review instructions.\n",
+            )
+            .unwrap();
+        fixture.with_agent_skill("codex", "setup-review", CODEX_OVERRIDE).unwrap();
+        fixture
+    }
+
+    fn override_dir(fixture: &TestFixture) -> std::path::PathBuf {
+        fixture.config.join("skills").join("codex").join("setup-review")
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_fix_renames_legacy_settings() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_settings(r#"{"apiKeyHelper":"legacy-helper"}"#).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "doctor", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Fixes:"))
+            .stdout(predicate::str::contains("Renamed"))
+            .stdout(predicate::str::contains("claude.settings.json"));
+        fixture
+            .claudius()
+            .args(["config", "doctor"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Legacy settings.json is still active").not());
+
+        assert!(!fixture.config.join("settings.json").exists());
+        assert_eq!(
+            fs::read_to_string(fixture.config.join("claude.settings.json")).unwrap(),
+            r#"{"apiKeyHelper":"legacy-helper"}"#
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_fix_prunes_stale_deployed_files() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture
+            .with_gemini_command(
+                "review",
+                "description = \"Review the current diff\"\nprompt = \"Review this change set.\"",
+            )
+            .unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "gemini"])
+            .assert()
+            .success();
+        fs::remove_file(fixture.config.join("commands").join("gemini").join("review.toml"))
+            .unwrap();
+        assert!(fixture.project_file_exists(".gemini/commands/review.toml"));
+
+        fixture
+            .claudius()
+            .args(["config", "doctor", "--agent", "gemini", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Removed 1 stale file(s)"));
+        fixture
+            .claudius()
+            .args(["config", "doctor", "--agent", "gemini"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("has stale deployed files").not());
+
+        assert!(!fixture.project_file_exists(".gemini/commands/review.toml"));
+
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("config doctor --agent gemini --fix"));
+        assert!(fixture.project_file_exists(".gemini/commands/review.toml"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_fix_dry_run_changes_nothing() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_settings(r#"{"apiKeyHelper":"legacy-helper"}"#).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "doctor", "--fix", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would rename"))
+            .stdout(predicate::str::contains("Legacy settings.json is still active"));
+
+        assert!(fixture.config.join("settings.json").exists());
+        assert!(!fixture.config.join("claude.settings.json").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_fix_skips_risky_fixes_without_confirmation() {
+        let fixture = override_fixture();
+
+        fixture
+            .claudius()
+            .args(["--no-input", "config", "doctor", "--agent", "codex", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Skipped: run `claudius skills migrate --agent codex`",
+            ))
+            .stdout(predicate::str::contains(
+                "Codex full override skill directories are still in use.",
+            ));
+
+        assert!(override_dir(&fixture).exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_fix_applies_risky_fixes_with_yes() {
+        let fixture = override_fixture();
+
+        fixture
+            .claudius()
+            .args(["--yes", "config", "doctor", "--agent", "codex", "--fix"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Migrated 1 Codex skill override(s)"));
+
+        assert!(!override_dir(&fixture).exists());
+        assert!(fixture
+            .config
+            .join("skills")
+            .join("setup-review")
+            .join("targets")
+            .join("codex.md")
+            .exists());
+
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("config doctor --agent codex --fix"));
+        assert_eq!(
+            fs::read_to_string(override_dir(&fixture).join("SKILL.md")).unwrap(),
+            CODEX_OVERRIDE
+        );
+        assert!(!fixture
+            .config
+            .join("skills")
+            .join("setup-review")
+            .join("targets")
+            .join("codex.md")
+            .exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_fix_json_lists_fixes_and_remaining_findings() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_settings(r#"{"apiKeyHelper":"legacy-helper"}"#).unwrap();
+
+        let output = fixture
+            .claudius()
+            .args(["config", "doctor", "--fix", "--format", "json"])
+            .assert()
+            .success();
+        let document: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+
+        assert_eq!(field(&document, "/command"), "config doctor");
+        assert_eq!(field(&document, "/dry_run"), false);
+        assert_eq!(field(&document, "/fixes/0/status"), "applied");
+        assert_eq!(field(&document, "/fixes/0/fix/kind"), "rename-source");
+        let findings = field(&document, "/findings").as_array().cloned().unwrap_or_default();
+        assert!(findings.iter().all(|finding| field(finding, "/fix").is_null()));
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_dry_run_requires_fix() {
+        let fixture = TestFixture::new().unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "doctor", "--dry-run"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--fix"));
+    }
+}
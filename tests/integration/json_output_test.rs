@@ -0,0 +1,157 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serde_json::Value;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `args` with `--format json` and parse stdout as one JSON document.
+    fn json_document(fixture: &TestFixture, args: &[&str], success: bool) -> Value {
+        let assert = fixture.claudius().args(args).args(["--format", "json"]).assert();
+        let output = if success { assert.success() } else { assert.failure() };
+        serde_json::from_slice(&output.get_output().stdout).unwrap()
+    }
+
+    /// Value at a JSON pointer such as `/agents/0/files`, or null when absent.
+    fn field(document: &Value, pointer: &str) -> Value {
+        document.pointer(pointer).cloned().unwrap_or(Value::Null)
+    }
+
+    const CLAUDE_REVIEW_SKILL: &str = "---\nname: shared-review\ndescription: Review changes.\ndisable-model-invocation: true\n---\n\nReview changes.\n";
+
+    #[test]
+    #[serial]
+    fn test_config_validate_json_reports_diagnostics() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"broken": {"args": ["--help"]}}}"#)
+            .unwrap();
+
+        let document = json_document(&fixture, &["config", "validate", "--strict"], false);
+
+        assert_eq!(field(&document, "/schema_version"), 1);
+        assert_eq!(field(&document, "/command"), "config validate");
+        let diagnostics = field(&document, "/diagnostics").as_array().cloned().unwrap_or_default();
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics.iter().all(|diagnostic| {
+            ["error", "warning", "info"]
+                .contains(&field(diagnostic, "/severity").as_str().unwrap_or(""))
+                && field(diagnostic, "/message").is_string()
+                && diagnostic.get("path").is_some()
+        }));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_sync_json_reports_per_file_outcomes() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"github": {"command": "github-mcp"}}}"#)
+            .unwrap();
+        fixture.with_skill("review", "# Review").unwrap();
+        fs::write(fixture.config.join("config.toml"), "[profiles.work]\nservers = [\"github\"]\n")
+            .unwrap();
+        let sync = ["config", "sync", "--agent", "claude-code", "--profile", "work"];
+
+        let first = json_document(&fixture, &sync, true);
+        assert_eq!(field(&first, "/command"), "config sync");
+        assert_eq!(field(&first, "/dry_run"), false);
+        assert_eq!(field(&first, "/profile"), "work");
+        let agent = field(&first, "/agents/0");
+        assert_eq!(field(&agent, "/agent"), "claude-code");
+        assert_eq!(field(&agent, "/files/0/label"), "MCP servers");
+        assert_eq!(field(&agent, "/files/0/status"), "created");
+        assert_eq!(field(&agent, "/assets/0/label"), "Skills");
+        assert_eq!(field(&agent, "/assets/0/synced_files/0"), "review/SKILL.md");
+
+        let second = json_document(&fixture, &sync, true);
+        assert_eq!(field(&second, "/agents/0/files/0/status"), "unchanged");
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_sync_json_dry_run_writes_nothing() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"github": {"command": "github-mcp"}}}"#)
+            .unwrap();
+
+        let document = json_document(
+            &fixture,
+            &["config", "sync", "--agent", "claude-code", "--dry-run"],
+            true,
+        );
+
+        assert_eq!(field(&document, "/dry_run"), true);
+        assert_eq!(field(&document, "/agents/0/files/0/status"), "created");
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_doctor_json_lists_findings() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_skill("review", "# Review").unwrap();
+
+        let document = json_document(&fixture, &["config", "doctor"], true);
+
+        assert_eq!(field(&document, "/command"), "config doctor");
+        assert_eq!(field(&document, "/global"), false);
+        let findings = field(&document, "/findings").as_array().cloned().unwrap_or_default();
+        assert!(findings.iter().any(|finding| field(finding, "/status") == "supported"));
+        assert!(findings.iter().all(|finding| field(finding, "/recommendation").is_string()));
+    }
+
+    #[test]
+    #[serial]
+    fn test_skills_json_output_for_validate_and_sync() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_skill("shared-review", CLAUDE_REVIEW_SKILL).unwrap();
+
+        let validation =
+            json_document(&fixture, &["skills", "validate", "--agent", "codex", "--strict"], false);
+        assert_eq!(field(&validation, "/command"), "skills validate");
+        assert_eq!(field(&validation, "/diagnostics/0/severity"), "warning");
+
+        let sync = json_document(
+            &fixture,
+            &["skills", "sync", "--agent", "claude-code", "--dry-run"],
+            true,
+        );
+        assert_eq!(field(&sync, "/command"), "skills sync");
+        assert_eq!(field(&sync, "/targets/0/synced_skills/0"), "shared-review");
+        assert!(!fixture.project_file_exists(".claude/skills"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_migrate_json_lists_file_changes() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_claude_settings(r#"{ "includeCoAuthoredBy": false }"#).unwrap();
+
+        let document = json_document(&fixture, &["config", "migrate", "--dry-run"], true);
+
+        assert_eq!(field(&document, "/command"), "config migrate");
+        let file = field(&document, "/files/0");
+        assert!(field(&file, "/path").as_str().unwrap_or("").ends_with("claude.settings.json"));
+        assert!(!field(&file, "/changes").as_array().cloned().unwrap_or_default().is_empty());
+        assert!(file.get("original").is_none());
+        assert_eq!(field(&document, "/backups"), Value::Array(Vec::new()));
+    }
+
+    #[test]
+    #[serial]
+    fn test_json_format_conflicts_with_watch() {
+        let fixture = TestFixture::new().unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--watch", "--format", "json"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::fs;
+use std::path::PathBuf;
+
+mod stand_in {
+    include!("../fixtures/mcp_http_server.rs");
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::stand_in::{McpHttpServer, Scenario, SESSION_ID};
+    use super::*;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+    }
+
+    /// A stdio server running the stub MCP server in `mode`.
+    fn stub_server(mode: &str) -> Value {
+        json!({
+            "command": "sh",
+            "args": [fixtures_dir().join("mcp_stub_server.sh").display().to_string(), mode],
+        })
+    }
+
+    fn with_servers(fixture: &TestFixture, servers: &Value) {
+        fixture.with_mcp_servers(&json!({ "mcpServers": servers }).to_string()).unwrap();
+    }
+
+    /// Value at a JSON pointer such as `/servers/0/status`, or null when absent.
+    fn field(document: &Value, pointer: &str) -> Value {
+        document.pointer(pointer).cloned().unwrap_or(Value::Null)
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_reports_server_info_and_tools() {
+        let fixture = TestFixture::new().unwrap();
+        let mut server = stub_server("ok");
+        if let Some(definition) = server.as_object_mut() {
+            definition.insert("env".to_string(), json!({ "STUB_NAME": "files" }));
+        }
+        with_servers(&fixture, &json!({ "files": server, "plain": stub_server("no-tools") }));
+
+        fixture
+            .claudius()
+            .args(["mcp", "check"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "ok      files (stdio): files 1.2.3, protocol 2025-06-18, 2 tool(s)",
+            ))
+            .stdout(predicate::str::contains("ok      plain (stdio): stub 0.1.0"))
+            .stdout(predicate::str::contains("no tools capability"))
+            .stdout(predicate::str::contains("2 ok, 0 failed, 0 require authorization"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_reports_failures_with_stderr() {
+        let fixture = TestFixture::new().unwrap();
+        with_servers(
+            &fixture,
+            &json!({
+                "crash": stub_server("crash"),
+                "error": stub_server("error"),
+                "noisy": stub_server("noisy"),
+                "missing": { "command": "claudius-no-such-mcp-server" },
+                "unreachable": { "type": "http", "url": "http://127.0.0.1:9/mcp" },
+            }),
+        );
+
+        fixture.claudius()
+            .args(["mcp", "check"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "FAILED  crash (stdio): server exited (exit status: 3) before answering `initialize`",
+            ))
+            .stdout(predicate::str::contains("stub: missing API token"))
+            .stdout(predicate::str::contains(
+                "`initialize` failed: unsupported protocol version (code -32602)",
+            ))
+            .stdout(predicate::str::contains("non-JSON-RPC line to stdout: Starting stub server"))
+            .stdout(predicate::str::contains("Failed to start `claudius-no-such-mcp-server`"))
+            .stdout(predicate::str::contains("FAILED  unreachable (http): Failed to reach"))
+            .stdout(predicate::str::contains("0 ok, 5 failed, 0 require authorization"))
+            .stderr(predicate::str::contains("5 MCP server(s) failed the check"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_times_out_unresponsive_servers() {
+        let fixture = TestFixture::new().unwrap();
+        with_servers(&fixture, &json!({ "stuck": stub_server("hang") }));
+
+        fixture
+            .claudius()
+            .args(["mcp", "check", "--timeout", "1"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "timed out after 1s waiting for the `initialize` response",
+            ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_applies_cwd_interpolation_and_a_single_name() {
+        let fixture = TestFixture::new().unwrap();
+        with_servers(
+            &fixture,
+            &json!({
+                "local": {
+                    "command": "sh",
+                    "args": ["mcp_stub_server.sh", "ok"],
+                    "cwd": fixtures_dir().display().to_string(),
+                    "env": { "STUB_NAME": "${STUB_PREFIX}-local" },
+                },
+                "broken": stub_server("crash"),
+            }),
+        );
+
+        fixture
+            .claudius()
+            .env("STUB_PREFIX", "interpolated")
+            .args(["mcp", "check", "local"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ok      local (stdio): interpolated-local 1.2.3"))
+            .stdout(predicate::str::contains("broken").not());
+
+        fixture
+            .claudius()
+            .args(["mcp", "check", "absent"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No MCP server named 'absent'"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_resolves_secrets_through_secrets_run() {
+        let fixture = TestFixture::new().unwrap();
+        fs::write(fixture.config.join("config.toml"), "[secret-manager]\ntype = \"1password\"\n")
+            .unwrap();
+        let mut server = stub_server("ok");
+        if let Some(definition) = server.as_object_mut() {
+            definition
+                .insert("env".to_string(), json!({ "STUB_NAME": "op://vault/test-item/api-key" }));
+        }
+        with_servers(&fixture, &json!({ "vaulted": server }));
+
+        fixture
+            .claudius()
+            .env("CLAUDIUS_TEST_MOCK_OP", "1")
+            .args(["mcp", "check"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("vaulted (stdio): secret-api-key-12345 1.2.3"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_probes_streamable_http_servers() {
+        let fixture = TestFixture::new().unwrap();
+        let json_server = McpHttpServer::start(Scenario::Streamable);
+        let stream_server = McpHttpServer::start(Scenario::StreamableSse);
+        with_servers(
+            &fixture,
+            &json!({
+                "remote": {
+                    "type": "http",
+                    "url": json_server.url("/mcp"),
+                    "headers": { "X-Api-Key": "${STUB_KEY}" },
+                },
+                "streamed": { "url": stream_server.url("/mcp") },
+            }),
+        );
+
+        fixture.claudius()
+            .env("STUB_KEY", "key-123")
+            .args(["mcp", "check"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "ok      remote (http): remote 2.0.0, protocol 2025-06-18, 3 tool(s), capabilities [prompts, tools], ",
+            ))
+            .stdout(predicate::str::contains("ok      streamed (http): remote 2.0.0"))
+            .stdout(predicate::str::contains("2 ok, 0 failed, 0 require authorization"));
+
+        let requests = json_server.requests();
+        let tools_list = requests
+            .iter()
+            .find(|request| request.rpc_method().as_deref() == Some("tools/list"))
+            .unwrap();
+        assert_eq!(tools_list.headers.get("mcp-session-id").map(String::as_str), Some(SESSION_ID));
+        assert_eq!(
+            tools_list.headers.get("mcp-protocol-version").map(String::as_str),
+            Some("2025-06-18")
+        );
+        assert!(requests.iter().all(|request| request
+            .headers
+            .get("x-api-key")
+            .map(String::as_str)
+            == Some("key-123")));
+        assert!(requests.iter().any(|request| request.method == "DELETE"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_probes_legacy_sse_servers() {
+        let fixture = TestFixture::new().unwrap();
+        let server = McpHttpServer::start(Scenario::LegacySse);
+        with_servers(&fixture, &json!({ "legacy": { "type": "sse", "url": server.url("/sse") } }));
+
+        fixture.claudius().args(["mcp", "check"]).assert().success().stdout(
+            predicate::str::contains(
+                "ok      legacy (sse): remote 2.0.0, protocol 2025-06-18, 3 tool(s)",
+            ),
+        );
+
+        let requests = server.requests();
+        assert!(requests.iter().any(|request| request.method == "GET" && request.path == "/sse"));
+        assert!(requests.iter().any(|request| {
+            request.path == format!("/messages?session={SESSION_ID}")
+                && request.rpc_method().as_deref() == Some("tools/list")
+        }));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_reports_servers_requiring_authorization() {
+        let fixture = TestFixture::new().unwrap();
+        let server = McpHttpServer::start(Scenario::Unauthorized);
+        with_servers(
+            &fixture,
+            &json!({
+                "keyed": {
+                    "type": "http",
+                    "url": server.url("/mcp"),
+                    "headers": { "Authorization": "Bearer expired" },
+                },
+                "locked": { "type": "http", "url": server.url("/mcp") },
+            }),
+        );
+
+        fixture.claudius()
+            .args(["mcp", "check"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "auth    locked (http): server requires authorization (HTTP 401, WWW-Authenticate: Bearer resource_metadata=",
+            ))
+            .stdout(predicate::str::contains(
+                "FAILED  keyed (http): server rejected the configured credentials",
+            ))
+            .stdout(predicate::str::contains("0 ok, 1 failed, 1 require authorization"));
+
+        fixture.claudius().args(["mcp", "check", "locked"]).assert().success();
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_check_json_reports_capabilities_and_latency() {
+        let fixture = TestFixture::new().unwrap();
+        let remote = McpHttpServer::start(Scenario::Streamable);
+        let locked = McpHttpServer::start(Scenario::Unauthorized);
+        with_servers(
+            &fixture,
+            &json!({
+                "files": stub_server("ok"),
+                "locked": { "type": "http", "url": locked.url("/mcp") },
+                "remote": { "type": "http", "url": remote.url("/mcp") },
+            }),
+        );
+
+        let output =
+            fixture.claudius().args(["mcp", "check", "--format", "json"]).assert().success();
+        let document: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+
+        assert_eq!(field(&document, "/command"), "mcp check");
+        assert_eq!(field(&document, "/servers/0/server"), "files");
+        assert_eq!(field(&document, "/servers/0/status"), "ok");
+        assert_eq!(field(&document, "/servers/0/server_info/name"), "stub");
+        assert_eq!(field(&document, "/servers/0/tool_count"), 2);
+        assert_eq!(field(&document, "/servers/0/capabilities"), json!(["tools"]));
+        assert_eq!(field(&document, "/servers/1/status"), "auth-required");
+        assert_eq!(field(&document, "/servers/2/transport"), "http");
+        assert_eq!(field(&document, "/servers/2/status"), "ok");
+        assert_eq!(field(&document, "/servers/2/capabilities"), json!(["prompts", "tools"]));
+        assert!(field(&document, "/servers/2/latency_ms").is_u64());
+    }
+}
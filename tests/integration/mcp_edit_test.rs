@@ -0,0 +1,301 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Value at a JSON pointer such as `/servers/0/name`, or null when absent.
+    fn field(document: &Value, pointer: &str) -> Value {
+        document.pointer(pointer).cloned().unwrap_or(Value::Null)
+    }
+
+    fn global_source(fixture: &TestFixture) -> String {
+        fs::read_to_string(fixture.config.join("mcpServers.json")).unwrap()
+    }
+
+    const FOUR_SPACE_SOURCE: &str = "{\n    \"mcpServers\": {\n        \"zeta\": {\"command\": \"zeta-server\"},\n        \"alpha\": {\"command\": \"alpha-server\"}\n    }\n}\n";
+
+    #[test]
+    #[serial]
+    fn test_mcp_add_keeps_order_and_formatting() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(FOUR_SPACE_SOURCE).unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "github", "--env", "TOKEN=op://dev/github/token"])
+            .args(["--", "npx", "-y", "@modelcontextprotocol/server-github"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Added MCP server 'github'"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "linear", "--url", "https://mcp.linear.app/mcp"])
+            .args(["--header", "X-Team=core", "--agent", "codex", "--exclude", "gemini"])
+            .assert()
+            .success();
+
+        let source = global_source(&fixture);
+        assert!(source.starts_with(
+            "{\n    \"mcpServers\": {\n        \"zeta\": {\"command\": \"zeta-server\"},\n        \"alpha\": {\"command\": \"alpha-server\"},\n        \"github\": {\n            \"command\": \"npx\",\n"
+        ));
+        let document: Value = serde_json::from_str(&source).unwrap();
+        assert_eq!(
+            field(&document, "/mcpServers/github"),
+            json!({
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-github"],
+                "env": { "TOKEN": "op://dev/github/token" },
+            })
+        );
+        assert_eq!(
+            field(&document, "/mcpServers/linear"),
+            json!({
+                "type": "http",
+                "url": "https://mcp.linear.app/mcp",
+                "headers": { "X-Team": "core" },
+                "x-claudius": { "agents": ["codex"], "exclude": ["gemini"] },
+            })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_add_rejects_invalid_definitions() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(FOUR_SPACE_SOURCE).unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "files", "--url", "ftp://example.com"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("must start with http:// or https://"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "files", "--type", "sse", "--", "files-server"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("type `sse` needs a `url`"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "files", "--agent", "vscode", "--", "files-server"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("unknown agent 'vscode'"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "files", "--env", "TOKEN"])
+            .args(["--", "files-server"])
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("expected KEY=VALUE"));
+        fixture.claudius().args(["mcp", "add", "files"]).assert().code(2);
+
+        assert_eq!(global_source(&fixture), FOUR_SPACE_SOURCE);
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_add_replaces_only_with_replace() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(FOUR_SPACE_SOURCE).unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "alpha", "--", "alpha-v2"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("already exists"))
+            .stderr(predicate::str::contains("--replace"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "alpha", "--replace", "--", "alpha-v2"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Replaced MCP server 'alpha'"));
+
+        assert_eq!(
+            global_source(&fixture),
+            "{\n    \"mcpServers\": {\n        \"zeta\": {\"command\": \"zeta-server\"},\n        \"alpha\": {\"command\": \"alpha-v2\"}\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_rename_and_remove_edit_in_place() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(FOUR_SPACE_SOURCE).unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "rename", "zeta", "omega"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Renamed MCP server 'zeta' to 'omega'"));
+        fixture
+            .claudius()
+            .args(["mcp", "rename", "omega", "alpha"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("MCP server 'alpha' already exists"));
+        fixture
+            .claudius()
+            .args(["mcp", "remove", "alpha"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("config sync --global --prune"));
+        fixture
+            .claudius()
+            .args(["mcp", "remove", "alpha"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No MCP server named 'alpha'"));
+
+        assert_eq!(
+            global_source(&fixture),
+            "{\n    \"mcpServers\": {\n        \"omega\": {\"command\": \"zeta-server\"}\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_list_and_show_report_layers() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.trust_project().unwrap();
+        fixture.with_mcp_servers(FOUR_SPACE_SOURCE).unwrap();
+        fixture
+            .claudius()
+            .args(["mcp", "add", "alpha", "--project", "--agent", "codex"])
+            .args(["--url", "https://alpha.example.com/mcp"])
+            .assert()
+            .success();
+        assert!(fixture.project_file_exists(".claudius/mcpServers.json"));
+
+        fixture.claudius()
+            .args(["mcp", "list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "  alpha (http, project, overrides global): https://alpha.example.com/mcp  [agents: codex]",
+            ))
+            .stdout(predicate::str::contains("  zeta (stdio, global): zeta-server"));
+        fixture
+            .claudius()
+            .args(["mcp", "show", "alpha"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("alpha (project layer, "))
+            .stdout(predicate::str::contains("\"url\": \"https://alpha.example.com/mcp\""));
+
+        let listed =
+            fixture.claudius().args(["mcp", "list", "--format", "json"]).assert().success();
+        let list: Value = serde_json::from_slice(&listed.get_output().stdout).unwrap();
+        assert_eq!(field(&list, "/command"), "mcp list");
+        assert_eq!(field(&list, "/servers/0/name"), "alpha");
+        assert_eq!(field(&list, "/servers/0/layer"), "project");
+        assert_eq!(field(&list, "/servers/0/overrides_global"), true);
+        assert_eq!(field(&list, "/servers/1/transport"), "stdio");
+
+        let shown = fixture
+            .claudius()
+            .args(["mcp", "show", "zeta", "--format", "json"])
+            .assert()
+            .success();
+        let show: Value = serde_json::from_slice(&shown.get_output().stdout).unwrap();
+        assert_eq!(field(&show, "/command"), "mcp show");
+        assert_eq!(field(&show, "/layer"), "global");
+        assert_eq!(field(&show, "/definition"), json!({ "command": "zeta-server" }));
+
+        fixture
+            .claudius()
+            .args(["mcp", "show", "missing"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("No MCP server named 'missing'"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_global_mcp_edits_sync_global_targets() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fs::write(fixture.config.join("config.toml"), "[default]\nagent = \"claude-code\"\n")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "notes", "--sync", "--", "notes-server"])
+            .assert()
+            .success();
+        let added: Value =
+            serde_json::from_str(&fixture.read_home_file(".claude.json").unwrap()).unwrap();
+        assert_eq!(field(&added, "/mcpServers/notes/command"), "notes-server");
+
+        fixture
+            .claudius()
+            .args(["mcp", "rename", "notes", "memo", "--sync"])
+            .assert()
+            .success();
+        let renamed: Value =
+            serde_json::from_str(&fixture.read_home_file(".claude.json").unwrap()).unwrap();
+        assert_eq!(field(&renamed, "/mcpServers/memo/command"), "notes-server");
+        assert!(field(&renamed, "/mcpServers/notes").is_null());
+    }
+
+    #[test]
+    #[serial]
+    fn test_project_mcp_edits_sync_the_project() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {}}"#)
+            .unwrap()
+            .trust_project()
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "notes", "--project", "--sync", "--", "notes-server"])
+            .assert()
+            .success();
+        let added: Value =
+            serde_json::from_str(&fixture.read_project_file(".mcp.json").unwrap()).unwrap();
+        assert_eq!(field(&added, "/mcpServers/notes/command"), "notes-server");
+        assert!(!fixture.home_file_exists(".claude.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_add_sync_does_not_prune() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fs::write(fixture.config.join("config.toml"), "[default]\nagent = \"claude-code\"\n")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "notes", "--sync", "--", "notes-server"])
+            .assert()
+            .success();
+        fixture.claudius().args(["mcp", "remove", "notes"]).assert().success();
+        fixture
+            .claudius()
+            .args(["mcp", "add", "tasks", "--sync", "--", "tasks-server"])
+            .assert()
+            .success();
+        let added: Value =
+            serde_json::from_str(&fixture.read_home_file(".claude.json").unwrap()).unwrap();
+        assert_eq!(field(&added, "/mcpServers/notes/command"), "notes-server");
+        assert_eq!(field(&added, "/mcpServers/tasks/command"), "tasks-server");
+
+        fixture.claudius().args(["mcp", "remove", "tasks", "--sync"]).assert().success();
+        let removed: Value =
+            serde_json::from_str(&fixture.read_home_file(".claude.json").unwrap()).unwrap();
+        assert!(field(&removed, "/mcpServers/notes").is_null());
+        assert!(field(&removed, "/mcpServers/tasks").is_null());
+    }
+}
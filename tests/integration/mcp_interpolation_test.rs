@@ -0,0 +1,181 @@
+use crate::fixtures::TestFixture;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs without a `DB_URL` from the host, so the `${DB_URL:-...}` default applies.
+    fn claudius(fixture: &TestFixture) -> Command {
+        let mut cmd = fixture.claudius();
+        cmd.env_remove("DB_URL");
+        cmd
+    }
+
+    #[test]
+    #[serial]
+    fn test_sync_interpolates_server_values() {
+        let fixture = TestFixture::new().unwrap();
+        fs::write(
+            fixture.config.join("config.toml"),
+            "[variables]\nDATA_DIR = \"${CLAUDIUS_PROJECT_ROOT}/data\"\n",
+        )
+        .unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {
+                    "db": {
+                        "command": "${HOME}/bin/db-server",
+                        "args": ["--data", "${DATA_DIR}"],
+                        "env": {
+                            "DB_URL": "${DB_URL:-postgres://localhost}",
+                            "API_TOKEN": "${API_TOKEN}"
+                        }
+                    }
+                }}"#,
+            )
+            .unwrap();
+
+        claudius(&fixture)
+            .env("API_TOKEN", "secret-token")
+            .args(["config", "sync", "--agent", "codex"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".codex/config.toml").unwrap();
+        let codex: toml::Value = toml::from_str(&content).unwrap();
+        let db = codex.get("mcp_servers").and_then(|servers| servers.get("db")).unwrap();
+        let project = fixture.project.canonicalize().unwrap();
+        assert_eq!(
+            db.get("command").and_then(toml::Value::as_str),
+            Some(format!("{}/bin/db-server", fixture.home_dir().display()).as_str())
+        );
+        assert_eq!(
+            db.get("args").and_then(|args| args.get(1)).and_then(toml::Value::as_str),
+            Some(format!("{}/data", project.display()).as_str())
+        );
+        let env = db.get("env").unwrap();
+        assert_eq!(env.get("DB_URL").and_then(toml::Value::as_str), Some("postgres://localhost"));
+        assert_eq!(env.get("API_TOKEN").and_then(toml::Value::as_str), Some("secret-token"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_claude_code_sync_keeps_environment_references() {
+        let fixture = TestFixture::new().unwrap();
+        fs::write(
+            fixture.config.join("config.toml"),
+            "[variables]\nDATA_DIR = \"${CLAUDIUS_PROJECT_ROOT}/data\"\n",
+        )
+        .unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {
+                    "db": {
+                        "command": "${HOME}/bin/db-server",
+                        "args": ["--data", "${DATA_DIR}"],
+                        "env": {
+                            "DB_URL": "${DB_URL:-postgres://localhost}",
+                            "API_KEY": "$${API_KEY}"
+                        }
+                    },
+                    "github": {
+                        "type": "http",
+                        "url": "https://api.githubcopilot.com/mcp/",
+                        "headers": {"Authorization": "Bearer ${GITHUB_TOKEN}"}
+                    }
+                }}"#,
+            )
+            .unwrap();
+
+        claudius(&fixture)
+            .env("GITHUB_TOKEN", "ghp_secret")
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(!content.contains("ghp_secret"));
+        let mcp: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let project = fixture.project.canonicalize().unwrap();
+        assert_eq!(
+            mcp.pointer("/mcpServers/github/headers/Authorization").unwrap(),
+            "Bearer ${GITHUB_TOKEN}"
+        );
+        assert_eq!(mcp.pointer("/mcpServers/db/command").unwrap(), "${HOME}/bin/db-server");
+        assert_eq!(
+            mcp.pointer("/mcpServers/db/args/1").unwrap(),
+            &format!("{}/data", project.display())
+        );
+        assert_eq!(
+            mcp.pointer("/mcpServers/db/env/DB_URL").unwrap(),
+            "${DB_URL:-postgres://localhost}"
+        );
+        assert_eq!(mcp.pointer("/mcpServers/db/env/API_KEY").unwrap(), "${API_KEY}");
+
+        claudius(&fixture)
+            .env_remove("GITHUB_TOKEN")
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+    }
+
+    #[test]
+    #[serial]
+    fn test_sync_fails_on_undefined_variables() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"db": {"command": "db", "args": ["${DB_URL}"]}}}"#)
+            .unwrap();
+
+        claudius(&fixture)
+            .args(["config", "sync", "--agent", "codex"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("mcpServers.db.args[0]"))
+            .stderr(predicate::str::contains("undefined variable 'DB_URL'"));
+        assert!(!fixture.project_file_exists(".codex/config.toml"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_codex_sync_warns_about_passthrough_references() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"db": {"command": "db", "env": {"TOKEN": "$${TOKEN}"}}}}"#,
+            )
+            .unwrap();
+
+        claudius(&fixture)
+            .args(["config", "sync", "--agent", "codex"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "Warning: mcpServers.db.env.TOKEN passes a literal ${...} through",
+            ));
+    }
+
+    #[test]
+    #[serial]
+    fn test_validate_reports_undefined_variables_and_cycles() {
+        let fixture = TestFixture::new().unwrap();
+        fs::write(fixture.config.join("config.toml"), "[variables]\nA = \"${B}\"\nB = \"${A}\"\n")
+            .unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"db": {"command": "db", "args": ["${DB_URL}"]}}}"#)
+            .unwrap();
+
+        claudius(&fixture)
+            .args(["config", "validate", "--strict"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("mcpServers.db.args[0]: undefined variable 'DB_URL'"))
+            .stdout(predicate::str::contains(
+                "config.toml variables.A: circular variable reference: A -> B -> A",
+            ));
+    }
+}
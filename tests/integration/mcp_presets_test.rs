@@ -0,0 +1,227 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Value at a JSON pointer such as `/mcpServers/db/args`, or null when absent.
+    fn field(document: &Value, pointer: &str) -> Value {
+        document.pointer(pointer).cloned().unwrap_or(Value::Null)
+    }
+
+    fn global_servers(fixture: &TestFixture) -> Value {
+        serde_json::from_str(&fs::read_to_string(fixture.config.join("mcpServers.json")).unwrap())
+            .unwrap()
+    }
+
+    /// A user catalog with a newer `git` preset and a team-only preset.
+    const USER_CATALOG: &str = r#"{
+  "presets": {
+    "git": {
+      "version": 2,
+      "description": "Git with the team's defaults",
+      "parameters": [{ "name": "repository", "default": "." }],
+      "server": { "command": "uvx", "args": ["mcp-server-git", "--repository", "{{repository}}", "--read-only"] }
+    },
+    "notes": {
+      "version": 1,
+      "description": "Team notes",
+      "parameters": [{ "name": "dir", "description": "Notes directory" }],
+      "server": { "command": "notes-mcp", "args": ["--dir", "{{dir}}"] }
+    }
+  }
+}"#;
+
+    fn write_user_catalog(fixture: &TestFixture) {
+        let catalog_dir = fixture.config.join("mcp-presets");
+        fs::create_dir_all(&catalog_dir).unwrap();
+        fs::write(catalog_dir.join("team.json"), USER_CATALOG).unwrap();
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_add_preset_renders_and_records_the_preset() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "db", "--preset", "postgres"])
+            .args(["--set", "url=postgresql://localhost/app", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Added MCP server 'db'"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "gh", "--preset", "github", "--header", "X-Toolsets=repos"])
+            .assert()
+            .success();
+
+        let servers = global_servers(&fixture);
+        assert_eq!(
+            field(&servers, "/mcpServers/db"),
+            json!({
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-postgres", "postgresql://localhost/app"],
+                "x-claudius": {
+                    "agents": ["claude-code"],
+                    "preset": { "name": "postgres", "version": 1 },
+                },
+            })
+        );
+        assert_eq!(
+            field(&servers, "/mcpServers/gh/headers"),
+            json!({ "Authorization": "Bearer $${GITHUB_TOKEN}", "X-Toolsets": "repos" })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_add_preset_checks_parameters() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+
+        fixture
+            .claudius()
+            .args(["mcp", "add", "db", "--preset", "postgres"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Preset 'postgres' needs --set url=..."));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "fs", "--preset", "filesystem", "--set", "root=/srv"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("has no parameter 'root' (parameters: path)"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "x", "--preset", "missing"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Unknown MCP preset 'missing'"));
+        fixture
+            .claudius()
+            .args(["mcp", "add", "fs", "--preset", "filesystem", "--type", "sse"])
+            .assert()
+            .code(2);
+        fixture
+            .claudius()
+            .args(["mcp", "add", "fs", "--set", "path=/srv", "--", "fs-server"])
+            .assert()
+            .code(2);
+
+        assert_eq!(field(&global_servers(&fixture), "/mcpServers"), json!({}));
+    }
+
+    #[test]
+    #[serial]
+    fn test_mcp_presets_lists_builtin_and_user_catalogs() {
+        let fixture = TestFixture::new().unwrap();
+        write_user_catalog(&fixture);
+
+        fixture
+            .claudius()
+            .args(["mcp", "presets"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("  playwright (version 1, built-in): "))
+            .stdout(predicate::str::contains("team.json): Team notes"))
+            .stdout(predicate::str::contains("    --set dir=...  Notes directory (required)"));
+
+        let output = fixture
+            .claudius()
+            .args(["mcp", "presets", "--format", "json"])
+            .assert()
+            .success();
+        let document: Value = serde_json::from_slice(&output.get_output().stdout).unwrap();
+        assert_eq!(field(&document, "/command"), "mcp presets");
+        let presets = document.get("presets").and_then(Value::as_array).unwrap();
+        let git = presets.iter().find(|preset| preset.get("name") == Some(&json!("git"))).unwrap();
+        assert_eq!(field(git, "/version"), 2);
+        assert!(field(git, "/source").as_str().unwrap().ends_with("team.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_doctor_reports_outdated_presets() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture
+            .claudius()
+            .args(["mcp", "add", "repo", "--preset", "git"])
+            .assert()
+            .success();
+        fixture
+            .claudius()
+            .args(["mcp", "add", "fetch", "--preset", "fetch"])
+            .assert()
+            .success();
+
+        fixture
+            .claudius()
+            .args(["config", "doctor"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("preset").not());
+
+        write_user_catalog(&fixture);
+        fixture
+            .claudius()
+            .args(["config", "doctor"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "MCP server 'repo' uses version 1 of preset 'git'; version 2 is available.",
+            ))
+            .stdout(predicate::str::contains("claudius mcp add repo --preset git --replace"))
+            .stdout(predicate::str::contains("server 'fetch'").not());
+
+        fs::write(fixture.config.join("mcp-presets/broken.json"), "{ \"presets\": ").unwrap();
+        fixture
+            .claudius()
+            .args(["config", "doctor"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "An MCP preset catalog file could not be loaded and was skipped.",
+            ))
+            .stdout(predicate::str::contains("broken.json"))
+            .stdout(predicate::str::contains("version 2 is available."));
+    }
+
+    #[test]
+    #[serial]
+    fn test_github_preset_keeps_the_token_out_of_agent_files() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture
+            .claudius()
+            .args(["mcp", "add", "github", "--preset", "github"])
+            .assert()
+            .success();
+
+        for agent in ["claude-code", "codex"] {
+            fixture
+                .claudius()
+                .env("GITHUB_TOKEN", "ghp_secret")
+                .args(["config", "sync", "--agent", agent])
+                .assert()
+                .success();
+        }
+        fixture
+            .claudius()
+            .env_remove("GITHUB_TOKEN")
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let mcp = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(mcp.contains("Bearer ${GITHUB_TOKEN}"));
+        let codex = fixture.read_project_file(".codex/config.toml").unwrap();
+        assert!(!codex.contains("ghp_secret"));
+    }
+}
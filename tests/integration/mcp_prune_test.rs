@@ -0,0 +1,133 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWO_SERVERS: &str = r#"{
+        "mcpServers": {
+            "alpha": {"command": "alpha-server"},
+            "beta": {"command": "beta-server"}
+        }
+    }"#;
+
+    const ONE_SERVER: &str = r#"{
+        "mcpServers": {
+            "alpha": {"command": "alpha-server"}
+        }
+    }"#;
+
+    #[test]
+    #[serial]
+    fn test_prune_removes_owned_servers_and_keeps_hand_added_ones() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TWO_SERVERS).unwrap();
+        fixture
+            .with_existing_mcp_json(r#"{"mcpServers": {"manual": {"command": "manual-server"}}}"#)
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        fixture.with_mcp_servers(ONE_SERVER).unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--prune"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(content.contains("alpha"));
+        assert!(content.contains("manual"));
+        assert!(!content.contains("beta"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_sync_without_prune_keeps_removed_servers() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TWO_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        fixture.with_mcp_servers(ONE_SERVER).unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("beta"));
+
+        // Ownership is retained, so a later prune still removes the server.
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--prune"])
+            .assert()
+            .success();
+        assert!(!fixture.read_project_file(".mcp.json").unwrap().contains("beta"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_prune_dry_run_reports_servers_without_writing() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TWO_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        fixture.with_mcp_servers(ONE_SERVER).unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--prune", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would prune 1 stale MCP server(s):"))
+            .stdout(predicate::str::contains("  - beta"));
+
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("beta"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_prune_codex_global_preserves_hand_added_servers() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TWO_SERVERS).unwrap();
+        fixture.with_codex_settings("model = \"o3\"\n").unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--global", "--agent", "codex"])
+            .assert()
+            .success();
+
+        let codex_path = fixture.home_dir().join(".codex").join("config.toml");
+        let mut initial = std::fs::read_to_string(&codex_path).unwrap();
+        initial.push_str("\n[mcp_servers.manual]\ncommand = \"manual-server\"\n");
+        std::fs::write(&codex_path, initial).unwrap();
+
+        fixture.with_mcp_servers(ONE_SERVER).unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--global", "--agent", "codex", "--prune"])
+            .assert()
+            .success();
+
+        let content = fixture.read_home_file(".codex/config.toml").unwrap();
+        assert!(content.contains("[mcp_servers.alpha]"));
+        assert!(content.contains("[mcp_servers.manual]"));
+        assert!(!content.contains("beta"));
+    }
+}
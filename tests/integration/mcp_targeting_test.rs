@@ -0,0 +1,132 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGETED_SERVERS: &str = r#"{
+        "mcpServers": {
+            "shared": {"command": "shared-server"},
+            "claude-only": {
+                "command": "claude-server",
+                "x-claudius": {"agents": ["claude-code"]}
+            },
+            "no-gemini": {
+                "command": "no-gemini-server",
+                "x-claudius": {"exclude": ["gemini"]}
+            },
+            "user-scope": {
+                "command": "user-server",
+                "x-claudius": {"agents": ["claude-code:user"]}
+            }
+        }
+    }"#;
+
+    #[test]
+    #[serial]
+    fn test_claude_code_receives_targeted_servers_without_metadata() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TARGETED_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(content.contains("\"shared\""));
+        assert!(content.contains("\"claude-only\""));
+        assert!(content.contains("\"no-gemini\""));
+        assert!(!content.contains("\"user-scope\""));
+        assert!(!content.contains("x-claudius"));
+
+        // The deployed servers match the sources, so a re-sync has nothing to ask.
+        fixture
+            .claudius()
+            .args(["--no-input", "config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+    }
+
+    #[test]
+    #[serial]
+    fn test_other_agents_skip_servers_that_do_not_select_them() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(TARGETED_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "codex"])
+            .assert()
+            .success();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "gemini"])
+            .assert()
+            .success();
+
+        let codex = fixture.read_project_file(".codex/config.toml").unwrap();
+        assert!(codex.contains("[mcp_servers.shared]"));
+        assert!(codex.contains("[mcp_servers.no-gemini]"));
+        assert!(!codex.contains("claude-only"));
+
+        let gemini = fixture.read_project_file(".gemini/settings.json").unwrap();
+        assert!(gemini.contains("\"shared\""));
+        assert!(!gemini.contains("no-gemini"));
+        assert!(!gemini.contains("claude-only"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_prune_removes_server_after_it_is_excluded() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-server"}}}"#)
+            .unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"alpha": {"command": "alpha-server", "x-claudius": {"exclude": ["claude-code"]}}}}"#,
+            )
+            .unwrap();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--prune"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(!content.contains("alpha"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_validate_reports_unknown_agents_and_scopes() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"alpha": {
+                    "command": "alpha-server",
+                    "x-claudius": {"agents": ["cursor"], "exclude": ["codex:local"]}
+                }}}"#,
+            )
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "validate", "--strict"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("mcpServers.alpha.x-claudius.agents"))
+            .stdout(predicate::str::contains("unknown agent 'cursor'"))
+            .stdout(predicate::str::contains("unknown scope 'local' for codex"));
+    }
+}
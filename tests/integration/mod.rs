@@ -1,21 +1,44 @@
 mod agent_assets_test;
+mod all_projects_test;
 mod app_config_test;
+mod backups_test;
+mod claude_json_format_test;
 mod cli_test;
+mod codex_format_preservation_test;
 mod codex_model_providers_test;
 mod codex_sync_test;
 mod codex_toml_test;
+mod config_import_test;
 mod config_migrate_test;
+mod config_status_test;
 mod context_test;
+mod doctor_fix_test;
 mod doctor_test;
 mod gemini_system_settings_test;
 mod init_test;
 mod install_context_test;
+mod json_output_test;
+mod mcp_check_test;
+mod mcp_edit_test;
+mod mcp_interpolation_test;
+mod mcp_presets_test;
+mod mcp_prune_test;
+mod mcp_targeting_test;
 mod merge_interactive_test;
 mod multi_agent_sync_test;
 mod parallel_performance_test;
+mod profile_sync_test;
+mod project_layer_test;
 mod run_command_test;
+mod sarif_test;
+mod secret_launch_test;
 mod secrets_fixture_test;
 mod settings_test;
 mod skills_test;
+mod sync_dry_run_diff_test;
+mod sync_strategy_test;
+mod sync_transaction_test;
+mod undo_test;
 mod validate_test;
 mod variable_expansion_test;
+mod watch_test;
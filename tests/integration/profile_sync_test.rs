@@ -0,0 +1,154 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROFILES: &str = r#"
+[profiles.work]
+servers = ["github", "jira"]
+
+[profiles.work.claude-settings.env]
+TEAM = "platform"
+
+[profiles.work.codex-settings]
+model = "o3"
+
+[profiles.oss]
+servers = ["github"]
+"#;
+
+    fn profile_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {
+                    "github": {"command": "github-mcp"},
+                    "jira": {"command": "jira-mcp"},
+                    "demo": {"command": "demo-mcp"}
+                }}"#,
+            )
+            .unwrap();
+        fixture.with_claude_settings(r#"{"env": {"SHARED": "1"}}"#).unwrap();
+        fs::write(fixture.config.join("config.toml"), PROFILES).unwrap();
+        fixture
+    }
+
+    #[test]
+    #[serial]
+    fn test_profile_selects_servers_and_overlays_settings() {
+        let fixture = profile_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--profile", "work"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Using profile 'work'"));
+
+        let mcp = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(mcp.contains("github-mcp"));
+        assert!(mcp.contains("jira-mcp"));
+        assert!(!mcp.contains("demo-mcp"));
+
+        let settings: serde_json::Value =
+            serde_json::from_str(&fixture.read_project_file(".claude/settings.json").unwrap())
+                .unwrap();
+        assert_eq!(*settings.pointer("/env/SHARED").unwrap(), "1");
+        assert_eq!(*settings.pointer("/env/TEAM").unwrap(), "platform");
+    }
+
+    #[test]
+    #[serial]
+    fn test_profile_is_remembered_until_cleared() {
+        let fixture = profile_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--profile", "oss"])
+            .assert()
+            .success();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "replace"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Using profile 'oss'"));
+
+        let mcp = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(mcp.contains("github-mcp"));
+        assert!(!mcp.contains("jira-mcp"));
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--no-profile"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Using profile").not());
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("demo-mcp"));
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Using profile").not());
+    }
+
+    #[test]
+    #[serial]
+    fn test_dry_run_does_not_remember_profile() {
+        let fixture = profile_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--profile", "work", "--dry-run"])
+            .assert()
+            .success();
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Using profile").not());
+    }
+
+    #[test]
+    #[serial]
+    fn test_profile_overlays_codex_settings() {
+        let fixture = profile_fixture();
+        fixture
+            .with_codex_settings("model = \"gpt-5\"\napproval_policy = \"never\"\n")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "codex", "--profile", "work"])
+            .assert()
+            .success();
+
+        let codex = fixture.read_project_file(".codex/config.toml").unwrap();
+        assert!(codex.contains("model = \"o3\""));
+        assert!(codex.contains("approval_policy = \"never\""));
+        assert!(!codex.contains("demo"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_unknown_profile_lists_available_profiles() {
+        let fixture = profile_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--profile", "demo"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Profile 'demo' is not defined"))
+            .stderr(predicate::str::contains("available: oss, work"));
+
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+}
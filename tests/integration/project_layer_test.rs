@@ -0,0 +1,203 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_layer_file(fixture: &TestFixture, relative_path: &str, content: &str) -> PathBuf {
+        let path = fixture.project.join(".claudius").join(relative_path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    #[serial]
+    fn test_project_servers_override_and_extend_global_servers() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.trust_project().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {
+                    "shared": {"command": "global-shared"},
+                    "global-only": {"command": "global-server"}
+                }}"#,
+            )
+            .unwrap();
+        write_layer_file(
+            &fixture,
+            "mcpServers.json",
+            r#"{"mcpServers": {
+                "shared": {"command": "project-shared"},
+                "project-only": {"command": "project-server"}
+            }}"#,
+        );
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(content.contains("project-shared"));
+        assert!(!content.contains("global-shared"));
+        assert!(content.contains("global-server"));
+        assert!(content.contains("project-server"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_project_settings_merge_over_global_settings() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.trust_project().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture
+            .with_claude_settings(r#"{"env": {"SHARED": "global", "GLOBAL_ONLY": "1"}}"#)
+            .unwrap();
+        write_layer_file(
+            &fixture,
+            "claude.settings.json",
+            r#"{"env": {"SHARED": "project", "PROJECT_ONLY": "1"}}"#,
+        );
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".claude/settings.json").unwrap();
+        let settings: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(settings.pointer("/env/SHARED").unwrap(), "project");
+        assert_eq!(settings.pointer("/env/GLOBAL_ONLY").unwrap(), "1");
+        assert_eq!(settings.pointer("/env/PROJECT_ONLY").unwrap(), "1");
+    }
+
+    #[test]
+    #[serial]
+    fn test_project_subagents_and_skills_replace_global_ones() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.trust_project().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_claude_code_agent("reviewer", "Global reviewer.").unwrap();
+        fixture.with_skill("review", "# Global review skill").unwrap();
+        write_layer_file(&fixture, "agents/claude-code/reviewer.md", "Project reviewer.");
+        write_layer_file(&fixture, "skills/review/SKILL.md", "# Project review skill");
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+        fixture
+            .claudius()
+            .args(["skills", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let agent = fixture.read_project_file(".claude/agents/reviewer.md").unwrap();
+        assert!(agent.contains("Project reviewer."));
+        let skill = fixture.read_project_file(".claude/skills/review/SKILL.md").unwrap();
+        assert!(skill.contains("# Project review skill"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_untrusted_project_layer_is_ignored() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"shared": {"command": "global-shared"}}}"#)
+            .unwrap();
+        write_layer_file(
+            &fixture,
+            "mcpServers.json",
+            r#"{"mcpServers": {
+                "shared": {"command": "project-shared"},
+                "project-only": {"command": "project-server"}
+            }}"#,
+        );
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("to `trusted-projects` in config.toml"));
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(content.contains("global-shared"));
+        assert!(!content.contains("project-shared"));
+        assert!(!content.contains("project-server"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_global_sync_ignores_project_layer() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"global-only": {"command": "global-server"}}}"#)
+            .unwrap();
+        write_layer_file(
+            &fixture,
+            "mcpServers.json",
+            r#"{"mcpServers": {"project-only": {"command": "project-server"}}}"#,
+        );
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--global"])
+            .assert()
+            .success();
+
+        let content = fs::read_to_string(fixture.home_dir().join(".claude.json")).unwrap();
+        assert!(content.contains("global-server"));
+        assert!(!content.contains("project-server"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_doctor_reports_the_layer_of_each_asset() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.trust_project().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"github": {"command": "gh"}, "jira": {"command": "jira"}}}"#,
+            )
+            .unwrap();
+        write_layer_file(
+            &fixture,
+            "mcpServers.json",
+            r#"{"mcpServers": {"github": {"command": "gh-project"}}}"#,
+        );
+
+        fixture
+            .claudius()
+            .args(["config", "doctor"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Project layer:"))
+            .stdout(predicate::str::contains("SOURCE LAYERS"))
+            .stdout(predicate::str::contains("MCP server github: project (overrides global)"))
+            .stdout(predicate::str::contains("MCP server jira: global"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_context_install_prefers_project_rules() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.trust_project().unwrap();
+        fs::create_dir_all(fixture.config.join("rules")).unwrap();
+        fs::write(fixture.config.join("rules").join("style.md"), "# Global style").unwrap();
+        write_layer_file(&fixture, "rules/style.md", "# Project style");
+
+        fixture.claudius().args(["context", "install", "style"]).assert().success();
+
+        let rule = fixture.read_project_file(".agents/rules/style.md").unwrap();
+        assert!(rule.contains("# Project style"));
+    }
+}
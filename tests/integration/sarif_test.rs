@@ -0,0 +1,156 @@
+use crate::fixtures::TestFixture;
+use serde_json::Value;
+use serial_test::serial;
+use std::fs;
+use std::path::Path;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `args` with `--format sarif` from `cwd` and parse stdout as a SARIF log.
+    fn sarif_log(fixture: &TestFixture, cwd: &Path, args: &[&str], success: bool) -> Value {
+        let assert = fixture
+            .claudius()
+            .current_dir(cwd)
+            .args(args)
+            .args(["--format", "sarif"])
+            .assert();
+        let output = if success { assert.success() } else { assert.failure() };
+        serde_json::from_slice(&output.get_output().stdout).unwrap()
+    }
+
+    /// Value at a JSON pointer such as `/runs/0/results`, or null when absent.
+    fn field(document: &Value, pointer: &str) -> Value {
+        document.pointer(pointer).cloned().unwrap_or(Value::Null)
+    }
+
+    fn results(log: &Value) -> Vec<Value> {
+        field(log, "/runs/0/results").as_array().cloned().unwrap_or_default()
+    }
+
+    fn result_for(log: &Value, rule_id: &str) -> Value {
+        results(log)
+            .into_iter()
+            .find(|result| field(result, "/ruleId") == rule_id)
+            .unwrap_or(Value::Null)
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_validate_sarif_reports_rule_level_and_relative_location() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_claude_settings(r#"{"includeCoAuthoredBy": false}"#).unwrap();
+
+        let log = sarif_log(
+            &fixture,
+            &fixture.config,
+            &["config", "validate", "--agent", "claude-code", "--strict"],
+            false,
+        );
+
+        assert_eq!(field(&log, "/version"), "2.1.0");
+        assert_eq!(field(&log, "/runs/0/tool/driver/name"), "claudius");
+        let deprecated = result_for(&log, "deprecated-key");
+        assert_eq!(field(&deprecated, "/level"), "warning");
+        assert!(field(&deprecated, "/message/text")
+            .as_str()
+            .is_some_and(|text| text.contains("includeCoAuthoredBy")));
+        let location = field(&deprecated, "/locations/0/physicalLocation/artifactLocation");
+        assert_eq!(field(&location, "/uri"), "claude.settings.json");
+        assert_eq!(field(&location, "/uriBaseId"), "%SRCROOT%");
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_validate_sarif_classifies_scope_and_gemini_checks() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_claude_settings(r#"{"askUserQuestionTimeout": "5m"}"#).unwrap();
+        fixture.with_gemini_settings(r#"{"notAGeminiSetting": true}"#).unwrap();
+
+        let claude_code = sarif_log(
+            &fixture,
+            &fixture.project,
+            &["config", "validate", "--agent", "claude-code", "--scope", "project"],
+            true,
+        );
+        assert_eq!(field(&result_for(&claude_code, "scope-restriction"), "/level"), "warning");
+
+        let gemini = sarif_log(
+            &fixture,
+            &fixture.project,
+            &["config", "validate", "--agent", "gemini"],
+            true,
+        );
+        let unknown = result_for(&gemini, "unknown-gemini-field");
+        let uri = field(&unknown, "/locations/0/physicalLocation/artifactLocation/uri");
+        assert!(uri.as_str().is_some_and(|text| {
+            text.starts_with("file:///") && text.ends_with("/gemini.settings.json")
+        }));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_validate_sarif_maps_info_to_note() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fs::write(fixture.config.join("settings.json"), r#"{"cleanupPeriodDays": 30}"#).unwrap();
+
+        let log = sarif_log(
+            &fixture,
+            &fixture.config,
+            &["config", "validate", "--agent", "claude-code", "--strict"],
+            true,
+        );
+
+        let legacy = result_for(&log, "legacy-source-file");
+        assert_eq!(field(&legacy, "/level"), "note");
+        assert_eq!(
+            field(&legacy, "/locations/0/physicalLocation/artifactLocation/uri"),
+            "settings.json"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_skills_validate_sarif_locates_skill_warnings() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_skill(
+                "shared-review",
+                "---\nname: shared-review\ndescription: Review changes.\ndisable-model-invocation: true\n---\n\nReview changes.\n",
+            )
+            .unwrap();
+
+        let log = sarif_log(
+            &fixture,
+            &fixture.config,
+            &["skills", "validate", "--agent", "codex", "--strict"],
+            false,
+        );
+
+        let legacy = result_for(&log, "legacy-skill");
+        assert_eq!(field(&legacy, "/level"), "warning");
+        assert_eq!(
+            field(&legacy, "/locations/0/physicalLocation/artifactLocation/uri"),
+            "skills/shared-review/SKILL.md"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_sarif_log_without_findings_still_lists_rules() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+
+        let log = sarif_log(&fixture, &fixture.project, &["config", "validate", "--strict"], true);
+
+        assert!(results(&log).is_empty());
+        let rules =
+            field(&log, "/runs/0/tool/driver/rules").as_array().cloned().unwrap_or_default();
+        assert!(rules.iter().any(|rule| field(rule, "/id") == "unknown-gemini-field"));
+        assert!(rules.iter().all(|rule| field(rule, "/shortDescription/text").is_string()));
+    }
+}
@@ -0,0 +1,212 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET_SERVERS: &str = r#"{
+        "mcpServers": {
+            "github": {
+                "command": "npx",
+                "args": ["-y", "github-mcp"],
+                "env": {"GITHUB_TOKEN": "op://dev/github/token", "LOG_LEVEL": "info"}
+            }
+        }
+    }"#;
+
+    #[test]
+    #[serial]
+    fn test_sync_launches_secret_servers_through_secrets_run() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(SECRET_SERVERS).unwrap();
+
+        fixture.claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "MCP server 'github' launches through `claudius secrets run` to resolve GITHUB_TOKEN",
+            ));
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        let mcp: serde_json::Value = serde_json::from_str(&content).unwrap();
+        let github = mcp.pointer("/mcpServers/github").unwrap();
+        assert_eq!(github.pointer("/command").unwrap(), "claudius");
+        assert_eq!(
+            github.pointer("/args").unwrap(),
+            &serde_json::json!(["secrets", "run", "--", "npx", "-y", "github-mcp"])
+        );
+        assert_eq!(
+            github.pointer("/env/CLAUDIUS_SECRET_GITHUB_TOKEN").unwrap(),
+            "op://dev/github/token"
+        );
+        assert_eq!(github.pointer("/env/LOG_LEVEL").unwrap(), "info");
+        assert!(github.pointer("/env/GITHUB_TOKEN").is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn test_dry_run_shows_secret_launch_rewrite() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(SECRET_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("launches through `claudius secrets run`"))
+            .stdout(predicate::str::contains("CLAUDIUS_SECRET_GITHUB_TOKEN"));
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_global_codex_sync_uses_the_configured_launcher() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(SECRET_SERVERS).unwrap();
+        let global_command = || {
+            fixture
+                .claudius()
+                .args(["config", "sync", "--agent", "codex", "--global"])
+                .assert()
+                .success();
+            let content =
+                fs::read_to_string(fixture.home_dir().join(".codex/config.toml")).unwrap();
+            assert!(!content.contains("\nGITHUB_TOKEN"));
+            let codex: toml::Value = toml::from_str(&content).unwrap();
+            codex
+                .get("mcp_servers")
+                .and_then(|servers| servers.get("github"))
+                .and_then(|github| github.get("command"))
+                .and_then(toml::Value::as_str)
+                .map(str::to_string)
+                .unwrap()
+        };
+
+        assert_eq!(global_command(), "claudius");
+
+        fs::write(
+            fixture.config.join("config.toml"),
+            "[secret-manager]\ntype = \"1password\"\nlauncher = \"/usr/local/bin/claudius\"\n",
+        )
+        .unwrap();
+        assert_eq!(global_command(), "/usr/local/bin/claudius");
+    }
+
+    #[test]
+    #[serial]
+    fn test_sync_rejects_remote_servers_with_secret_headers() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"remote": {
+                    "type": "http",
+                    "url": "https://example.com/mcp",
+                    "headers": {"Authorization": "Bearer {{op://dev/api/token}}"}
+                }}}"#,
+            )
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("mcpServers.remote: references op:// secrets"));
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_validate_reports_secret_launches() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(SECRET_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "validate"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "mcpServers.github: launches through `claudius secrets run`",
+            ))
+            .stdout(predicate::str::contains("config.toml has no [secret-manager]"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_sync_does_not_wrap_servers_twice() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"github": {
+                    "command": "claudius",
+                    "args": ["secrets", "run", "--", "npx", "-y", "github-mcp"],
+                    "env": {"CLAUDIUS_SECRET_GITHUB_TOKEN": "op://dev/github/token"}
+                }}}"#,
+            )
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        let mcp: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(mcp.pointer("/mcpServers/github/command").unwrap(), "claudius");
+        assert_eq!(
+            mcp.pointer("/mcpServers/github/args").unwrap(),
+            &serde_json::json!(["secrets", "run", "--", "npx", "-y", "github-mcp"])
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_validate_warns_when_the_launcher_is_not_on_path() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(SECRET_SERVERS).unwrap();
+        let bin_dir = fixture.home_dir().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+
+        fixture
+            .claudius()
+            .env("PATH", &bin_dir)
+            .args(["config", "validate", "--agent", "codex"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "mcpServers.github launch through 'claudius', which is not on PATH",
+            ));
+
+        fs::write(bin_dir.join("claudius"), "").unwrap();
+        fixture
+            .claudius()
+            .env("PATH", &bin_dir)
+            .args(["config", "validate", "--agent", "codex"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("which is not on PATH").not());
+    }
+
+    #[test]
+    #[serial]
+    fn test_validate_warns_about_bare_commands_for_claude_desktop() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(SECRET_SERVERS).unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "validate", "--agent", "claude"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(
+                "mcpServers.github: Claude Desktop starts servers without your shell's PATH",
+            ));
+    }
+}
@@ -480,9 +480,12 @@ agent = "claude-code"
             .arg("--global")
             .arg("--agent")
             .arg("claude-code")
+            .arg("--yes")
             .assert()
             .success();
 
+        // Accepting the conflicting definition replaces the whole entry, so the stale stdio
+        // transport fields and extras are gone.
         let content = fixture.read_home_file(".claude.json").unwrap();
         let json: serde_json::Value = serde_json::from_str(&content).unwrap();
         let notion = json.get("mcpServers").and_then(|servers| servers.get("notion")).unwrap();
@@ -493,7 +496,7 @@ agent = "claude-code"
         );
         assert_eq!(notion.get("type"), Some(&serde_json::Value::String("http".to_string())));
         assert!(notion.get("command").is_none());
-        assert_eq!(notion.get("startupTimeoutSec"), Some(&serde_json::Value::Number(30.into())));
+        assert!(notion.get("args").is_none_or(|args| args.as_array().is_some_and(Vec::is_empty)));
     }
 
     #[test]
@@ -0,0 +1,105 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn test_dry_run_shows_unified_diff_against_existing_target() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"beta": {"command": "beta-server"}}}"#)
+            .unwrap();
+        fixture
+            .with_existing_mcp_json(
+                "{\n  \"mcpServers\": {\n    \"alpha\": {\n      \"command\": \"alpha-server\"\n    }\n  }\n}",
+            )
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[modified]"))
+            .stdout(predicate::str::contains("--- current"))
+            .stdout(predicate::str::contains("+++ synced"))
+            .stdout(predicate::str::contains("+    \"beta\": {"));
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(!content.contains("beta"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_dry_run_marks_created_and_unchanged_files() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-server"}}}"#)
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[created]"))
+            .stdout(predicate::str::contains("+    \"alpha\": {"));
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("[unchanged]"))
+            .stdout(predicate::str::contains("+++ synced").not());
+    }
+
+    #[test]
+    #[serial]
+    fn test_dry_run_diffs_codex_requirements() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_mcp_servers(r#"{"mcpServers": {}}"#).unwrap();
+        fixture.with_codex_settings("model = \"o3\"\n").unwrap();
+        std::fs::write(
+            fixture.config.join("codex.requirements.toml"),
+            "allowed_approval_policies = [\"on-request\", \"never\"]\n",
+        )
+        .unwrap();
+
+        let requirements_path = fixture.home_dir().join("etc").join("requirements.toml");
+        std::fs::create_dir_all(requirements_path.parent().unwrap()).unwrap();
+        std::fs::write(&requirements_path, "allowed_approval_policies = [\"on-request\"]\n")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .env("CLAUDIUS_CODEX_REQUIREMENTS_PATH", &requirements_path)
+            .args([
+                "config",
+                "sync",
+                "--global",
+                "--agent",
+                "codex",
+                "--codex-requirements",
+                "--dry-run",
+            ])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("requirements.toml ("))
+            .stdout(predicate::str::contains("[modified]"))
+            .stdout(predicate::str::contains("-allowed_approval_policies = [\"on-request\"]"));
+
+        let content = std::fs::read_to_string(&requirements_path).unwrap();
+        assert_eq!(content, "allowed_approval_policies = [\"on-request\"]\n");
+    }
+}
@@ -0,0 +1,117 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING_MCP_JSON: &str = r#"{"mcpServers": {"alpha": {"command": "old-alpha"}}}"#;
+
+    fn conflicting_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "new-alpha"}}}"#)
+            .unwrap();
+        fixture.with_existing_mcp_json(EXISTING_MCP_JSON).unwrap();
+        fixture
+    }
+
+    #[test]
+    #[serial]
+    fn test_conflict_without_terminal_fails_instead_of_prompting() {
+        let fixture = conflicting_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("stdin is not a terminal"))
+            .stderr(predicate::str::contains("--strategy"));
+
+        assert_eq!(fixture.read_project_file(".mcp.json").unwrap(), EXISTING_MCP_JSON);
+    }
+
+    #[test]
+    #[serial]
+    fn test_no_input_fails_on_conflict() {
+        let fixture = conflicting_fixture();
+
+        fixture
+            .claudius()
+            .args(["--no-input", "config", "sync", "--agent", "claude-code"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("--no-input is set"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_yes_accepts_conflicting_servers() {
+        let fixture = conflicting_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--yes"])
+            .assert()
+            .success();
+
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("new-alpha"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_strategy_flag_resolves_conflicts_without_prompting() {
+        let fixture = conflicting_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "preserve-existing"])
+            .assert()
+            .success();
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("old-alpha"));
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "merge"])
+            .assert()
+            .success();
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("new-alpha"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_toml_strategy_is_the_default_and_cli_overrides_it() {
+        let fixture = conflicting_fixture();
+        std::fs::write(fixture.config.join("config.toml"), "[sync]\nstrategy = \"merge\"\n")
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "preserve-existing"])
+            .assert()
+            .success();
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("old-alpha"));
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("new-alpha"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_yes_and_no_input_are_mutually_exclusive() {
+        let fixture = TestFixture::new().unwrap();
+
+        fixture
+            .claudius()
+            .args(["--yes", "--no-input", "config", "sync"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
@@ -0,0 +1,71 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn test_failed_write_rolls_back_files_already_committed() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-server"}}}"#)
+            .unwrap();
+        fixture.with_claude_settings(r#"{"apiKeyHelper": "/bin/helper"}"#).unwrap();
+        fixture.with_existing_global_config(r#"{"theme": "dark"}"#).unwrap();
+        // A regular file where ~/.claude/ should be makes the settings write fail.
+        std::fs::write(fixture.home_dir().join(".claude"), "").unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--global", "--agent", "claude-code"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("all target files were restored"));
+
+        let global = fixture.read_home_file(".claude.json").unwrap();
+        assert_eq!(global, r#"{"theme": "dark"}"#);
+        assert!(!fixture.config.join(".claudius-sync-journal.json").exists());
+    }
+
+    #[test]
+    #[serial]
+    fn test_sync_recovers_interrupted_run_from_journal() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-server"}}}"#)
+            .unwrap();
+        fixture.with_existing_mcp_json(r#"{"mcpServers": {"half": {}}}"#).unwrap();
+
+        let journal = serde_json::json!({
+            "version": 1,
+            "entries": [{
+                "path": fixture.project.join(".mcp.json"),
+                "original": "{\"mcpServers\": {}}",
+            }],
+        });
+        std::fs::write(fixture.config.join(".claudius-sync-journal.json"), journal.to_string())
+            .unwrap();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success();
+        assert!(fixture.config.join(".claudius-sync-journal.json").exists());
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Recovered from an interrupted sync"));
+
+        let content = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(content.contains("alpha"));
+        assert!(!content.contains("half"));
+        assert!(!fixture.config.join(".claudius-sync-journal.json").exists());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::fixtures::TestFixture;
+use predicates::prelude::*;
+use serial_test::serial;
+use std::fs;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND_WRITTEN: &str = r#"{"mcpServers": {"legacy": {"command": "legacy-mcp"}}}"#;
+
+    fn sync_fixture() -> TestFixture {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"github": {"command": "github-mcp"}}}"#)
+            .unwrap();
+        fixture
+    }
+
+    fn sync_claude_code(fixture: &TestFixture) {
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--strategy", "replace"])
+            .assert()
+            .success();
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_restores_files_overwritten_by_config_sync() {
+        let fixture = sync_fixture();
+        fs::write(fixture.project.join(".mcp.json"), HAND_WRITTEN).unwrap();
+        sync_claude_code(&fixture);
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("github-mcp"));
+
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Undoing `claudius config sync --agent claude-code"))
+            .stdout(predicate::str::contains(".mcp.json"));
+
+        assert_eq!(fixture.read_project_file(".mcp.json").unwrap(), HAND_WRITTEN);
+        assert!(!fixture.config.join(".claudius-managed-mcp-servers.json").exists());
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Nothing to undo"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_steps_back_through_consecutive_syncs() {
+        let fixture = sync_fixture();
+        sync_claude_code(&fixture);
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"slack": {"command": "slack-mcp"}}}"#)
+            .unwrap();
+        sync_claude_code(&fixture);
+
+        fixture.claudius().arg("undo").assert().success();
+        let restored = fixture.read_project_file(".mcp.json").unwrap();
+        assert!(restored.contains("github-mcp"));
+        assert!(!restored.contains("slack-mcp"));
+
+        fixture.claudius().arg("undo").assert().success();
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_removes_skills_created_by_skills_sync() {
+        let fixture = sync_fixture();
+        fixture.with_skill("review", "# Review").unwrap();
+
+        fixture
+            .claudius()
+            .args(["skills", "sync", "--agent", "claude-code"])
+            .assert()
+            .success();
+        assert!(fixture.project_file_exists(".claude/skills/review/SKILL.md"));
+
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("remove  "));
+        assert!(!fixture.project_file_exists(".claude"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_refuses_when_a_file_changed_after_the_sync() {
+        let fixture = sync_fixture();
+        sync_claude_code(&fixture);
+        fs::write(fixture.project.join(".mcp.json"), HAND_WRITTEN).unwrap();
+
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("Refusing to undo"))
+            .stderr(predicate::str::contains(".mcp.json"));
+
+        assert_eq!(fixture.read_project_file(".mcp.json").unwrap(), HAND_WRITTEN);
+    }
+
+    #[test]
+    #[serial]
+    fn test_undo_dry_run_and_list_change_nothing() {
+        let fixture = sync_fixture();
+        sync_claude_code(&fixture);
+
+        fixture
+            .claudius()
+            .args(["undo", "--list"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("claudius config sync --agent claude-code"));
+        fixture
+            .claudius()
+            .args(["undo", "--dry-run"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Would undo"));
+
+        assert!(fixture.project_file_exists(".mcp.json"));
+        fixture.claudius().arg("undo").assert().success();
+        assert!(!fixture.project_file_exists(".mcp.json"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_dry_run_sync_records_nothing() {
+        let fixture = sync_fixture();
+
+        fixture
+            .claudius()
+            .args(["config", "sync", "--agent", "claude-code", "--dry-run"])
+            .assert()
+            .success();
+
+        fixture
+            .claudius()
+            .arg("undo")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("Nothing to undo"));
+    }
+}
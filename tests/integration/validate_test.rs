@@ -548,4 +548,55 @@ mode = "service-account"
             .failure()
             .stderr(predicate::str::contains("--scope is only supported with --agent claude-code"));
     }
+
+    #[test]
+    #[serial]
+    fn test_config_validate_lints_mcp_server_definitions() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.setup_env();
+        let bin_dir = fixture.home_dir().join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(bin_dir.join("npx"), "").unwrap();
+
+        fixture
+            .with_mcp_servers(
+                r#"{
+        "mcpServers": {
+            "both": { "command": "npx", "args": ["-y", "both-server"], "url": "https://example.com/mcp" },
+            "browser": { "command": "npx", "args": ["@playwright/mcp@latest"] },
+            "browser-copy": { "command": "npx", "args": ["@playwright/mcp@latest"] },
+            "local": { "command": "missing-server", "headers": { "X-Team": "core" } },
+            "remote": { "type": "sse", "url": "https://example.com/sse", "cwd": "/srv" },
+            "codex-home": { "command": "/opt/server", "env": { "CODEX_HOME": "/tmp/codex" } }
+        }
+    }"#,
+            )
+            .unwrap();
+
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_claudius"));
+        cmd.current_dir(&fixture.project)
+            .env("XDG_CONFIG_HOME", fixture.config_home())
+            .env("HOME", fixture.home_dir())
+            .env("PATH", &bin_dir)
+            .args(["config", "validate", "--strict"])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains(
+                "mcpServers.both: sets both `command` and `url`",
+            ))
+            .stdout(predicate::str::contains(
+                "mcpServers.local.headers does not apply to stdio servers and is dropped when syncing",
+            ))
+            .stdout(predicate::str::contains(
+                "mcpServers.remote.cwd does not apply to remote servers",
+            ))
+            .stdout(predicate::str::contains("mcpServers.local.command 'missing-server' is not on PATH"))
+            .stdout(predicate::str::contains("mcpServers.browser: npx asks before installing"))
+            .stdout(predicate::str::contains("'@playwright/mcp@latest' installs whatever version"))
+            .stdout(predicate::str::contains(
+                "mcpServers.browser, mcpServers.browser-copy run the same command",
+            ))
+            .stdout(predicate::str::contains("mcpServers.codex-home.env.CODEX_HOME is reserved by Codex"))
+            .stdout(predicate::str::contains("'npx' is not on PATH").not());
+    }
 }
@@ -0,0 +1,141 @@
+use crate::fixtures::TestFixture;
+use serial_test::serial;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WAIT: Duration = Duration::from_secs(20);
+
+    /// A running `--watch` process whose stdout lines arrive on a channel.
+    struct Watching {
+        child: Child,
+        lines: Receiver<String>,
+        seen: Vec<String>,
+    }
+
+    impl Watching {
+        fn start(fixture: &TestFixture, args: &[&str]) -> Self {
+            let mut child = Command::new(env!("CARGO_BIN_EXE_claudius"))
+                .current_dir(&fixture.project)
+                .env("XDG_CONFIG_HOME", fixture.config_home())
+                .env("HOME", fixture.home_dir())
+                .args(args)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .unwrap();
+
+            let stdout = child.stdout.take().unwrap();
+            let (sender, receiver) = mpsc::channel();
+            std::thread::spawn(move || {
+                let mut output = BufReader::new(stdout).lines().map_while(Result::ok);
+                while output.next().is_some_and(|line| sender.send(line).is_ok()) {}
+            });
+
+            let mut watching = Self { child, lines: receiver, seen: Vec::new() };
+            watching.wait_for("Watching");
+            watching
+        }
+
+        fn wait_for(&mut self, needle: &str) -> String {
+            let found = self.next_line_containing(needle);
+            assert!(
+                found.is_some(),
+                "'{needle}' not printed; output so far:\n{}",
+                self.seen.join("\n")
+            );
+            found.unwrap_or_default()
+        }
+
+        fn next_line_containing(&mut self, needle: &str) -> Option<String> {
+            let started = Instant::now();
+            while let Some(remaining) = WAIT.checked_sub(started.elapsed()) {
+                let line = self.lines.recv_timeout(remaining).ok()?;
+                self.seen.push(line.clone());
+                if line.contains(needle) {
+                    return Some(line);
+                }
+            }
+            None
+        }
+    }
+
+    impl Drop for Watching {
+        fn drop(&mut self) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_sync_watch_resyncs_configuration_on_change() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-server"}}}"#)
+            .unwrap();
+
+        let mut watching =
+            Watching::start(&fixture, &["config", "sync", "--agent", "claude-code", "--watch"]);
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("alpha-server"));
+
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"beta": {"command": "beta-server"}}}"#)
+            .unwrap();
+
+        let summary = watching.wait_for("change(s):");
+        assert!(summary.contains("~ mcpServers.json"));
+        watching.wait_for("Re-synced configuration");
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("beta-server"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_config_sync_watch_skips_sync_when_validation_fails() {
+        let fixture = TestFixture::new().unwrap();
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"alpha": {"command": "alpha-server"}}}"#)
+            .unwrap();
+
+        let mut watching =
+            Watching::start(&fixture, &["config", "sync", "--agent", "claude-code", "--watch"]);
+
+        fixture
+            .with_mcp_servers(
+                r#"{"mcpServers": {"alpha": {"command": "alpha", "args": ["${UNDEFINED_NAME}"]}}}"#,
+            )
+            .unwrap();
+        watching.wait_for("Validation failed");
+        watching.wait_for("undefined variable 'UNDEFINED_NAME'");
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("alpha-server"));
+
+        fixture
+            .with_mcp_servers(r#"{"mcpServers": {"gamma": {"command": "gamma-server"}}}"#)
+            .unwrap();
+        watching.wait_for("Re-synced configuration");
+        assert!(fixture.read_project_file(".mcp.json").unwrap().contains("gamma-server"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_skills_sync_watch_resyncs_skills() {
+        let fixture = TestFixture::new().unwrap();
+        fixture.with_skill("review", "# Review v1").unwrap();
+
+        let mut watching =
+            Watching::start(&fixture, &["skills", "sync", "--agent", "claude-code", "--watch"]);
+
+        fixture.with_skill("review", "# Review v2 with more detail").unwrap();
+
+        let summary = watching.wait_for("change(s):");
+        assert!(summary.contains("~ skills/review/SKILL.md"));
+        watching.wait_for("Re-synced skills");
+        let skill = fixture.read_project_file(".claude/skills/review/SKILL.md").unwrap();
+        assert!(skill.contains("# Review v2"));
+    }
+}
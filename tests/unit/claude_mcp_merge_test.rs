@@ -19,6 +19,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -55,6 +56,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -33,6 +33,7 @@ mod tests {
             server_type: None,
             url: None,
             headers: HashMap::new(),
+            targeting: None,
             extra: HashMap::new(),
         }
     }
@@ -1,4 +1,5 @@
-use claudius::config::{McpServerConfig, McpServersConfig};
+use claudius::app_config::Agent;
+use claudius::config::{parse_target_selector, McpServerConfig, McpServersConfig, ServerTargeting};
 use std::collections::HashMap;
 
 #[cfg(test)]
@@ -15,6 +16,7 @@ mod tests {
             url: None,
             headers: HashMap::new(),
             extra: HashMap::new(),
+            targeting: None,
         };
 
         assert_eq!(config.command.as_deref(), Some("node"));
@@ -35,6 +37,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -56,6 +59,7 @@ mod tests {
             url: None,
             headers: HashMap::new(),
             extra: HashMap::new(),
+            targeting: None,
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -93,6 +97,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -147,4 +152,44 @@ mod tests {
         assert!(config.args.is_empty());
         assert!(config.env.is_empty());
     }
+
+    #[test]
+    fn test_x_claudius_targeting_is_parsed_but_not_serialized() {
+        let json = r#"{
+            "command": "server",
+            "x-claudius": {"agents": ["claude-code"], "exclude": ["claude-code:local"]}
+        }"#;
+
+        let config: McpServerConfig = serde_json::from_str(json).unwrap();
+        let targeting = config.targeting.as_ref().unwrap();
+        assert_eq!(targeting.agents, ["claude-code"]);
+        assert!(config.extra.is_empty());
+
+        let serialized = serde_json::to_string(&config).unwrap();
+        assert!(!serialized.contains("x-claudius"));
+    }
+
+    #[test]
+    fn test_server_targeting_matches_agents_and_scopes() {
+        let targeting = ServerTargeting {
+            agents: vec!["claude-code".to_string(), "codex:global".to_string()],
+            exclude: vec!["claude-code:local".to_string()],
+        };
+
+        assert!(targeting.includes(Agent::ClaudeCode, &["project"]));
+        assert!(!targeting.includes(Agent::ClaudeCode, &["local", "project"]));
+        assert!(targeting.includes(Agent::Codex, &["global"]));
+        assert!(!targeting.includes(Agent::Codex, &["project"]));
+        assert!(!targeting.includes(Agent::Gemini, &["global"]));
+        assert!(ServerTargeting::default().includes(Agent::Gemini, &["project"]));
+    }
+
+    #[test]
+    fn test_parse_target_selector_rejects_unknown_names() {
+        assert_eq!(parse_target_selector("gemini:project"), Ok((Agent::Gemini, Some("project"))));
+        assert!(parse_target_selector("cursor").unwrap_err().contains("unknown agent 'cursor'"));
+        assert!(parse_target_selector("codex:user")
+            .unwrap_err()
+            .contains("unknown scope 'user'"));
+    }
 }
@@ -17,6 +17,7 @@ mod tests {
             url: None,
             headers: HashMap::new(),
             extra: HashMap::new(),
+            targeting: None,
         }
     }
 
@@ -166,6 +167,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -39,8 +39,11 @@ mod tests {
     #[serial]
     fn test_onepassword_non_op_reference() {
         cleanup_claudius_secrets();
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::OnePassword, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::OnePassword,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Set a non-op:// value
@@ -57,8 +60,11 @@ mod tests {
     #[serial]
     fn test_vault_warning() {
         cleanup_claudius_secrets();
-        let config =
-            SecretManagerConfig { manager_type: SecretManagerType::Vault, onepassword: None };
+        let config = SecretManagerConfig {
+            manager_type: SecretManagerType::Vault,
+            onepassword: None,
+            launcher: None,
+        };
         let resolver = SecretResolver::new(Some(config));
 
         // Set a test environment variable
@@ -1,4 +1,5 @@
 use assert_fs::prelude::*;
+use claudius::backups::BackupSettings;
 use claudius::config::{writer, ClaudeConfig, McpServerConfig, McpServersConfig, Settings};
 use serde_json::json;
 use std::collections::HashMap;
@@ -24,6 +25,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 
@@ -92,6 +94,7 @@ mod tests {
                     url: None,
                     headers: HashMap::new(),
                     extra: HashMap::new(),
+                    targeting: None,
                 },
             )])),
             other: HashMap::new(),
@@ -110,7 +113,8 @@ mod tests {
         let temp_file = assert_fs::NamedTempFile::new("claude.json").unwrap();
         temp_file.write_str(r#"{"test": "data"}"#).unwrap();
 
-        let backup_path = writer::backup_file(temp_file.path()).unwrap();
+        let backup_path =
+            writer::backup_file(temp_file.path(), &BackupSettings::default()).unwrap();
         assert!(backup_path.is_some());
 
         let backup_file_path = backup_path.unwrap();
@@ -125,7 +129,8 @@ mod tests {
 
     #[test]
     fn test_backup_file_nonexistent_returns_none() {
-        let result = writer::backup_file("/nonexistent/file.json").unwrap();
+        let result =
+            writer::backup_file("/nonexistent/file.json", &BackupSettings::default()).unwrap();
         assert!(result.is_none());
     }
 
@@ -134,7 +139,9 @@ mod tests {
         let temp_file = assert_fs::NamedTempFile::new("test.json").unwrap();
         temp_file.write_str("{}").unwrap();
 
-        let backup_path = writer::backup_file(temp_file.path()).unwrap().unwrap();
+        let backup_path = writer::backup_file(temp_file.path(), &BackupSettings::default())
+            .unwrap()
+            .unwrap();
 
         // Check that backup filename contains timestamp pattern
         assert!(backup_path.contains("test.json.backup."));
@@ -162,6 +169,7 @@ mod tests {
                 url: None,
                 headers: HashMap::new(),
                 extra: HashMap::new(),
+                targeting: None,
             },
         );
 